@@ -4,8 +4,8 @@
 
 use crate::message::{KeyboardShortcut, Message, View};
 use crate::services::{
-    start_tray, CurveDaemon, GpuMonitor, GpuSettings, GuiConfig, Profile, ProfileService,
-    TrayHandle,
+    start_tray, CurveDaemon, FanCurveConfig, GpuMonitor, GpuSettings, GuiConfig, ProcessSwitch,
+    Profile, ProfileService, TrayHandle,
 };
 use crate::state::{AppState, Notification};
 use crate::theme::{colors, font_size, nvctl_theme, spacing};
@@ -14,9 +14,15 @@ use crate::views;
 use iced::keyboard::{self, key::Named, Key, Modifiers};
 use iced::widget::{button, column, container, horizontal_space, row, text, Column, Space};
 use iced::{Alignment, Element, Length, Subscription, Task, Theme};
-use nvctl::domain::FanPolicy;
+use nvctl::alerts::{
+    AlertConfig, AlertManagerConfig, DesktopNotifier, NotificationManager, RuleKind,
+};
+use nvctl::config::{Config, ConfigFile, GpuIdentity};
+use nvctl::domain::{FanPolicy, PowerLimit, Temperature};
+use nvctl::history::HistoryStore;
+use nvctl::services::{AlertService, ProcessWatcher, Scheduler};
 use std::sync::{Arc, RwLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Main application
 pub struct NvctlGui {
@@ -37,14 +43,49 @@ pub struct NvctlGui {
 
     /// Fan curve daemon for automatic fan control
     curve_daemon: Arc<RwLock<CurveDaemon>>,
+
+    /// Profile schedule from the nvctl config file (optional)
+    scheduler: Option<Scheduler>,
+
+    /// Profile last selected by the schedule
+    scheduled_profile: Option<String>,
+
+    /// nvctl config file, for its `[profiles.<name>]` sections
+    nvctl_config: Config,
+
+    /// Settings replaced by a scheduled or process-triggered profile
+    saved_settings: Option<SavedSettings>,
+}
+
+/// Settings in effect before an automatic profile was applied
+struct SavedSettings {
+    /// Curve daemon configuration
+    curves: Vec<FanCurveConfig>,
+    /// Power and acoustic limits by GPU index
+    limits: Vec<(u32, PowerLimit, Option<Temperature>)>,
+    /// Selected profile
+    active_profile: Option<String>,
 }
 
 impl NvctlGui {
     /// Create a new application instance
     pub fn new() -> (Self, Task<Message>) {
-        let monitor = GpuMonitor::new();
+        // Schedule, process rules and history from the nvctl config file
+        let nvctl_config = ConfigFile::load_default().unwrap_or_default();
+
+        let mut monitor = GpuMonitor::new();
+        if nvctl_config.history.enabled {
+            monitor = monitor.with_history(HistoryStore::from_config(&nvctl_config.history));
+        }
+        if let Some(alerts) = load_alert_service() {
+            monitor = monitor.with_alerts(alerts);
+        }
         let gpus = monitor.detect_gpus();
-        let profile_service = ProfileService::new();
+        let mut profile_service = ProfileService::new();
+
+        if let Some(watcher) = load_process_watcher(&nvctl_config) {
+            profile_service.set_process_watcher(watcher);
+        }
 
         // Load GUI config (fan labels, etc.)
         let mut config = GuiConfig::load();
@@ -99,11 +140,288 @@ impl NvctlGui {
             tray,
             config,
             curve_daemon,
+            scheduler: load_scheduler(&nvctl_config),
+            scheduled_profile: None,
+            nvctl_config,
+            saved_settings: None,
         };
 
         (app, Task::none())
     }
 
+    /// Apply the scheduled profile whenever the schedule switches
+    ///
+    /// Profiles picked by hand stay active until the next scheduled change.
+    /// When no rule matches, the settings from before the schedule took over
+    /// are restored. While a process rule is in effect, this happens once the
+    /// rule stops matching instead.
+    fn apply_schedule(&mut self) -> Task<Message> {
+        let Some(scheduler) = &self.scheduler else {
+            return Task::none();
+        };
+
+        let profile = scheduler.active().profile;
+        if profile == self.scheduled_profile {
+            return Task::none();
+        }
+        self.scheduled_profile = profile.clone();
+
+        if self.profile_service.process_rule_active() {
+            self.profile_service.set_restore_profile(profile);
+            return Task::none();
+        }
+
+        match profile {
+            Some(name) => self.apply_config_profile(name, "Schedule"),
+            None => {
+                self.restore_settings();
+                Task::none()
+            }
+        }
+    }
+
+    /// Switch profiles while processes matching the process rules run
+    ///
+    /// When the rules stop matching, the profile selected before is applied
+    /// again, or the settings from before the rule took over are restored.
+    fn apply_process_rules(&mut self) -> Task<Message> {
+        if !self.profile_service.has_process_rules() {
+            return Task::none();
+        }
+
+        let processes = self.monitor.running_processes();
+        match self
+            .profile_service
+            .check_processes(&processes, Instant::now())
+        {
+            Some(ProcessSwitch::Apply(name)) => self.apply_config_profile(name, "Process rule"),
+            Some(ProcessSwitch::Restore(Some(name))) => {
+                self.apply_config_profile(name, "Process rule")
+            }
+            Some(ProcessSwitch::Restore(None)) => {
+                self.restore_settings();
+                Task::none()
+            }
+            None => Task::none(),
+        }
+    }
+
+    /// Apply a `[profiles.<name>]` section of the nvctl config to every GPU
+    ///
+    /// The settings in effect before the first automatic profile are saved
+    /// for `restore_settings`. Names without a section restore those settings
+    /// and select the GUI profile of that name instead.
+    fn apply_config_profile(&mut self, name: String, source: &str) -> Task<Message> {
+        let settings = match self.nvctl_config.profile_settings(&name) {
+            Some(Ok(settings)) => settings,
+            Some(Err(e)) => {
+                log::warn!("{} selected invalid profile '{}': {}", source, name, e);
+                return Task::none();
+            }
+            None => {
+                self.restore_settings();
+                return self.select_profile(name, source);
+            }
+        };
+
+        if self.saved_settings.is_none() {
+            self.saved_settings = Some(self.current_settings());
+        }
+
+        log::info!("{} applied profile '{}'", source, name);
+        let mut curves = Vec::new();
+        let mut errors = Vec::new();
+        for gpu in &self.state.gpus {
+            let identity = GpuIdentity {
+                index: gpu.index,
+                uuid: gpu.info.uuid.clone(),
+                name: gpu.info.name.clone(),
+                pci_bus_id: gpu.info.pci_bus_id.clone(),
+            };
+            let device = settings.settings_for(&identity);
+            curves.extend(
+                (0..gpu.fan_speeds.len() as u32).map(|fan_index| FanCurveConfig {
+                    gpu_index: gpu.index,
+                    fan_index,
+                    curve: device.fan_curve.clone(),
+                    enabled: true,
+                }),
+            );
+            if let Some(limit) = device.power_limit {
+                if let Err(e) = self.monitor.set_power_limit(gpu.index, limit) {
+                    errors.push(e);
+                }
+            }
+            if let Some(limit) = device.acoustic_limit {
+                if let Err(e) = self.monitor.set_acoustic_limit(gpu.index, limit) {
+                    errors.push(e);
+                }
+            }
+        }
+        self.set_daemon_curves(curves);
+
+        self.profile_service.set_active(Some(name.clone()));
+        self.state.active_profile = Some(name.clone());
+        if errors.is_empty() {
+            self.state
+                .set_notification(Notification::success(format!("Profile '{}' applied", name)));
+        } else {
+            self.state.set_notification(Notification::warning(format!(
+                "Profile '{}' partially applied: {}",
+                name,
+                errors.join(", ")
+            )));
+        }
+        Task::none()
+    }
+
+    /// Settings to bring back once automatic profiles stop applying
+    fn current_settings(&self) -> SavedSettings {
+        let curves = self
+            .curve_daemon
+            .read()
+            .ok()
+            .and_then(|daemon| daemon.state().read().ok().map(|state| state.curves()))
+            .unwrap_or_default();
+        let limits = self
+            .state
+            .gpus
+            .iter()
+            .map(|gpu| {
+                (
+                    gpu.index,
+                    gpu.power_limit,
+                    self.monitor.acoustic_limit(gpu.index),
+                )
+            })
+            .collect();
+        SavedSettings {
+            curves,
+            limits,
+            active_profile: self.profile_service.active_name().map(String::from),
+        }
+    }
+
+    /// Restore the settings saved before the first automatic profile
+    fn restore_settings(&mut self) {
+        let Some(saved) = self.saved_settings.take() else {
+            return;
+        };
+
+        log::info!("Restoring settings from before the automatic profile");
+        for (gpu_index, power_limit, acoustic_limit) in saved.limits {
+            let current = self.state.gpus.iter().find(|gpu| gpu.index == gpu_index);
+            if current.map(|gpu| gpu.power_limit) != Some(power_limit) {
+                if let Err(e) = self.monitor.set_power_limit(gpu_index, power_limit) {
+                    log::warn!("Failed to restore power limit of GPU {}: {}", gpu_index, e);
+                }
+            }
+            let Some(limit) = acoustic_limit else {
+                continue;
+            };
+            if self.monitor.acoustic_limit(gpu_index) != Some(limit) {
+                if let Err(e) = self.monitor.set_acoustic_limit(gpu_index, limit) {
+                    log::warn!(
+                        "Failed to restore acoustic limit of GPU {}: {}",
+                        gpu_index,
+                        e
+                    );
+                }
+            }
+        }
+        self.set_daemon_curves(saved.curves);
+
+        self.profile_service
+            .set_active(saved.active_profile.clone());
+        self.state.active_profile = saved.active_profile;
+        self.state
+            .set_notification(Notification::success("Previous settings restored"));
+    }
+
+    /// Replace the curve daemon's curves
+    ///
+    /// Fans that lose their curve are handed back to the driver, and the
+    /// curve editor of the selected GPU shows the new curves.
+    fn set_daemon_curves(&mut self, curves: Vec<FanCurveConfig>) {
+        let mut released = Vec::new();
+        if let Ok(mut daemon) = self.curve_daemon.write() {
+            let daemon_state = daemon.state();
+            let has_enabled = match daemon_state.write() {
+                Ok(mut state) => {
+                    released = state
+                        .enabled_curves()
+                        .iter()
+                        .map(|c| (c.gpu_index, c.fan_index))
+                        .filter(|&(gpu, fan)| {
+                            !curves
+                                .iter()
+                                .any(|c| c.enabled && c.gpu_index == gpu && c.fan_index == fan)
+                        })
+                        .collect();
+                    state.replace_curves(curves);
+                    state.has_enabled_curves()
+                }
+                Err(_) => return,
+            };
+            drop(daemon_state);
+
+            if has_enabled && !daemon.is_running() {
+                daemon.start();
+                log::info!("Fan curve daemon started");
+            } else if !has_enabled && daemon.is_running() {
+                daemon.stop();
+                log::info!("Fan curve daemon stopped");
+            }
+        }
+
+        for (gpu_index, fan_index) in released {
+            let _ = self
+                .monitor
+                .set_fan_policy(gpu_index, fan_index, FanPolicy::Auto);
+        }
+        self.sync_curve_editor();
+    }
+
+    /// Show the curve daemon's curves for the selected GPU in the editor
+    fn sync_curve_editor(&mut self) {
+        let Some(gpu_index) = self.state.current_gpu().map(|gpu| gpu.index) else {
+            return;
+        };
+        let Ok(daemon) = self.curve_daemon.read() else {
+            return;
+        };
+        let daemon_state = daemon.state();
+        let Ok(state) = daemon_state.read() else {
+            return;
+        };
+        for fan_idx in 0..self.state.editing_curves.len() {
+            let config = state.curve(gpu_index, fan_idx as u32);
+            if let Some(config) = config {
+                self.state.editing_curves[fan_idx] = Some(config.curve.clone());
+            }
+            if let Some(enabled) = self.state.curve_control_enabled.get_mut(fan_idx) {
+                *enabled = config.is_some_and(|c| c.enabled);
+            }
+        }
+    }
+
+    /// Select a saved profile on behalf of `source`, if it exists
+    fn select_profile(&mut self, name: String, source: &str) -> Task<Message> {
+        if !self.profile_service.exists(&name) {
+            log::warn!(
+                "{} selected profile '{}', which does not exist",
+                source,
+                name
+            );
+            return Task::none();
+        }
+
+        log::info!("{} selected profile '{}'", source, name);
+        self.update(Message::Profile(crate::message::ProfileMessage::Selected(
+            name,
+        )))
+    }
+
     /// Update application state based on a message
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
@@ -203,7 +521,8 @@ impl NvctlGui {
                     }
                 }
 
-                Task::none()
+                let process_task = self.apply_process_rules();
+                Task::batch([process_task, self.apply_schedule()])
             }
 
             Message::GpuStateUpdated(snapshot) => {
@@ -1186,6 +1505,88 @@ impl Default for NvctlGui {
             config: GuiConfig::default(),
             tray: None,
             curve_daemon: Arc::new(RwLock::new(CurveDaemon::new())),
+            scheduler: None,
+            scheduled_profile: None,
+            nvctl_config: Config::default(),
+            saved_settings: None,
+        }
+    }
+}
+
+/// Load the process rules from the nvctl config file, if it has any
+fn load_process_watcher(config: &Config) -> Option<ProcessWatcher> {
+    if config.process_triggers.is_empty() {
+        return None;
+    }
+
+    match ProcessWatcher::new(&config.process_triggers) {
+        Ok(watcher) => {
+            log::info!(
+                "Loaded {} process rules",
+                config.process_triggers.rules.len()
+            );
+            Some(watcher)
+        }
+        Err(e) => {
+            log::warn!("Ignoring process rules: {}", e);
+            None
+        }
+    }
+}
+
+/// Evaluate the metric rules of `alerts.toml` with desktop notifications
+fn load_alert_service() -> Option<AlertService> {
+    let config = match AlertConfig::load_or_default() {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("Ignoring alert rules: {}", e);
+            return None;
+        }
+    };
+    if !config.settings.enabled {
+        return None;
+    }
+
+    // Event rules need an NVML event listener, which the GUI doesn't run
+    let rules = match config.rules_of(RuleKind::Metric) {
+        Ok(rules) => rules,
+        Err(e) => {
+            log::warn!("Ignoring alert rules: {}", e);
+            return None;
+        }
+    };
+    let notifier = match DesktopNotifier::connect() {
+        Ok(notifier) => notifier,
+        Err(e) => {
+            log::info!("Desktop notifications not available: {}", e);
+            return None;
+        }
+    };
+
+    let mut notifications = NotificationManager::new();
+    notifications.add_notifier(Box::new(notifier));
+    let manager_config = AlertManagerConfig {
+        enabled: true,
+        check_interval: Duration::from_secs(config.settings.check_interval_secs),
+        max_history: config.settings.max_history,
+    };
+    Some(AlertService::new(manager_config, rules).with_notifications(notifications))
+}
+
+/// Load the profile schedule from the nvctl config file, if it has one
+fn load_scheduler(config: &Config) -> Option<Scheduler> {
+    if config.schedule.is_empty() {
+        return None;
+    }
+
+    match Scheduler::new(&config.schedule) {
+        Ok(scheduler) => {
+            log::info!("Loaded {} schedule rules", scheduler.rules().len());
+            Some(scheduler)
+        }
+        Err(e) => {
+            log::warn!("Ignoring profile schedule: {}", e);
+            None
         }
     }
 }
@@ -14,6 +14,7 @@ use app::NvctlGui;
 use clap::Parser;
 use fs2::FileExt;
 use iced::{window, Size};
+use nvctl::replay::ReplayOptions;
 use std::fs::{self, File};
 use std::path::PathBuf;
 use std::process;
@@ -29,6 +30,18 @@ struct Args {
     /// Config file path for daemon mode
     #[arg(short, long)]
     config: Option<String>,
+
+    /// Show telemetry from a recording made by `nvctl record` instead of live GPUs
+    #[arg(long, value_name = "FILE", conflicts_with = "daemon")]
+    replay: Option<PathBuf>,
+
+    /// Playback speed multiplier for --replay
+    #[arg(long, value_name = "X", default_value_t = 1.0, requires = "replay")]
+    replay_speed: f64,
+
+    /// Start the recording over when --replay reaches its end
+    #[arg(long, requires = "replay")]
+    replay_loop: bool,
 }
 
 /// Get the lock file path
@@ -104,6 +117,18 @@ fn main() -> iced::Result {
 
     log::info!("Starting nvctl-gui");
 
+    if let Some(path) = args.replay {
+        if !(args.replay_speed.is_finite() && args.replay_speed > 0.0) {
+            eprintln!("--replay-speed must be a positive number");
+            process::exit(2);
+        }
+        services::set_replay(ReplayOptions {
+            path,
+            speed: args.replay_speed,
+            looped: args.replay_loop,
+        });
+    }
+
     // Check for existing instance
     let _lock = match acquire_instance_lock() {
         Some(lock) => lock,
@@ -4,7 +4,10 @@
 //! according to configured fan curves.
 
 use crate::services::GpuMonitor;
-use nvctl::domain::{FanCurve, FanPolicy, FanSpeed};
+use crate::state::GpuState;
+use nvctl::config::{Config, ConfigFile, GpuIdentity, ProfileSettings};
+use nvctl::domain::{FanCurve, FanPolicy, FanSpeed, PowerLimit, Temperature};
+use nvctl::services::Scheduler;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
@@ -59,6 +62,25 @@ impl CurveDaemonState {
         self.last_speeds.remove(&(gpu_index, fan_index));
     }
 
+    /// Get the curve configuration of a fan
+    pub fn curve(&self, gpu_index: u32, fan_index: u32) -> Option<&FanCurveConfig> {
+        self.curves.get(&(gpu_index, fan_index))
+    }
+
+    /// Get all curve configurations
+    pub fn curves(&self) -> Vec<FanCurveConfig> {
+        self.curves.values().cloned().collect()
+    }
+
+    /// Replace all curve configurations
+    pub fn replace_curves(&mut self, curves: Vec<FanCurveConfig>) {
+        self.last_speeds.clear();
+        self.curves = curves
+            .into_iter()
+            .map(|c| ((c.gpu_index, c.fan_index), c))
+            .collect();
+    }
+
     /// Get all enabled curve configurations
     pub fn enabled_curves(&self) -> Vec<&FanCurveConfig> {
         self.curves.values().filter(|c| c.enabled).collect()
@@ -123,7 +145,7 @@ impl CurveDaemon {
 
         let handle = thread::spawn(move || {
             log::info!("Fan curve daemon started");
-            let monitor = GpuMonitor::new();
+            let mut monitor = GpuMonitor::new();
 
             while running.load(Ordering::SeqCst) {
                 // Process curves
@@ -239,8 +261,155 @@ impl Drop for CurveDaemon {
     }
 }
 
+/// Follows the `[schedule]` of the nvctl config in daemon mode
+struct DaemonSchedule {
+    config: Config,
+    scheduler: Scheduler,
+    profile: Option<String>,
+}
+
+impl DaemonSchedule {
+    /// Load the schedule of the nvctl config, if it has one
+    fn load(config: Config) -> Option<Self> {
+        if config.schedule.is_empty() {
+            return None;
+        }
+
+        match Scheduler::new(&config.schedule) {
+            Ok(scheduler) => {
+                log::info!("Loaded {} schedule rules", scheduler.rules().len());
+                Some(Self {
+                    config,
+                    scheduler,
+                    profile: None,
+                })
+            }
+            Err(e) => {
+                log::warn!("Ignoring profile schedule: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Use a different clock
+    #[cfg(test)]
+    fn with_clock(mut self, clock: impl nvctl::services::Clock + 'static) -> Self {
+        self.scheduler = self.scheduler.with_clock(clock);
+        self
+    }
+
+    /// The profile to switch to, if the schedule changed since the last call
+    ///
+    /// `Some(None)` means no profile is scheduled any more.
+    fn poll(&mut self) -> Option<Option<String>> {
+        let profile = self.scheduler.active().profile;
+        if profile == self.profile {
+            return None;
+        }
+        self.profile = profile.clone();
+        Some(profile)
+    }
+
+    /// Resolve a `[profiles.<name>]` section, logging why it can't be used
+    fn profile_settings(&self, name: &str) -> Option<ProfileSettings> {
+        match self.config.profile_settings(name) {
+            Some(Ok(settings)) => Some(settings),
+            Some(Err(e)) => {
+                log::warn!("Schedule selected invalid profile '{}': {}", name, e);
+                None
+            }
+            None => {
+                log::warn!("Schedule selected undefined profile '{}'", name);
+                None
+            }
+        }
+    }
+}
+
+/// Curves and limits in effect before the schedule applied a profile
+struct DaemonDefaults {
+    curves: Vec<FanCurveConfig>,
+    limits: Vec<(u32, PowerLimit, Option<Temperature>)>,
+}
+
+/// Curves of a profile for every fan of every GPU
+fn profile_curves(settings: &ProfileSettings, gpus: &[GpuState]) -> Vec<FanCurveConfig> {
+    gpus.iter()
+        .flat_map(|gpu| {
+            let curve = &settings.settings_for(&gpu_identity(gpu)).fan_curve;
+            (0..gpu.fan_speeds.len() as u32).map(move |fan_index| FanCurveConfig {
+                gpu_index: gpu.index,
+                fan_index,
+                curve: curve.clone(),
+                enabled: true,
+            })
+        })
+        .collect()
+}
+
+fn gpu_identity(gpu: &GpuState) -> GpuIdentity {
+    GpuIdentity {
+        index: gpu.index,
+        uuid: gpu.info.uuid.clone(),
+        name: gpu.info.name.clone(),
+        pci_bus_id: gpu.info.pci_bus_id.clone(),
+    }
+}
+
+/// Apply a scheduled profile to every GPU
+fn apply_profile(
+    monitor: &GpuMonitor,
+    daemon: &CurveDaemon,
+    gpus: &[GpuState],
+    name: &str,
+    settings: &ProfileSettings,
+) {
+    log::info!("Schedule applied profile '{}'", name);
+    for gpu in gpus {
+        let device = settings.settings_for(&gpu_identity(gpu));
+        if let Some(limit) = device.power_limit {
+            if let Err(e) = monitor.set_power_limit(gpu.index, limit) {
+                log::warn!("Failed to set power limit of GPU {}: {}", gpu.index, e);
+            }
+        }
+        if let Some(limit) = device.acoustic_limit {
+            if let Err(e) = monitor.set_acoustic_limit(gpu.index, limit) {
+                log::warn!("Failed to set acoustic limit of GPU {}: {}", gpu.index, e);
+            }
+        }
+    }
+    if let Ok(mut state) = daemon.state().write() {
+        state.replace_curves(profile_curves(settings, gpus));
+    }
+}
+
+/// Bring back the curves and limits from before the schedule took over
+fn restore_defaults(monitor: &GpuMonitor, daemon: &CurveDaemon, defaults: &DaemonDefaults) {
+    log::info!("Restoring settings from before the scheduled profile");
+    for &(gpu_index, power_limit, acoustic_limit) in &defaults.limits {
+        if let Err(e) = monitor.set_power_limit(gpu_index, power_limit) {
+            log::warn!("Failed to restore power limit of GPU {}: {}", gpu_index, e);
+        }
+        if let Some(limit) = acoustic_limit {
+            if let Err(e) = monitor.set_acoustic_limit(gpu_index, limit) {
+                log::warn!(
+                    "Failed to restore acoustic limit of GPU {}: {}",
+                    gpu_index,
+                    e
+                );
+            }
+        }
+    }
+    if let Ok(mut state) = daemon.state().write() {
+        state.replace_curves(defaults.curves.clone());
+    }
+}
+
 /// Run the daemon in standalone mode (no GUI)
 /// This is used when running with --daemon flag
+///
+/// Profiles selected by the `[schedule]` of the nvctl config are applied to
+/// every GPU while their window lasts.
 pub fn run_daemon_standalone(config_path: Option<&str>) -> Result<(), String> {
     use crate::services::GuiConfig;
 
@@ -271,7 +440,7 @@ pub fn run_daemon_standalone(config_path: Option<&str>) -> Result<(), String> {
 
     // For standalone mode, we need to load curves from a config file
     // For now, use default balanced curve for all fans if curve_control is enabled
-    {
+    let defaults = {
         let daemon_state = daemon.state();
         let mut state = daemon_state.write().map_err(|e| e.to_string())?;
 
@@ -289,7 +458,23 @@ pub fn run_daemon_standalone(config_path: Option<&str>) -> Result<(), String> {
                 );
             }
         }
-    }
+
+        DaemonDefaults {
+            curves: state.curves(),
+            limits: gpus
+                .iter()
+                .map(|gpu| {
+                    (
+                        gpu.index,
+                        gpu.power_limit,
+                        monitor.acoustic_limit(gpu.index),
+                    )
+                })
+                .collect(),
+        }
+    };
+
+    let mut schedule = DaemonSchedule::load(ConfigFile::load_default().unwrap_or_default());
 
     // Start daemon
     daemon.start();
@@ -307,14 +492,31 @@ pub fn run_daemon_standalone(config_path: Option<&str>) -> Result<(), String> {
     })
     .map_err(|e| format!("Failed to set signal handler: {}", e))?;
 
-    // Wait for shutdown signal
+    // Wait for shutdown signal, following the schedule meanwhile
     while running.load(Ordering::SeqCst) {
+        if let Some(schedule) = &mut schedule {
+            if let Some(profile) = schedule.poll() {
+                let settings = profile
+                    .as_deref()
+                    .and_then(|name| Some((name, schedule.profile_settings(name)?)));
+                match settings {
+                    Some((name, settings)) => {
+                        apply_profile(&monitor, &daemon, &gpus, name, &settings)
+                    }
+                    None => restore_defaults(&monitor, &daemon, &defaults),
+                }
+            }
+        }
         thread::sleep(Duration::from_millis(100));
     }
 
     // Stop daemon
     daemon.stop();
 
+    if schedule.is_some_and(|schedule| schedule.profile.is_some()) {
+        restore_defaults(&monitor, &daemon, &defaults);
+    }
+
     // Restore fans to auto mode
     log::info!("Restoring fans to auto mode...");
     for gpu in &gpus {
@@ -329,3 +531,59 @@ pub fn run_daemon_standalone(config_path: Option<&str>) -> Result<(), String> {
     log::info!("Daemon stopped");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nvctl::domain::GpuInfo;
+    use nvctl::services::ManualClock;
+
+    const NIGHTS: &str = r#"
+        [profiles.quiet.fan]
+        default_speed = 20
+        curve = [{ temperature = 70, speed = 40 }]
+
+        [schedule]
+        timezone = "UTC"
+
+        [[schedule.rules]]
+        profile = "quiet"
+        start = "22:00"
+        end = "07:00"
+    "#;
+
+    #[test]
+    fn test_daemon_schedule_switches_profiles() {
+        let config: Config = toml::from_str(NIGHTS).unwrap();
+        let clock = ManualClock::new("2024-06-12T10:00:00Z".parse().unwrap());
+        let mut schedule = DaemonSchedule::load(config)
+            .unwrap()
+            .with_clock(clock.clone());
+
+        // No profile during the day, so nothing changes
+        assert_eq!(schedule.poll(), None);
+
+        clock.set("2024-06-12T23:00:00Z".parse().unwrap());
+        assert_eq!(schedule.poll(), Some(Some("quiet".to_string())));
+        assert_eq!(schedule.poll(), None);
+
+        let mut gpu = GpuState::new(GpuInfo::new(0, "Test GPU".to_string(), "GPU-0".to_string()));
+        gpu.fan_speeds = vec![FanSpeed::new(30).unwrap(); 2];
+        let settings = schedule.profile_settings("quiet").unwrap();
+        let curves = profile_curves(&settings, &[gpu]);
+        assert_eq!(curves.len(), 2);
+        assert_eq!(
+            curves[0].curve.speed_for_temperature(80).as_percentage(),
+            40
+        );
+
+        // The window ends and the defaults come back
+        clock.set("2024-06-13T08:00:00Z".parse().unwrap());
+        assert_eq!(schedule.poll(), Some(None));
+    }
+
+    #[test]
+    fn test_daemon_schedule_needs_rules() {
+        assert!(DaemonSchedule::load(Config::default()).is_none());
+    }
+}
@@ -7,25 +7,107 @@
 use crate::message::GpuStateSnapshot;
 use crate::state::GpuState;
 
-use nvctl::domain::{
-    ClockSpeed, ClockType, CoolerTarget, FanPolicy, FanSpeed, MemoryInfo, PerformanceState,
-    PowerLimit, Temperature, Utilization,
-};
+use nvctl::domain::{CoolerTarget, FanPolicy, FanSpeed, GpuProcess, PowerLimit, Temperature};
 use nvctl::error::NvmlError;
+use nvctl::health::HealthCalculator;
+use nvctl::history::{downsample, HistoryStore};
 use nvctl::nvml::traits::{GpuDevice, GpuManager};
-use nvctl::nvml::wrapper::NvmlManager;
-use std::time::Instant;
+use nvctl::nvml::{Backend, GpuSnapshot};
+use nvctl::recorder::RecordField;
+use nvctl::replay::ReplayOptions;
+use nvctl::services::AlertService;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// How much stored history to show in the charts at startup
+const HISTORY_WINDOW: Duration = Duration::from_secs(300);
+
+/// Recording to replay instead of NVML, set once at startup
+static REPLAY: OnceLock<ReplayOptions> = OnceLock::new();
+
+/// Make every GPU monitor replay a recording instead of using NVML
+pub fn set_replay(options: ReplayOptions) {
+    let _ = REPLAY.set(options);
+}
 
 /// GPU monitoring service
 pub struct GpuMonitor {
-    manager: Option<NvmlManager>,
+    manager: Option<Backend>,
+    history: Option<HistoryStore>,
+    alerts: Option<AlertService>,
 }
 
 impl GpuMonitor {
     /// Create a new GPU monitor
     pub fn new() -> Self {
-        let manager = NvmlManager::new().ok();
-        Self { manager }
+        let manager = match Backend::open(REPLAY.get()) {
+            Ok(manager) => Some(manager),
+            Err(e) => {
+                log::warn!("GPU backend unavailable: {}", e);
+                None
+            }
+        };
+        Self {
+            manager,
+            history: None,
+            alerts: None,
+        }
+    }
+
+    /// Fill the charts of detected GPUs from the on-disk history
+    pub fn with_history(mut self, history: HistoryStore) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Evaluate alert rules on every poll
+    pub fn with_alerts(mut self, alerts: AlertService) -> Self {
+        self.alerts = Some(alerts);
+        self
+    }
+
+    /// Stored values of a metric over the last `window`, oldest first
+    pub fn stored_history(&self, uuid: &str, field: RecordField, window: Duration) -> Vec<f32> {
+        let Some(store) = &self.history else {
+            return Vec::new();
+        };
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() as i64);
+        let since = now - window.as_secs() as i64;
+        let tier = store.tier_for(since, now);
+        let max_points = match tier.resolution() {
+            Some(resolution) => (window.as_secs() as i64 / resolution) as usize,
+            None => window.as_secs() as usize,
+        };
+
+        match store.query(uuid, tier, field, since, now) {
+            Ok(points) => downsample(points, max_points)
+                .into_iter()
+                .map(|p| p.avg as f32)
+                .collect(),
+            Err(e) => {
+                log::warn!("Failed to read history for {}: {}", uuid, e);
+                Vec::new()
+            }
+        }
+    }
+
+    /// Prefill the chart buffers of a GPU with the recent history
+    fn load_history(&self, state: &mut GpuState) {
+        if self.manager.as_ref().is_some_and(Backend::is_replay) {
+            return;
+        }
+        let uuid = state.info.uuid.clone();
+        for (field, series) in [
+            (RecordField::Temp, &mut state.temp_history),
+            (RecordField::Power, &mut state.power_history),
+            (RecordField::Util, &mut state.gpu_util_history),
+        ] {
+            for value in self.stored_history(&uuid, field, HISTORY_WINDOW) {
+                series.push(value);
+            }
+        }
     }
 
     /// Check if NVML is available
@@ -58,6 +140,7 @@ impl GpuMonitor {
                 }
 
                 let mut state = GpuState::new(info);
+                self.load_history(&mut state);
 
                 // Populate initial state
                 if let Ok(temp) = device.temperature() {
@@ -94,101 +177,43 @@ impl GpuMonitor {
     }
 
     /// Poll current state for a GPU
-    pub fn poll_gpu(&self, index: u32) -> Option<GpuStateSnapshot> {
+    pub fn poll_gpu(&mut self, index: u32) -> Option<GpuStateSnapshot> {
         let manager = self.manager.as_ref()?;
         let device = manager.device_by_index(index).ok()?;
 
-        let name = device.name().unwrap_or_else(|_| "Unknown GPU".to_string());
-        let temperature = device.temperature().unwrap_or(Temperature::new(0));
-        let power_usage = device.power_usage().unwrap_or(PowerLimit::from_watts(0));
-        let power_limit = device.power_limit().unwrap_or(PowerLimit::from_watts(0));
-
-        // Get clock speeds
-        let gpu_clock = device
-            .clock_speed(ClockType::Graphics)
-            .unwrap_or(ClockSpeed::default());
-        let mem_clock = device
-            .clock_speed(ClockType::Memory)
-            .unwrap_or(ClockSpeed::default());
-
-        // Get utilization
-        let utilization = device.utilization().unwrap_or(Utilization::default());
-
-        // Get memory info
-        let memory_info = device.memory_info().unwrap_or(MemoryInfo::default());
-
-        // Get performance state
-        let perf_state = device
-            .performance_state()
-            .unwrap_or(PerformanceState::default());
-
-        let mut fan_speeds = Vec::new();
-        let mut fan_policies = Vec::new();
-
-        if let Ok(fan_count) = device.fan_count() {
-            for i in 0..fan_count {
-                if let Ok(speed) = device.fan_speed(i) {
-                    fan_speeds.push(speed);
-                }
-                if let Ok(policy) = device.fan_policy(i) {
-                    fan_policies.push(policy);
-                }
+        // Read every metric once; the GUI state, health score and alerts share it
+        let snapshot = GpuSnapshot::capture(&device);
+        if let Some(alerts) = &mut self.alerts {
+            if let Err(e) = alerts.evaluate(&snapshot, index) {
+                log::warn!("Failed to evaluate alerts for GPU {}: {}", index, e);
             }
         }
+        let health_score = HealthCalculator::default()
+            .calculate_snapshot(&snapshot)
+            .map(|health| health.overall);
+
+        let name = snapshot.name.unwrap_or_else(|_| "Unknown GPU".to_string());
+        let temperature = snapshot.temperature.unwrap_or(Temperature::new(0));
+        let power_usage = snapshot.power_usage.unwrap_or(PowerLimit::from_watts(0));
+        let power_limit = snapshot.power_limit.unwrap_or(PowerLimit::from_watts(0));
+
+        // Get clock speeds
+        let gpu_clock = snapshot.graphics_clock.unwrap_or_default();
+        let mem_clock = snapshot.memory_clock.unwrap_or_default();
+
+        let utilization = snapshot.utilization.unwrap_or_default();
+        let memory_info = snapshot.memory_info.unwrap_or_default();
+        let perf_state = snapshot.performance_state.unwrap_or_default();
+
+        let fan_speeds = snapshot.fan_speeds.into_iter().flatten().collect();
+        let fan_policies = snapshot.fan_policies.into_iter().flatten().collect();
 
         // Get Phase 1 metrics
-        let memory_temperature = device.memory_temperature().ok().flatten();
-        let ecc_errors = device.ecc_errors().ok().flatten();
-        let pcie_metrics = device.pcie_metrics().ok();
-        let encoder_util = device.encoder_utilization().ok().flatten();
-        let decoder_util = device.decoder_utilization().ok().flatten();
-
-        // Calculate health score
-        let health_score = if let Ok(thresholds) = device.thermal_thresholds() {
-            let _power_constraints = device.power_constraints().ok();
-
-            // Determine throttling status
-            let is_thermal_throttling = if let Some(slowdown) = thresholds.slowdown {
-                temperature.as_celsius() >= slowdown.as_celsius()
-            } else {
-                false
-            };
-
-            let is_power_throttling =
-                power_usage.as_watts() as f64 >= power_limit.as_watts() as f64 * 0.99;
-
-            // Calculate VRAM usage ratio
-            let vram_usage_ratio = if memory_info.total > 0 {
-                Some(memory_info.used as f64 / memory_info.total as f64)
-            } else {
-                None
-            };
-
-            // Use uptime estimate of 1 hour for ECC error rate
-            let uptime_seconds = 3600;
-
-            // Build health params
-            let params = nvctl::health::HealthParams {
-                temperature,
-                thresholds: &thresholds,
-                power_usage,
-                power_limit,
-                is_thermal_throttling,
-                is_power_throttling,
-                ecc_errors: ecc_errors.as_ref(),
-                vram_usage_ratio,
-                utilization: Some(&utilization),
-                pcie_metrics: pcie_metrics.as_ref(),
-                uptime_seconds,
-            };
-
-            // Calculate health using default weights
-            let calculator = nvctl::health::HealthCalculator::default();
-            let health = calculator.calculate(&params);
-            Some(health.overall)
-        } else {
-            None
-        };
+        let memory_temperature = snapshot.memory_temperature.ok().flatten();
+        let ecc_errors = snapshot.ecc_errors.ok().flatten();
+        let pcie_metrics = snapshot.pcie_metrics.ok();
+        let encoder_util = snapshot.encoder_utilization.ok().flatten();
+        let decoder_util = snapshot.decoder_utilization.ok().flatten();
 
         Some(GpuStateSnapshot {
             index,
@@ -319,6 +344,42 @@ impl GpuMonitor {
             .map_err(|e| Self::format_nvml_error(e, "set power limit"))
     }
 
+    /// Get the current acoustic temperature limit of a GPU
+    pub fn acoustic_limit(&self, gpu_index: u32) -> Option<Temperature> {
+        let device = self.manager.as_ref()?.device_by_index(gpu_index).ok()?;
+        device.acoustic_limits().ok()?.current
+    }
+
+    /// Set the acoustic temperature limit of a GPU
+    pub fn set_acoustic_limit(&self, gpu_index: u32, limit: Temperature) -> Result<(), String> {
+        let manager = self
+            .manager
+            .as_ref()
+            .ok_or_else(|| "NVML not available".to_string())?;
+
+        let mut device = manager
+            .device_by_index(gpu_index)
+            .map_err(|e| format!("Failed to get GPU {}: {}", gpu_index, e))?;
+
+        device
+            .set_acoustic_limit(limit)
+            .map_err(|e| Self::format_nvml_error(e, "set acoustic limit"))
+    }
+
+    /// Get the processes running on all GPUs
+    pub fn running_processes(&self) -> Vec<GpuProcess> {
+        let Some(ref manager) = self.manager else {
+            return Vec::new();
+        };
+
+        let count = manager.device_count().unwrap_or(0);
+        (0..count)
+            .filter_map(|index| manager.device_by_index(index).ok())
+            .filter_map(|device| device.running_processes().ok())
+            .flat_map(|list| list.processes)
+            .collect()
+    }
+
     /// Format NVML error into user-friendly message
     fn format_nvml_error(error: NvmlError, operation: &str) -> String {
         match error {
@@ -10,7 +10,7 @@ mod tray;
 
 #[allow(unused_imports)]
 pub use config::{GpuFanConfig, GuiConfig, Preferences};
-pub use curve_daemon::CurveDaemon;
-pub use gpu_monitor::GpuMonitor;
-pub use profiles::{GpuSettings, Profile, ProfileService};
+pub use curve_daemon::{CurveDaemon, FanCurveConfig};
+pub use gpu_monitor::{set_replay, GpuMonitor};
+pub use profiles::{GpuSettings, ProcessSwitch, Profile, ProfileService};
 pub use tray::{start_tray, TrayHandle};
@@ -2,12 +2,14 @@
 //!
 //! Handles saving, loading, and managing GPU configuration profiles.
 
-use nvctl::domain::{FanCurve, PowerLimit, Temperature};
+use nvctl::domain::{FanCurve, GpuProcess, PowerLimit, Temperature};
+use nvctl::services::ProcessWatcher;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::PathBuf;
+use std::time::Instant;
 use thiserror::Error;
 
 /// Profile management errors
@@ -151,6 +153,15 @@ impl GpuSettings {
     }
 }
 
+/// Profile change requested by process rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessSwitch {
+    /// A rule started matching; apply its profile
+    Apply(String),
+    /// No rule matches any more; restore the profile active before
+    Restore(Option<String>),
+}
+
 /// Profile service for managing profiles
 #[allow(dead_code)]
 pub struct ProfileService {
@@ -162,6 +173,12 @@ pub struct ProfileService {
 
     /// Currently active profile name
     active_profile: Option<String>,
+
+    /// Process rules from the nvctl config file
+    process_watcher: Option<ProcessWatcher>,
+
+    /// Profile to restore once process rules stop matching
+    restore_profile: Option<Option<String>>,
 }
 
 #[allow(dead_code)]
@@ -174,6 +191,8 @@ impl ProfileService {
             profiles_dir,
             profiles: HashMap::new(),
             active_profile: None,
+            process_watcher: None,
+            restore_profile: None,
         };
 
         // Try to load existing profiles
@@ -338,6 +357,54 @@ impl ProfileService {
         self.active_profile = name;
     }
 
+    /// Switch profiles while GPU processes match the given rules
+    pub fn set_process_watcher(&mut self, watcher: ProcessWatcher) {
+        self.process_watcher = Some(watcher);
+    }
+
+    /// Check if process rules are configured
+    pub fn has_process_rules(&self) -> bool {
+        self.process_watcher.is_some()
+    }
+
+    /// Check running GPU processes against the process rules
+    ///
+    /// Returns the switch to perform when the matching rule changes.
+    pub fn check_processes(
+        &mut self,
+        processes: &[GpuProcess],
+        now: Instant,
+    ) -> Option<ProcessSwitch> {
+        let watcher = self.process_watcher.as_mut()?;
+        if !watcher.update(processes, now) {
+            return None;
+        }
+
+        match watcher.active_profile() {
+            Some(name) => {
+                if self.restore_profile.is_none() {
+                    self.restore_profile = Some(self.active_profile.clone());
+                }
+                Some(ProcessSwitch::Apply(name.to_string()))
+            }
+            None => Some(ProcessSwitch::Restore(
+                self.restore_profile.take().flatten(),
+            )),
+        }
+    }
+
+    /// Check if a process rule currently controls the active profile
+    pub fn process_rule_active(&self) -> bool {
+        self.restore_profile.is_some()
+    }
+
+    /// Change the profile restored after process rules stop matching
+    pub fn set_restore_profile(&mut self, name: Option<String>) {
+        if self.restore_profile.is_some() {
+            self.restore_profile = Some(name);
+        }
+    }
+
     /// Get the default profile (marked as is_default)
     pub fn default_profile(&self) -> Option<&Profile> {
         self.profiles.values().find(|p| p.is_default)
@@ -491,6 +558,46 @@ mod tests {
         assert!(ProfileService::validate_name("invalid/name").is_err());
     }
 
+    #[test]
+    fn test_check_processes_restores_previous_profile() {
+        use nvctl::config::ProcessTriggerConfig;
+        use nvctl::domain::ProcessType;
+
+        let config: ProcessTriggerConfig = toml::from_str(
+            r#"
+            debounce_secs = 0
+
+            [[rules]]
+            profile = "Performance"
+            name = "blender"
+            "#,
+        )
+        .unwrap();
+        let mut service = ProfileService {
+            profiles_dir: PathBuf::from("unused"),
+            profiles: HashMap::new(),
+            active_profile: Some("Silent".to_string()),
+            process_watcher: Some(ProcessWatcher::new(&config).unwrap()),
+            restore_profile: None,
+        };
+
+        let blender = GpuProcess::with_name(1, "blender".to_string(), 0, ProcessType::Graphics);
+        let now = Instant::now();
+        assert_eq!(service.check_processes(&[], now), None);
+        assert_eq!(
+            service.check_processes(&[blender], now),
+            Some(ProcessSwitch::Apply("Performance".to_string()))
+        );
+        assert!(service.process_rule_active());
+
+        service.set_active(Some("Performance".to_string()));
+        assert_eq!(
+            service.check_processes(&[], now),
+            Some(ProcessSwitch::Restore(Some("Silent".to_string())))
+        );
+        assert!(!service.process_rule_active());
+    }
+
     #[test]
     fn test_profile_serialization() {
         let profile = Profile::new("Test")
@@ -5,6 +5,7 @@
 use super::types::{AlertRule, AlertSeverity, Condition, GpuFilter, MetricType};
 use crate::error::{ConfigError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
@@ -18,6 +19,9 @@ pub struct AlertConfig {
     /// Alert rules
     #[serde(default)]
     pub rules: Vec<AlertRuleConfig>,
+    /// Notification channels, the terminal if none are configured
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifiers: Vec<NotifierConfig>,
 }
 
 impl AlertConfig {
@@ -40,6 +44,16 @@ impl AlertConfig {
         Ok(())
     }
 
+    /// Load the configuration at the default path, or the defaults if there is none
+    pub fn load_or_default() -> Result<Self> {
+        let path = Self::default_path();
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
     /// Get default configuration path
     pub fn default_path() -> PathBuf {
         if let Some(config_dir) = dirs::config_dir() {
@@ -120,7 +134,19 @@ impl AlertConfig {
                     gpu_filter: "all".to_string(),
                     enabled: true,
                 },
+                // Critical Xid errors reported by the driver
+                AlertRuleConfig {
+                    id: "xid-error".to_string(),
+                    name: "Critical Xid Error".to_string(),
+                    metric: "xid_error".to_string(),
+                    condition: ConditionConfig::GreaterThan(0.0),
+                    severity: "critical".to_string(),
+                    duration_secs: None,
+                    gpu_filter: "all".to_string(),
+                    enabled: true,
+                },
             ],
+            notifiers: Vec::new(),
         }
     }
 
@@ -132,6 +158,38 @@ impl AlertConfig {
             .map(|r| r.to_alert_rule())
             .collect()
     }
+
+    /// Convert the rules of one kind to alert rules
+    pub fn rules_of(&self, kind: RuleKind) -> Result<Vec<AlertRule>> {
+        Ok(self
+            .to_alert_rules()?
+            .into_iter()
+            .filter(|rule| kind.matches(rule))
+            .collect())
+    }
+}
+
+/// Which alert rules a consumer can evaluate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// Rules on polled metrics
+    Metric,
+    /// Rules on NVML events, which need an event listener
+    Event,
+}
+
+impl RuleKind {
+    /// Check if a rule is of this kind
+    pub fn matches(self, rule: &AlertRule) -> bool {
+        rule.metric.is_event() == (self == Self::Event)
+    }
+}
+
+/// Metric alert rules from the alert config (or the defaults)
+///
+/// Event rules are left out; the dashboard and exporter don't subscribe to NVML events.
+pub fn metric_rules() -> Result<Vec<AlertRule>> {
+    AlertConfig::load_or_default()?.rules_of(RuleKind::Metric)
 }
 
 impl Default for AlertConfig {
@@ -246,6 +304,12 @@ impl AlertRuleConfig {
             "ecc_uncorrectable_errors" => Ok(MetricType::EccUncorrectableErrors),
             "pcie_throughput" => Ok(MetricType::PcieThroughput),
             "pcie_replay_counter" => Ok(MetricType::PcieReplayCounter),
+            "xid_error" => Ok(MetricType::XidError),
+            "single_bit_ecc_event" => Ok(MetricType::SingleBitEccEvent),
+            "double_bit_ecc_event" => Ok(MetricType::DoubleBitEccEvent),
+            "pstate_change" => Ok(MetricType::PStateChange),
+            "clock_change" => Ok(MetricType::ClockChange),
+            "power_source_change" => Ok(MetricType::PowerSourceChange),
             _ => Err(ConfigError::InvalidValue {
                 key: "metric".to_string(),
                 message: format!("Unknown metric type: {}", self.metric),
@@ -285,6 +349,239 @@ impl AlertRuleConfig {
     }
 }
 
+/// A notification channel (`[[notifiers]]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierConfig {
+    /// Print alerts to the terminal
+    Terminal {
+        /// Print to stdout instead of stderr
+        #[serde(default)]
+        stdout: bool,
+    },
+    /// POST alerts to an HTTP endpoint
+    Webhook(WebhookConfig),
+    /// Desktop notifications on the session bus
+    Desktop,
+    /// Email digests over SMTP
+    Email(EmailConfig),
+    /// Run a command for every alert
+    Exec(ExecConfig),
+    /// Write to the systemd journal, or to syslog without it
+    Journal(JournalConfig),
+}
+
+impl NotifierConfig {
+    /// The `type` of the channel
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Terminal { .. } => "terminal",
+            Self::Webhook(_) => "webhook",
+            Self::Desktop => "desktop",
+            Self::Email(_) => "email",
+            Self::Exec(_) => "exec",
+            Self::Journal(_) => "journal",
+        }
+    }
+}
+
+/// Webhook notification channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Endpoint URL
+    pub url: String,
+    /// Payload format
+    #[serde(default)]
+    pub format: WebhookFormat,
+    /// Extra request headers, e.g. for authentication
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Request timeout in seconds
+    #[serde(default = "default_webhook_timeout")]
+    pub timeout_secs: u64,
+    /// Retries after a failed request
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+}
+
+fn default_webhook_timeout() -> u64 {
+    10
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+/// Payload format of a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookFormat {
+    /// The alert's fields as a JSON object
+    #[default]
+    Json,
+    /// Slack incoming webhook
+    Slack,
+    /// Discord webhook
+    Discord,
+    /// Microsoft Teams incoming webhook
+    Teams,
+}
+
+/// Email notification channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    /// SMTP server host name
+    pub host: String,
+    /// SMTP server port, by default 587, 465 or 25 depending on `tls`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Connection encryption
+    #[serde(default)]
+    pub tls: EmailTls,
+    /// SMTP user name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// SMTP password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// Sender address, e.g. `nvctl <gpu@example.com>`
+    pub from: String,
+    /// Recipient addresses
+    pub to: Vec<String>,
+    /// Least severe alerts to send
+    #[serde(default = "default_email_severity")]
+    pub min_severity: AlertSeverity,
+    /// Seconds to collect alerts into one message
+    #[serde(default = "default_digest_window")]
+    pub digest_window_secs: u64,
+    /// Connection timeout in seconds
+    #[serde(default = "default_webhook_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl EmailConfig {
+    /// Configured port or the standard port of the encryption mode
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.tls {
+            EmailTls::Starttls => 587,
+            EmailTls::Tls => 465,
+            EmailTls::None => 25,
+        })
+    }
+}
+
+fn default_email_severity() -> AlertSeverity {
+    AlertSeverity::Critical
+}
+
+fn default_digest_window() -> u64 {
+    60
+}
+
+/// Encryption of the SMTP connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTls {
+    /// Upgrade a plain connection with STARTTLS
+    #[default]
+    Starttls,
+    /// Implicit TLS from the start of the connection
+    Tls,
+    /// No encryption, for local relays only
+    None,
+}
+
+/// Command notification channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecConfig {
+    /// Program and its arguments
+    pub command: Vec<String>,
+    /// Seconds before a running command is killed
+    #[serde(default = "default_exec_timeout")]
+    pub timeout_secs: u64,
+    /// Commands allowed to run at the same time
+    #[serde(default = "default_exec_concurrency")]
+    pub max_concurrent: usize,
+    /// Severities to run the command for, all if empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub severities: Vec<AlertSeverity>,
+}
+
+fn default_exec_timeout() -> u64 {
+    30
+}
+
+fn default_exec_concurrency() -> usize {
+    4
+}
+
+/// Journal notification channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfig {
+    /// Native protocol socket of systemd-journald
+    #[serde(default = "default_journal_socket")]
+    pub journal_socket: PathBuf,
+    /// Syslog used when journald is not running: a socket path or `udp://host:port`
+    #[serde(default = "default_syslog")]
+    pub syslog: String,
+    /// Syslog facility
+    #[serde(default)]
+    pub facility: SyslogFacility,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            journal_socket: default_journal_socket(),
+            syslog: default_syslog(),
+            facility: SyslogFacility::default(),
+        }
+    }
+}
+
+fn default_journal_socket() -> PathBuf {
+    PathBuf::from("/run/systemd/journal/socket")
+}
+
+fn default_syslog() -> String {
+    "/dev/log".to_string()
+}
+
+/// Syslog facility of journal and syslog entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyslogFacility {
+    User,
+    #[default]
+    Daemon,
+    Local0,
+    Local1,
+    Local2,
+    Local3,
+    Local4,
+    Local5,
+    Local6,
+    Local7,
+}
+
+impl SyslogFacility {
+    /// Facility code of RFC 5424
+    pub fn code(self) -> u8 {
+        match self {
+            Self::User => 1,
+            Self::Daemon => 3,
+            Self::Local0 => 16,
+            Self::Local1 => 17,
+            Self::Local2 => 18,
+            Self::Local3 => 19,
+            Self::Local4 => 20,
+            Self::Local5 => 21,
+            Self::Local6 => 22,
+            Self::Local7 => 23,
+        }
+    }
+}
+
 /// Condition configuration (TOML-friendly format)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -410,6 +707,80 @@ mod tests {
         assert!(rules.iter().all(|r| r.enabled));
     }
 
+    #[test]
+    fn test_rules_of_kind() {
+        let config = AlertConfig::default();
+        let metric = config.rules_of(RuleKind::Metric).unwrap();
+        let event = config.rules_of(RuleKind::Event).unwrap();
+        assert_eq!(metric.len() + event.len(), config.rules.len());
+        assert!(metric.iter().all(|r| !r.metric.is_event()));
+        assert_eq!(event.len(), 1);
+        assert_eq!(event[0].id, "xid-error");
+    }
+
+    #[test]
+    fn test_parse_notifiers() {
+        let config: AlertConfig = toml::from_str(
+            r#"
+            [[notifiers]]
+            type = "terminal"
+
+            [[notifiers]]
+            type = "webhook"
+            url = "https://hooks.slack.com/services/T0/B0/X"
+            format = "slack"
+            headers = { "X-Token" = "secret" }
+
+            [[notifiers]]
+            type = "email"
+            host = "smtp.example.com"
+            tls = "tls"
+            from = "nvctl <gpu@example.com>"
+            to = ["oncall@example.com"]
+            min_severity = "emergency"
+
+            [[notifiers]]
+            type = "exec"
+            command = ["/usr/local/bin/page", "--team", "gpu"]
+            severities = ["critical", "emergency"]
+
+            [[notifiers]]
+            type = "journal"
+            syslog = "udp://logs.example.com:514"
+            facility = "local3"
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(
+            config.notifiers[0],
+            NotifierConfig::Terminal { stdout: false }
+        ));
+        let NotifierConfig::Webhook(webhook) = &config.notifiers[1] else {
+            panic!("expected a webhook");
+        };
+        assert_eq!(webhook.format, WebhookFormat::Slack);
+        assert_eq!(webhook.headers["X-Token"], "secret");
+        assert_eq!(webhook.retries, 3);
+        let NotifierConfig::Email(email) = &config.notifiers[2] else {
+            panic!("expected an email channel");
+        };
+        assert_eq!(email.port(), 465);
+        assert_eq!(email.min_severity, AlertSeverity::Emergency);
+        assert_eq!(email.digest_window_secs, 60);
+        let NotifierConfig::Exec(exec) = &config.notifiers[3] else {
+            panic!("expected a command");
+        };
+        assert_eq!(exec.command[0], "/usr/local/bin/page");
+        assert_eq!(exec.severities.len(), 2);
+        assert_eq!(exec.max_concurrent, 4);
+        let NotifierConfig::Journal(journal) = &config.notifiers[4] else {
+            panic!("expected a journal");
+        };
+        assert_eq!(journal.journal_socket, default_journal_socket());
+        assert_eq!(journal.facility.code(), 19);
+    }
+
     #[test]
     fn test_condition_config_conversion() {
         let config = ConditionConfig::GreaterThan(80.0);
@@ -0,0 +1,276 @@
+//! Desktop notifications
+//!
+//! Alerts are shown through the freedesktop notification service
+//! (`org.freedesktop.Notifications`) on the session bus. Updates of an alert
+//! replace its notification in place, and a resolved alert replaces it with
+//! a low-urgency notice.
+
+use super::notifier::Notifier;
+use super::types::{Alert, AlertSeverity, AlertState};
+use crate::error::{NotifyError, Result};
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use zbus::blocking::Connection;
+use zbus::zvariant::Value;
+
+const DESTINATION: &str = "org.freedesktop.Notifications";
+const PATH: &str = "/org/freedesktop/Notifications";
+
+/// Notification urgency levels of the specification
+const URGENCY_LOW: u8 = 0;
+const URGENCY_NORMAL: u8 = 1;
+const URGENCY_CRITICAL: u8 = 2;
+
+/// Sends alerts to the desktop notification service
+pub struct DesktopNotifier {
+    connection: Connection,
+    /// Notification IDs by alert ID, for replacing notifications in place
+    ids: Mutex<HashMap<String, u32>>,
+}
+
+impl DesktopNotifier {
+    /// Connect to the session bus
+    pub fn connect() -> Result<Self> {
+        let connection = Connection::session().map_err(NotifyError::from)?;
+        Ok(Self::new(connection))
+    }
+
+    /// Use an existing bus connection
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            ids: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Icon, urgency and expiry (0 = until dismissed, -1 = server default)
+fn appearance(alert: &Alert) -> (&'static str, u8, i32) {
+    if alert.state == AlertState::Resolved {
+        return ("emblem-default", URGENCY_LOW, -1);
+    }
+    match alert.severity {
+        AlertSeverity::Info => ("dialog-information", URGENCY_LOW, -1),
+        AlertSeverity::Warning => ("dialog-warning", URGENCY_NORMAL, -1),
+        AlertSeverity::Critical | AlertSeverity::Emergency => ("dialog-error", URGENCY_CRITICAL, 0),
+    }
+}
+
+fn summary(alert: &Alert) -> String {
+    if alert.state == AlertState::Resolved {
+        format!("GPU {}: resolved", alert.gpu_index)
+    } else {
+        format!("GPU {}: {}", alert.gpu_index, alert.severity)
+    }
+}
+
+impl Notifier for DesktopNotifier {
+    fn notify(&self, alert: &Alert) -> Result<()> {
+        let mut ids = self.ids.lock().unwrap_or_else(|e| e.into_inner());
+        let replaces_id = ids.get(&alert.id).copied().unwrap_or(0);
+        let (icon, urgency, expire_timeout) = appearance(alert);
+        let category = if alert.state == AlertState::Resolved {
+            "device"
+        } else {
+            "device.error"
+        };
+        let hints = HashMap::from([
+            ("urgency", Value::from(urgency)),
+            ("category", Value::from(category)),
+        ]);
+
+        let reply = self
+            .connection
+            .call_method(
+                Some(DESTINATION),
+                PATH,
+                Some(DESTINATION),
+                "Notify",
+                &(
+                    "nvctl",
+                    replaces_id,
+                    icon,
+                    summary(alert),
+                    &alert.message,
+                    Vec::<&str>::new(),
+                    hints,
+                    expire_timeout,
+                ),
+            )
+            .map_err(NotifyError::from)?;
+        let id: u32 = reply.body().deserialize().map_err(NotifyError::from)?;
+
+        if alert.state == AlertState::Resolved {
+            ids.remove(&alert.id);
+        } else {
+            ids.insert(alert.id.clone(), id);
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "desktop"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::types::firing_alert;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::Arc;
+    use zbus::zvariant::OwnedValue;
+
+    /// A private bus, stopped on drop
+    struct Bus {
+        daemon: Child,
+        address: String,
+        _dir: tempfile::TempDir,
+    }
+
+    impl Bus {
+        fn start() -> Option<Self> {
+            let dir = tempfile::tempdir().unwrap();
+            let config = dir.path().join("bus.conf");
+            std::fs::write(
+                &config,
+                format!(
+                    r#"<busconfig>
+  <type>session</type>
+  <listen>unix:path={}</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>"#,
+                    dir.path().join("bus").display()
+                ),
+            )
+            .unwrap();
+
+            let mut daemon = Command::new("dbus-daemon")
+                .arg(format!("--config-file={}", config.display()))
+                .args(["--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+            Some(Self {
+                daemon,
+                address: address.trim().to_string(),
+                _dir: dir,
+            })
+        }
+
+        fn connect(&self) -> zbus::blocking::connection::Builder<'static> {
+            zbus::blocking::connection::Builder::address(self.address.as_str()).unwrap()
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    /// (replaces_id, icon, summary, urgency, expire_timeout)
+    type Call = (u32, String, String, u8, i32);
+
+    /// Notification service stand-in that records calls
+    struct Server {
+        calls: Arc<Mutex<Vec<Call>>>,
+        next_id: u32,
+    }
+
+    #[zbus::interface(name = "org.freedesktop.Notifications")]
+    impl Server {
+        #[allow(clippy::too_many_arguments)]
+        fn notify(
+            &mut self,
+            _app_name: String,
+            replaces_id: u32,
+            app_icon: String,
+            summary: String,
+            _body: String,
+            _actions: Vec<String>,
+            hints: HashMap<String, OwnedValue>,
+            expire_timeout: i32,
+        ) -> u32 {
+            let urgency = u8::try_from(&hints["urgency"]).unwrap();
+            self.calls.lock().unwrap().push((
+                replaces_id,
+                app_icon,
+                summary,
+                urgency,
+                expire_timeout,
+            ));
+            if replaces_id != 0 {
+                return replaces_id;
+            }
+            self.next_id += 1;
+            self.next_id
+        }
+    }
+
+    #[test]
+    #[ignore = "Requires dbus-daemon"]
+    fn test_notify_replaces_and_resolves() {
+        let bus = Bus::start().expect("dbus-daemon not found");
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let _server = bus
+            .connect()
+            .name(DESTINATION)
+            .unwrap()
+            .serve_at(
+                PATH,
+                Server {
+                    calls: Arc::clone(&calls),
+                    next_id: 0,
+                },
+            )
+            .unwrap()
+            .build()
+            .unwrap();
+        let notifier = DesktopNotifier::new(bus.connect().build().unwrap());
+
+        let mut alert = firing_alert(AlertSeverity::Critical, 0);
+        notifier.notify(&alert).unwrap();
+        alert.update_value(93.0);
+        notifier.notify(&alert).unwrap();
+        alert.resolve();
+        notifier.notify(&alert).unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(
+            calls[0],
+            (
+                0,
+                "dialog-error".to_string(),
+                "GPU 0: CRITICAL".to_string(),
+                2,
+                0
+            )
+        );
+        assert_eq!(calls[1].0, 1);
+        assert_eq!(
+            calls[2],
+            (
+                1,
+                "emblem-default".to_string(),
+                "GPU 0: resolved".to_string(),
+                0,
+                -1
+            )
+        );
+        assert!(notifier.ids.lock().unwrap().is_empty());
+    }
+}
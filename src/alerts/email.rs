@@ -0,0 +1,350 @@
+//! Email notifications
+//!
+//! Alerts are collected for a configurable window and sent over SMTP as one
+//! plain-text digest, so a burst of alerts on several GPUs pages once.
+
+use super::config::{EmailConfig, EmailTls};
+use super::notifier::Notifier;
+use super::types::{Alert, AlertSeverity};
+use crate::error::{NotifyError, Result};
+
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{Message, SmtpTransport, Transport};
+use std::fmt::Write;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Alerts waiting for the digest window to close
+#[derive(Default)]
+struct Queue {
+    alerts: Vec<Alert>,
+    /// Set when the notifier is dropped, to flush and stop the worker
+    closed: bool,
+}
+
+type Shared = Arc<(Mutex<Queue>, Condvar)>;
+
+/// SMTP connection and envelope of the digests
+struct Mailer {
+    transport: SmtpTransport,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl Mailer {
+    fn send(&self, alerts: &[Alert]) -> std::result::Result<(), NotifyError> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(subject(alerts))
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+        let message = builder
+            .body(body(alerts))
+            .map_err(|e| NotifyError::Email(e.to_string()))?;
+        self.transport
+            .send(&message)
+            .map_err(|e| NotifyError::Email(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// Sends alert digests by email
+///
+/// Alerts below the configured severity are ignored. The first queued alert
+/// opens the digest window; everything queued until it closes is sent in
+/// one message. Pending alerts are flushed when the notifier is dropped.
+pub struct EmailNotifier {
+    min_severity: AlertSeverity,
+    shared: Shared,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl EmailNotifier {
+    /// Create a notifier from its configuration
+    pub fn new(config: &EmailConfig) -> Result<Self> {
+        let mailer = Mailer {
+            transport: transport(config)?,
+            from: mailbox(&config.from)?,
+            to: config
+                .to
+                .iter()
+                .map(String::as_str)
+                .map(mailbox)
+                .collect::<Result<_>>()?,
+        };
+        if mailer.to.is_empty() {
+            return Err(NotifyError::Email("no recipients configured".to_string()).into());
+        }
+
+        let window = Duration::from_secs(config.digest_window_secs);
+        Ok(Self::start(mailer, window, config.min_severity))
+    }
+
+    fn start(mailer: Mailer, window: Duration, min_severity: AlertSeverity) -> Self {
+        let shared: Shared = Arc::default();
+        let worker = {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || run(&shared, &mailer, window))
+        };
+        Self {
+            min_severity,
+            shared,
+            worker: Some(worker),
+        }
+    }
+}
+
+fn transport(config: &EmailConfig) -> Result<SmtpTransport> {
+    let tls_parameters =
+        || TlsParameters::new(config.host.clone()).map_err(|e| NotifyError::Email(e.to_string()));
+    let tls = match config.tls {
+        EmailTls::Starttls => Tls::Required(tls_parameters()?),
+        EmailTls::Tls => Tls::Wrapper(tls_parameters()?),
+        EmailTls::None => Tls::None,
+    };
+
+    let mut builder = SmtpTransport::builder_dangerous(&config.host)
+        .port(config.port())
+        .tls(tls)
+        .timeout(Some(Duration::from_secs(config.timeout_secs)));
+    if let Some(username) = &config.username {
+        let password = config.password.clone().unwrap_or_default();
+        builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+    Ok(builder.build())
+}
+
+fn mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .parse()
+        .map_err(|e| NotifyError::Email(format!("invalid address '{}': {}", address, e)).into())
+}
+
+/// Worker loop: wait for an alert, hold the window open, send the digest
+fn run(shared: &Shared, mailer: &Mailer, window: Duration) {
+    let (queue, ready) = &**shared;
+    loop {
+        let mut guard = queue.lock().unwrap_or_else(|e| e.into_inner());
+        while guard.alerts.is_empty() && !guard.closed {
+            guard = ready.wait(guard).unwrap_or_else(|e| e.into_inner());
+        }
+        let deadline = Instant::now() + window;
+        while !guard.closed {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            guard = ready
+                .wait_timeout(guard, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        let alerts = std::mem::take(&mut guard.alerts);
+        let closed = guard.closed;
+        drop(guard);
+
+        if !alerts.is_empty() {
+            if let Err(e) = mailer.send(&alerts) {
+                log::error!(
+                    "Failed to send alert digest ({} alerts): {}",
+                    alerts.len(),
+                    e
+                );
+            }
+        }
+        if closed {
+            return;
+        }
+    }
+}
+
+/// Subject naming the alert, or the count and highest severity of a digest
+fn subject(alerts: &[Alert]) -> String {
+    match alerts {
+        [alert] => format!(
+            "[nvctl] {} GPU {}: {}",
+            alert.severity, alert.gpu_index, alert.message
+        ),
+        _ => {
+            let highest = alerts.iter().map(|a| a.severity).max();
+            format!(
+                "[nvctl] {} GPU alerts (highest: {})",
+                alerts.len(),
+                highest.map(|s| s.to_string()).unwrap_or_default()
+            )
+        }
+    }
+}
+
+/// Plain-text digest body with one block per alert
+fn body(alerts: &[Alert]) -> String {
+    let mut body = format!("nvctl raised {} alert(s):\n", alerts.len());
+    for alert in alerts {
+        let _ = write!(
+            body,
+            "\n[{}] {}\n  GPU:       {}{}\n  UUID:      {}\n  Metric:    {}\n  Value:     {:.2}\n  Threshold: {:.2}\n  State:     {}\n  Time:      {}\n  Alert ID:  {}\n",
+            alert.severity,
+            alert.message,
+            alert.gpu_index,
+            alert
+                .gpu_name
+                .as_deref()
+                .map(|name| format!(" ({})", name))
+                .unwrap_or_default(),
+            alert.gpu_uuid.as_deref().unwrap_or("unknown"),
+            alert.metric,
+            alert.current_value,
+            alert.threshold_value,
+            alert.state,
+            timestamp(alert),
+            alert.id,
+        );
+    }
+    body
+}
+
+/// When the alert last changed state, in RFC 3339
+fn timestamp(alert: &Alert) -> String {
+    let time: SystemTime = alert
+        .resolved_at
+        .or(alert.fired_at)
+        .unwrap_or(alert.started_at);
+    jiff::Timestamp::try_from(time)
+        .map(|t| t.to_string())
+        .unwrap_or_default()
+}
+
+impl Notifier for EmailNotifier {
+    fn notify(&self, alert: &Alert) -> Result<()> {
+        self.notify_batch(std::slice::from_ref(alert))
+    }
+
+    fn notify_batch(&self, alerts: &[Alert]) -> Result<()> {
+        let (queue, ready) = &*self.shared;
+        let mut guard = queue.lock().unwrap_or_else(|e| e.into_inner());
+        let before = guard.alerts.len();
+        guard.alerts.extend(
+            alerts
+                .iter()
+                .filter(|a| a.severity >= self.min_severity)
+                .cloned(),
+        );
+        if guard.alerts.len() > before {
+            ready.notify_one();
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "email"
+    }
+}
+
+impl Drop for EmailNotifier {
+    fn drop(&mut self) {
+        let (queue, ready) = &*self.shared;
+        queue.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+        ready.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::types::firing_alert;
+    use std::io::{BufRead, BufReader, Write as _};
+    use std::net::TcpListener;
+
+    /// Minimal SMTP server that records the DATA of each message
+    fn smtp_sink(listener: TcpListener, messages: usize) -> Vec<String> {
+        let mut received = Vec::new();
+        while received.len() < messages {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            writer.write_all(b"220 sink ESMTP\r\n").unwrap();
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                let command = line.trim_end().to_ascii_uppercase();
+                line.clear();
+                if command.starts_with("EHLO") {
+                    writer.write_all(b"250 sink\r\n").unwrap();
+                } else if command == "DATA" {
+                    writer.write_all(b"354 go ahead\r\n").unwrap();
+                    let mut data = String::new();
+                    while reader.read_line(&mut line).unwrap() > 0 && line != ".\r\n" {
+                        data.push_str(&line);
+                        line.clear();
+                    }
+                    line.clear();
+                    received.push(data);
+                    writer.write_all(b"250 queued\r\n").unwrap();
+                } else if command == "QUIT" {
+                    writer.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    writer.write_all(b"250 ok\r\n").unwrap();
+                }
+            }
+        }
+        received
+    }
+
+    fn alert(severity: AlertSeverity, gpu_index: u32) -> Alert {
+        firing_alert(severity, gpu_index).with_gpu(
+            Some("NVIDIA GeForce RTX 4090".to_string()),
+            Some(format!("GPU-{}", gpu_index)),
+        )
+    }
+
+    #[test]
+    fn test_digest_sent_to_smtp_sink() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let sink = std::thread::spawn(move || smtp_sink(listener, 1));
+
+        let config: EmailConfig = toml::from_str(&format!(
+            r#"
+            host = "127.0.0.1"
+            port = {}
+            tls = "none"
+            from = "nvctl <gpu@example.com>"
+            to = ["oncall@example.com"]
+            digest_window_secs = 1
+            "#,
+            port
+        ))
+        .unwrap();
+        let notifier = EmailNotifier::new(&config).unwrap();
+        notifier
+            .notify_batch(&[
+                alert(AlertSeverity::Critical, 0),
+                alert(AlertSeverity::Warning, 0),
+            ])
+            .unwrap();
+        notifier
+            .notify(&alert(AlertSeverity::Emergency, 1))
+            .unwrap();
+
+        let received = sink.join().unwrap();
+        drop(notifier);
+        assert_eq!(received.len(), 1);
+        let message = &received[0];
+        assert!(message.contains("Subject: [nvctl] 2 GPU alerts (highest: EMERGENCY)"));
+        assert!(message.contains("[CRITICAL] Critical GPU Temperature"));
+        assert!(message.contains("[EMERGENCY] Critical GPU Temperature"));
+        assert!(!message.contains("[WARNING]"));
+        assert!(message.contains("GPU:       1 (NVIDIA GeForce RTX 4090)"));
+        assert!(message.contains("UUID:      GPU-1"));
+        assert!(message.contains("Threshold: 85.00"));
+    }
+}
@@ -0,0 +1,325 @@
+//! Command notifications
+//!
+//! Runs a configured command for every alert, e.g. an existing paging
+//! script. The alert's fields are passed as `NVCTL_*` environment variables
+//! and as the JSON webhook payload on stdin.
+
+use super::config::{ExecConfig, WebhookFormat};
+use super::notifier::Notifier;
+use super::types::{Alert, AlertSeverity};
+use super::webhook::payload;
+use crate::error::{NotifyError, Result};
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// How often a running command is checked for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long to wait for stderr after the command exited
+const STDERR_GRACE: Duration = Duration::from_secs(1);
+
+/// Alerts waiting for a free command slot before new ones are dropped
+const MAX_QUEUED: usize = 64;
+
+/// The configured command
+struct Runner {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+    max_concurrent: usize,
+}
+
+impl Runner {
+    fn new(config: &ExecConfig) -> Result<Self> {
+        let Some((program, args)) = config.command.split_first() else {
+            return Err(NotifyError::Exec("no command configured".to_string()).into());
+        };
+        Ok(Self {
+            program: program.clone(),
+            args: args.to_vec(),
+            timeout: Duration::from_secs(config.timeout_secs),
+            max_concurrent: config.max_concurrent.max(1),
+        })
+    }
+
+    /// Start the command with the alert's environment
+    fn spawn(&self, alert: &Alert) -> std::result::Result<Child, NotifyError> {
+        Command::new(&self.program)
+            .args(&self.args)
+            .envs(environment(alert))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| NotifyError::Exec(format!("{}: {}", self.program, e)))
+    }
+}
+
+/// Alerts waiting for their command, shared with the worker
+#[derive(Default)]
+struct Queue {
+    alerts: VecDeque<Alert>,
+    /// Number of running commands
+    running: usize,
+    closed: bool,
+}
+
+type Shared = Arc<(Mutex<Queue>, Condvar)>;
+
+/// Worker loop: start a command for each queued alert once a slot is free
+///
+/// Returns when the notifier closes and every command has finished.
+fn run(shared: &Shared, runner: &Runner) {
+    let (queue, changed) = &**shared;
+    loop {
+        let mut guard = queue.lock().unwrap_or_else(|e| e.into_inner());
+        while (guard.alerts.is_empty() && !guard.closed)
+            || (!guard.alerts.is_empty() && guard.running >= runner.max_concurrent)
+        {
+            guard = changed.wait(guard).unwrap_or_else(|e| e.into_inner());
+        }
+        let Some(alert) = guard.alerts.pop_front() else {
+            while guard.running > 0 {
+                guard = changed.wait(guard).unwrap_or_else(|e| e.into_inner());
+            }
+            return;
+        };
+
+        let child = match runner.spawn(&alert) {
+            Ok(child) => child,
+            Err(e) => {
+                log::error!("Alert command for {} failed: {}", alert.id, e);
+                continue;
+            }
+        };
+        guard.running += 1;
+        drop(guard);
+
+        let input = payload(WebhookFormat::Json, &alert).to_string();
+        let program = runner.program.clone();
+        let timeout = runner.timeout;
+        let shared = Arc::clone(shared);
+        std::thread::spawn(move || {
+            if let Err(e) = finish(child, &input, timeout) {
+                log::error!("Alert command {} for {} failed: {}", program, alert.id, e);
+            }
+            let (queue, changed) = &*shared;
+            queue.lock().unwrap_or_else(|e| e.into_inner()).running -= 1;
+            changed.notify_all();
+        });
+    }
+}
+
+/// Runs a command for each alert
+///
+/// Commands run in the background, so `notify` never waits for them. At
+/// most `max_concurrent` commands run at a time; further alerts queue up,
+/// and while 64 are waiting new ones are dropped. Commands that exceed the
+/// timeout are killed, and failures are logged with their stderr.
+pub struct ExecNotifier {
+    program: String,
+    severities: Vec<AlertSeverity>,
+    shared: Shared,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl ExecNotifier {
+    /// Create a notifier from its configuration
+    pub fn new(config: &ExecConfig) -> Result<Self> {
+        let runner = Runner::new(config)?;
+        Ok(Self::start(runner, config.severities.clone()))
+    }
+
+    fn start(runner: Runner, severities: Vec<AlertSeverity>) -> Self {
+        let program = runner.program.clone();
+        let shared: Shared = Arc::default();
+        let worker = {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || run(&shared, &runner))
+        };
+        Self {
+            program,
+            severities,
+            shared,
+            worker: Some(worker),
+        }
+    }
+
+    /// Check if the command runs for an alert's severity
+    fn routes(&self, alert: &Alert) -> bool {
+        self.severities.is_empty() || self.severities.contains(&alert.severity)
+    }
+}
+
+/// Alert fields as environment variables
+fn environment(alert: &Alert) -> Vec<(&'static str, String)> {
+    vec![
+        ("NVCTL_ALERT_ID", alert.id.clone()),
+        ("NVCTL_RULE_ID", alert.rule_id.clone()),
+        ("NVCTL_GPU_INDEX", alert.gpu_index.to_string()),
+        ("NVCTL_GPU_NAME", alert.gpu_name.clone().unwrap_or_default()),
+        ("NVCTL_GPU_UUID", alert.gpu_uuid.clone().unwrap_or_default()),
+        ("NVCTL_SEVERITY", alert.severity.to_string().to_lowercase()),
+        ("NVCTL_STATE", alert.state.to_string().to_lowercase()),
+        ("NVCTL_METRIC", alert.metric.to_string()),
+        ("NVCTL_VALUE", alert.current_value.to_string()),
+        ("NVCTL_THRESHOLD", alert.threshold_value.to_string()),
+        ("NVCTL_MESSAGE", alert.message.clone()),
+    ]
+}
+
+/// Feed stdin, wait for exit or kill on timeout, and collect stderr
+fn finish(
+    mut child: Child,
+    input: &str,
+    timeout: Duration,
+) -> std::result::Result<(), NotifyError> {
+    if let Some(mut stdin) = child.stdin.take() {
+        // The command may exit without reading its input
+        let _ = stdin.write_all(input.as_bytes());
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    if let Some(mut stderr) = child.stderr.take() {
+        std::thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            let _ = sender.send(output);
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(NotifyError::Exec(format!("timed out after {:?}", timeout)));
+            }
+            Ok(None) => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => return Err(NotifyError::Exec(e.to_string())),
+        }
+    };
+    if status.success() {
+        return Ok(());
+    }
+
+    let stderr = receiver.recv_timeout(STDERR_GRACE).unwrap_or_default();
+    Err(NotifyError::Exec(format!("{}: {}", status, stderr.trim())))
+}
+
+impl Notifier for ExecNotifier {
+    fn notify(&self, alert: &Alert) -> Result<()> {
+        if !self.routes(alert) {
+            return Ok(());
+        }
+
+        let (queue, changed) = &*self.shared;
+        let mut guard = queue.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.alerts.len() >= MAX_QUEUED {
+            log::error!(
+                "Alert command {} is falling behind, dropping alert {}",
+                self.program,
+                alert.id
+            );
+            return Ok(());
+        }
+        guard.alerts.push_back(alert.clone());
+        changed.notify_all();
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "exec"
+    }
+}
+
+impl Drop for ExecNotifier {
+    fn drop(&mut self) {
+        let (queue, changed) = &*self.shared;
+        queue.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+        changed.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::types::firing_alert;
+
+    fn alert(severity: AlertSeverity) -> Alert {
+        firing_alert(severity, 0).with_gpu(None, Some("GPU-1234".to_string()))
+    }
+
+    fn config(command: &[&str]) -> ExecConfig {
+        ExecConfig {
+            command: command.iter().map(|s| s.to_string()).collect(),
+            timeout_secs: 5,
+            max_concurrent: 1,
+            severities: vec![AlertSeverity::Critical],
+        }
+    }
+
+    #[test]
+    fn test_command_receives_environment_and_stdin() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("out");
+        let script = r#"echo "$NVCTL_SEVERITY $NVCTL_GPU_UUID $NVCTL_VALUE" >> "$0"; cat >> "$0""#;
+        let output_arg = output.to_str().unwrap();
+        let notifier = ExecNotifier::new(&config(&["sh", "-c", script, output_arg])).unwrap();
+
+        notifier.notify(&alert(AlertSeverity::Warning)).unwrap();
+        notifier.notify(&alert(AlertSeverity::Critical)).unwrap();
+        drop(notifier);
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        let (env, json) = written.split_once('\n').unwrap();
+        assert_eq!(env, "critical GPU-1234 91");
+        let json: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(json["rule_id"], "critical-temp");
+        assert_eq!(json["threshold_value"], 85.0);
+    }
+
+    #[test]
+    fn test_failures_report_stderr_and_timeout() {
+        let runner = Runner::new(&config(&["sh", "-c", "echo paging down >&2; exit 3"])).unwrap();
+        let child = runner.spawn(&alert(AlertSeverity::Critical)).unwrap();
+        let err = finish(child, "{}", Duration::from_secs(5)).unwrap_err();
+        assert!(err.to_string().contains("paging down"));
+        assert!(err.to_string().contains('3'));
+
+        let runner = Runner::new(&config(&["sleep", "10"])).unwrap();
+        let started = Instant::now();
+        let child = runner.spawn(&alert(AlertSeverity::Critical)).unwrap();
+        let err = finish(child, "{}", Duration::from_millis(100)).unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_notify_does_not_wait_for_slots() {
+        let mut runner = Runner::new(&config(&["sleep", "10"])).unwrap();
+        runner.timeout = Duration::from_millis(500);
+        let notifier = ExecNotifier::start(runner, Vec::new());
+
+        // One slot, so only the first command starts and the rest queue up
+        let started = Instant::now();
+        for _ in 0..3 {
+            notifier.notify(&alert(AlertSeverity::Critical)).unwrap();
+        }
+        assert!(started.elapsed() < Duration::from_millis(250));
+
+        // Dropping waits for the queued commands, each killed on timeout
+        drop(notifier);
+        assert!(started.elapsed() >= Duration::from_millis(1500));
+    }
+}
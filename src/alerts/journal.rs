@@ -0,0 +1,327 @@
+//! Journal and syslog notifications
+//!
+//! Alerts are written to the systemd journal over its native protocol, with
+//! the alert's fields as structured journal fields. Without journald they
+//! are sent as RFC 5424 syslog messages to `/dev/log` or a UDP collector.
+
+use super::config::{JournalConfig, SyslogFacility};
+use super::notifier::Notifier;
+use super::types::{Alert, AlertSeverity, AlertState};
+use crate::error::{NotifyError, Result};
+
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::os::unix::net::UnixDatagram;
+use std::path::PathBuf;
+
+const APP_NAME: &str = "nvctl";
+
+/// Structured data ID of syslog messages (`name@private-enterprise-number`)
+const SD_ID: &str = "nvctl@32473";
+
+/// Syslog severities
+const PRIORITY_CRIT: u8 = 2;
+const PRIORITY_ERR: u8 = 3;
+const PRIORITY_WARNING: u8 = 4;
+const PRIORITY_NOTICE: u8 = 5;
+const PRIORITY_INFO: u8 = 6;
+
+/// Where entries are written
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Target {
+    /// journald native protocol socket
+    Journal(PathBuf),
+    /// Local syslog datagram socket
+    Unix(PathBuf),
+    /// Remote syslog collector (`host:port`)
+    Udp(String),
+}
+
+/// Writes alerts to the systemd journal or syslog
+///
+/// The journal is used when its socket exists. Otherwise alerts go to the
+/// configured syslog socket or UDP collector.
+pub struct JournalNotifier {
+    target: Target,
+    facility: SyslogFacility,
+    hostname: String,
+}
+
+impl JournalNotifier {
+    /// Create a notifier from its configuration
+    pub fn new(config: &JournalConfig) -> Result<Self> {
+        let target = if config.journal_socket.exists() {
+            Target::Journal(config.journal_socket.clone())
+        } else if let Some(address) = config.syslog.strip_prefix("udp://") {
+            Target::Udp(address.to_string())
+        } else {
+            Target::Unix(PathBuf::from(&config.syslog))
+        };
+        log::debug!("Writing alerts to {:?}", target);
+
+        let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .map(|name| name.trim().to_string())
+            .ok()
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "-".to_string());
+        Ok(Self {
+            target,
+            facility: config.facility,
+            hostname,
+        })
+    }
+
+    fn send(&self, entry: &[u8]) -> std::io::Result<()> {
+        match &self.target {
+            Target::Journal(path) | Target::Unix(path) => {
+                UnixDatagram::unbound()?.send_to(entry, path)?;
+            }
+            Target::Udp(address) => {
+                let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+                    std::io::Error::new(std::io::ErrorKind::NotFound, address.clone())
+                })?;
+                let local = if address.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                UdpSocket::bind(local)?.send_to(entry, address)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Syslog severity of an alert
+fn priority(alert: &Alert) -> u8 {
+    if alert.state == AlertState::Resolved {
+        return PRIORITY_NOTICE;
+    }
+    match alert.severity {
+        AlertSeverity::Info => PRIORITY_INFO,
+        AlertSeverity::Warning => PRIORITY_WARNING,
+        AlertSeverity::Critical => PRIORITY_ERR,
+        AlertSeverity::Emergency => PRIORITY_CRIT,
+    }
+}
+
+/// Alert fields shared by journal and syslog entries
+fn fields(alert: &Alert) -> Vec<(&'static str, String)> {
+    let mut fields = vec![
+        ("ALERT_ID", alert.id.clone()),
+        ("RULE_ID", alert.rule_id.clone()),
+        ("GPU_INDEX", alert.gpu_index.to_string()),
+    ];
+    if let Some(name) = &alert.gpu_name {
+        fields.push(("GPU_NAME", name.clone()));
+    }
+    if let Some(uuid) = &alert.gpu_uuid {
+        fields.push(("GPU_UUID", uuid.clone()));
+    }
+    fields.extend([
+        ("SEVERITY", alert.severity.to_string().to_lowercase()),
+        ("STATE", alert.state.to_string().to_lowercase()),
+        ("METRIC", alert.metric.to_string()),
+        ("VALUE", alert.current_value.to_string()),
+        ("THRESHOLD", alert.threshold_value.to_string()),
+    ]);
+    fields
+}
+
+fn message(alert: &Alert) -> String {
+    let label = if alert.state == AlertState::Resolved {
+        "RESOLVED".to_string()
+    } else {
+        alert.severity.to_string()
+    };
+    format!("[{}] GPU {}: {}", label, alert.gpu_index, alert.message)
+}
+
+/// Entry in the journal's native protocol
+///
+/// Fields are `NAME=value` lines; values containing a newline use the
+/// binary form `NAME\n<length as u64 LE><value>\n`.
+fn journal_entry(alert: &Alert, facility: SyslogFacility) -> Vec<u8> {
+    let mut entry = Vec::new();
+    let mut field = |name: &str, value: &str| {
+        entry.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            entry.push(b'\n');
+            entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            entry.push(b'=');
+        }
+        entry.extend_from_slice(value.as_bytes());
+        entry.push(b'\n');
+    };
+
+    field("MESSAGE", &message(alert));
+    field("PRIORITY", &priority(alert).to_string());
+    field("SYSLOG_FACILITY", &facility.code().to_string());
+    field("SYSLOG_IDENTIFIER", APP_NAME);
+    for (name, value) in fields(alert) {
+        field(name, &value);
+    }
+    entry
+}
+
+/// RFC 5424 syslog message with the alert fields as structured data
+fn syslog_entry(alert: &Alert, facility: SyslogFacility, hostname: &str) -> Vec<u8> {
+    let timestamp = jiff::Timestamp::try_from(
+        alert
+            .resolved_at
+            .or(alert.fired_at)
+            .unwrap_or(alert.started_at),
+    )
+    .map(|t| format!("{:.6}", t))
+    .unwrap_or_else(|_| "-".to_string());
+
+    let params: String = fields(alert)
+        .into_iter()
+        .map(|(name, value)| format!(" {}=\"{}\"", name.to_lowercase(), sd_escape(&value)))
+        .collect();
+    format!(
+        "<{}>1 {} {} {} {} alert [{}{}] {}",
+        facility.code() * 8 + priority(alert),
+        timestamp,
+        hostname,
+        APP_NAME,
+        std::process::id(),
+        SD_ID,
+        params,
+        message(alert)
+    )
+    .into_bytes()
+}
+
+/// Escape a structured data parameter value
+fn sd_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+impl Notifier for JournalNotifier {
+    fn notify(&self, alert: &Alert) -> Result<()> {
+        let entry = match self.target {
+            Target::Journal(_) => journal_entry(alert, self.facility),
+            Target::Unix(_) | Target::Udp(_) => syslog_entry(alert, self.facility, &self.hostname),
+        };
+        self.send(&entry).map_err(NotifyError::from)?;
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "journal"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::types::firing_alert;
+    use std::collections::HashMap;
+
+    fn alert() -> Alert {
+        firing_alert(AlertSeverity::Critical, 1).with_gpu(
+            Some("NVIDIA \"A100\"".to_string()),
+            Some("GPU-1234".to_string()),
+        )
+    }
+
+    /// Parse a native protocol entry
+    fn parse_journal(mut entry: &[u8]) -> HashMap<String, String> {
+        let mut fields = HashMap::new();
+        while !entry.is_empty() {
+            let end = entry.iter().position(|&b| b == b'\n').unwrap();
+            let line = &entry[..end];
+            if let Some(eq) = line.iter().position(|&b| b == b'=') {
+                let name = String::from_utf8(line[..eq].to_vec()).unwrap();
+                fields.insert(name, String::from_utf8(line[eq + 1..].to_vec()).unwrap());
+                entry = &entry[end + 1..];
+            } else {
+                let name = String::from_utf8(line.to_vec()).unwrap();
+                let rest = &entry[end + 1..];
+                let len = u64::from_le_bytes(rest[..8].try_into().unwrap()) as usize;
+                fields.insert(name, String::from_utf8(rest[8..8 + len].to_vec()).unwrap());
+                entry = &rest[8 + len + 1..];
+            }
+        }
+        fields
+    }
+
+    #[test]
+    fn test_journal_native_protocol() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("journal.socket");
+        let journal = UnixDatagram::bind(&socket).unwrap();
+
+        let config = JournalConfig {
+            journal_socket: socket,
+            ..JournalConfig::default()
+        };
+        let notifier = JournalNotifier::new(&config).unwrap();
+        let mut alert = alert();
+        alert.message.push_str("\nsecond line");
+        notifier.notify(&alert).unwrap();
+
+        let mut buf = vec![0; 4096];
+        let len = journal.recv(&mut buf).unwrap();
+        let fields = parse_journal(&buf[..len]);
+        assert_eq!(fields["PRIORITY"], "3");
+        assert_eq!(fields["SYSLOG_IDENTIFIER"], "nvctl");
+        assert_eq!(fields["GPU_UUID"], "GPU-1234");
+        assert_eq!(fields["METRIC"], "temperature");
+        assert_eq!(fields["SEVERITY"], "critical");
+        assert_eq!(fields["ALERT_ID"], alert.id);
+        assert!(fields["MESSAGE"].ends_with("\nsecond line"));
+    }
+
+    #[test]
+    fn test_syslog_fallback() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("log");
+        let syslog = UnixDatagram::bind(&socket).unwrap();
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+
+        let mut config = JournalConfig {
+            journal_socket: dir.path().join("missing"),
+            syslog: socket.to_str().unwrap().to_string(),
+            facility: SyslogFacility::Local0,
+        };
+        let alert = alert();
+        JournalNotifier::new(&config)
+            .unwrap()
+            .notify(&alert)
+            .unwrap();
+        config.syslog = format!("udp://{}", udp.local_addr().unwrap());
+        JournalNotifier::new(&config)
+            .unwrap()
+            .notify(&alert)
+            .unwrap();
+
+        let mut buf = vec![0; 4096];
+        let len = syslog.recv(&mut buf).unwrap();
+        let message = String::from_utf8(buf[..len].to_vec()).unwrap();
+        // local0 (16) * 8 + err (3)
+        assert!(message.starts_with("<131>1 "));
+        let timestamp = message.split(' ').nth(1).unwrap();
+        assert!(
+            regex::Regex::new(r"^\d{4}-\d\d-\d\dT\d\d:\d\d:\d\d\.\d{6}Z$")
+                .unwrap()
+                .is_match(timestamp)
+        );
+        assert!(message.contains(" nvctl "));
+        assert!(message.contains("[nvctl@32473 alert_id="));
+        assert!(message.contains("gpu_uuid=\"GPU-1234\""));
+        assert!(message.contains("gpu_name=\"NVIDIA \\\"A100\\\"\""));
+        assert!(message.ends_with(&format!("] [CRITICAL] GPU 1: {}", alert.message)));
+
+        let len = udp.recv(&mut buf).unwrap();
+        assert_eq!(String::from_utf8_lossy(&buf[..len]), message);
+    }
+}
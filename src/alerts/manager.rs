@@ -2,12 +2,16 @@
 //!
 //! Manages alert rules, evaluates conditions, and triggers notifications.
 
-use super::types::{Alert, AlertRule, AlertState, MetricType};
+use super::types::{Alert, AlertRule, AlertSeverity, AlertState, Condition, MetricType};
 use crate::error::Result;
-use crate::nvml::GpuDevice;
+use crate::events::GpuEvent;
+use crate::nvml::{GpuSnapshot, SnapshotScope};
 use std::collections::HashMap;
 use std::time::Duration;
 
+/// Rule ID used for GPU lost/recovered events
+pub const GPU_LOST_RULE_ID: &str = "gpu-lost";
+
 /// Alert manager configuration
 #[derive(Debug, Clone)]
 pub struct AlertManagerConfig {
@@ -39,6 +43,8 @@ pub struct AlertManager {
     active_alerts: HashMap<String, Alert>,
     /// Alert history (resolved alerts)
     history: Vec<Alert>,
+    /// Alerts resolved by the last evaluation
+    just_resolved: Vec<Alert>,
     /// Configuration
     config: AlertManagerConfig,
 }
@@ -50,6 +56,7 @@ impl AlertManager {
             rules: Vec::new(),
             active_alerts: HashMap::new(),
             history: Vec::new(),
+            just_resolved: Vec::new(),
             config,
         }
     }
@@ -69,6 +76,16 @@ impl AlertManager {
         &self.rules
     }
 
+    /// Get the snapshot readings the enabled rules evaluate
+    pub fn snapshot_scope(&self) -> SnapshotScope {
+        self.rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .fold(SnapshotScope::CONTROL, |scope, rule| {
+                scope.union(rule.metric.snapshot_scope())
+            })
+    }
+
     /// Get active alerts
     pub fn active_alerts(&self) -> Vec<&Alert> {
         self.active_alerts.values().collect()
@@ -79,14 +96,19 @@ impl AlertManager {
         &self.history
     }
 
-    /// Evaluate all rules for a GPU
-    pub fn evaluate<D: GpuDevice>(&mut self, device: &D, gpu_index: u32) -> Result<Vec<Alert>> {
+    /// Get the alerts resolved by the last call to [`evaluate`](Self::evaluate)
+    pub fn just_resolved(&self) -> &[Alert] {
+        &self.just_resolved
+    }
+
+    /// Evaluate all rules for a GPU against a snapshot
+    pub fn evaluate(&mut self, snapshot: &GpuSnapshot, gpu_index: u32) -> Result<Vec<Alert>> {
         if !self.config.enabled {
             return Ok(Vec::new());
         }
 
         let mut new_alerts = Vec::new();
-        let mut resolved_alerts = Vec::new();
+        self.just_resolved.clear();
 
         // Clone rules to avoid borrow issues
         let rules = self.rules.clone();
@@ -96,8 +118,8 @@ impl AlertManager {
                 continue;
             }
 
-            // Extract metric value from device
-            let value = match self.extract_metric_value(device, rule.metric) {
+            // Extract metric value from snapshot
+            let value = match self.extract_metric_value(snapshot, rule.metric) {
                 Ok(Some(v)) => v,
                 Ok(None) => continue, // Metric not available
                 Err(_) => continue,   // Error reading metric
@@ -121,7 +143,8 @@ impl AlertManager {
                     }
                 } else {
                     // Create new pending alert
-                    let mut alert = Alert::new_pending(rule, gpu_index, value, threshold_value);
+                    let mut alert = Alert::new_pending(rule, gpu_index, value, threshold_value)
+                        .with_gpu(snapshot.name.clone().ok(), snapshot.uuid.clone().ok());
 
                     // Fire immediately if no duration requirement
                     if alert.should_fire(rule) {
@@ -137,83 +160,160 @@ impl AlertManager {
                 if let Some(mut alert) = self.active_alerts.remove(&alert_key) {
                     if matches!(alert.state, AlertState::Firing | AlertState::Acknowledged) {
                         alert.resolve();
-                        resolved_alerts.push(alert);
+                        self.just_resolved.push(alert);
                     }
                 }
             }
         }
 
         // Add resolved alerts to history
-        for alert in resolved_alerts {
+        for alert in self.just_resolved.clone() {
             self.add_to_history(alert);
         }
 
         Ok(new_alerts)
     }
 
-    /// Extract metric value from device
-    fn extract_metric_value<D: GpuDevice>(
+    /// Record that a GPU has been lost, returning the fired alert
+    ///
+    /// Lost alerts are keyed by UUID since the GPU may come back at another index.
+    pub fn record_gpu_lost(&mut self, gpu_index: u32, uuid: &str) -> Alert {
+        let key = format!("{}-{}", GPU_LOST_RULE_ID, uuid);
+        if let Some(alert) = self.active_alerts.get(&key) {
+            return alert.clone();
+        }
+
+        let rule = AlertRule::new(
+            GPU_LOST_RULE_ID.to_string(),
+            "GPU Lost".to_string(),
+            MetricType::GpuLost,
+            Condition::Equals(1.0),
+            AlertSeverity::Emergency,
+        );
+        let mut alert =
+            Alert::new_pending(&rule, gpu_index, 1.0, 1.0).with_gpu(None, Some(uuid.to_string()));
+        alert.message = format!("GPU {} ({}) is lost or inaccessible", gpu_index, uuid);
+        alert.fire();
+
+        self.active_alerts.insert(key, alert.clone());
+        alert
+    }
+
+    /// Record that a lost GPU is reachable again, returning the resolved alert
+    pub fn record_gpu_recovered(&mut self, gpu_index: u32, uuid: &str) -> Option<Alert> {
+        let key = format!("{}-{}", GPU_LOST_RULE_ID, uuid);
+        let mut alert = self.active_alerts.remove(&key)?;
+        alert.current_value = 0.0;
+        alert.message = format!("GPU {} ({}) recovered", gpu_index, uuid);
+        alert.gpu_index = gpu_index;
+        alert.resolve();
+        self.add_to_history(alert.clone());
+        Some(alert)
+    }
+
+    /// Evaluate event rules against a GPU event, returning the fired alerts
+    ///
+    /// Events are instantaneous, so matching alerts fire and go straight to history.
+    pub fn process_event(&mut self, event: &GpuEvent) -> Vec<Alert> {
+        if !self.config.enabled {
+            return Vec::new();
+        }
+
+        let metric = event.kind.metric();
+        let value = event.kind.value();
+        let mut fired = Vec::new();
+
+        for rule in &self.rules {
+            if !rule.enabled
+                || rule.metric != metric
+                || !rule.gpu_filter.matches(event.gpu_index)
+                || !rule.evaluate(value)
+            {
+                continue;
+            }
+
+            let threshold_value = self.get_threshold_value(&rule.condition);
+            let mut alert = Alert::new_pending(rule, event.gpu_index, value, threshold_value)
+                .with_gpu(None, Some(event.uuid.clone()));
+            alert.message = format!("{}: {} on GPU {}", rule.name, event.kind, event.gpu_index);
+            alert.fire();
+            fired.push(alert);
+        }
+
+        for alert in &fired {
+            self.add_to_history(alert.clone());
+        }
+        fired
+    }
+
+    /// Extract metric value from snapshot
+    fn extract_metric_value(
         &self,
-        device: &D,
+        snapshot: &GpuSnapshot,
         metric: MetricType,
     ) -> Result<Option<f64>> {
         Ok(Some(match metric {
-            MetricType::Temperature => device.temperature()?.as_celsius() as f64,
+            MetricType::Temperature => snapshot.temperature.clone()?.as_celsius() as f64,
             MetricType::MemoryTemperature => {
-                if let Some(temp) = device.memory_temperature()? {
+                if let Some(temp) = snapshot.memory_temperature.clone()? {
                     temp.as_celsius() as f64
                 } else {
                     return Ok(None);
                 }
             }
-            MetricType::PowerUsage => device.power_usage()?.as_watts() as f64,
+            MetricType::PowerUsage => snapshot.power_usage.clone()?.as_watts() as f64,
             MetricType::PowerPercent => {
-                let usage = device.power_usage()?.as_watts();
-                let limit = device.power_limit()?.as_watts();
+                let usage = snapshot.power_usage.clone()?.as_watts();
+                let limit = snapshot.power_limit.clone()?.as_watts();
                 if limit > 0 {
                     (usage as f64 / limit as f64) * 100.0
                 } else {
                     return Ok(None);
                 }
             }
-            MetricType::GpuUtilization => device.utilization()?.gpu as f64,
-            MetricType::MemoryUtilization => device.utilization()?.memory as f64,
+            MetricType::GpuUtilization => snapshot.utilization.clone()?.gpu as f64,
+            MetricType::MemoryUtilization => snapshot.utilization.clone()?.memory as f64,
             MetricType::FanSpeed => {
-                if let Ok(speed) = device.fan_speed(0) {
+                if let Some(Ok(speed)) = snapshot.fan_speeds.first() {
                     speed.as_percentage() as f64
                 } else {
                     return Ok(None);
                 }
             }
-            MetricType::ClockSpeed => {
-                use crate::domain::performance::ClockType;
-                device.clock_speed(ClockType::Graphics)?.as_mhz() as f64
-            }
+            MetricType::ClockSpeed => snapshot.graphics_clock.clone()?.as_mhz() as f64,
             MetricType::EccCorrectableErrors => {
-                if let Some(ecc) = device.ecc_errors()? {
+                if let Some(ecc) = snapshot.ecc_errors.clone()? {
                     ecc.correctable_current as f64
                 } else {
                     return Ok(None);
                 }
             }
             MetricType::EccUncorrectableErrors => {
-                if let Some(ecc) = device.ecc_errors()? {
+                if let Some(ecc) = snapshot.ecc_errors.clone()? {
                     ecc.uncorrectable_current as f64
                 } else {
                     return Ok(None);
                 }
             }
             MetricType::PcieThroughput => {
-                let metrics = device.pcie_metrics()?;
+                let metrics = snapshot.pcie_metrics.clone()?;
                 (metrics.throughput.tx_bytes_per_sec() + metrics.throughput.rx_bytes_per_sec())
                     as f64
                     / 1024.0
                     / 1024.0 // Convert to MB/s
             }
             MetricType::PcieReplayCounter => {
-                let metrics = device.pcie_metrics()?;
+                let metrics = snapshot.pcie_metrics.clone()?;
                 metrics.replay_counter.count() as f64
             }
+            // Reported by the control loop or the event thread, not polled
+            MetricType::GpuLost
+            | MetricType::XidError
+            | MetricType::SingleBitEccEvent
+            | MetricType::DoubleBitEccEvent
+            | MetricType::PStateChange
+            | MetricType::ClockChange
+            | MetricType::PowerSourceChange => return Ok(None),
         }))
     }
 
@@ -329,7 +429,7 @@ mod tests {
         device.set_temperature(Temperature::new(85));
 
         // Evaluate
-        let new_alerts = manager.evaluate(&device, 0).unwrap();
+        let new_alerts = manager.evaluate(&GpuSnapshot::capture(&device), 0).unwrap();
 
         // Should fire immediately (no duration requirement)
         assert_eq!(new_alerts.len(), 1);
@@ -354,16 +454,21 @@ mod tests {
         // High temperature - should fire
         let device = MockDevice::new(0);
         device.set_temperature(Temperature::new(85));
-        let alerts = manager.evaluate(&device, 0).unwrap();
+        let alerts = manager.evaluate(&GpuSnapshot::capture(&device), 0).unwrap();
         assert_eq!(alerts.len(), 1);
         assert_eq!(manager.active_alerts().len(), 1);
 
         // Lower temperature - should resolve
         device.set_temperature(Temperature::new(75));
-        let alerts = manager.evaluate(&device, 0).unwrap();
+        let alerts = manager.evaluate(&GpuSnapshot::capture(&device), 0).unwrap();
         assert_eq!(alerts.len(), 0);
         assert_eq!(manager.active_alerts().len(), 0);
         assert_eq!(manager.history().len(), 1);
+        assert_eq!(manager.just_resolved().len(), 1);
+        assert_eq!(manager.just_resolved()[0].state, AlertState::Resolved);
+
+        manager.evaluate(&GpuSnapshot::capture(&device), 0).unwrap();
+        assert!(manager.just_resolved().is_empty());
     }
 
     #[test]
@@ -386,11 +491,57 @@ mod tests {
         device.set_temperature(Temperature::new(85));
 
         // Should not fire for GPU 0
-        let alerts = manager.evaluate(&device, 0).unwrap();
+        let alerts = manager.evaluate(&GpuSnapshot::capture(&device), 0).unwrap();
         assert_eq!(alerts.len(), 0);
 
         // Should fire for GPU 1
-        let alerts = manager.evaluate(&device, 1).unwrap();
+        let alerts = manager.evaluate(&GpuSnapshot::capture(&device), 1).unwrap();
+        assert_eq!(alerts.len(), 1);
+    }
+
+    #[test]
+    fn test_gpu_lost_and_recovered() {
+        let mut manager = AlertManager::default();
+
+        let alert = manager.record_gpu_lost(0, "GPU-MOCK-0000");
+        assert_eq!(alert.severity, AlertSeverity::Emergency);
+        assert_eq!(alert.state, AlertState::Firing);
+        assert_eq!(manager.active_alerts().len(), 1);
+
+        // Repeated loss does not duplicate the alert
+        manager.record_gpu_lost(0, "GPU-MOCK-0000");
+        assert_eq!(manager.active_alerts().len(), 1);
+
+        let resolved = manager.record_gpu_recovered(1, "GPU-MOCK-0000").unwrap();
+        assert_eq!(resolved.state, AlertState::Resolved);
+        assert_eq!(resolved.gpu_index, 1);
+        assert_eq!(manager.active_alerts().len(), 0);
+        assert_eq!(manager.history().len(), 1);
+        assert!(manager.record_gpu_recovered(1, "GPU-MOCK-0000").is_none());
+    }
+
+    #[test]
+    fn test_process_xid_event() {
+        use crate::events::GpuEventKind;
+
+        let mut manager = AlertManager::default();
+        manager.add_rule(AlertRule::new(
+            "xid-79".to_string(),
+            "GPU Fell Off Bus".to_string(),
+            MetricType::XidError,
+            Condition::Equals(79.0),
+            AlertSeverity::Emergency,
+        ));
+
+        let other = GpuEvent::new(0, "GPU-MOCK-0000", GpuEventKind::XidError(Some(13)));
+        assert!(manager.process_event(&other).is_empty());
+
+        let event = GpuEvent::new(0, "GPU-MOCK-0000", GpuEventKind::XidError(Some(79)));
+        let alerts = manager.process_event(&event);
         assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric, MetricType::XidError);
+        assert!(alerts[0].message.contains("Xid 79"));
+        assert_eq!(manager.active_alerts().len(), 0);
+        assert_eq!(manager.history().len(), 1);
     }
 }
@@ -3,11 +3,25 @@
 //! Provides threshold-based alerting with multiple notification channels.
 
 mod config;
+mod desktop;
+mod email;
+mod exec;
+mod journal;
 mod manager;
 mod notifier;
 mod types;
+mod webhook;
 
-pub use config::{AlertConfig, AlertRuleConfig, AlertSettings, ConditionConfig};
-pub use manager::{AlertManager, AlertManagerConfig};
+pub use config::{
+    metric_rules, AlertConfig, AlertRuleConfig, AlertSettings, ConditionConfig, EmailConfig,
+    EmailTls, ExecConfig, JournalConfig, NotifierConfig, RuleKind, SyslogFacility, WebhookConfig,
+    WebhookFormat,
+};
+pub use desktop::DesktopNotifier;
+pub use email::EmailNotifier;
+pub use exec::ExecNotifier;
+pub use journal::JournalNotifier;
+pub use manager::{AlertManager, AlertManagerConfig, GPU_LOST_RULE_ID};
 pub use notifier::{NotificationManager, Notifier, TerminalNotifier};
 pub use types::{Alert, AlertRule, AlertSeverity, AlertState, Condition, GpuFilter, MetricType};
+pub use webhook::WebhookNotifier;
@@ -2,7 +2,13 @@
 //!
 //! Provides notification channels for alerts including terminal, desktop, email, and webhooks.
 
-use super::types::{Alert, AlertSeverity};
+use super::config::NotifierConfig;
+use super::desktop::DesktopNotifier;
+use super::email::EmailNotifier;
+use super::exec::ExecNotifier;
+use super::journal::JournalNotifier;
+use super::types::{Alert, AlertSeverity, AlertState};
+use super::webhook::WebhookNotifier;
 use crate::error::Result;
 use std::io::{self, Write};
 
@@ -11,6 +17,14 @@ pub trait Notifier: Send + Sync {
     /// Send a notification for an alert
     fn notify(&self, alert: &Alert) -> Result<()>;
 
+    /// Send notifications for alerts raised or resolved together
+    fn notify_batch(&self, alerts: &[Alert]) -> Result<()> {
+        for alert in alerts {
+            self.notify(alert)?;
+        }
+        Ok(())
+    }
+
     /// Channel name for identification
     fn name(&self) -> &str;
 }
@@ -60,9 +74,14 @@ impl TerminalNotifier {
 
     /// Format alert with colors
     fn format_alert(&self, alert: &Alert) -> String {
-        let severity_str = self.format_severity(alert.severity);
+        let severity_str = if alert.state == AlertState::Resolved {
+            self.format_resolved()
+        } else {
+            self.format_severity(alert.severity)
+        };
         let timestamp = alert
-            .fired_at
+            .resolved_at
+            .or(alert.fired_at)
             .or(Some(alert.started_at))
             .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
             .map(|d| {
@@ -95,6 +114,15 @@ impl TerminalNotifier {
 
         format!("{}{}\x1b[0m", color_code, text)
     }
+
+    /// Format the label of a resolved alert
+    fn format_resolved(&self) -> String {
+        if self.use_colors {
+            "\x1b[32mRESOLVED\x1b[0m".to_string() // Green
+        } else {
+            "RESOLVED".to_string()
+        }
+    }
 }
 
 impl Default for TerminalNotifier {
@@ -140,6 +168,25 @@ impl NotificationManager {
         }
     }
 
+    /// Create the channels of `[[notifiers]]`, or the terminal if there are none
+    ///
+    /// A channel that fails to start is logged and skipped, so the others
+    /// still get alerts.
+    pub fn from_config(configs: &[NotifierConfig]) -> Self {
+        if configs.is_empty() {
+            return Self::default();
+        }
+
+        let mut manager = Self::new();
+        for config in configs {
+            match build_notifier(config) {
+                Ok(notifier) => manager.add_notifier(notifier),
+                Err(e) => log::error!("Skipping {} notifier: {}", config.kind(), e),
+            }
+        }
+        manager
+    }
+
     /// Add a notifier
     pub fn add_notifier(&mut self, notifier: Box<dyn Notifier>) {
         self.notifiers.push(notifier);
@@ -155,10 +202,15 @@ impl NotificationManager {
         Ok(())
     }
 
-    /// Send notifications for multiple alerts
+    /// Send notifications for multiple alerts, as one batch per channel
     pub fn notify_batch(&self, alerts: &[Alert]) -> Result<()> {
-        for alert in alerts {
-            self.notify_all(alert)?;
+        if alerts.is_empty() {
+            return Ok(());
+        }
+        for notifier in &self.notifiers {
+            if let Err(e) = notifier.notify_batch(alerts) {
+                eprintln!("Failed to notify via {}: {}", notifier.name(), e);
+            }
         }
         Ok(())
     }
@@ -178,6 +230,19 @@ impl Default for NotificationManager {
     }
 }
 
+/// Create the channel of one `[[notifiers]]` entry
+fn build_notifier(config: &NotifierConfig) -> Result<Box<dyn Notifier>> {
+    Ok(match config {
+        NotifierConfig::Terminal { stdout: false } => Box::new(TerminalNotifier::new()),
+        NotifierConfig::Terminal { stdout: true } => Box::new(TerminalNotifier::stdout()),
+        NotifierConfig::Webhook(webhook) => Box::new(WebhookNotifier::new(webhook)),
+        NotifierConfig::Desktop => Box::new(DesktopNotifier::connect()?),
+        NotifierConfig::Email(email) => Box::new(EmailNotifier::new(email)?),
+        NotifierConfig::Exec(exec) => Box::new(ExecNotifier::new(exec)?),
+        NotifierConfig::Journal(journal) => Box::new(JournalNotifier::new(journal)?),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -225,6 +290,38 @@ mod tests {
         assert_eq!(manager.notifier_count(), 1); // Default terminal notifier
     }
 
+    #[test]
+    fn test_notification_manager_from_config() {
+        let manager = NotificationManager::from_config(&[]);
+        assert_eq!(manager.notifier_count(), 1);
+
+        let manager = NotificationManager::from_config(&[
+            NotifierConfig::Terminal { stdout: true },
+            NotifierConfig::Webhook(crate::alerts::WebhookConfig {
+                url: "http://127.0.0.1:9/hook".to_string(),
+                format: Default::default(),
+                headers: Default::default(),
+                timeout_secs: 1,
+                retries: 0,
+            }),
+        ]);
+        assert_eq!(manager.notifier_count(), 2);
+    }
+
+    #[test]
+    fn test_from_config_skips_failed_notifiers() {
+        let manager = NotificationManager::from_config(&[
+            NotifierConfig::Exec(crate::alerts::ExecConfig {
+                command: Vec::new(),
+                timeout_secs: 1,
+                max_concurrent: 1,
+                severities: Vec::new(),
+            }),
+            NotifierConfig::Terminal { stdout: true },
+        ]);
+        assert_eq!(manager.notifier_count(), 1);
+    }
+
     #[test]
     fn test_notification_manager_add_notifier() {
         let mut manager = NotificationManager::new();
@@ -2,6 +2,7 @@
 //!
 //! Defines validated types for the alerting system including rules, conditions, and alert states.
 
+use crate::nvml::SnapshotScope;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::time::Duration;
@@ -10,12 +11,16 @@ use std::time::Duration;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum AlertSeverity {
     /// Informational, no action needed
+    #[serde(alias = "info")]
     Info,
     /// Attention recommended
+    #[serde(alias = "warning")]
     Warning,
     /// Action required soon
+    #[serde(alias = "critical")]
     Critical,
     /// Immediate action required
+    #[serde(alias = "emergency")]
     Emergency,
 }
 
@@ -99,6 +104,53 @@ pub enum MetricType {
     PcieThroughput,
     /// PCIe replay counter
     PcieReplayCounter,
+    /// GPU lost or no longer reachable (1 = lost)
+    GpuLost,
+    /// Critical Xid error event (value is the Xid code)
+    XidError,
+    /// Single-bit ECC error event
+    SingleBitEccEvent,
+    /// Double-bit ECC error event
+    DoubleBitEccEvent,
+    /// Performance state change event
+    PStateChange,
+    /// Clock change event
+    ClockChange,
+    /// Power source change event
+    PowerSourceChange,
+}
+
+impl MetricType {
+    /// Check if this metric is delivered as an event rather than polled
+    pub fn is_event(&self) -> bool {
+        matches!(
+            self,
+            Self::XidError
+                | Self::SingleBitEccEvent
+                | Self::DoubleBitEccEvent
+                | Self::PStateChange
+                | Self::ClockChange
+                | Self::PowerSourceChange
+        )
+    }
+
+    /// Snapshot readings needed to evaluate this metric
+    pub fn snapshot_scope(&self) -> SnapshotScope {
+        let mut scope = SnapshotScope::CONTROL;
+        match self {
+            Self::MemoryTemperature => scope.thermal = true,
+            Self::PowerUsage
+            | Self::PowerPercent
+            | Self::GpuUtilization
+            | Self::MemoryUtilization
+            | Self::ClockSpeed => scope.activity = true,
+            Self::FanSpeed => scope.fans = true,
+            Self::EccCorrectableErrors | Self::EccUncorrectableErrors => scope.ecc = true,
+            Self::PcieThroughput | Self::PcieReplayCounter => scope.pcie = true,
+            _ => {}
+        }
+        scope
+    }
 }
 
 impl fmt::Display for MetricType {
@@ -116,6 +168,13 @@ impl fmt::Display for MetricType {
             Self::EccUncorrectableErrors => write!(f, "ecc_uncorrectable_errors"),
             Self::PcieThroughput => write!(f, "pcie_throughput"),
             Self::PcieReplayCounter => write!(f, "pcie_replay_counter"),
+            Self::GpuLost => write!(f, "gpu_lost"),
+            Self::XidError => write!(f, "xid_error"),
+            Self::SingleBitEccEvent => write!(f, "single_bit_ecc_event"),
+            Self::DoubleBitEccEvent => write!(f, "double_bit_ecc_event"),
+            Self::PStateChange => write!(f, "pstate_change"),
+            Self::ClockChange => write!(f, "clock_change"),
+            Self::PowerSourceChange => write!(f, "power_source_change"),
         }
     }
 }
@@ -251,6 +310,12 @@ pub struct Alert {
     pub rule_id: String,
     /// GPU index
     pub gpu_index: u32,
+    /// GPU name, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_name: Option<String>,
+    /// GPU UUID, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_uuid: Option<String>,
     /// Timestamp when condition first met
     pub started_at: std::time::SystemTime,
     /// Timestamp when alert fired (if fired)
@@ -298,6 +363,8 @@ impl Alert {
             id,
             rule_id: rule.id.clone(),
             gpu_index,
+            gpu_name: None,
+            gpu_uuid: None,
             started_at: std::time::SystemTime::now(),
             fired_at: None,
             resolved_at: None,
@@ -310,6 +377,13 @@ impl Alert {
         }
     }
 
+    /// Set the name and UUID of the alert's GPU
+    pub fn with_gpu(mut self, name: Option<String>, uuid: Option<String>) -> Self {
+        self.gpu_name = name;
+        self.gpu_uuid = uuid;
+        self
+    }
+
     /// Mark alert as firing
     pub fn fire(&mut self) {
         self.state = AlertState::Firing;
@@ -356,6 +430,21 @@ impl Alert {
     }
 }
 
+/// A firing alert of a "critical-temp" rule (above 85°C, currently 91°C)
+#[cfg(test)]
+pub(crate) fn firing_alert(severity: AlertSeverity, gpu_index: u32) -> Alert {
+    let rule = AlertRule::new(
+        "critical-temp".to_string(),
+        "Critical GPU Temperature".to_string(),
+        MetricType::Temperature,
+        Condition::GreaterThan(85.0),
+        severity,
+    );
+    let mut alert = Alert::new_pending(&rule, gpu_index, 91.0, 85.0);
+    alert.fire();
+    alert
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,445 @@
+//! Webhook notifications
+//!
+//! Alerts are POSTed as JSON, either as the alert's own fields or shaped as
+//! a Slack, Discord or Microsoft Teams message.
+
+use super::config::{WebhookConfig, WebhookFormat};
+use super::notifier::Notifier;
+use super::types::{Alert, AlertSeverity, AlertState};
+use crate::error::{NotifyError, Result};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Delay before the first retry of a failed request
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Alerts waiting to be sent before new ones are dropped
+const MAX_QUEUED: usize = 64;
+
+/// Alert fields sent in the generic JSON format
+#[derive(Debug, Serialize)]
+struct AlertPayload<'a> {
+    id: &'a str,
+    rule_id: &'a str,
+    gpu_index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gpu_name: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gpu_uuid: Option<&'a str>,
+    severity: String,
+    state: String,
+    metric: String,
+    current_value: f64,
+    threshold_value: f64,
+    message: &'a str,
+    started_at: Option<String>,
+    fired_at: Option<String>,
+    resolved_at: Option<String>,
+}
+
+/// Build the request body for an alert
+pub fn payload(format: WebhookFormat, alert: &Alert) -> Value {
+    let title = title(alert);
+    let facts = [
+        ("GPU", alert.gpu_index.to_string()),
+        ("Severity", alert.severity.to_string()),
+        ("Metric", alert.metric.to_string()),
+        ("Value", format!("{:.2}", alert.current_value)),
+        ("Threshold", format!("{:.2}", alert.threshold_value)),
+    ];
+    let color = color(alert);
+
+    match format {
+        WebhookFormat::Json => json!(AlertPayload {
+            id: &alert.id,
+            rule_id: &alert.rule_id,
+            gpu_index: alert.gpu_index,
+            gpu_name: alert.gpu_name.as_deref(),
+            gpu_uuid: alert.gpu_uuid.as_deref(),
+            severity: alert.severity.to_string().to_lowercase(),
+            state: alert.state.to_string().to_lowercase(),
+            metric: alert.metric.to_string(),
+            current_value: alert.current_value,
+            threshold_value: alert.threshold_value,
+            message: &alert.message,
+            started_at: rfc3339(Some(alert.started_at)),
+            fired_at: rfc3339(alert.fired_at),
+            resolved_at: rfc3339(alert.resolved_at),
+        }),
+        WebhookFormat::Slack => json!({
+            "text": title,
+            "attachments": [{
+                "color": format!("#{:06x}", color),
+                "text": alert.message,
+                "fields": facts
+                    .iter()
+                    .map(|(name, value)| json!({ "title": name, "value": value, "short": true }))
+                    .collect::<Vec<_>>(),
+            }],
+        }),
+        WebhookFormat::Discord => json!({
+            "embeds": [{
+                "title": title,
+                "description": alert.message,
+                "color": color,
+                "fields": facts
+                    .iter()
+                    .map(|(name, value)| json!({ "name": name, "value": value, "inline": true }))
+                    .collect::<Vec<_>>(),
+                "timestamp": rfc3339(Some(event_time(alert))),
+            }],
+        }),
+        WebhookFormat::Teams => json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "themeColor": format!("{:06X}", color),
+            "summary": title,
+            "title": title,
+            "text": alert.message,
+            "sections": [{
+                "facts": facts
+                    .iter()
+                    .map(|(name, value)| json!({ "name": name, "value": value }))
+                    .collect::<Vec<_>>(),
+            }],
+        }),
+    }
+}
+
+/// One-line summary, e.g. `[CRITICAL] GPU 0: High GPU Temperature: ...`
+fn title(alert: &Alert) -> String {
+    let label = if alert.state == AlertState::Resolved {
+        "RESOLVED".to_string()
+    } else {
+        alert.severity.to_string()
+    };
+    format!("[{}] GPU {}: {}", label, alert.gpu_index, alert.message)
+}
+
+/// Message color as RGB
+fn color(alert: &Alert) -> u32 {
+    if alert.state == AlertState::Resolved {
+        return 0x2eb67d;
+    }
+    match alert.severity {
+        AlertSeverity::Info => 0x3aa3e3,
+        AlertSeverity::Warning => 0xe5a50a,
+        AlertSeverity::Critical => 0xe01b24,
+        AlertSeverity::Emergency => 0x8b0000,
+    }
+}
+
+/// When the alert last changed state
+fn event_time(alert: &Alert) -> SystemTime {
+    alert
+        .resolved_at
+        .or(alert.fired_at)
+        .unwrap_or(alert.started_at)
+}
+
+fn rfc3339(time: Option<SystemTime>) -> Option<String> {
+    time.and_then(|t| jiff::Timestamp::try_from(t).ok())
+        .map(|t| t.to_string())
+}
+
+/// Alerts waiting to be sent
+#[derive(Default)]
+struct Queue {
+    alerts: VecDeque<Alert>,
+    /// Set when the notifier is dropped, to stop the worker
+    closed: bool,
+}
+
+type Shared = Arc<(Mutex<Queue>, Condvar)>;
+
+/// Where and how alerts are sent
+struct Endpoint {
+    url: String,
+    format: WebhookFormat,
+    headers: BTreeMap<String, String>,
+    retries: u32,
+    backoff: Duration,
+    agent: ureq::Agent,
+}
+
+impl Endpoint {
+    fn new(config: &WebhookConfig) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build();
+        Self {
+            url: config.url.clone(),
+            format: config.format,
+            headers: config.headers.clone(),
+            retries: config.retries,
+            backoff: INITIAL_BACKOFF,
+            agent,
+        }
+    }
+
+    fn send(&self, body: &str) -> std::result::Result<(), NotifyError> {
+        let mut request = self
+            .agent
+            .post(&self.url)
+            .set("Content-Type", "application/json");
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+
+        match request.send_string(body) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(status, response)) => Err(NotifyError::Rejected {
+                status,
+                body: response.into_string().unwrap_or_default(),
+            }),
+            Err(e) => Err(NotifyError::Http(e.to_string())),
+        }
+    }
+
+    /// Send an alert, retrying transient failures until the notifier closes
+    fn deliver(&self, shared: &Shared, alert: &Alert) -> std::result::Result<(), NotifyError> {
+        let body = payload(self.format, alert).to_string();
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.send(&body) {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < self.retries && e.is_transient() => {
+                    attempt += 1;
+                    log::warn!(
+                        "Webhook {} failed (attempt {}), retrying in {:?}: {}",
+                        self.url,
+                        attempt,
+                        backoff,
+                        e
+                    );
+                    if !wait_unless_closed(shared, backoff) {
+                        return Err(e);
+                    }
+                    backoff *= 2;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Sleep for `duration`, returning `false` early if the notifier closes
+fn wait_unless_closed(shared: &Shared, duration: Duration) -> bool {
+    let (queue, ready) = &**shared;
+    let deadline = Instant::now() + duration;
+    let mut guard = queue.lock().unwrap_or_else(|e| e.into_inner());
+    while !guard.closed {
+        let now = Instant::now();
+        if now >= deadline {
+            return true;
+        }
+        guard = ready
+            .wait_timeout(guard, deadline - now)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+    false
+}
+
+/// Worker loop: send queued alerts in order
+fn run(shared: &Shared, endpoint: &Endpoint) {
+    let (queue, ready) = &**shared;
+    loop {
+        let mut guard = queue.lock().unwrap_or_else(|e| e.into_inner());
+        while guard.alerts.is_empty() && !guard.closed {
+            guard = ready.wait(guard).unwrap_or_else(|e| e.into_inner());
+        }
+        let Some(alert) = guard.alerts.pop_front() else {
+            return;
+        };
+        drop(guard);
+
+        if let Err(e) = endpoint.deliver(shared, &alert) {
+            log::error!(
+                "Failed to send alert {} to webhook {}: {}",
+                alert.id,
+                endpoint.url,
+                e
+            );
+        }
+    }
+}
+
+/// Sends alerts to an HTTP endpoint
+///
+/// Alerts are sent in the background, so a slow or failing endpoint never
+/// holds up the caller. Failed requests are retried with exponential
+/// backoff, except when the endpoint rejects the payload with a 4xx status
+/// other than 429. While 64 alerts are waiting, new ones are dropped.
+pub struct WebhookNotifier {
+    url: String,
+    shared: Shared,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl WebhookNotifier {
+    /// Create a notifier from its configuration
+    pub fn new(config: &WebhookConfig) -> Self {
+        Self::start(Endpoint::new(config))
+    }
+
+    fn start(endpoint: Endpoint) -> Self {
+        let url = endpoint.url.clone();
+        let shared: Shared = Arc::default();
+        let worker = {
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || run(&shared, &endpoint))
+        };
+        Self {
+            url,
+            shared,
+            worker: Some(worker),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify(&self, alert: &Alert) -> Result<()> {
+        let (queue, ready) = &*self.shared;
+        let mut guard = queue.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.alerts.len() >= MAX_QUEUED {
+            log::error!(
+                "Webhook {} is falling behind, dropping alert {}",
+                self.url,
+                alert.id
+            );
+            return Ok(());
+        }
+        guard.alerts.push_back(alert.clone());
+        ready.notify_one();
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "webhook"
+    }
+}
+
+impl Drop for WebhookNotifier {
+    fn drop(&mut self) {
+        let (queue, ready) = &*self.shared;
+        queue.lock().unwrap_or_else(|e| e.into_inner()).closed = true;
+        ready.notify_one();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alerts::types::firing_alert;
+    use std::thread;
+
+    fn alert() -> Alert {
+        firing_alert(AlertSeverity::Critical, 1)
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let alert = alert();
+
+        let generic = payload(WebhookFormat::Json, &alert);
+        assert_eq!(generic["severity"], "critical");
+        assert_eq!(generic["state"], "firing");
+        assert_eq!(generic["metric"], "temperature");
+        assert_eq!(generic["gpu_index"], 1);
+        assert!(generic["fired_at"].is_string());
+
+        let slack = payload(WebhookFormat::Slack, &alert);
+        assert!(slack["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("[CRITICAL] GPU 1"));
+        assert_eq!(slack["attachments"][0]["color"], "#e01b24");
+
+        let discord = payload(WebhookFormat::Discord, &alert);
+        assert_eq!(discord["embeds"][0]["color"], 0xe01b24);
+        assert_eq!(discord["embeds"][0]["fields"][3]["value"], "91.00");
+
+        let teams = payload(WebhookFormat::Teams, &alert);
+        assert_eq!(teams["@type"], "MessageCard");
+        assert_eq!(teams["sections"][0]["facts"][2]["value"], "temperature");
+    }
+
+    #[test]
+    fn test_notify_retries_server_errors() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.server_addr());
+        let handle = thread::spawn(move || {
+            let mut received = Vec::new();
+            for status in [503, 200] {
+                let mut request = server.recv().unwrap();
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let token = request
+                    .headers()
+                    .iter()
+                    .find(|h| h.field.equiv("X-Token"))
+                    .map(|h| h.value.to_string());
+                received.push((body, token));
+                request.respond(tiny_http::Response::empty(status)).unwrap();
+            }
+            received
+        });
+
+        let config = WebhookConfig {
+            url,
+            format: WebhookFormat::Discord,
+            headers: BTreeMap::from([("X-Token".to_string(), "secret".to_string())]),
+            timeout_secs: 5,
+            retries: 2,
+        };
+        let mut endpoint = Endpoint::new(&config);
+        endpoint.backoff = Duration::from_millis(10);
+        let notifier = WebhookNotifier::start(endpoint);
+        notifier.notify(&alert()).unwrap();
+
+        let received = handle.join().unwrap();
+        assert_eq!(received.len(), 2);
+        let body: Value = serde_json::from_str(&received[1].0).unwrap();
+        assert!(body["embeds"][0]["title"]
+            .as_str()
+            .unwrap()
+            .contains("Critical GPU Temperature"));
+        assert_eq!(received[1].1.as_deref(), Some("secret"));
+    }
+
+    #[test]
+    fn test_notify_does_not_wait_for_retries() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let config = WebhookConfig {
+            url: format!("http://{}/hook", server.server_addr()),
+            format: WebhookFormat::Json,
+            headers: BTreeMap::new(),
+            timeout_secs: 5,
+            retries: 5,
+        };
+        let handle = thread::spawn(move || {
+            let request = server.recv().unwrap();
+            request.respond(tiny_http::Response::empty(503)).unwrap();
+        });
+
+        let started = Instant::now();
+        let notifier = WebhookNotifier::new(&config);
+        notifier.notify(&alert()).unwrap();
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        // Closing interrupts the backoff of the pending retry
+        handle.join().unwrap();
+        drop(notifier);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
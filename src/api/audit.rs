@@ -0,0 +1,61 @@
+//! Audit trail of API requests
+//!
+//! Every request is appended to the audit log as one JSON line with the
+//! token name, never the token itself.
+
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// One audited request
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    /// Request time (RFC 3339)
+    pub time: String,
+    /// Client address
+    pub remote: Option<String>,
+    /// Name of the token used, if it was valid
+    pub token: Option<String>,
+    /// HTTP method
+    pub method: String,
+    /// Request path including the query
+    pub path: String,
+    /// Request body of a settings change
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+    /// Response status
+    pub status: u16,
+    /// Whether changes were only simulated
+    pub dry_run: bool,
+}
+
+/// Append-only audit log file
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    /// Use the audit log at `path`, created on the first request
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Get the log file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry
+    pub fn record(&self, entry: &AuditEntry) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        writeln!(file, "{}", line)
+    }
+}
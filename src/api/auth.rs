@@ -0,0 +1,110 @@
+//! Bearer token authentication
+//!
+//! Tokens come from `[[api.tokens]]`. A `control` token can do everything a
+//! `read` token can.
+
+use crate::config::ApiToken;
+
+/// Why a request was not authorized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    /// No bearer token was sent
+    Missing,
+    /// The token is not configured
+    Invalid,
+    /// The token lacks the required scope
+    Forbidden,
+}
+
+impl AuthError {
+    /// HTTP status code for the failure
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::Missing | Self::Invalid => 401,
+            Self::Forbidden => 403,
+        }
+    }
+
+    /// Message returned to the client
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Missing => "missing bearer token",
+            Self::Invalid => "invalid bearer token",
+            Self::Forbidden => "token is not allowed to change settings",
+        }
+    }
+}
+
+/// Configured API tokens
+#[derive(Debug, Clone)]
+pub struct Tokens {
+    tokens: Vec<ApiToken>,
+}
+
+impl Tokens {
+    /// Create the token set
+    pub fn new(tokens: Vec<ApiToken>) -> Self {
+        Self { tokens }
+    }
+
+    /// Check if no token is configured
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Find the token of an `Authorization` header value
+    pub fn authenticate(&self, authorization: Option<&str>) -> Result<&ApiToken, AuthError> {
+        let secret = authorization
+            .and_then(|value| value.trim().strip_prefix("Bearer "))
+            .map(str::trim)
+            .filter(|secret| !secret.is_empty())
+            .ok_or(AuthError::Missing)?;
+
+        self.tokens
+            .iter()
+            .find(|token| constant_time_eq(token.token.as_bytes(), secret.as_bytes()))
+            .ok_or(AuthError::Invalid)
+    }
+}
+
+/// Compare secrets without exiting at the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiScope;
+
+    #[test]
+    fn test_authenticate() {
+        let tokens = Tokens::new(vec![
+            ApiToken {
+                name: "grafana".to_string(),
+                token: "read-secret".to_string(),
+                scope: ApiScope::Read,
+            },
+            ApiToken {
+                name: "ops".to_string(),
+                token: "control-secret".to_string(),
+                scope: ApiScope::Control,
+            },
+        ]);
+
+        assert_eq!(tokens.authenticate(None).unwrap_err(), AuthError::Missing);
+        assert_eq!(
+            tokens.authenticate(Some("Basic b3BzOg==")).unwrap_err(),
+            AuthError::Missing
+        );
+        assert_eq!(
+            tokens.authenticate(Some("Bearer wrong")).unwrap_err(),
+            AuthError::Invalid
+        );
+        let token = tokens.authenticate(Some("Bearer control-secret")).unwrap();
+        assert_eq!(token.name, "ops");
+        assert!(token.scope >= ApiScope::Read);
+        let token = tokens.authenticate(Some("Bearer read-secret")).unwrap();
+        assert!(token.scope < ApiScope::Control);
+    }
+}
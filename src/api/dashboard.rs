@@ -0,0 +1,73 @@
+//! Live web dashboard
+//!
+//! A static page embedded in the binary that draws gauges and charts from
+//! the `/api/v1/stream` Server-Sent Events feed. The control panel only
+//! appears for `control` tokens and uses the regular API endpoints.
+
+use super::GpuSnapshotView;
+use crate::health::{HealthCalculator, HealthIssue};
+use crate::nvml::GpuSnapshot;
+
+use serde::Serialize;
+
+const INDEX_HTML: &str = include_str!("dashboard/index.html");
+const DASHBOARD_JS: &str = include_str!("dashboard/dashboard.js");
+const DASHBOARD_CSS: &str = include_str!("dashboard/dashboard.css");
+
+/// Content type of a Server-Sent Events stream
+pub const EVENT_STREAM: &str = "text/event-stream";
+
+/// Get an embedded asset and its content type by request path
+pub fn asset(path: &str) -> Option<(&'static str, &'static str)> {
+    match path {
+        "/" | "/index.html" => Some(("text/html; charset=utf-8", INDEX_HTML)),
+        "/dashboard.js" => Some(("text/javascript; charset=utf-8", DASHBOARD_JS)),
+        "/dashboard.css" => Some(("text/css; charset=utf-8", DASHBOARD_CSS)),
+        _ => None,
+    }
+}
+
+/// Dashboard readings of one GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardGpu {
+    #[serde(flatten)]
+    pub gpu: GpuSnapshotView,
+    pub pcie_generation: Option<u8>,
+    pub pcie_width: Option<u8>,
+    pub pcie_tx_bytes_per_sec: Option<u64>,
+    pub pcie_rx_bytes_per_sec: Option<u64>,
+    pub pcie_replays: Option<u64>,
+    pub ecc_corrected: Option<u64>,
+    pub ecc_uncorrected: Option<u64>,
+    pub health: Option<u8>,
+    pub health_status: Option<String>,
+    pub health_issues: Vec<HealthIssue>,
+}
+
+impl DashboardGpu {
+    /// Collect the dashboard readings of a snapshot
+    pub fn new(snapshot: &GpuSnapshot, health: &HealthCalculator) -> Self {
+        let pcie = snapshot.pcie_metrics.as_ref().ok();
+        let ecc = snapshot.ecc_errors.as_ref().ok().copied().flatten();
+        let breakdown = health.calculate_snapshot(snapshot);
+        Self {
+            gpu: GpuSnapshotView::new(snapshot),
+            pcie_generation: pcie.map(|p| p.link_status.current_generation.generation_number()),
+            pcie_width: pcie.map(|p| p.link_status.current_width.lanes()),
+            pcie_tx_bytes_per_sec: pcie.map(|p| p.throughput.tx_bytes_per_sec()),
+            pcie_rx_bytes_per_sec: pcie.map(|p| p.throughput.rx_bytes_per_sec()),
+            pcie_replays: pcie.map(|p| p.replay_counter.count()),
+            ecc_corrected: ecc.map(|e| e.correctable_current),
+            ecc_uncorrected: ecc.map(|e| e.uncorrectable_current),
+            health: breakdown.as_ref().map(|b| b.overall.score()),
+            health_status: breakdown.as_ref().map(|b| b.overall.status().to_string()),
+            health_issues: breakdown.map(|b| b.issues).unwrap_or_default(),
+        }
+    }
+}
+
+/// Format readings as one Server-Sent Event
+pub fn event(gpus: &[DashboardGpu]) -> String {
+    let data = serde_json::to_string(gpus).unwrap_or_else(|_| "[]".to_string());
+    format!("event: gpus\ndata: {}\n\n", data)
+}
@@ -0,0 +1,275 @@
+// nvctl web dashboard: renders the /api/v1/stream feed and, for control
+// tokens, posts settings changes to the REST API.
+"use strict";
+
+const HISTORY = 120;
+const TOKEN_KEY = "nvctl-token";
+const ARC_LENGTH = 126;
+
+const cards = new Map();
+let source = null;
+let knownGpus = "";
+
+const $ = (id) => document.getElementById(id);
+const token = () => sessionStorage.getItem(TOKEN_KEY) || "";
+
+function setStatus(text, kind) {
+  const status = $("status");
+  status.textContent = text;
+  status.className = "status " + (kind || "");
+}
+
+function connect() {
+  if (source) {
+    source.close();
+  }
+  const url = token()
+    ? "/api/v1/stream?access_token=" + encodeURIComponent(token())
+    : "/api/v1/stream";
+  source = new EventSource(url);
+  source.addEventListener("gpus", (event) => {
+    setStatus("live", "live");
+    render(JSON.parse(event.data));
+  });
+  source.onerror = () => {
+    if (source.readyState === EventSource.CLOSED) {
+      setStatus(token() ? "token rejected" : "token required", "error");
+    } else {
+      setStatus("reconnecting…", "error");
+    }
+  };
+}
+
+function render(gpus) {
+  for (const gpu of gpus) {
+    let card = cards.get(gpu.index);
+    if (!card) {
+      card = createCard(gpu);
+      cards.set(gpu.index, card);
+    }
+    updateCard(card, gpu);
+  }
+  const ids = gpus.map((gpu) => gpu.index + ":" + gpu.name).join(",");
+  if (ids !== knownGpus) {
+    knownGpus = ids;
+    $("control-gpu").replaceChildren(
+      ...gpus.map((gpu) => new Option(gpu.index + ": " + (gpu.name || "GPU"), gpu.index))
+    );
+  }
+}
+
+function createCard(gpu) {
+  const node = $("gpu-card").content.firstElementChild.cloneNode(true);
+  $("gpus").appendChild(node);
+  return { node, history: [] };
+}
+
+function setGauge(node, metric, value, max, text) {
+  const gauge = node.querySelector(`.gauge[data-metric="${metric}"]`);
+  const fraction = value == null || !max ? 0 : Math.min(Math.max(value / max, 0), 1);
+  const arc = gauge.querySelector(".value");
+  arc.style.strokeDasharray = `${fraction * ARC_LENGTH} 200`;
+  arc.style.stroke = fraction > 0.9 ? "var(--bad)" : fraction > 0.75 ? "var(--warn)" : "";
+  gauge.querySelector(".reading").textContent =
+    value == null ? "—" : text + gauge.dataset.unit;
+}
+
+function updateCard(card, gpu) {
+  const { node } = card;
+  node.querySelector(".name").textContent = gpu.name || "GPU";
+  node.querySelector(".index").textContent = "#" + gpu.index;
+
+  const fans = gpu.fan_speeds.filter((speed) => speed != null);
+  const fan = fans.length ? Math.max(...fans) : null;
+  const vram = gpu.memory_total ? (100 * gpu.memory_used) / gpu.memory_total : null;
+  setGauge(node, "temperature", gpu.temperature, 100, gpu.temperature);
+  setGauge(node, "fan", fan, 100, fan);
+  setGauge(node, "power", gpu.power_usage, gpu.power_limit, gpu.power_usage);
+  setGauge(node, "vram", vram, 100, vram == null ? "" : vram.toFixed(0));
+
+  const health = node.querySelector(".health");
+  health.textContent =
+    gpu.health == null
+      ? "—"
+      : `${gpu.health}/100 (${gpu.health_status})` +
+        gpu.health_issues.map((issue) => " · " + issue.description).join("");
+  health.className = "health " + (gpu.health == null ? "" : gpu.health < 50 ? "bad" : gpu.health < 75 ? "warn" : "");
+
+  node.querySelector(".pcie").textContent =
+    gpu.pcie_generation == null
+      ? "—"
+      : `Gen ${gpu.pcie_generation} x${gpu.pcie_width}, ` +
+        `TX ${formatRate(gpu.pcie_tx_bytes_per_sec)}, RX ${formatRate(gpu.pcie_rx_bytes_per_sec)}, ` +
+        `${gpu.pcie_replays} replays`;
+
+  const ecc = node.querySelector(".ecc");
+  ecc.textContent =
+    gpu.ecc_corrected == null
+      ? "not available"
+      : `${gpu.ecc_corrected} corrected, ${gpu.ecc_uncorrected} uncorrected`;
+  ecc.className = "ecc " + (gpu.ecc_uncorrected > 0 ? "bad" : "");
+
+  node.querySelector(".clocks").textContent =
+    `${gpu.graphics_clock ?? "—"} MHz graphics, ${gpu.memory_clock ?? "—"} MHz memory`;
+  node.querySelector(".pstate").textContent = gpu.performance_state || "—";
+
+  card.history.push({
+    temp: gpu.temperature,
+    power: gpu.power_limit ? (100 * gpu.power_usage) / gpu.power_limit : null,
+    util: gpu.gpu_utilization,
+  });
+  if (card.history.length > HISTORY) {
+    card.history.shift();
+  }
+  drawChart(node.querySelector(".chart"), card.history);
+}
+
+function formatRate(bytes) {
+  if (bytes == null) {
+    return "—";
+  }
+  const units = ["B/s", "KB/s", "MB/s", "GB/s"];
+  let unit = 0;
+  while (bytes >= 1000 && unit < units.length - 1) {
+    bytes /= 1000;
+    unit += 1;
+  }
+  return bytes.toFixed(unit ? 1 : 0) + " " + units[unit];
+}
+
+// Every series is drawn on a 0-100 scale (°C or percent)
+function drawChart(canvas, history) {
+  const ctx = canvas.getContext("2d");
+  const { width, height } = canvas;
+  ctx.clearRect(0, 0, width, height);
+  ctx.strokeStyle = "#2a2e35";
+  ctx.lineWidth = 1;
+  for (const level of [25, 50, 75]) {
+    const y = height - (level / 100) * height;
+    ctx.beginPath();
+    ctx.moveTo(0, y);
+    ctx.lineTo(width, y);
+    ctx.stroke();
+  }
+
+  const style = getComputedStyle(document.documentElement);
+  for (const key of ["util", "power", "temp"]) {
+    ctx.strokeStyle = style.getPropertyValue("--" + key);
+    ctx.lineWidth = 2;
+    ctx.beginPath();
+    let drawing = false;
+    history.forEach((sample, i) => {
+      const value = sample[key];
+      if (value == null) {
+        drawing = false;
+        return;
+      }
+      const x = (i / (HISTORY - 1)) * width;
+      const y = height - (Math.min(value, 100) / 100) * height;
+      if (drawing) {
+        ctx.lineTo(x, y);
+      } else {
+        ctx.moveTo(x, y);
+        drawing = true;
+      }
+    });
+    ctx.stroke();
+  }
+}
+
+async function api(method, path, body) {
+  const response = await fetch(path, {
+    method,
+    headers: {
+      Authorization: "Bearer " + token(),
+      "Content-Type": "application/json",
+    },
+    body: body === undefined ? undefined : JSON.stringify(body),
+  });
+  const result = await response.json();
+  if (!response.ok) {
+    throw new Error(result.error || response.statusText);
+  }
+  return result;
+}
+
+function log(message) {
+  const output = $("control-log");
+  output.textContent = `${new Date().toLocaleTimeString()} ${message}\n` + output.textContent;
+}
+
+async function showControls() {
+  $("control").hidden = true;
+  if (!token()) {
+    return;
+  }
+  try {
+    const info = await api("GET", "/api/v1/token");
+    if (info.scope !== "control") {
+      return;
+    }
+    const profiles = await api("GET", "/api/v1/profiles");
+    $("profile").replaceChildren(...profiles.map((name) => new Option(name, name)));
+    $("control").hidden = false;
+  } catch (error) {
+    setStatus(error.message, "error");
+  }
+}
+
+$("control-form").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  const gpu = $("control-gpu").value;
+  const query = $("dry-run").checked ? "?dry_run=true" : "";
+  const changes = [];
+  if ($("fan-policy").value) {
+    changes.push(["fan/policy", { policy: $("fan-policy").value }]);
+  }
+  if ($("fan-speed").value !== "") {
+    changes.push(["fan/speed", { speed: Number($("fan-speed").value) }]);
+  }
+  if ($("power-limit").value !== "") {
+    changes.push(["power-limit", { watts: Number($("power-limit").value) }]);
+  }
+  if ($("acoustic-limit").value !== "") {
+    changes.push(["acoustic-limit", { celsius: Number($("acoustic-limit").value) }]);
+  }
+  for (const [path, body] of changes) {
+    try {
+      const result = await api("POST", `/api/v1/gpus/${gpu}/${path}${query}`, body);
+      log(`GPU ${result.gpu}: ${result.message}${result.dry_run ? " (dry run)" : ""}`);
+    } catch (error) {
+      log(`GPU ${gpu}: ${error.message}`);
+    }
+  }
+});
+
+$("profile-form").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  const name = $("profile").value;
+  const query = $("dry-run").checked ? "?dry_run=true" : "";
+  try {
+    const results = await api("POST", `/api/v1/profiles/${encodeURIComponent(name)}/apply${query}`);
+    for (const result of results) {
+      log(`GPU ${result.gpu}: ${result.message}${result.dry_run ? " (dry run)" : ""}`);
+    }
+  } catch (error) {
+    log(error.message);
+  }
+});
+
+$("token-form").addEventListener("submit", (event) => {
+  event.preventDefault();
+  sessionStorage.setItem(TOKEN_KEY, $("token").value.trim());
+  $("token").value = "";
+  connect();
+  showControls();
+});
+
+$("forget").addEventListener("click", () => {
+  sessionStorage.removeItem(TOKEN_KEY);
+  connect();
+  showControls();
+});
+
+connect();
+showControls();
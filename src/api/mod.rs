@@ -0,0 +1,1087 @@
+//! Authenticated REST API
+//!
+//! Serves GPU state as JSON under `/api/v1` and accepts settings changes.
+//! Every request needs a bearer token, changes need a `control` token and go
+//! through the same services and domain validation as the CLI. Each request
+//! is recorded in the audit log. The live dashboard is served from `/`.
+
+pub mod audit;
+pub mod auth;
+pub mod dashboard;
+
+pub use audit::{AuditEntry, AuditLog};
+pub use auth::{AuthError, Tokens};
+pub use dashboard::DashboardGpu;
+
+use crate::cli::output::GpuListEntry;
+use crate::commands::health::health_status;
+use crate::config::{ApiScope, ApiToken, GpuIdentity, ProfileSettings};
+use crate::domain::{FanPolicy, FanSpeed, PowerLimit, Temperature};
+use crate::error::{AppError, NvmlError, Result, ServiceError};
+use crate::health::HealthCalculator;
+use crate::nvml::{GpuDevice, GpuManager, GpuSnapshot, SnapshotScope};
+use crate::services::{FanService, PowerService, ThermalService};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest request body accepted
+const MAX_BODY: u64 = 64 * 1024;
+
+/// Longest request body kept in the audit log
+const MAX_AUDIT_BODY: usize = 4096;
+
+/// Server-Sent Events feed of all GPUs
+const STREAM_PATH: &str = "/api/v1/stream";
+
+/// Events queued for a stream before its client counts as stalled
+const STREAM_QUEUE: usize = 8;
+
+/// How often the serve loop picks up bodies read in the background
+const BODY_POLL: Duration = Duration::from_millis(20);
+
+/// A request whose body was read off the serve thread
+type ReadBody = (Request, io::Result<String>);
+
+/// An HTTP request to the API
+#[derive(Debug, Clone)]
+pub struct ApiRequest<'a> {
+    /// HTTP method
+    pub method: &'a Method,
+    /// Path and query
+    pub url: &'a str,
+    /// `Authorization` header value
+    pub authorization: Option<&'a str>,
+    /// Request body
+    pub body: &'a str,
+    /// Client address
+    pub remote: Option<String>,
+}
+
+/// Response of the API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiResponse {
+    /// Status code
+    pub status: u16,
+    /// `Content-Type` of the body
+    pub content_type: &'static str,
+    /// Response body, the first event of a stream
+    pub body: String,
+    /// Whether the connection stays open for further events
+    pub stream: bool,
+}
+
+impl ApiResponse {
+    fn json(status: u16, body: String) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body,
+            stream: false,
+        }
+    }
+}
+
+/// A failed request, returned as `{"error": ...}`
+#[derive(Debug, Clone)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            message: message.into(),
+        }
+    }
+
+    fn not_found() -> Self {
+        Self::new(404, "not found")
+    }
+}
+
+impl From<AppError> for ApiError {
+    fn from(err: AppError) -> Self {
+        let status = match &err {
+            AppError::Domain(_) => 400,
+            AppError::Nvml(e) => match e {
+                NvmlError::DeviceNotFound(_) | NvmlError::DeviceNotFoundByUuid(_) => 404,
+                NvmlError::InvalidArgument(_) => 400,
+                NvmlError::InsufficientPermissions(_) => 403,
+                NvmlError::NotSupported(_) | NvmlError::FanControlNotAvailable => 501,
+                _ => 500,
+            },
+            _ => 500,
+        };
+        Self::new(status, err.to_string())
+    }
+}
+
+impl From<ServiceError> for ApiError {
+    fn from(err: ServiceError) -> Self {
+        AppError::from(err).into()
+    }
+}
+
+impl From<NvmlError> for ApiError {
+    fn from(err: NvmlError) -> Self {
+        AppError::from(err).into()
+    }
+}
+
+/// Current readings of one GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuSnapshotView {
+    pub index: u32,
+    pub uuid: Option<String>,
+    pub name: Option<String>,
+    pub temperature: Option<i32>,
+    pub memory_temperature: Option<i32>,
+    pub fan_speeds: Vec<Option<u8>>,
+    pub fan_policies: Vec<Option<FanPolicy>>,
+    pub power_usage: Option<u32>,
+    pub power_limit: Option<u32>,
+    pub power_limit_min: Option<u32>,
+    pub power_limit_max: Option<u32>,
+    pub graphics_clock: Option<u32>,
+    pub memory_clock: Option<u32>,
+    pub gpu_utilization: Option<u8>,
+    pub memory_utilization: Option<u8>,
+    pub memory_used: Option<u64>,
+    pub memory_total: Option<u64>,
+    pub performance_state: Option<String>,
+}
+
+impl GpuSnapshotView {
+    /// Flatten a snapshot, dropping readings that failed
+    pub fn new(snapshot: &GpuSnapshot) -> Self {
+        let constraints = snapshot.power_constraints.as_ref().ok();
+        let utilization = snapshot.utilization.as_ref().ok();
+        let memory = snapshot.memory_info.as_ref().ok();
+        Self {
+            index: snapshot.index,
+            uuid: snapshot.uuid.clone().ok(),
+            name: snapshot.name.clone().ok(),
+            temperature: snapshot.temperature.as_ref().ok().map(|t| t.as_celsius()),
+            memory_temperature: snapshot
+                .memory_temperature
+                .as_ref()
+                .ok()
+                .copied()
+                .flatten()
+                .map(|t| t.as_celsius()),
+            fan_speeds: snapshot
+                .fan_speeds
+                .iter()
+                .map(|s| s.as_ref().ok().map(|s| s.as_percentage()))
+                .collect(),
+            fan_policies: snapshot
+                .fan_policies
+                .iter()
+                .map(|p| p.as_ref().ok().copied())
+                .collect(),
+            power_usage: snapshot.power_usage.as_ref().ok().map(|p| p.as_watts()),
+            power_limit: snapshot.power_limit.as_ref().ok().map(|p| p.as_watts()),
+            power_limit_min: constraints.map(|c| c.min.as_watts()),
+            power_limit_max: constraints.map(|c| c.max.as_watts()),
+            graphics_clock: snapshot.graphics_clock.as_ref().ok().map(|c| c.as_mhz()),
+            memory_clock: snapshot.memory_clock.as_ref().ok().map(|c| c.as_mhz()),
+            gpu_utilization: utilization.map(|u| u.gpu),
+            memory_utilization: utilization.map(|u| u.memory),
+            memory_used: memory.map(|m| m.used),
+            memory_total: memory.map(|m| m.total),
+            performance_state: snapshot
+                .performance_state
+                .as_ref()
+                .ok()
+                .map(|p| p.to_string()),
+        }
+    }
+}
+
+/// Outcome of a settings change on one GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct ActionResult {
+    /// GPU index
+    pub gpu: u32,
+    /// What was done
+    pub message: String,
+    /// Whether the change was only simulated
+    pub dry_run: bool,
+}
+
+/// Outcome of applying a profile on one GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileResult {
+    /// The change made
+    #[serde(flatten)]
+    pub result: ActionResult,
+    /// Whether the profile's fan curve was applied, which the API never does
+    pub fan_curve_applied: bool,
+}
+
+#[derive(Deserialize)]
+struct FanPolicyBody {
+    policy: FanPolicy,
+}
+
+#[derive(Deserialize)]
+struct FanSpeedBody {
+    speed: u8,
+    fan: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct PowerLimitBody {
+    watts: u32,
+}
+
+#[derive(Deserialize)]
+struct AcousticLimitBody {
+    celsius: i32,
+}
+
+/// REST API over all GPUs of a manager
+pub struct Api<'a, M: GpuManager> {
+    manager: &'a M,
+    tokens: Tokens,
+    profiles: BTreeMap<String, ProfileSettings>,
+    dry_run: bool,
+    audit: Option<AuditLog>,
+    health: HealthCalculator,
+    dashboard: bool,
+    public_stream: bool,
+    stream_interval: Duration,
+}
+
+impl<'a, M: GpuManager> Api<'a, M> {
+    /// Create an API accepting the given tokens
+    pub fn new(manager: &'a M, tokens: Tokens) -> Self {
+        Self {
+            manager,
+            tokens,
+            profiles: BTreeMap::new(),
+            dry_run: false,
+            audit: None,
+            health: HealthCalculator::default(),
+            dashboard: false,
+            public_stream: false,
+            stream_interval: Duration::from_secs(1),
+        }
+    }
+
+    /// Profiles that can be applied
+    pub fn with_profiles(mut self, profiles: BTreeMap<String, ProfileSettings>) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    /// Only simulate every change
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Record every request in an audit log
+    pub fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Serve the web dashboard from `/`
+    pub fn with_dashboard(mut self, dashboard: bool) -> Self {
+        self.dashboard = dashboard;
+        self
+    }
+
+    /// Allow reading the event stream without a token
+    pub fn with_public_stream(mut self, public: bool) -> Self {
+        self.public_stream = public;
+        self
+    }
+
+    /// Time between events of the stream
+    pub fn with_stream_interval(mut self, interval: Duration) -> Self {
+        self.stream_interval = interval;
+        self
+    }
+
+    /// Handle a request and record it in the audit log
+    ///
+    /// `GET` requests need a `read` token and `POST` requests a `control`
+    /// token. `?dry_run=true` simulates a single change. Browsers cannot set
+    /// headers on an event stream, so it also takes `?access_token=`.
+    pub fn handle(&self, request: &ApiRequest<'_>) -> ApiResponse {
+        let (path, query) = request.url.split_once('?').unwrap_or((request.url, ""));
+        if self.dashboard && *request.method == Method::Get {
+            if let Some((content_type, body)) = dashboard::asset(path) {
+                return ApiResponse {
+                    status: 200,
+                    content_type,
+                    body: body.to_string(),
+                    stream: false,
+                };
+            }
+        }
+
+        let stream = *request.method == Method::Get && path.trim_end_matches('/') == STREAM_PATH;
+        let query_token = query_param(query, "access_token")
+            .filter(|_| stream)
+            .map(|token| format!("Bearer {}", percent_decode(token)));
+        let authorization = request.authorization.or(query_token.as_deref());
+        let dry_run = self.dry_run
+            || query
+                .split('&')
+                .any(|pair| matches!(pair, "dry_run" | "dry_run=true" | "dry_run=1"));
+        let required = if *request.method == Method::Post {
+            ApiScope::Control
+        } else {
+            ApiScope::Read
+        };
+
+        let token = match authorization {
+            None if stream && self.public_stream => Ok(None),
+            _ => self.tokens.authenticate(authorization).map(Some),
+        };
+        let token_name = token.ok().flatten().map(|t| t.name.clone());
+        let result = match token {
+            Err(e) => Err(ApiError::new(e.status(), e.message())),
+            Ok(Some(t)) if t.scope < required => Err(ApiError::new(
+                AuthError::Forbidden.status(),
+                AuthError::Forbidden.message(),
+            )),
+            Ok(_) if stream => Ok(ApiResponse {
+                status: 200,
+                content_type: dashboard::EVENT_STREAM,
+                body: self.stream_event(),
+                stream: true,
+            }),
+            Ok(Some(t)) => self
+                .route(request.method, path, query, request.body, t, dry_run)
+                .map(|value| ApiResponse::json(200, value.to_string())),
+            // Only the stream is public
+            Ok(None) => Err(ApiError::new(
+                AuthError::Missing.status(),
+                AuthError::Missing.message(),
+            )),
+        };
+        let response = result.unwrap_or_else(|e| {
+            ApiResponse::json(e.status, json!({ "error": e.message }).to_string())
+        });
+
+        if let Some(audit) = &self.audit {
+            let entry = AuditEntry {
+                time: jiff::Timestamp::now().to_string(),
+                remote: request.remote.clone(),
+                token: token_name,
+                method: request.method.to_string(),
+                path: redact_token(request.url),
+                body: (required == ApiScope::Control && !request.body.is_empty())
+                    .then(|| truncate(request.body, MAX_AUDIT_BODY)),
+                status: response.status,
+                dry_run,
+            };
+            if let Err(e) = audit.record(&entry) {
+                log::warn!(
+                    "Failed to write audit log {}: {}",
+                    audit.path().display(),
+                    e
+                );
+            }
+        }
+        response
+    }
+
+    /// Serve HTTP requests until the process exits
+    ///
+    /// Requests and stream events are handled on this thread, so GPUs are only
+    /// read from one thread. Bodies are only read for requests whose token
+    /// may send them, each on its own thread, so a slow client can't hold up
+    /// others. Each stream is written by its own thread, and a client that
+    /// stops reading is dropped instead of stalling requests.
+    pub fn serve(&self, listen: &str) -> Result<()> {
+        let server = Server::http(listen).map_err(io::Error::other)?;
+        log::info!("Serving REST API on http://{}/api/v1", listen);
+
+        let mut streams: Vec<SyncSender<String>> = Vec::new();
+        let (body_sender, bodies) = mpsc::channel::<ReadBody>();
+        let mut reading = 0usize;
+        let mut next_event = Instant::now() + self.stream_interval;
+        loop {
+            let mut timeout = next_event.saturating_duration_since(Instant::now());
+            if reading > 0 {
+                timeout = timeout.min(BODY_POLL);
+            }
+            if let Some(request) = server.recv_timeout(timeout)? {
+                if self.accepts_body(&request) {
+                    reading += 1;
+                    read_body(request, body_sender.clone());
+                } else if let Some(stream) = self.respond(request, Ok(String::new())) {
+                    streams.push(stream);
+                }
+            }
+            while let Ok((request, body)) = bodies.try_recv() {
+                reading -= 1;
+                if let Some(stream) = self.respond(request, body) {
+                    streams.push(stream);
+                }
+            }
+
+            if Instant::now() >= next_event {
+                if !streams.is_empty() {
+                    let event = self.stream_event();
+                    streams.retain(|stream| match stream.try_send(event.clone()) {
+                        Ok(()) => true,
+                        Err(TrySendError::Full(_)) => {
+                            log::debug!("Dropping stalled event stream");
+                            false
+                        }
+                        Err(TrySendError::Disconnected(_)) => false,
+                    });
+                }
+                next_event = Instant::now() + self.stream_interval;
+            }
+        }
+    }
+
+    /// Check if a request is a change by a token allowed to make it
+    ///
+    /// Only these requests have their body read; everything else is answered
+    /// from the headers alone.
+    fn accepts_body(&self, request: &Request) -> bool {
+        *request.method() == Method::Post
+            && self
+                .tokens
+                .authenticate(authorization(request).as_deref())
+                .is_ok_and(|token| token.scope >= ApiScope::Control)
+    }
+
+    /// Answer one HTTP request, returning the event queue of a new stream
+    fn respond(&self, request: Request, body: io::Result<String>) -> Option<SyncSender<String>> {
+        let response = match body {
+            Ok(body) => self.handle(&ApiRequest {
+                method: request.method(),
+                url: request.url(),
+                authorization: authorization(&request).as_deref(),
+                body: &body,
+                remote: request.remote_addr().map(|addr| addr.to_string()),
+            }),
+            Err(_) => ApiResponse::json(
+                400,
+                json!({ "error": "request body is not UTF-8" }).to_string(),
+            ),
+        };
+        log::debug!(
+            "{} {} -> {}",
+            request.method(),
+            redact_token(request.url()),
+            response.status
+        );
+
+        if response.stream {
+            // tiny_http buffers chunked bodies, so the stream is written raw
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\n\
+                 Connection: keep-alive\r\n\r\n{}",
+                response.content_type, response.body
+            );
+            return Some(spawn_stream(request.into_writer(), head));
+        }
+
+        // Responding drains what is left of the body, which a slow client
+        // can drag out, so those requests are answered from their own thread
+        if has_body(&request) {
+            std::thread::spawn(move || send(request, response));
+        } else {
+            send(request, response);
+        }
+        None
+    }
+
+    /// Read every GPU as one stream event
+    fn stream_event(&self) -> String {
+        let gpus = match self.manager.all_devices() {
+            Ok(devices) => devices
+                .iter()
+                .map(|device| DashboardGpu::new(&GpuSnapshot::capture(device), &self.health))
+                .collect(),
+            Err(e) => {
+                log::warn!("Failed to read GPUs for the event stream: {}", e);
+                Vec::new()
+            }
+        };
+        dashboard::event(&gpus)
+    }
+
+    fn route(
+        &self,
+        method: &Method,
+        path: &str,
+        query: &str,
+        body: &str,
+        token: &ApiToken,
+        dry_run: bool,
+    ) -> std::result::Result<Value, ApiError> {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+        let Some(route) = segments.strip_prefix(&["api", "v1"]) else {
+            return Err(ApiError::not_found());
+        };
+
+        match (method, route) {
+            (Method::Get, ["token"]) => Ok(json!({ "name": token.name, "scope": token.scope })),
+            (Method::Get, ["gpus"]) => self.gpus(),
+            (Method::Get, ["gpus", id]) => {
+                let device = self.device(id)?;
+                Ok(json!(GpuSnapshotView::new(&GpuSnapshot::capture(&device))))
+            }
+            (Method::Get, ["gpus", id, "health"]) => {
+                let device = self.device(id)?;
+                let snapshot = GpuSnapshot::capture(&device);
+                let name = snapshot.name.clone().unwrap_or_default();
+                Ok(json!(health_status(&self.health, name, &snapshot)?))
+            }
+            (Method::Get, ["gpus", id, "processes"]) => {
+                Ok(json!(self.device(id)?.running_processes()?))
+            }
+            (Method::Post, ["gpus", id, "fan", "policy"]) => {
+                let FanPolicyBody { policy } = parse_body(body)?;
+                let mut device = self.device(id)?;
+                FanService::new(Default::default(), dry_run).set_policy(&mut device, policy)?;
+                Ok(action(
+                    &device,
+                    format!("Set fan policy to {}", policy),
+                    dry_run,
+                ))
+            }
+            (Method::Post, ["gpus", id, "fan", "speed"]) => {
+                let FanSpeedBody { speed, fan } = parse_body(body)?;
+                let speed = FanSpeed::new(speed).map_err(AppError::from)?;
+                let mut device = self.device(id)?;
+                FanService::new(Default::default(), dry_run).set_speed(&mut device, speed, fan)?;
+                let fans = fan.map_or("every fan".to_string(), |f| format!("fan {}", f));
+                Ok(action(
+                    &device,
+                    format!("Set {} speed to {}", fans, speed),
+                    dry_run,
+                ))
+            }
+            (Method::Post, ["gpus", id, "power-limit"]) => {
+                let PowerLimitBody { watts } = parse_body(body)?;
+                let mut device = self.device(id)?;
+                let limit = PowerLimit::from_watts(watts);
+                let snapshot = GpuSnapshot::capture(&device);
+                PowerService::new(Some(limit), dry_run).apply_limit(&mut device, &snapshot)?;
+                Ok(action(
+                    &device,
+                    format!("Set power limit to {}", limit),
+                    dry_run,
+                ))
+            }
+            (Method::Post, ["gpus", id, "acoustic-limit"]) => {
+                let AcousticLimitBody { celsius } = parse_body(body)?;
+                let mut device = self.device(id)?;
+                let limit = Temperature::new(celsius);
+                ThermalService::new(Some(limit), dry_run).apply_limit(&mut device)?;
+                Ok(action(
+                    &device,
+                    format!("Set acoustic limit to {}", limit),
+                    dry_run,
+                ))
+            }
+            (Method::Get, ["profiles"]) => Ok(json!(self.profiles.keys().collect::<Vec<_>>())),
+            (Method::Post, ["profiles", name, "apply"]) => {
+                let gpu = query_param(query, "gpu");
+                self.apply_profile(name, gpu, dry_run)
+            }
+            (Method::Get | Method::Post, _) => Err(ApiError::not_found()),
+            _ => Err(ApiError::new(405, "method not allowed")),
+        }
+    }
+
+    fn gpus(&self) -> std::result::Result<Value, ApiError> {
+        let gpus = self
+            .manager
+            .all_devices()?
+            .iter()
+            .map(|device| device.info().map(|info| GpuListEntry::from(&info)))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(json!(gpus))
+    }
+
+    /// Open a GPU by index or UUID
+    fn device(&self, id: &str) -> std::result::Result<M::Device, ApiError> {
+        let device = match id.parse() {
+            Ok(index) => self.manager.device_by_index(index),
+            Err(_) => self.manager.device_by_uuid(id),
+        };
+        Ok(device?)
+    }
+
+    /// Apply a profile's power and acoustic limits
+    ///
+    /// The fan curve is left alone: following it needs a control loop, which
+    /// the API doesn't run.
+    fn apply_profile(
+        &self,
+        name: &str,
+        gpu: Option<&str>,
+        dry_run: bool,
+    ) -> std::result::Result<Value, ApiError> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| ApiError::new(404, format!("profile '{}' is not defined", name)))?;
+        let devices = match gpu {
+            Some(id) => vec![self.device(id)?],
+            None => self.manager.all_devices()?,
+        };
+
+        let mut results = Vec::new();
+        for mut device in devices {
+            let identity = GpuIdentity::from_device(&device)?;
+            let settings = profile.settings_for(&identity);
+            let snapshot = GpuSnapshot::capture_scope(&device, SnapshotScope::CONTROL);
+
+            let power = PowerService::new(settings.power_limit, dry_run)
+                .apply_limit(&mut device, &snapshot)?;
+            let acoustic =
+                ThermalService::new(settings.acoustic_limit, dry_run).apply_limit(&mut device)?;
+
+            let mut message = format!("Applied profile '{}'", name);
+            if let Some(limit) = power {
+                message.push_str(&format!(": power limit {}", limit));
+            }
+            if let Some(limit) = acoustic {
+                message.push_str(if power.is_some() { ", " } else { ": " });
+                message.push_str(&format!("acoustic limit {}", limit));
+            }
+            message.push_str(" (fan curve not applied; run `nvctl control` to follow it)");
+            results.push(ProfileResult {
+                result: ActionResult {
+                    gpu: device.index(),
+                    message,
+                    dry_run,
+                },
+                fan_curve_applied: false,
+            });
+        }
+        Ok(json!(results))
+    }
+}
+
+/// `Authorization` header of a request
+fn authorization(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.equiv("Authorization"))
+        .map(|h| h.value.as_str().to_string())
+}
+
+/// Check if a request was sent with a body
+fn has_body(request: &Request) -> bool {
+    request.body_length().is_some_and(|len| len > 0)
+        || request
+            .headers()
+            .iter()
+            .any(|h| h.field.equiv("Transfer-Encoding"))
+}
+
+/// Write a response to a request
+fn send(request: Request, response: ApiResponse) {
+    let header =
+        Header::from_bytes("Content-Type", response.content_type).expect("static header is valid");
+    let result = request.respond(
+        Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(header),
+    );
+    if let Err(e) = result {
+        log::warn!("Failed to send response: {}", e);
+    }
+}
+
+/// Read a request body on its own thread and send it back with the request
+fn read_body(mut request: Request, done: mpsc::Sender<ReadBody>) {
+    std::thread::spawn(move || {
+        let mut body = String::new();
+        let read = request.as_reader().take(MAX_BODY).read_to_string(&mut body);
+        let _ = done.send((request, read.map(|_| body)));
+    });
+}
+
+/// Write queued events to a stream connection until it closes
+///
+/// Returns the queue; dropping it ends the stream.
+fn spawn_stream(mut writer: Box<dyn Write + Send>, head: String) -> SyncSender<String> {
+    let (sender, receiver) = mpsc::sync_channel::<String>(STREAM_QUEUE);
+    std::thread::spawn(move || {
+        for data in std::iter::once(head).chain(receiver) {
+            let sent = writer
+                .write_all(data.as_bytes())
+                .and_then(|_| writer.flush());
+            if sent.is_err() {
+                return;
+            }
+        }
+    });
+    sender
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> std::result::Result<T, ApiError> {
+    serde_json::from_str(body).map_err(|e| ApiError::new(400, format!("invalid body: {}", e)))
+}
+
+fn action<D: GpuDevice>(device: &D, message: String, dry_run: bool) -> Value {
+    json!(ActionResult {
+        gpu: device.index(),
+        message,
+        dry_run,
+    })
+}
+
+fn query_param<'q>(query: &'q str, name: &str) -> Option<&'q str> {
+    query.split('&').find_map(|pair| {
+        pair.strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('='))
+    })
+}
+
+/// Decode `%XX` escapes of a query value
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(if bytes[i] == b'+' { b' ' } else { bytes[i] });
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Hide a token passed in the query from logs
+fn redact_token(url: &str) -> String {
+    let Some((path, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let query: Vec<&str> = query
+        .split('&')
+        .map(|pair| {
+            if pair.starts_with("access_token=") {
+                "access_token=REDACTED"
+            } else {
+                pair
+            }
+        })
+        .collect();
+    format!("{}?{}", path, query.join("&"))
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    let mut end = text.len().min(max);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ApiToken, DeviceSettings};
+    use crate::domain::FanCurve;
+    use crate::mock::MockManager;
+
+    fn api(manager: &MockManager) -> Api<'_, MockManager> {
+        Api::new(
+            manager,
+            Tokens::new(vec![
+                ApiToken {
+                    name: "viewer".to_string(),
+                    token: "r".to_string(),
+                    scope: ApiScope::Read,
+                },
+                ApiToken {
+                    name: "ops".to_string(),
+                    token: "c".to_string(),
+                    scope: ApiScope::Control,
+                },
+            ]),
+        )
+    }
+
+    fn request<'a>(
+        method: &'a Method,
+        url: &'a str,
+        token: &'a str,
+        body: &'a str,
+    ) -> ApiRequest<'a> {
+        ApiRequest {
+            method,
+            url,
+            authorization: Some(token),
+            body,
+            remote: None,
+        }
+    }
+
+    #[test]
+    fn test_read_endpoints_and_scopes() {
+        let manager = MockManager::new(2);
+        let api = api(&manager);
+
+        let response = api.handle(&request(&Method::Get, "/api/v1/gpus", "Bearer r", ""));
+        assert_eq!(response.status, 200);
+        let gpus: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(gpus.as_array().unwrap().len(), 2);
+
+        let response = api.handle(&request(&Method::Get, "/api/v1/gpus/1", "Bearer r", ""));
+        let snapshot: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(snapshot["index"], 1);
+        assert!(snapshot["temperature"].is_number());
+
+        assert_eq!(
+            api.handle(&request(&Method::Get, "/api/v1/gpus", "Bearer x", ""))
+                .status,
+            401
+        );
+        let body = r#"{"watts": 250}"#;
+        let url = "/api/v1/gpus/0/power-limit";
+        assert_eq!(
+            api.handle(&request(&Method::Post, url, "Bearer r", body))
+                .status,
+            403
+        );
+        assert_eq!(
+            api.handle(&request(&Method::Get, "/api/v1/gpus/7", "Bearer r", ""))
+                .status,
+            404
+        );
+    }
+
+    #[test]
+    fn test_control_endpoints_validate_and_audit() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = MockManager::new(1);
+        let api = api(&manager).with_audit_log(AuditLog::new(dir.path().join("audit.log")));
+        let url = "/api/v1/gpus/0/power-limit";
+
+        let response = api.handle(&request(
+            &Method::Post,
+            url,
+            "Bearer c",
+            r#"{"watts": 250}"#,
+        ));
+        assert_eq!(response.status, 200, "{}", response.body);
+        let result: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(result["dry_run"], false);
+
+        // Outside the GPU's allowed range
+        let response = api.handle(&request(
+            &Method::Post,
+            url,
+            "Bearer c",
+            r#"{"watts": 5000}"#,
+        ));
+        assert_eq!(response.status, 400);
+
+        let url = "/api/v1/gpus/0/fan/speed?dry_run=true";
+        let response = api.handle(&request(&Method::Post, url, "Bearer c", r#"{"speed": 60}"#));
+        let result: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(result["dry_run"], true);
+
+        let audit = std::fs::read_to_string(dir.path().join("audit.log")).unwrap();
+        let entries: Vec<Value> = audit
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0]["token"], "ops");
+        assert_eq!(entries[0]["body"], r#"{"watts": 250}"#);
+        assert_eq!(entries[1]["status"], 400);
+        assert_eq!(entries[2]["dry_run"], true);
+        assert!(!audit.contains("Bearer"));
+    }
+
+    #[test]
+    fn test_stalled_stream_does_not_block() {
+        /// A client that stopped reading
+        struct Stalled(mpsc::Receiver<()>);
+
+        impl Write for Stalled {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                let _ = self.0.recv();
+                Err(io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let (release, stalled) = mpsc::channel();
+        let stream = spawn_stream(Box::new(Stalled(stalled)), "head".to_string());
+        let started = Instant::now();
+        let full = (0..=STREAM_QUEUE).any(|_| {
+            matches!(
+                stream.try_send("event".to_string()),
+                Err(TrySendError::Full(_))
+            )
+        });
+        assert!(full);
+        assert!(started.elapsed() < Duration::from_secs(1));
+        drop(release);
+    }
+
+    #[test]
+    fn test_slow_body_does_not_block() {
+        use std::io::{BufRead, BufReader};
+        use std::net::{TcpListener, TcpStream};
+
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let listen = format!("127.0.0.1:{}", port);
+        let manager: &'static MockManager = Box::leak(Box::new(MockManager::new(1)));
+        let serve_on = listen.clone();
+        std::thread::spawn(move || api(manager).serve(&serve_on));
+
+        let connect = || loop {
+            if let Ok(stream) = TcpStream::connect(&listen) {
+                stream
+                    .set_read_timeout(Some(Duration::from_secs(5)))
+                    .unwrap();
+                return stream;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let status = |stream: TcpStream| {
+            let mut line = String::new();
+            BufReader::new(stream).read_line(&mut line).unwrap();
+            line
+        };
+        let post = |token: &str| {
+            format!(
+                "POST /api/v1/gpus/0/power-limit HTTP/1.1\r\nHost: x\r\n{}\
+                 Content-Length: 4096\r\n\r\n",
+                token
+            )
+        };
+
+        // A client with a control token that never sends its body
+        let mut stalled = connect();
+        stalled
+            .write_all(post("Authorization: Bearer c\r\n").as_bytes())
+            .unwrap();
+
+        // Without a token the body is never waited for
+        let mut anonymous = connect();
+        anonymous.write_all(post("").as_bytes()).unwrap();
+        assert!(status(anonymous).contains("401"));
+
+        let mut reader = connect();
+        reader
+            .write_all(b"GET /api/v1/gpus HTTP/1.1\r\nHost: x\r\nAuthorization: Bearer r\r\n\r\n")
+            .unwrap();
+        assert!(status(reader).contains("200"));
+        drop(stalled);
+    }
+
+    #[test]
+    fn test_apply_profile_leaves_fans_alone() {
+        let manager = MockManager::new(1);
+        let profile = ProfileSettings {
+            settings: DeviceSettings {
+                fan_curve: FanCurve::default(),
+                power_limit: Some(PowerLimit::from_watts(250)),
+                acoustic_limit: None,
+            },
+            gpu_overrides: Vec::new(),
+        };
+        let api = api(&manager).with_profiles(BTreeMap::from([("quiet".to_string(), profile)]));
+
+        let url = "/api/v1/profiles/quiet/apply";
+        let response = api.handle(&request(&Method::Post, url, "Bearer c", ""));
+        assert_eq!(response.status, 200, "{}", response.body);
+        let results: Value = serde_json::from_str(&response.body).unwrap();
+        let message = results[0]["message"].as_str().unwrap();
+        assert!(message.contains("power limit 250W"));
+        assert!(message.contains("fan curve not applied"));
+        assert_eq!(results[0]["fan_curve_applied"], false);
+        assert_eq!(results[0]["gpu"], 0);
+        assert!(!message.contains("fan speed"));
+    }
+
+    #[test]
+    fn test_dashboard_and_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = MockManager::new(2);
+        let api = api(&manager)
+            .with_dashboard(true)
+            .with_audit_log(AuditLog::new(dir.path().join("audit.log")));
+        let anonymous = |url| ApiRequest {
+            method: &Method::Get,
+            url,
+            authorization: None,
+            body: "",
+            remote: None,
+        };
+
+        let page = api.handle(&anonymous("/"));
+        assert_eq!(page.status, 200);
+        assert!(page.content_type.starts_with("text/html"));
+        assert!(page.body.contains("/dashboard.js"));
+
+        assert_eq!(api.handle(&anonymous("/api/v1/stream")).status, 401);
+        let response = api.handle(&anonymous("/api/v1/stream?access_token=r"));
+        assert!(response.stream);
+        assert_eq!(response.content_type, "text/event-stream");
+        let data = response
+            .body
+            .lines()
+            .find_map(|line| line.strip_prefix("data: "))
+            .unwrap();
+        let gpus: Value = serde_json::from_str(data).unwrap();
+        assert_eq!(gpus.as_array().unwrap().len(), 2);
+        assert!(gpus[0]["temperature"].is_number());
+        assert!(gpus[0]["pcie_generation"].is_number());
+
+        // The query token only works for the stream, and only it is public
+        assert_eq!(
+            api.handle(&anonymous("/api/v1/gpus?access_token=r")).status,
+            401
+        );
+        let api = api.with_public_stream(true);
+        assert!(api.handle(&anonymous("/api/v1/stream")).stream);
+        assert_eq!(api.handle(&anonymous("/api/v1/token")).status, 401);
+        let response = api.handle(&request(&Method::Get, "/api/v1/token", "Bearer c", ""));
+        let token: Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(token["scope"], "control");
+
+        let audit = std::fs::read_to_string(dir.path().join("audit.log")).unwrap();
+        assert!(audit.contains("access_token=REDACTED"));
+        assert!(!audit.contains("access_token=r\""));
+    }
+}
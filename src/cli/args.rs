@@ -4,6 +4,11 @@
 
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::recorder::{RecordField, RecordFormat};
+use crate::replay::ReplayOptions;
 
 /// NVML-based GPU control tool
 ///
@@ -41,6 +46,37 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub dry_run: bool,
 
+    /// Repeat read commands every SECS seconds
+    #[arg(long, global = true, value_name = "SECS")]
+    pub watch: Option<f64>,
+
+    /// Stop watching after N updates
+    #[arg(long, global = true, value_name = "N", requires = "watch")]
+    pub count: Option<u64>,
+
+    /// Read GPU telemetry from a recording made by `nvctl record`
+    #[arg(long, global = true, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+
+    /// Playback speed multiplier for --replay
+    #[arg(
+        long,
+        global = true,
+        value_name = "X",
+        default_value_t = 1.0,
+        requires = "replay"
+    )]
+    pub replay_speed: f64,
+
+    /// Start the recording over when --replay reaches its end
+    #[arg(long, global = true, requires = "replay")]
+    pub replay_loop: bool,
+
+    /// Use this many simulated GPUs instead of NVML
+    #[cfg(feature = "mock")]
+    #[arg(long, global = true, value_name = "GPUS", conflicts_with = "replay")]
+    pub mock: Option<u32>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -75,6 +111,36 @@ pub enum Commands {
     /// List processes running on GPU
     Processes(ProcessesArgs),
 
+    /// Inspect the configuration file
+    Config(ConfigArgs),
+
+    /// Show GPU events (Xid errors, ECC errors, clock and power changes)
+    Events(EventsArgs),
+
+    /// Inspect the profile schedule
+    Schedule(ScheduleArgs),
+
+    /// Full-screen dashboard of all GPUs, processes and alerts
+    Top(TopArgs),
+
+    /// Serve Prometheus metrics over HTTP
+    Exporter(ExporterArgs),
+
+    /// Push metrics to InfluxDB or StatsD
+    Push(PushArgs),
+
+    /// Record GPU telemetry to CSV or NDJSON files
+    Record(RecordArgs),
+
+    /// Summarize a live session or a recording (percentiles, bands, throttling)
+    Stats(StatsArgs),
+
+    /// Query the on-disk metrics history
+    History(HistoryArgs),
+
+    /// Serve the authenticated REST API for remote control
+    Api(ApiArgs),
+
     /// Generate shell completions
     Completions {
         /// Shell to generate completions for
@@ -83,6 +149,66 @@ pub enum Commands {
     },
 }
 
+impl Commands {
+    /// Name of the command as typed, for watch mode headers
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::List => "list",
+            Self::Info(_) => "info",
+            Self::Fan(_) => "fan",
+            Self::Power(_) => "power",
+            Self::Thermal(_) => "thermal",
+            Self::Control(_) => "control",
+            Self::Alerts(_) => "alerts",
+            Self::Health => "health",
+            Self::Processes(_) => "processes",
+            Self::Config(_) => "config",
+            Self::Events(_) => "events",
+            Self::Schedule(_) => "schedule",
+            Self::Top(_) => "top",
+            Self::Exporter(_) => "exporter",
+            Self::Push(_) => "push",
+            Self::Record(_) => "record",
+            Self::Stats(_) => "stats",
+            Self::History(_) => "history",
+            Self::Api(_) => "api",
+            Self::Completions { .. } => "completions",
+        }
+    }
+
+    /// Check if the command only reads state and can be repeated with `--watch`
+    pub fn supports_watch(&self) -> bool {
+        match self {
+            Self::Info(_) | Self::Health | Self::History(_) => true,
+            Self::Fan(args) => matches!(args.command, FanCommands::Status),
+            Self::Power(args) => matches!(args.command, PowerCommands::Status),
+            Self::Processes(args) => args.accounting.is_none(),
+            _ => false,
+        }
+    }
+
+    /// Check if the command can run against a recording with `--replay`
+    pub fn supports_replay(&self) -> bool {
+        match self {
+            Self::Info(_) | Self::Health | Self::Control(_) | Self::Top(_) => true,
+            Self::Exporter(_) => true,
+            Self::Alerts(args) => matches!(args.command, AlertCommands::Start { .. }),
+            _ => false,
+        }
+    }
+}
+
+impl Cli {
+    /// Replay settings, if `--replay` was given
+    pub fn replay_options(&self) -> Option<ReplayOptions> {
+        self.replay.as_ref().map(|path| ReplayOptions {
+            path: path.clone(),
+            speed: self.replay_speed,
+            looped: self.replay_loop,
+        })
+    }
+}
+
 /// Arguments for the info command
 #[derive(Parser, Debug)]
 pub struct InfoArgs {
@@ -119,6 +245,205 @@ pub struct InfoArgs {
     pub video: bool,
 }
 
+/// Arguments for the events command
+#[derive(Parser, Debug)]
+pub struct EventsArgs {
+    /// Keep streaming events until interrupted
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Seconds to listen for events when not following
+    #[arg(short, long, default_value = "5")]
+    pub timeout: u64,
+}
+
+/// Arguments for the top command
+#[derive(Parser, Debug)]
+pub struct TopArgs {
+    /// Refresh interval in seconds
+    #[arg(short, long, default_value = "1")]
+    pub interval: u64,
+}
+
+/// Arguments for the exporter command
+#[derive(Parser, Debug)]
+pub struct ExporterArgs {
+    /// Address to listen on [default: 127.0.0.1:9835]
+    #[arg(short, long, value_name = "ADDR")]
+    pub listen: Option<String>,
+
+    /// Reuse scrape results for this many seconds [default: 2]
+    #[arg(long, value_name = "SECS")]
+    pub cache: Option<u64>,
+}
+
+/// Arguments for the push command
+///
+/// Sinks given on the command line replace the `[[push.sinks]]` of the config.
+#[derive(Parser, Debug)]
+pub struct PushArgs {
+    /// InfluxDB write URL (e.g. http://localhost:8086/api/v2/write?org=o&bucket=b)
+    #[arg(long, value_name = "URL")]
+    pub influx: Option<String>,
+
+    /// InfluxDB API token
+    #[arg(
+        long,
+        env = "INFLUX_TOKEN",
+        requires = "influx",
+        hide_env_values = true
+    )]
+    pub token: Option<String>,
+
+    /// Append InfluxDB line protocol to a file
+    #[arg(long, value_name = "PATH")]
+    pub influx_file: Option<PathBuf>,
+
+    /// StatsD server address (host:port)
+    #[arg(long, value_name = "ADDR")]
+    pub statsd: Option<String>,
+
+    /// Prefix for StatsD metric names
+    #[arg(long, requires = "statsd")]
+    pub prefix: Option<String>,
+
+    /// Seconds between pushes [default: general.interval_seconds]
+    #[arg(short, long, value_name = "SECS")]
+    pub interval: Option<u64>,
+
+    /// Push once and exit
+    #[arg(long)]
+    pub once: bool,
+}
+
+/// Arguments for the record command
+#[derive(Parser, Debug)]
+pub struct RecordArgs {
+    /// Output file (.csv, .ndjson or .jsonl; .gz enables compression)
+    #[arg(short, long, value_name = "PATH")]
+    pub output: PathBuf,
+
+    /// Time between samples (e.g. 500ms, 2s, 1m)
+    #[arg(short, long, default_value = "1s", value_parser = parse_duration)]
+    pub interval: Duration,
+
+    /// Comma-separated fields to record
+    #[arg(
+        long,
+        value_delimiter = ',',
+        default_value = "temp,fan,power,clock_gpu,util,throttled"
+    )]
+    pub fields: Vec<RecordField>,
+
+    /// File format [default: from the output extension]
+    #[arg(long, value_enum)]
+    pub file_format: Option<RecordFormat>,
+
+    /// Compress output with gzip
+    #[arg(long)]
+    pub gzip: bool,
+
+    /// Start a new file after this much data (e.g. 100MB)
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub rotate_size: Option<u64>,
+
+    /// Start a new file after this long (e.g. 1h)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub rotate_interval: Option<Duration>,
+
+    /// Stop recording after this long
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub duration: Option<Duration>,
+}
+
+/// Arguments for the stats command
+#[derive(Parser, Debug)]
+pub struct StatsArgs {
+    /// Read a recording made by `nvctl record` instead of sampling live
+    #[arg(short, long, value_name = "FILE")]
+    pub input: Option<PathBuf>,
+
+    /// How long to sample live GPUs
+    #[arg(long, default_value = "60s", value_parser = parse_duration)]
+    pub duration: Duration,
+
+    /// Time between live samples
+    #[arg(long, default_value = "1s", value_parser = parse_duration)]
+    pub interval: Duration,
+
+    /// Comma-separated temperature band edges in °C
+    #[arg(long, value_delimiter = ',', default_value = "50,60,70,80,90")]
+    pub temp_bands: Vec<f64>,
+
+    /// Temperature counted as throttling [default: the GPU's slowdown threshold]
+    #[arg(long, value_name = "CELSIUS")]
+    pub slowdown_temp: Option<f64>,
+}
+
+/// Arguments for the history command
+#[derive(Parser, Debug)]
+pub struct HistoryArgs {
+    /// Metric to show
+    #[arg(long, default_value = "temp")]
+    pub metric: RecordField,
+
+    /// How far back to look (e.g. 30m, 24h, 7d)
+    #[arg(long, default_value = "24h", value_parser = parse_duration)]
+    pub since: Duration,
+
+    /// End of the range as time before now [default: now]
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub until: Option<Duration>,
+
+    /// Merge into at most N points per GPU (0 keeps all)
+    #[arg(long, default_value_t = 60)]
+    pub points: usize,
+}
+
+/// Arguments for the api command
+#[derive(Parser, Debug)]
+pub struct ApiArgs {
+    /// Address to listen on [default: 127.0.0.1:9836]
+    #[arg(short, long, value_name = "ADDR")]
+    pub listen: Option<String>,
+
+    /// Audit log file [default: ~/.local/share/nvctl/api-audit.log]
+    #[arg(long, value_name = "FILE")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Do not serve the web dashboard
+    #[arg(long)]
+    pub no_dashboard: bool,
+}
+
+/// Arguments for config commands
+#[derive(Parser, Debug)]
+pub struct ConfigArgs {
+    #[command(subcommand)]
+    pub command: ConfigCommands,
+}
+
+/// Config subcommands
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommands {
+    /// Show which `[[gpus]]` section applies to each GPU
+    Match,
+}
+
+/// Arguments for schedule commands
+#[derive(Parser, Debug)]
+pub struct ScheduleArgs {
+    #[command(subcommand)]
+    pub command: ScheduleCommands,
+}
+
+/// Schedule subcommands
+#[derive(Subcommand, Debug)]
+pub enum ScheduleCommands {
+    /// Show the active and next schedule entries
+    Show,
+}
+
 /// Arguments for fan control commands
 #[derive(Parser, Debug)]
 pub struct FanArgs {
@@ -212,6 +537,10 @@ pub struct ProcessesArgs {
     #[arg(long)]
     pub sort_pid: bool,
 
+    /// Sort order
+    #[arg(long, value_enum, conflicts_with_all = ["sort_memory", "sort_pid"])]
+    pub sort: Option<ProcessSort>,
+
     /// Show top N processes (by memory usage)
     #[arg(short = 'n', long)]
     pub top: Option<usize>,
@@ -219,6 +548,42 @@ pub struct ProcessesArgs {
     /// Filter by process type
     #[arg(short = 't', long, value_enum)]
     pub process_type: Option<ProcessTypeFilter>,
+
+    /// Only show processes owned by this user
+    #[arg(short, long)]
+    pub user: Option<String>,
+
+    /// Only show processes in containers or pods whose ID starts with this
+    #[arg(long)]
+    pub container: Option<String>,
+
+    /// Show finished processes from NVML accounting or the process history file
+    #[arg(long)]
+    pub history: bool,
+
+    /// Enable or disable NVML accounting mode (requires root)
+    #[arg(long, value_enum, conflicts_with = "history")]
+    pub accounting: Option<AccountingMode>,
+}
+
+/// Accounting mode switch for CLI
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountingMode {
+    /// Record per-process statistics
+    On,
+    /// Stop recording and clear statistics
+    Off,
+}
+
+/// Process sort order for CLI
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSort {
+    /// Memory usage (descending)
+    Memory,
+    /// Process ID
+    Pid,
+    /// SM utilization, then memory (descending)
+    Util,
 }
 
 /// Process type filter for CLI
@@ -345,6 +710,55 @@ pub enum OutputFormat {
     Compact,
 }
 
+/// Parse a duration like `500ms`, `1.5s`, `10m` or `1h` (bare numbers are seconds)
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}'", value))?;
+    let secs = match unit.trim() {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        "d" => number * 86400.0,
+        other => {
+            return Err(format!(
+                "unknown duration unit '{}' (use ms, s, m, h or d)",
+                other
+            ))
+        }
+    };
+    match Duration::try_from_secs_f64(secs) {
+        Ok(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err(format!("duration '{}' must be positive", value)),
+    }
+}
+
+/// Parse a size like `500K`, `100MB` or `1G` (bare numbers are bytes)
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid size '{}'", value))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().trim_end_matches('B') {
+        "" => 1,
+        "K" | "KI" => 1 << 10,
+        "M" | "MI" => 1 << 20,
+        "G" | "GI" => 1 << 30,
+        _ => return Err(format!("unknown size unit in '{}' (use K, M or G)", value)),
+    };
+    Ok(number.saturating_mul(multiplier))
+}
+
 /// Generate shell completions and print to stdout
 pub fn generate_completions(shell: Shell) {
     let mut cmd = Cli::command();
@@ -433,6 +847,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_duration_and_size() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("5 parsecs").is_err());
+
+        assert_eq!(parse_size("100MB"), Ok(100 << 20));
+        assert_eq!(parse_size("512"), Ok(512));
+        assert!(parse_size("1T").is_err());
+    }
+
     #[test]
     fn test_cli_thermal_limit_validation() {
         // Should fail for > 100
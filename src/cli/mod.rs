@@ -4,5 +4,6 @@
 
 pub mod args;
 pub mod output;
+pub mod watch;
 
 pub use args::{generate_completions, Cli, Commands};
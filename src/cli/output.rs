@@ -3,12 +3,95 @@
 //! Provides table and JSON output formatting for CLI commands.
 
 use crate::cli::args::OutputFormat;
-use crate::domain::GpuInfo;
+use crate::domain::{ContainerInfo, GpuInfo, ProcessUtilization};
 use serde::Serialize;
+use std::cell::RefCell;
 use std::io::{self, Write};
 
+/// Output collected for one `--watch` update
+struct WatchFrame {
+    timestamp: String,
+    sequence: u64,
+    buffer: String,
+}
+
+thread_local! {
+    static FRAME: RefCell<Option<WatchFrame>> = const { RefCell::new(None) };
+}
+
+/// Start collecting output for a watch update instead of printing it
+pub(crate) fn begin_frame(sequence: u64, timestamp: String) {
+    FRAME.with(|frame| {
+        *frame.borrow_mut() = Some(WatchFrame {
+            timestamp,
+            sequence,
+            buffer: String::new(),
+        })
+    });
+}
+
+/// Stop collecting and return the output of the current watch update
+pub(crate) fn end_frame() -> String {
+    FRAME.with(|frame| {
+        frame
+            .borrow_mut()
+            .take()
+            .map(|f| f.buffer)
+            .unwrap_or_default()
+    })
+}
+
+/// Write to the current watch update, or return the text if there is none
+fn write_frame(write: impl FnOnce(&mut WatchFrame)) -> bool {
+    FRAME.with(|frame| match frame.borrow_mut().as_mut() {
+        Some(frame) => {
+            write(frame);
+            true
+        }
+        None => false,
+    })
+}
+
+/// NDJSON record emitted for each output in `--watch --format json` mode
+#[derive(Serialize)]
+struct WatchRecord<'a, T> {
+    timestamp: &'a str,
+    sequence: u64,
+    data: &'a T,
+}
+
+/// Print a blank line between GPUs (skipped in NDJSON output)
+pub fn print_separator(format: OutputFormat) {
+    let collected = write_frame(|frame| {
+        if !matches!(format, OutputFormat::Json) {
+            frame.buffer.push('\n');
+        }
+    });
+    if !collected {
+        println!();
+    }
+}
+
 /// Format and print output based on the selected format
 pub fn print_output<T: Serialize + TableDisplay>(data: &T, format: OutputFormat) -> io::Result<()> {
+    let collected = write_frame(|frame| {
+        let line = match format {
+            OutputFormat::Json => serde_json::to_string(&WatchRecord {
+                timestamp: &frame.timestamp,
+                sequence: frame.sequence,
+                data,
+            })
+            .unwrap_or_else(|_| "{}".to_string()),
+            OutputFormat::Table => data.to_table(),
+            OutputFormat::Compact => data.to_compact(),
+        };
+        frame.buffer.push_str(&line);
+        frame.buffer.push('\n');
+    });
+    if collected {
+        return Ok(());
+    }
+
     let stdout = io::stdout();
     let mut handle = stdout.lock();
 
@@ -101,6 +184,239 @@ impl TableDisplay for GpuList {
     }
 }
 
+/// Config section match for one GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuSectionMatch {
+    pub index: u32,
+    pub name: String,
+    pub uuid: String,
+    pub pci_bus_id: Option<String>,
+    /// Matched `[[gpus]]` section, or None when global settings apply
+    pub section: Option<String>,
+}
+
+impl TableDisplay for GpuSectionMatch {
+    fn to_table(&self) -> String {
+        format!(
+            "[{}] {} ({}, PCI: {}) -> {}",
+            self.index,
+            self.name,
+            self.uuid,
+            self.pci_bus_id.as_deref().unwrap_or("N/A"),
+            self.section.as_deref().unwrap_or("global settings")
+        )
+    }
+
+    fn to_compact(&self) -> String {
+        format!(
+            "{}:{}",
+            self.index,
+            self.section.as_deref().unwrap_or("global")
+        )
+    }
+}
+
+/// Config section matches for all GPUs
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuSectionMatches {
+    pub gpus: Vec<GpuSectionMatch>,
+}
+
+impl TableDisplay for GpuSectionMatches {
+    fn to_table(&self) -> String {
+        self.gpus
+            .iter()
+            .map(|g| g.to_table())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn to_compact(&self) -> String {
+        self.gpus
+            .iter()
+            .map(|g| g.to_compact())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// A schedule entry for display
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleEntryInfo {
+    /// Matching rule, or None for the default
+    pub rule: Option<String>,
+    /// Profile, or None for the global settings
+    pub profile: Option<String>,
+    /// When the entry takes effect, for upcoming entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+}
+
+impl ScheduleEntryInfo {
+    fn describe(&self) -> String {
+        format!(
+            "{} ({})",
+            self.profile.as_deref().unwrap_or("global settings"),
+            self.rule.as_deref().unwrap_or("default")
+        )
+    }
+}
+
+/// Schedule status display
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleStatus {
+    pub timezone: String,
+    pub now: String,
+    pub active: ScheduleEntryInfo,
+    /// Next change, if the profile ever changes
+    pub next: Option<ScheduleEntryInfo>,
+    pub rules: Vec<ScheduleRuleInfo>,
+}
+
+/// A schedule rule for display
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleRuleInfo {
+    pub name: String,
+    pub days: String,
+    pub start: String,
+    pub end: String,
+    pub profile: String,
+    pub active: bool,
+}
+
+impl TableDisplay for ScheduleStatus {
+    fn to_table(&self) -> String {
+        let mut output = format!("Timezone: {} (now {})\n", self.timezone, self.now);
+        output.push_str(&format!("Active:   {}\n", self.active.describe()));
+        match &self.next {
+            Some(next) => output.push_str(&format!(
+                "Next:     {} at {}\n",
+                next.describe(),
+                next.at.as_deref().unwrap_or("?")
+            )),
+            None => output.push_str("Next:     no change scheduled\n"),
+        }
+
+        if self.rules.is_empty() {
+            output.push_str("No schedule rules configured");
+        } else {
+            output.push_str("Rules:");
+            for rule in &self.rules {
+                output.push_str(&format!(
+                    "\n  {} {}: {} {}-{} -> {}",
+                    if rule.active { "*" } else { " " },
+                    rule.name,
+                    rule.days,
+                    rule.start,
+                    rule.end,
+                    rule.profile
+                ));
+            }
+        }
+        output
+    }
+
+    fn to_compact(&self) -> String {
+        format!(
+            "active={} next={}",
+            self.active.profile.as_deref().unwrap_or("global"),
+            self.next
+                .as_ref()
+                .map(|n| format!(
+                    "{}@{}",
+                    n.profile.as_deref().unwrap_or("global"),
+                    n.at.as_deref().unwrap_or("?")
+                ))
+                .unwrap_or_else(|| "none".to_string())
+        )
+    }
+}
+
+impl TableDisplay for crate::events::GpuEvent {
+    fn to_table(&self) -> String {
+        self.to_string()
+    }
+
+    fn to_compact(&self) -> String {
+        format!("{}:{}", self.gpu_index, self.kind)
+    }
+}
+
+impl TableDisplay for crate::stats::StatsReport {
+    fn to_table(&self) -> String {
+        let mut output = String::new();
+        for gpu in &self.gpus {
+            output.push_str(&format!(
+                "[{}] {} ({}, {:.0}s, {} samples)\n",
+                gpu.gpu, gpu.uuid, self.source, gpu.duration_secs, gpu.samples
+            ));
+            output.push_str(&format!(
+                "  {:<16} {:>8} {:>8} {:>8} {:>8} {:>8} {:>8}\n",
+                "Metric", "Min", "Avg", "p50", "p95", "p99", "Max"
+            ));
+            for metric in &gpu.metrics {
+                let name = if metric.unit.is_empty() {
+                    metric.metric.to_string()
+                } else {
+                    format!("{} ({})", metric.metric, metric.unit)
+                };
+                let s = &metric.summary;
+                output.push_str(&format!(
+                    "  {:<16} {:>8.1} {:>8.1} {:>8.1} {:>8.1} {:>8.1} {:>8.1}\n",
+                    name, s.min, s.avg, s.p50, s.p95, s.p99, s.max
+                ));
+            }
+
+            if gpu.temperature_bands.iter().any(|b| b.seconds > 0.0) {
+                output.push_str("\n  Time in temperature band:\n");
+                for band in &gpu.temperature_bands {
+                    output.push_str(&format!(
+                        "    {:<10} {:>8.0}s {:>6.1}%\n",
+                        band.band,
+                        band.seconds,
+                        band.share * 100.0
+                    ));
+                }
+            }
+
+            if let (Some(temp), Some(secs)) =
+                (gpu.slowdown_temperature, gpu.time_above_slowdown_secs)
+            {
+                output.push_str(&format!("\n  Above slowdown ({}°C): {:.0}s\n", temp, secs));
+            }
+            if let Some(share) = gpu.throttle_share {
+                output.push_str(&format!("  Throttled: {:.1}% of the time\n", share * 100.0));
+            }
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    fn to_compact(&self) -> String {
+        self.gpus
+            .iter()
+            .map(|gpu| {
+                let mut parts = vec![format!("gpu{}", gpu.gpu)];
+                for metric in &gpu.metrics {
+                    parts.push(format!(
+                        "{} p50={:.1} p95={:.1} p99={:.1} max={:.1}",
+                        metric.metric,
+                        metric.summary.p50,
+                        metric.summary.p95,
+                        metric.summary.p99,
+                        metric.summary.max
+                    ));
+                }
+                if let Some(share) = gpu.throttle_share {
+                    parts.push(format!("throttled={:.1}%", share * 100.0));
+                }
+                parts.join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
 /// Fan status display
 #[derive(Debug, Clone, Serialize)]
 pub struct FanStatus {
@@ -430,6 +746,16 @@ pub struct ProcessListOutput {
     pub total_memory_mb: f64,
     pub total_memory_gb: f64,
     pub processes: Vec<ProcessEntry>,
+    pub users: Vec<VramTotal>,
+    pub containers: Vec<VramTotal>,
+}
+
+/// VRAM used by a group of processes (a user or a container)
+#[derive(Debug, Clone, Serialize)]
+pub struct VramTotal {
+    pub name: String,
+    pub process_count: usize,
+    pub memory_mb: f64,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -439,6 +765,20 @@ pub struct ProcessEntry {
     pub memory_mb: f64,
     pub memory_gb: f64,
     pub process_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub utilization: Option<ProcessUtilization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmdline: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_pid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cgroup: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerInfo>,
 }
 
 impl TableDisplay for ProcessListOutput {
@@ -455,20 +795,51 @@ impl TableDisplay for ProcessListOutput {
         }
 
         // Table header
-        output.push_str("  PID      Memory      Type           Name\n");
-        output.push_str("  ────────────────────────────────────────────────────────────\n");
+        output.push_str(
+            "  PID      Memory      SM    Mem   Enc   Dec   Type           User       Container     Name\n",
+        );
+        output.push_str(
+            "  ─────────────────────────────────────────────────────────────────────────────────────────────────────\n",
+        );
 
         // Table rows
         for process in &self.processes {
+            let percent = |value: Option<u32>| value.map_or("-".to_string(), |v| format!("{}%", v));
+            let util = process.utilization;
             output.push_str(&format!(
-                "  {:<8} {:<11} {:<14} {}\n",
+                "  {:<8} {:<11} {:<5} {:<5} {:<5} {:<5} {:<14} {:<10} {:<13} {}\n",
                 process.pid,
                 format!("{:.1} MB", process.memory_mb),
+                percent(util.map(|u| u.sm)),
+                percent(util.map(|u| u.memory)),
+                percent(util.map(|u| u.encoder)),
+                percent(util.map(|u| u.decoder)),
                 process.process_type,
+                process.user.as_deref().unwrap_or("-"),
+                process
+                    .container
+                    .as_ref()
+                    .map_or("-".to_string(), |c| c.short_id()),
                 process.name
             ));
         }
 
+        for (title, totals) in [("By user", &self.users), ("By container", &self.containers)] {
+            if totals.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\n  {}:\n", title));
+            for total in totals {
+                output.push_str(&format!(
+                    "    {:<24} {:>10} ({} process{})\n",
+                    total.name,
+                    format!("{:.1} MB", total.memory_mb),
+                    total.process_count,
+                    if total.process_count == 1 { "" } else { "es" }
+                ));
+            }
+        }
+
         output
     }
 
@@ -484,6 +855,181 @@ impl TableDisplay for ProcessListOutput {
     }
 }
 
+/// Finished processes of a GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessHistoryOutput {
+    pub gpu_name: String,
+    pub gpu_index: u32,
+    /// Where the entries come from: "accounting" or "history file"
+    pub source: String,
+    pub entries: Vec<ProcessHistoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessHistoryEntry {
+    pub pid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub running: bool,
+    pub max_memory_mb: Option<f64>,
+    pub gpu_utilization: Option<u32>,
+    pub memory_utilization: Option<u32>,
+    pub start_time: u64,
+    pub run_time_secs: Option<u64>,
+}
+
+impl TableDisplay for ProcessHistoryOutput {
+    fn to_table(&self) -> String {
+        let mut output = format!("[{}] {}\n", self.gpu_index, self.gpu_name);
+        output.push_str(&format!(
+            "  Process history: {} (source: {})\n\n",
+            self.entries.len(),
+            self.source
+        ));
+
+        if self.entries.is_empty() {
+            output.push_str("  No finished processes recorded\n");
+            return output;
+        }
+
+        output.push_str(
+            "  PID      Max Memory   GPU   Mem   Run Time    State     User       Name\n",
+        );
+        output.push_str(
+            "  ─────────────────────────────────────────────────────────────────────────────────\n",
+        );
+
+        for entry in &self.entries {
+            let percent = |value: Option<u32>| value.map_or("-".to_string(), |v| format!("{}%", v));
+            output.push_str(&format!(
+                "  {:<8} {:<12} {:<5} {:<5} {:<11} {:<9} {:<10} {}\n",
+                entry.pid,
+                entry
+                    .max_memory_mb
+                    .map_or("-".to_string(), |mb| format!("{:.1} MB", mb)),
+                percent(entry.gpu_utilization),
+                percent(entry.memory_utilization),
+                entry.run_time_secs.map_or("-".to_string(), format_run_time),
+                if entry.running { "running" } else { "finished" },
+                entry.user.as_deref().unwrap_or("-"),
+                entry.name.as_deref().unwrap_or("-"),
+            ));
+        }
+
+        output
+    }
+
+    fn to_compact(&self) -> String {
+        format!(
+            "GPU {}: {} processes in history ({})",
+            self.gpu_index,
+            self.entries.len(),
+            self.source
+        )
+    }
+}
+
+/// Metrics history of all GPUs
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryOutput {
+    pub metric: String,
+    pub unit: String,
+    /// Tier the points were read from ("raw", "1m" or "1h")
+    pub tier: String,
+    /// Range as Unix times in seconds
+    pub since: i64,
+    pub until: i64,
+    pub gpus: Vec<HistorySeries>,
+}
+
+/// Metrics history of one GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct HistorySeries {
+    pub gpu_index: u32,
+    pub gpu_name: String,
+    pub uuid: String,
+    pub points: Vec<crate::history::HistoryPoint>,
+}
+
+impl TableDisplay for HistoryOutput {
+    fn to_table(&self) -> String {
+        let mut output = String::new();
+        for gpu in &self.gpus {
+            output.push_str(&format!("[{}] {}\n", gpu.gpu_index, gpu.gpu_name));
+            output.push_str(&format!(
+                "  {} ({}) from the {} tier, {} points\n\n",
+                self.metric,
+                self.unit,
+                self.tier,
+                gpu.points.len()
+            ));
+            if gpu.points.is_empty() {
+                output.push_str("  No samples in range\n\n");
+                continue;
+            }
+
+            output.push_str("  Time                      Avg       Min       Max\n");
+            for point in &gpu.points {
+                output.push_str(&format!(
+                    "  {:<19} {:>9.1} {:>9.1} {:>9.1}\n",
+                    format_unix_time(point.timestamp),
+                    point.avg,
+                    point.min,
+                    point.max
+                ));
+            }
+            output.push('\n');
+        }
+        output.trim_end().to_string()
+    }
+
+    fn to_compact(&self) -> String {
+        self.gpus
+            .iter()
+            .map(|gpu| {
+                let latest = gpu
+                    .points
+                    .last()
+                    .map_or("-".to_string(), |p| format!("{:.1}", p.avg));
+                format!(
+                    "GPU {}: {} {} points, latest {}{}",
+                    gpu.gpu_index,
+                    gpu.points.len(),
+                    self.metric,
+                    latest,
+                    self.unit
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// Format a Unix time in the local time zone
+fn format_unix_time(secs: i64) -> String {
+    jiff::Timestamp::from_second(secs)
+        .map(|t| {
+            t.to_zoned(jiff::tz::TimeZone::system())
+                .strftime("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| secs.to_string())
+}
+
+/// Format a run time as e.g. "1h 02m 03s"
+fn format_run_time(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
 /// Simple message output
 #[derive(Debug, Clone, Serialize)]
 pub struct Message {
@@ -0,0 +1,187 @@
+//! Repeating read commands (`--watch`)
+//!
+//! Table and compact output are redrawn in place with changed values
+//! highlighted; JSON output becomes newline-delimited records.
+
+use crate::cli::args::OutputFormat;
+use crate::cli::output::{begin_frame, end_frame};
+use crate::error::Result;
+
+use jiff::{Timestamp, Zoned};
+use std::io::{self, IsTerminal, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CLEAR_SCREEN: &str = "\x1b[H\x1b[2J";
+const HIGHLIGHT: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+/// Watch mode settings
+#[derive(Debug, Clone, Copy)]
+pub struct WatchOptions {
+    /// Time between updates
+    pub interval: Duration,
+    /// Stop after this many updates
+    pub count: Option<u64>,
+}
+
+/// Run `body` repeatedly, rendering each update
+pub fn run_watch(
+    options: WatchOptions,
+    format: OutputFormat,
+    command: &str,
+    mut body: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    let stdout = io::stdout();
+    let redraw = stdout.is_terminal() && !matches!(format, OutputFormat::Json);
+    let mut previous: Option<String> = None;
+    let mut sequence = 0;
+
+    loop {
+        let started = Instant::now();
+        begin_frame(sequence, Timestamp::now().to_string());
+        let result = body();
+        let output = end_frame();
+
+        let mut handle = stdout.lock();
+        let header = || {
+            format!(
+                "Every {}s: nvctl {}    {}",
+                options.interval.as_secs_f64(),
+                command,
+                Zoned::now().strftime("%Y-%m-%d %H:%M:%S")
+            )
+        };
+        // A failed update is reported in place of its frame; the watch goes on
+        let failed = result.is_err();
+        match (result, format) {
+            (Err(e), OutputFormat::Json) => eprintln!("Error: {}", e),
+            (Err(e), _) if redraw => {
+                write!(handle, "{}{}\n\nError: {}\n", CLEAR_SCREEN, header(), e)?
+            }
+            (Err(e), _) => write!(handle, "--- {} ---\nError: {}\n", Timestamp::now(), e)?,
+            (Ok(()), OutputFormat::Json) => write!(handle, "{}", output)?,
+            (Ok(()), _) if redraw => {
+                let body = match &previous {
+                    Some(previous) => highlight_changes(previous, &output),
+                    None => output.clone(),
+                };
+                write!(handle, "{}{}\n\n{}", CLEAR_SCREEN, header(), body)?;
+            }
+            // Piped table output: one block per update
+            (Ok(()), _) => write!(handle, "--- {} ---\n{}", Timestamp::now(), output)?,
+        }
+        handle.flush()?;
+        drop(handle);
+
+        if !failed {
+            previous = Some(output);
+        }
+        sequence += 1;
+        if options.count.is_some_and(|count| sequence >= count) {
+            return Ok(());
+        }
+        thread::sleep(options.interval.saturating_sub(started.elapsed()));
+    }
+}
+
+/// Highlight the words of `current` that differ from the same position in `previous`
+pub fn highlight_changes(previous: &str, current: &str) -> String {
+    let mut previous_lines = previous.lines();
+    let mut output = String::with_capacity(current.len());
+
+    for line in current.lines() {
+        let old_tokens = previous_lines.next().map(tokens).unwrap_or_default();
+        for (pos, token) in tokens(line).into_iter().enumerate() {
+            let changed = !token.trim().is_empty() && old_tokens.get(pos) != Some(&token);
+            if changed {
+                output.push_str(HIGHLIGHT);
+                output.push_str(token);
+                output.push_str(RESET);
+            } else {
+                output.push_str(token);
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// Split a line into alternating runs of whitespace and non-whitespace
+fn tokens(line: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = None;
+    for (pos, c) in line.char_indices() {
+        let space = c.is_whitespace();
+        if in_space.is_some_and(|s| s != space) {
+            tokens.push(&line[start..pos]);
+            start = pos;
+        }
+        in_space = Some(space);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::output::{print_output, Message};
+
+    #[test]
+    fn test_highlight_changes() {
+        let previous = "Temp: 60°C  Fan: 40%\nPower: 200W";
+        let current = "Temp: 61°C  Fan: 40%\nPower: 200W\nNew";
+        let highlighted = highlight_changes(previous, current);
+
+        assert_eq!(
+            highlighted,
+            format!(
+                "Temp: {h}61°C{r}  Fan: 40%\nPower: 200W\n{h}New{r}\n",
+                h = HIGHLIGHT,
+                r = RESET
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_frames_are_ndjson() {
+        let message = Message {
+            message: "ok".to_string(),
+            success: true,
+        };
+        begin_frame(3, "2026-01-01T00:00:00Z".to_string());
+        print_output(&message, OutputFormat::Json).unwrap();
+        print_output(&message, OutputFormat::Json).unwrap();
+        let output = end_frame();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["sequence"], 3);
+        assert_eq!(record["timestamp"], "2026-01-01T00:00:00Z");
+        assert_eq!(record["data"]["message"], "ok");
+    }
+
+    #[test]
+    fn test_watch_continues_after_failed_update() {
+        let options = WatchOptions {
+            interval: Duration::ZERO,
+            count: Some(3),
+        };
+        let mut calls = 0;
+        let result = run_watch(options, OutputFormat::Json, "status", || {
+            calls += 1;
+            if calls == 1 {
+                return Err(crate::error::AppError::NoGpusFound);
+            }
+            Ok(())
+        });
+
+        assert!(result.is_ok());
+        assert_eq!(calls, 3);
+    }
+}
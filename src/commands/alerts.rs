@@ -2,21 +2,27 @@
 //!
 //! Handles alert-related CLI commands.
 
-use crate::alerts::{AlertConfig, AlertManager, AlertManagerConfig, NotificationManager};
+use crate::alerts::{Alert, AlertConfig, AlertManager, AlertManagerConfig, NotificationManager};
 use crate::cli::args::{AlertCommands, OutputFormat};
 use crate::error::Result;
-use crate::nvml::{GpuManager, NvmlManager};
+use crate::events::{EventListener, NvmlEventSource};
+use crate::nvml::{Backend, DeviceState, DeviceTracker, GpuDevice, GpuManager, GpuSnapshot};
+use crate::replay::ReplayOptions;
 use std::path::PathBuf;
 use std::time::Duration;
 
 /// Execute alert commands
-pub fn run_alerts(command: &AlertCommands, format: OutputFormat) -> Result<()> {
+pub fn run_alerts(
+    command: &AlertCommands,
+    format: OutputFormat,
+    replay: Option<&ReplayOptions>,
+) -> Result<()> {
     match command {
         AlertCommands::Start {
             interval,
             config,
             foreground,
-        } => run_alert_start(*interval, config.clone(), *foreground),
+        } => run_alert_start(*interval, config.clone(), *foreground, replay),
         AlertCommands::Stop => run_alert_stop(),
         AlertCommands::List { all, severity } => run_alert_list(*all, severity.clone(), format),
         AlertCommands::Rules { config } => run_alert_rules(config.clone(), format),
@@ -28,7 +34,12 @@ pub fn run_alerts(command: &AlertCommands, format: OutputFormat) -> Result<()> {
 }
 
 /// Start alert monitoring
-fn run_alert_start(interval: u64, config_path: Option<String>, foreground: bool) -> Result<()> {
+fn run_alert_start(
+    interval: u64,
+    config_path: Option<String>,
+    foreground: bool,
+    replay: Option<&ReplayOptions>,
+) -> Result<()> {
     // Load alert configuration
     let config_path = config_path
         .map(PathBuf::from)
@@ -69,13 +80,23 @@ fn run_alert_start(interval: u64, config_path: Option<String>, foreground: bool)
     println!("Loaded {} alert rules", rules.len());
     manager.add_rules(rules);
 
-    // Create notification manager
-    let notifier = NotificationManager::default();
+    // Create notification channels
+    let notifier = NotificationManager::from_config(&alert_config.notifiers);
+    println!(
+        "Sending notifications to {} channel(s)",
+        notifier.notifier_count()
+    );
+
+    // Initialize NVML, or the recording when replaying
+    let mut nvml = Backend::open(replay)?;
+    let indices: Vec<u32> = nvml.all_devices()?.iter().map(GpuDevice::index).collect();
+    println!("Monitoring {} GPU(s)", indices.len());
+
+    // Track GPUs by UUID so alerts follow them across driver reloads
+    let mut tracker = DeviceTracker::from_indices(&nvml, &indices)?;
 
-    // Initialize NVML
-    let nvml = NvmlManager::new()?;
-    let device_count = nvml.device_count()?;
-    println!("Monitoring {} GPU(s)", device_count);
+    // Xid, ECC, clock and power events arrive asynchronously; recordings have none
+    let events = (!nvml.is_replay()).then(|| EventListener::spawn(NvmlEventSource::new));
 
     if !foreground {
         println!("Starting alert monitoring daemon (interval: {}s)", interval);
@@ -85,17 +106,47 @@ fn run_alert_start(interval: u64, config_path: Option<String>, foreground: bool)
     // Main monitoring loop
     let check_interval = Duration::from_secs(interval);
     loop {
-        for gpu_idx in 0..device_count {
-            let device = nvml.device_by_index(gpu_idx)?;
+        // SAFETY: devices are opened per check below and none outlive it
+        unsafe { tracker.reinit_if_due(&mut nvml) };
+
+        for event in events.iter().flat_map(EventListener::drain) {
+            for alert in manager.process_event(&event) {
+                notify(&notifier, &alert);
+            }
+        }
+
+        for pos in 0..tracker.len() {
+            let device = match tracker.open(&nvml, pos) {
+                Ok(device) => device,
+                Err(e) if e.is_gpu_lost() || e.requires_reinit() => {
+                    if e.requires_reinit() {
+                        tracker.request_reinit();
+                    }
+                    if tracker.mark_lost(pos) {
+                        let gpu = &tracker.gpus()[pos];
+                        let alert = manager.record_gpu_lost(gpu.index, &gpu.uuid);
+                        notify(&notifier, &alert);
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let gpu_idx = tracker.gpus()[pos].index;
+            if tracker.mark_online(pos) == DeviceState::Lost {
+                let uuid = tracker.gpus()[pos].uuid.clone();
+                if let Some(alert) = manager.record_gpu_recovered(gpu_idx, &uuid) {
+                    notify(&notifier, &alert);
+                }
+            }
 
             // Evaluate alert rules
-            match manager.evaluate(&device, gpu_idx) {
-                Ok(new_alerts) => {
-                    // Send notifications for new alerts
-                    for alert in &new_alerts {
-                        if let Err(e) = notifier.notify_all(alert) {
-                            eprintln!("Failed to send notification: {}", e);
-                        }
+            match manager.evaluate(&GpuSnapshot::capture(&device), gpu_idx) {
+                Ok(mut alerts) => {
+                    // Send notifications for new and resolved alerts
+                    alerts.extend_from_slice(manager.just_resolved());
+                    if let Err(e) = notifier.notify_batch(&alerts) {
+                        eprintln!("Failed to send notifications: {}", e);
                     }
                 }
                 Err(e) => {
@@ -108,6 +159,13 @@ fn run_alert_start(interval: u64, config_path: Option<String>, foreground: bool)
     }
 }
 
+/// Send an alert to all notification channels, reporting failures
+fn notify(notifier: &NotificationManager, alert: &Alert) {
+    if let Err(e) = notifier.notify_all(alert) {
+        eprintln!("Failed to send notification: {}", e);
+    }
+}
+
 /// Stop alert monitoring
 fn run_alert_stop() -> Result<()> {
     // TODO: Implement daemon PID file and signal handling
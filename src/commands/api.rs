@@ -0,0 +1,63 @@
+//! API command implementation
+//!
+//! Serves the authenticated REST API and the web dashboard.
+
+use crate::api::{Api, AuditLog, Tokens};
+use crate::cli::args::ApiArgs;
+use crate::config::{ApiConfig, ConfigFile, ProfileSettings};
+use crate::error::{ConfigError, Result};
+use crate::nvml::{Backend, GpuManager};
+
+use std::collections::BTreeMap;
+
+/// Execute the api command
+pub fn run_api(args: &ApiArgs, config_path: Option<&str>, dry_run: bool) -> Result<()> {
+    let config = ConfigFile::load_or_default(config_path)?;
+    if config.api.tokens.is_empty() {
+        return Err(ConfigError::InvalidValue {
+            key: "api.tokens".to_string(),
+            message: "at least one [[api.tokens]] entry is required".to_string(),
+        }
+        .into());
+    }
+
+    let mut profiles = BTreeMap::new();
+    for name in config.profiles.keys() {
+        if let Some(settings) = config.profile_settings(name) {
+            profiles.insert(name.clone(), settings?);
+        }
+    }
+    let listen = args.listen.clone().unwrap_or(config.api.listen.clone());
+    let audit_log = args
+        .audit_log
+        .clone()
+        .unwrap_or_else(|| config.api.audit_log());
+    let api_config = ApiConfig {
+        dashboard: config.api.dashboard && !args.no_dashboard,
+        ..config.api
+    };
+
+    let manager = Backend::open(None)?;
+    serve(&manager, api_config, profiles, &listen, audit_log, dry_run)
+}
+
+fn serve<M: GpuManager>(
+    manager: &M,
+    config: ApiConfig,
+    profiles: BTreeMap<String, ProfileSettings>,
+    listen: &str,
+    audit_log: std::path::PathBuf,
+    dry_run: bool,
+) -> Result<()> {
+    let api = Api::new(manager, Tokens::new(config.tokens))
+        .with_profiles(profiles)
+        .with_dry_run(dry_run)
+        .with_audit_log(AuditLog::new(audit_log))
+        .with_dashboard(config.dashboard)
+        .with_public_stream(config.public_dashboard);
+    println!("Serving REST API on http://{}/api/v1", listen);
+    if config.dashboard {
+        println!("Dashboard available at http://{}/", listen);
+    }
+    api.serve(listen)
+}
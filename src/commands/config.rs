@@ -0,0 +1,75 @@
+//! Config command implementation
+//!
+//! Inspects how the configuration file applies to the detected GPUs.
+
+use crate::cli::args::{ConfigArgs, ConfigCommands, OutputFormat};
+use crate::cli::output::{print_output, GpuSectionMatch, GpuSectionMatches};
+use crate::config::{Config, ConfigFile, GpuIdentity};
+use crate::error::Result;
+use crate::nvml::{Backend, GpuManager};
+
+/// Execute config commands
+pub fn run_config(
+    args: &ConfigArgs,
+    format: OutputFormat,
+    config_path: Option<&str>,
+) -> Result<()> {
+    match args.command {
+        ConfigCommands::Match => {
+            let config = ConfigFile::load_or_default(config_path)?;
+            let manager = Backend::open(None)?;
+            let matches = match_sections(&manager, &config)?;
+            print_output(&matches, format)?;
+            Ok(())
+        }
+    }
+}
+
+/// Find the `[[gpus]]` section matching each GPU
+fn match_sections<M: GpuManager>(manager: &M, config: &Config) -> Result<GpuSectionMatches> {
+    let mut gpus = Vec::new();
+
+    for index in 0..manager.device_count()? {
+        let device = manager.device_by_index(index)?;
+        let identity = GpuIdentity::from_device(&device)?;
+        let section = config
+            .match_gpu(&identity)
+            .map(|(pos, section)| section.display_name(pos));
+
+        gpus.push(GpuSectionMatch {
+            index: identity.index,
+            name: identity.name,
+            uuid: identity.uuid,
+            pci_bus_id: identity.pci_bus_id,
+            section,
+        });
+    }
+
+    Ok(GpuSectionMatches { gpus })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{GpuSection, GpuSelector};
+    use crate::mock::MockManager;
+
+    #[test]
+    fn test_match_sections() {
+        let manager = MockManager::new(2);
+        let config = Config {
+            gpus: vec![GpuSection {
+                selector: GpuSelector {
+                    pci_bus_id: Some("02:00.0".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Config::default()
+        };
+
+        let matches = match_sections(&manager, &config).unwrap();
+        assert_eq!(matches.gpus[0].section, None);
+        assert_eq!(matches.gpus[1].section.as_deref(), Some("gpus[0]"));
+    }
+}
@@ -2,13 +2,22 @@
 //!
 //! Runs the main control loop for continuous GPU management.
 
+use crate::alerts::{AlertConfig, AlertManagerConfig, NotificationManager, RuleKind};
 use crate::cli::args::{ControlArgs, OutputFormat};
 use crate::cli::output::{print_output, Message};
-use crate::domain::{FanCurve, FanCurvePoint, FanPolicy, FanSpeed, PowerLimit};
-use crate::error::{AppError, DomainError, Result};
-use crate::nvml::{GpuDevice, GpuManager, NvmlManager};
-
-use std::thread;
+use crate::config::ConfigFile;
+use crate::domain::{FanCurve, FanCurvePoint, FanSpeed, PowerLimit};
+use crate::error::{AppError, ConfigError, DomainError, Result};
+use crate::events::{EventListener, NvmlEventSource};
+use crate::history::{HistoryStore, HistoryWriter};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
+use crate::replay::ReplayOptions;
+use crate::services::{
+    AlertService, MetricsPusher, Monitor, MonitorConfig, MqttPublisher, ProcessHistory,
+    ProcessWatcher, Scheduler,
+};
+
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// Execute the control command
@@ -16,16 +25,83 @@ pub fn run_control(
     args: &ControlArgs,
     format: OutputFormat,
     gpu_index: Option<u32>,
+    config_path: Option<&str>,
     dry_run: bool,
     verbose: bool,
+    replay: Option<&ReplayOptions>,
 ) -> Result<()> {
-    let manager = NvmlManager::new()?;
+    let mut manager = Backend::open(replay)?;
+
+    // Global settings and [[gpus]] overrides from the config file
+    let config = ConfigFile::load_or_default(config_path)?;
+    let settings = config.base_settings()?;
+    let mut gpu_overrides = config.gpu_overrides()?;
 
-    // Parse fan curve from speed pairs
-    let curve = parse_fan_curve(args)?;
+    // Speed pairs and --power-limit on the command line replace the configured
+    // settings, including those of [[gpus]] sections
+    let cli_curve = if args.speed_pairs.is_empty() {
+        None
+    } else {
+        Some(parse_fan_curve(args)?)
+    };
+    let cli_power_limit = args.power_limit.map(PowerLimit::from_watts);
+    for gpu_override in &mut gpu_overrides {
+        if let Some(curve) = &cli_curve {
+            gpu_override.settings.fan_curve = curve.clone();
+        }
+        if cli_power_limit.is_some() {
+            gpu_override.settings.power_limit = cli_power_limit;
+        }
+    }
+    let curve = cli_curve.unwrap_or(settings.fan_curve);
+    let power_limit = cli_power_limit.or(settings.power_limit);
+
+    // Profiles referenced by the schedule and process rules must be defined
+    let mut profiles = HashMap::new();
+    let trigger_profiles = config
+        .process_triggers
+        .rules
+        .iter()
+        .map(|rule| rule.profile.as_str());
+    for name in config.schedule.profile_names().chain(trigger_profiles) {
+        let settings =
+            config
+                .profile_settings(name)
+                .ok_or_else(|| ConfigError::InvalidValue {
+                    key: "profiles".to_string(),
+                    message: format!("profile '{}' is not defined in [profiles]", name),
+                })??;
+        profiles.insert(name.to_string(), settings);
+    }
+    // Every profile can be selected over MQTT; dry runs don't connect, since
+    // MQTT commands would change the GPUs
+    let mqtt_config = config.mqtt.clone();
+    let mqtt_enabled = mqtt_config.enabled && !args.single_use && !dry_run;
+    let mqtt_profiles: Vec<String> = config.profiles.keys().cloned().collect();
+    if mqtt_enabled {
+        for name in &mqtt_profiles {
+            if let Some(settings) = config.profile_settings(name) {
+                profiles.insert(name.clone(), settings?);
+            }
+        }
+    }
+    let scheduler = if config.schedule.is_empty() {
+        None
+    } else {
+        Some(Scheduler::new(&config.schedule)?)
+    };
+    let process_watcher = if config.process_triggers.is_empty() {
+        None
+    } else {
+        Some(ProcessWatcher::new(&config.process_triggers)?)
+    };
 
-    // Parse power limit if provided
-    let power_limit = args.power_limit.map(PowerLimit::from_watts);
+    let history_config = config.process_history.clone();
+    let push_config = config.push.clone();
+    let history_store = config
+        .history
+        .enabled
+        .then(|| HistoryStore::from_config(&config.history));
 
     let interval = Duration::from_secs(args.interval);
     let retry_interval = Duration::from_secs(args.retry_interval);
@@ -34,7 +110,11 @@ pub fn run_control(
     let indices: Vec<u32> = if let Some(idx) = gpu_index {
         vec![idx]
     } else {
-        (0..manager.device_count()?).collect()
+        manager
+            .all_devices()?
+            .iter()
+            .map(GpuDevice::index)
+            .collect()
     };
 
     if verbose {
@@ -44,126 +124,83 @@ pub fn run_control(
         log::info!("  Dry run: {}", dry_run);
         log::info!("  GPUs: {:?}", indices);
         log::info!("  Fan curve: {:?}", curve.points());
+        log::info!("  GPU sections: {}", gpu_overrides.len());
+        log::info!("  Schedule rules: {}", config.schedule.rules.len());
+        log::info!("  Process rules: {}", config.process_triggers.rules.len());
     }
 
-    // Initial setup: set fan policy to manual
-    if !dry_run {
-        for &idx in &indices {
-            let mut device = manager.device_by_index(idx)?;
-            let fan_count = device.fan_count().unwrap_or(0);
-            for fan_idx in 0..fan_count {
-                if let Err(e) = device.set_fan_policy(fan_idx, FanPolicy::Manual) {
-                    log::warn!(
-                        "Failed to set fan policy on GPU {} fan {}: {}",
-                        idx,
-                        fan_idx,
-                        e
-                    );
-                }
-            }
-        }
-    }
-
-    loop {
-        match control_tick(
-            &manager,
-            &indices,
-            &curve,
-            power_limit.as_ref(),
-            dry_run,
-            verbose,
-        ) {
-            Ok(()) => {}
-            Err(e) => {
-                log::error!("Control tick failed: {}", e);
-
-                if args.retry {
-                    log::info!("Retrying in {:?}...", retry_interval);
-                    thread::sleep(retry_interval);
-                    continue;
-                } else {
-                    return Err(e);
-                }
-            }
-        }
+    let config = MonitorConfig {
+        interval,
+        single_use: args.single_use,
+        retry: args.retry,
+        retry_interval,
+        fan_curve: curve,
+        power_limit,
+        acoustic_limit: settings.acoustic_limit,
+        gpu_overrides,
+        profiles,
+        dry_run,
+        verbose,
+    };
 
-        if args.single_use {
-            let msg = Message {
-                message: "Control tick completed (single-use mode)".to_string(),
-                success: true,
-            };
-            print_output(&msg, format)?;
-            break;
-        }
+    // Lost/recovered GPUs and event rules (e.g. Xid errors) are reported
+    // through the alert service
+    let alert_config = load_alert_config();
+    let alert_service =
+        AlertService::new(AlertManagerConfig::default(), event_rules(&alert_config))
+            .with_notifications(NotificationManager::from_config(&alert_config.notifiers));
+    let mut monitor = Monitor::new(config).with_alert_service(alert_service);
+    if let Some(scheduler) = scheduler {
+        monitor = monitor.with_scheduler(scheduler);
+    }
+    if let Some(watcher) = process_watcher {
+        monitor = monitor.with_process_watcher(watcher);
+    }
+    // Dry runs leave the on-disk history untouched
+    let record_history = !args.single_use && !dry_run;
+    if history_config.enabled && record_history {
+        monitor = monitor.with_process_history(ProcessHistory::from_config(&history_config));
+    }
+    // Replayed samples would mix recorded data into the live history
+    if let Some(store) = history_store.filter(|_| record_history && !manager.is_replay()) {
+        monitor = monitor.with_history(HistoryWriter::new(store));
+    }
+    if mqtt_enabled {
+        monitor = monitor.with_mqtt(MqttPublisher::connect(&mqtt_config, mqtt_profiles)?);
+    }
+    if !push_config.sinks.is_empty() {
+        monitor = monitor.with_metrics_pusher(MetricsPusher::from_config(&push_config)?);
+    }
+    if !args.single_use && !manager.is_replay() {
+        monitor = monitor.with_event_listener(EventListener::spawn(NvmlEventSource::new));
+    }
+    monitor.run(&mut manager, &indices)?;
 
-        thread::sleep(interval);
+    if args.single_use {
+        let msg = Message {
+            message: "Control tick completed (single-use mode)".to_string(),
+            success: true,
+        };
+        print_output(&msg, format)?;
     }
 
     Ok(())
 }
 
-/// Execute a single control tick
-fn control_tick(
-    manager: &NvmlManager,
-    indices: &[u32],
-    curve: &FanCurve,
-    power_limit: Option<&PowerLimit>,
-    dry_run: bool,
-    verbose: bool,
-) -> Result<()> {
-    for &idx in indices {
-        let mut device = manager.device_by_index(idx)?;
-
-        // Get current temperature
-        let temp = device.temperature()?.as_celsius();
-
-        // Calculate target fan speed from curve
-        let target_speed = curve.speed_for_temperature(temp);
-
-        if verbose {
-            log::info!(
-                "GPU {}: temp={}°C, target_speed={}%",
-                idx,
-                temp,
-                target_speed.as_percentage()
-            );
-        }
-
-        // Apply fan speed
-        let fan_count = device.fan_count().unwrap_or(0);
-        for fan_idx in 0..fan_count {
-            if dry_run {
-                log::info!(
-                    "[DRY RUN] Would set GPU {} fan {} to {}%",
-                    idx,
-                    fan_idx,
-                    target_speed.as_percentage()
-                );
-            } else if let Err(e) = device.set_fan_speed(fan_idx, target_speed) {
-                log::warn!(
-                    "Failed to set fan speed on GPU {} fan {}: {}",
-                    idx,
-                    fan_idx,
-                    e
-                );
-            }
-        }
-
-        // Apply power limit if specified
-        if let Some(limit) = power_limit {
-            if dry_run {
-                log::info!(
-                    "[DRY RUN] Would set GPU {} power limit to {}W",
-                    idx,
-                    limit.as_watts()
-                );
-            } else if let Err(e) = device.set_power_limit(*limit) {
-                log::warn!("Failed to set power limit on GPU {}: {}", idx, e);
-            }
-        }
-    }
+/// Load the alert configuration, or the defaults if it is missing or invalid
+fn load_alert_config() -> AlertConfig {
+    AlertConfig::load_or_default().unwrap_or_else(|e| {
+        log::warn!("Ignoring alert configuration: {}", e);
+        AlertConfig::default()
+    })
+}
 
-    Ok(())
+/// Get the event-based rules of the alert configuration
+fn event_rules(config: &AlertConfig) -> Vec<crate::alerts::AlertRule> {
+    config.rules_of(RuleKind::Event).unwrap_or_else(|e| {
+        log::warn!("Ignoring alert rules: {}", e);
+        Vec::new()
+    })
 }
 
 /// Parse fan curve from command line arguments
@@ -276,4 +313,11 @@ mod tests {
 
         assert!(parse_fan_curve(&args).is_err());
     }
+
+    #[test]
+    fn test_event_rules_ignore_invalid_config() {
+        let mut config = AlertConfig::default();
+        config.rules[0].metric = "not-a-metric".to_string();
+        assert!(event_rules(&config).is_empty());
+    }
 }
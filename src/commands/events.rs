@@ -0,0 +1,115 @@
+//! Events command implementation
+//!
+//! Streams NVML events such as Xid errors and clock changes.
+
+use crate::cli::args::{EventsArgs, OutputFormat};
+use crate::cli::output::print_output;
+use crate::error::Result;
+use crate::events::{EventListener, GpuEvent, NvmlEventSource};
+
+use std::time::{Duration, Instant};
+
+/// Execute the events command
+pub fn run_events(args: &EventsArgs, format: OutputFormat, gpu_index: Option<u32>) -> Result<()> {
+    let listener = EventListener::spawn(NvmlEventSource::new);
+
+    let timeout = (!args.follow).then(|| Duration::from_secs(args.timeout));
+    let count = stream_events(&listener, gpu_index, timeout, |event| {
+        print_output(event, format)
+    })?;
+
+    if !args.follow && count == 0 && matches!(format, OutputFormat::Table) {
+        println!("No events in {}s", args.timeout);
+    }
+
+    Ok(())
+}
+
+/// Pass events to `emit` until `timeout` elapses (or forever if None)
+///
+/// Returns the number of events emitted.
+fn stream_events<F>(
+    listener: &EventListener,
+    gpu_index: Option<u32>,
+    timeout: Option<Duration>,
+    mut emit: F,
+) -> std::io::Result<usize>
+where
+    F: FnMut(&GpuEvent) -> std::io::Result<()>,
+{
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let mut count = 0;
+
+    loop {
+        let wait = match deadline {
+            Some(deadline) => {
+                let now = Instant::now();
+                if now >= deadline {
+                    break;
+                }
+                deadline - now
+            }
+            None => Duration::from_secs(1),
+        };
+
+        let Some(event) = listener.recv_timeout(wait) else {
+            continue;
+        };
+        if gpu_index.is_some_and(|idx| idx != event.gpu_index) {
+            continue;
+        }
+
+        emit(&event)?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::NvmlError;
+    use crate::events::GpuEventKind;
+    use crate::mock::MockEventSource;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_stream_events_filters_by_gpu() {
+        let (sender, source) = MockEventSource::channel();
+        let source = Mutex::new(Some(source));
+        let listener = EventListener::spawn(move || {
+            source
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or_else(|| NvmlError::Unknown("already opened".to_string()))
+        });
+
+        sender
+            .send(GpuEvent::new(0, "GPU-MOCK-0000", GpuEventKind::ClockChange))
+            .unwrap();
+        sender
+            .send(GpuEvent::new(
+                1,
+                "GPU-MOCK-0001",
+                GpuEventKind::XidError(Some(31)),
+            ))
+            .unwrap();
+
+        let mut seen = Vec::new();
+        let count = stream_events(
+            &listener,
+            Some(1),
+            Some(Duration::from_millis(300)),
+            |event| {
+                seen.push(event.kind);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(seen, vec![GpuEventKind::XidError(Some(31))]);
+    }
+}
@@ -0,0 +1,33 @@
+//! Exporter command implementation
+//!
+//! Serves GPU metrics for Prometheus.
+
+use crate::cli::args::ExporterArgs;
+use crate::config::ConfigFile;
+use crate::error::Result;
+use crate::exporter::Exporter;
+use crate::nvml::{Backend, GpuManager};
+use crate::replay::ReplayOptions;
+
+use crate::alerts::metric_rules;
+use std::time::Duration;
+
+/// Execute the exporter command
+pub fn run_exporter(
+    args: &ExporterArgs,
+    config_path: Option<&str>,
+    replay: Option<&ReplayOptions>,
+) -> Result<()> {
+    let config = ConfigFile::load_or_default(config_path)?.exporter;
+    let listen = args.listen.clone().unwrap_or(config.listen);
+    let cache = Duration::from_secs(args.cache.unwrap_or(config.cache_seconds));
+
+    let manager = Backend::open(replay)?;
+    serve(&manager, &listen, cache)
+}
+
+fn serve<M: GpuManager>(manager: &M, listen: &str, cache: Duration) -> Result<()> {
+    let mut exporter = Exporter::new(manager, cache).with_alert_rules(metric_rules()?);
+    println!("Serving metrics on http://{}/metrics", listen);
+    exporter.serve(listen)
+}
@@ -6,7 +6,7 @@ use crate::cli::args::{FanArgs, FanCommands, FanPolicyArg, OutputFormat};
 use crate::cli::output::{print_output, FanInfo, FanStatus, Message};
 use crate::domain::{FanPolicy, FanSpeed};
 use crate::error::Result;
-use crate::nvml::{GpuDevice, GpuManager, NvmlManager};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
 
 /// Execute fan commands
 pub fn run_fan(
@@ -15,7 +15,7 @@ pub fn run_fan(
     gpu_index: Option<u32>,
     dry_run: bool,
 ) -> Result<()> {
-    let manager = NvmlManager::new()?;
+    let manager = Backend::open(None)?;
 
     match &args.command {
         FanCommands::Status => run_fan_status(&manager, format, gpu_index),
@@ -28,11 +28,7 @@ pub fn run_fan(
     }
 }
 
-fn run_fan_status(
-    manager: &NvmlManager,
-    format: OutputFormat,
-    gpu_index: Option<u32>,
-) -> Result<()> {
+fn run_fan_status(manager: &Backend, format: OutputFormat, gpu_index: Option<u32>) -> Result<()> {
     let indices: Vec<u32> = if let Some(idx) = gpu_index {
         vec![idx]
     } else {
@@ -75,7 +71,7 @@ fn run_fan_status(
 }
 
 fn run_fan_policy(
-    manager: &NvmlManager,
+    manager: &Backend,
     policy_arg: FanPolicyArg,
     format: OutputFormat,
     gpu_index: Option<u32>,
@@ -125,7 +121,7 @@ fn run_fan_policy(
 }
 
 fn run_fan_speed(
-    manager: &NvmlManager,
+    manager: &Backend,
     speed: u8,
     fan_index: Option<u32>,
     format: OutputFormat,
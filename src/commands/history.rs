@@ -0,0 +1,75 @@
+//! History command implementation
+//!
+//! Queries the on-disk metrics history written by the control loop.
+
+use crate::cli::args::{HistoryArgs, OutputFormat};
+use crate::cli::output::{print_output, HistoryOutput, HistorySeries, Message};
+use crate::config::ConfigFile;
+use crate::error::{AppError, Result};
+use crate::history::{downsample, HistoryStore};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Execute the history command
+pub fn run_history(
+    args: &HistoryArgs,
+    format: OutputFormat,
+    gpu_index: Option<u32>,
+    config_path: Option<&str>,
+) -> Result<()> {
+    let config = ConfigFile::load_or_default(config_path)?.history;
+    let store = HistoryStore::from_config(&config);
+
+    let gpus: Vec<_> = store
+        .gpus()?
+        .into_iter()
+        .filter(|gpu| gpu_index.is_none_or(|idx| gpu.index == idx))
+        .collect();
+    if gpus.is_empty() {
+        if let Some(idx) = gpu_index {
+            return Err(AppError::GpuNotFound(format!(
+                "GPU {} in history {}",
+                idx,
+                store.dir().display()
+            )));
+        }
+        let msg = Message {
+            message: format!(
+                "No history in {} (enable [history] and run 'nvctl control')",
+                store.dir().display()
+            ),
+            success: false,
+        };
+        print_output(&msg, format)?;
+        return Ok(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64);
+    let since = now - args.since.as_secs() as i64;
+    let until = now - args.until.map_or(0, |d| d.as_secs() as i64);
+    let tier = store.tier_for(since, now);
+
+    let mut series = Vec::with_capacity(gpus.len());
+    for gpu in gpus {
+        let points = store.query(&gpu.uuid, tier, args.metric, since, until)?;
+        series.push(HistorySeries {
+            gpu_index: gpu.index,
+            gpu_name: gpu.name,
+            uuid: gpu.uuid,
+            points: downsample(points, args.points),
+        });
+    }
+
+    let output = HistoryOutput {
+        metric: args.metric.name().to_string(),
+        unit: args.metric.unit().to_string(),
+        tier: tier.name().to_string(),
+        since,
+        until,
+        gpus: series,
+    };
+    print_output(&output, format)?;
+    Ok(())
+}
@@ -4,21 +4,31 @@
 
 use crate::cli::args::{InfoArgs, OutputFormat};
 use crate::cli::output::{
-    print_output, EccStatus, FanInfo, FanStatus, MemoryTempStatus, PcieStatus, PowerStatus,
-    ThermalStatus, VideoStatus,
+    print_output, print_separator, EccStatus, FanInfo, FanStatus, MemoryTempStatus, PcieStatus,
+    PowerStatus, ThermalStatus, VideoStatus,
 };
 use crate::error::Result;
-use crate::nvml::{GpuDevice, GpuManager, NvmlManager};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
+use crate::replay::ReplayOptions;
 
 /// Execute the info command
-pub fn run_info(args: &InfoArgs, format: OutputFormat, gpu_index: Option<u32>) -> Result<()> {
-    let manager = NvmlManager::new()?;
+pub fn run_info(
+    args: &InfoArgs,
+    format: OutputFormat,
+    gpu_index: Option<u32>,
+    replay: Option<&ReplayOptions>,
+) -> Result<()> {
+    let manager = Backend::open(replay)?;
 
     // Determine which GPUs to show info for
     let indices: Vec<u32> = if let Some(idx) = gpu_index {
         vec![idx]
     } else {
-        (0..manager.device_count()?).collect()
+        manager
+            .all_devices()?
+            .iter()
+            .map(GpuDevice::index)
+            .collect()
     };
 
     // Determine what info to show
@@ -198,7 +208,7 @@ pub fn run_info(args: &InfoArgs, format: OutputFormat, gpu_index: Option<u32>) -
             print_output(&video_status, format)?;
         }
 
-        println!(); // Separator between GPUs
+        print_separator(format); // Separator between GPUs
     }
 
     Ok(())
@@ -5,11 +5,11 @@
 use crate::cli::args::OutputFormat;
 use crate::cli::output::{print_output, GpuList, GpuListEntry};
 use crate::error::Result;
-use crate::nvml::{GpuDevice, GpuManager, NvmlManager};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
 
 /// Execute the list command
 pub fn run_list(format: OutputFormat) -> Result<()> {
-    let manager = NvmlManager::new()?;
+    let manager = Backend::open(None)?;
     let driver_version = manager.driver_version()?;
     let count = manager.device_count()?;
 
@@ -3,21 +3,41 @@
 //! Each command handler orchestrates the execution of a CLI command.
 
 pub mod alerts;
+pub mod api;
+pub mod config;
 pub mod control;
+pub mod events;
+pub mod exporter;
 pub mod fan;
 pub mod health;
+pub mod history;
 pub mod info;
 pub mod list;
 pub mod power;
 pub mod processes;
+pub mod push;
+pub mod record;
+pub mod schedule;
+pub mod stats;
 pub mod thermal;
+pub mod top;
 
 pub use alerts::run_alerts;
+pub use api::run_api;
+pub use config::run_config;
 pub use control::run_control;
+pub use events::run_events;
+pub use exporter::run_exporter;
 pub use fan::run_fan;
 pub use health::run_health;
+pub use history::run_history;
 pub use info::run_info;
 pub use list::run_list;
 pub use power::run_power;
 pub use processes::run_processes;
+pub use push::run_push;
+pub use record::run_record;
+pub use schedule::run_schedule;
+pub use stats::run_stats;
 pub use thermal::run_thermal;
+pub use top::run_top;
@@ -6,7 +6,7 @@ use crate::cli::args::{OutputFormat, PowerArgs, PowerCommands};
 use crate::cli::output::{print_output, Message, PowerStatus};
 use crate::domain::PowerLimit;
 use crate::error::Result;
-use crate::nvml::{GpuDevice, GpuManager, NvmlManager};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
 
 /// Execute power commands
 pub fn run_power(
@@ -15,7 +15,7 @@ pub fn run_power(
     gpu_index: Option<u32>,
     dry_run: bool,
 ) -> Result<()> {
-    let manager = NvmlManager::new()?;
+    let manager = Backend::open(None)?;
 
     match &args.command {
         PowerCommands::Status => run_power_status(&manager, format, gpu_index),
@@ -25,11 +25,7 @@ pub fn run_power(
     }
 }
 
-fn run_power_status(
-    manager: &NvmlManager,
-    format: OutputFormat,
-    gpu_index: Option<u32>,
-) -> Result<()> {
+fn run_power_status(manager: &Backend, format: OutputFormat, gpu_index: Option<u32>) -> Result<()> {
     let indices: Vec<u32> = if let Some(idx) = gpu_index {
         vec![idx]
     } else {
@@ -61,7 +57,7 @@ fn run_power_status(
 }
 
 fn run_power_limit(
-    manager: &NvmlManager,
+    manager: &Backend,
     watts: u32,
     format: OutputFormat,
     gpu_index: Option<u32>,
@@ -1,20 +1,37 @@
 //! Processes command implementation
 //!
-//! Lists processes running on GPU with memory usage.
+//! Lists processes running on GPU with memory usage, utilization and owners,
+//! and finished processes from NVML accounting or the process history file.
 
-use crate::cli::args::{OutputFormat, ProcessTypeFilter, ProcessesArgs};
-use crate::cli::output::{print_output, ProcessEntry, ProcessListOutput};
-use crate::domain::{GpuProcess, ProcessType};
+use crate::cli::args::{
+    AccountingMode, OutputFormat, ProcessSort, ProcessTypeFilter, ProcessesArgs,
+};
+use crate::cli::output::{
+    print_output, print_separator, Message, ProcessEntry, ProcessHistoryEntry,
+    ProcessHistoryOutput, ProcessListOutput, VramTotal,
+};
+use crate::config::ConfigFile;
+use crate::domain::{GpuProcess, ProcessList, ProcessType};
 use crate::error::Result;
-use crate::nvml::{GpuDevice, GpuManager, NvmlManager};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
+use crate::services::ProcessHistory;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// How far back utilization samples are averaged
+const UTILIZATION_WINDOW: Duration = Duration::from_secs(1);
 
 /// Execute the processes command
 pub fn run_processes(
     args: &ProcessesArgs,
     format: OutputFormat,
     gpu_index: Option<u32>,
+    config_path: Option<&str>,
+    dry_run: bool,
 ) -> Result<()> {
-    let manager = NvmlManager::new()?;
+    let manager = Backend::open(None)?;
 
     // Determine which GPUs to check
     let indices: Vec<u32> = if let Some(idx) = gpu_index {
@@ -23,34 +40,37 @@ pub fn run_processes(
         (0..manager.device_count()?).collect()
     };
 
+    if let Some(mode) = args.accounting {
+        return run_set_accounting(&manager, &indices, mode, format, dry_run);
+    }
+
+    if args.history {
+        let config = ConfigFile::load_or_default(config_path)?;
+        let history_path = config.process_history.path();
+        for &idx in &indices {
+            let device = manager.device_by_index(idx)?;
+            print_output(&process_history(&device, &history_path, args)?, format)?;
+        }
+        return Ok(());
+    }
+
     for &idx in &indices {
         let device = manager.device_by_index(idx)?;
         let info = device.info()?;
-        let process_list = device.running_processes()?;
-
-        // Filter by process type if specified
-        let mut processes: Vec<&GpuProcess> = if let Some(filter) = args.process_type {
-            match filter {
-                ProcessTypeFilter::Graphics => process_list.graphics_processes(),
-                ProcessTypeFilter::Compute => process_list.compute_processes(),
-                ProcessTypeFilter::Both => process_list
-                    .processes
-                    .iter()
-                    .filter(|p| p.process_type == ProcessType::GraphicsCompute)
-                    .collect(),
-            }
-        } else {
-            process_list.processes.iter().collect()
-        };
+        let mut process_list = device.running_processes()?;
 
-        // Sort processes
-        if args.sort_pid {
-            processes.sort_by_key(|p| p.pid);
-        } else {
-            // Default: sort by memory (descending)
-            processes.sort_by(|a, b| b.used_memory.cmp(&a.used_memory));
+        // Utilization isn't supported on every GPU; leave it out rather than fail
+        match device.process_utilization(UTILIZATION_WINDOW) {
+            Ok(utilization) => process_list.apply_utilization(&utilization),
+            Err(e) => log::debug!("Process utilization unavailable on GPU {}: {}", idx, e),
         }
 
+        let mut processes = select_processes(&process_list, args);
+
+        // Totals cover every selected process, not just the top N
+        let users = vram_totals(&processes, |p| p.user.clone());
+        let containers = vram_totals(&processes, |p| p.container.as_ref().map(|c| c.short_id()));
+
         // Limit to top N if specified
         if let Some(n) = args.top {
             processes.truncate(n);
@@ -65,6 +85,13 @@ pub fn run_processes(
                 memory_mb: p.memory_mb(),
                 memory_gb: p.memory_gb(),
                 process_type: p.process_type.to_string(),
+                utilization: p.utilization,
+                user: p.user.clone(),
+                cmdline: p.cmdline.clone(),
+                start_time: p.start_time,
+                parent_pid: p.parent_pid,
+                cgroup: p.cgroup.clone(),
+                container: p.container.clone(),
             })
             .collect();
 
@@ -75,14 +102,318 @@ pub fn run_processes(
             total_memory_mb: process_list.total_memory_mb(),
             total_memory_gb: process_list.total_memory_gb(),
             processes: process_entries,
+            users,
+            containers,
         };
 
         print_output(&output, format)?;
 
         if indices.len() > 1 {
-            println!(); // Separator between GPUs
+            print_separator(format); // Separator between GPUs
         }
     }
 
     Ok(())
 }
+
+fn run_set_accounting(
+    manager: &Backend,
+    indices: &[u32],
+    mode: AccountingMode,
+    format: OutputFormat,
+    dry_run: bool,
+) -> Result<()> {
+    let enabled = mode == AccountingMode::On;
+    let state = if enabled { "on" } else { "off" };
+
+    for &idx in indices {
+        let mut device = manager.device_by_index(idx)?;
+        let info = device.info()?;
+
+        let message = if dry_run {
+            format!(
+                "[DRY RUN] Would turn accounting mode {} on GPU {}",
+                state, info.name
+            )
+        } else {
+            device.set_accounting(enabled)?;
+            format!("Turned accounting mode {} on GPU {}", state, info.name)
+        };
+
+        print_output(
+            &Message {
+                message,
+                success: true,
+            },
+            format,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Build the process history of a GPU, newest first
+///
+/// Uses NVML accounting statistics when accounting mode is on, and the
+/// history file recorded by the control loop otherwise.
+fn process_history<D: GpuDevice>(
+    device: &D,
+    history_path: &Path,
+    args: &ProcessesArgs,
+) -> Result<ProcessHistoryOutput> {
+    let info = device.info()?;
+
+    let accounting = match device.accounting_enabled() {
+        Ok(true) => device
+            .accounting_stats()
+            .inspect_err(|e| log::debug!("Accounting stats unavailable: {}", e))
+            .ok(),
+        Ok(false) => None,
+        Err(e) => {
+            log::debug!("Accounting mode unavailable: {}", e);
+            None
+        }
+    };
+
+    let (source, mut entries) = match accounting {
+        Some(stats) => {
+            let entries = stats
+                .into_iter()
+                .map(|s| ProcessHistoryEntry {
+                    pid: s.pid,
+                    name: None,
+                    user: None,
+                    running: s.is_running,
+                    max_memory_mb: s.max_memory.map(|b| b as f64 / 1024.0 / 1024.0),
+                    gpu_utilization: s.gpu_utilization,
+                    memory_utilization: s.memory_utilization,
+                    start_time: s.start_time,
+                    // Only known once the process has exited
+                    run_time_secs: (!s.is_running).then_some(s.run_time_ms / 1000),
+                })
+                .collect::<Vec<_>>();
+            ("accounting", entries)
+        }
+        None => {
+            let entries = ProcessHistory::load(history_path)?
+                .into_iter()
+                .filter(|r| r.gpu_uuid == info.uuid)
+                .map(|r| ProcessHistoryEntry {
+                    pid: r.pid,
+                    running: false,
+                    max_memory_mb: Some(r.max_memory as f64 / 1024.0 / 1024.0),
+                    gpu_utilization: r.sm_utilization,
+                    memory_utilization: r.memory_utilization,
+                    start_time: r.start_time,
+                    run_time_secs: Some(r.run_time_secs()),
+                    name: r.name,
+                    user: r.user,
+                })
+                .collect::<Vec<_>>();
+            ("history file", entries)
+        }
+    };
+
+    if let Some(user) = &args.user {
+        entries.retain(|e| e.user.as_ref() == Some(user));
+    }
+    entries.sort_by_key(|e| std::cmp::Reverse(e.start_time));
+    if let Some(n) = args.top {
+        entries.truncate(n);
+    }
+
+    Ok(ProcessHistoryOutput {
+        gpu_name: info.name,
+        gpu_index: device.index(),
+        source: source.to_string(),
+        entries,
+    })
+}
+
+/// Filter and sort processes according to the arguments
+fn select_processes<'a>(
+    process_list: &'a ProcessList,
+    args: &ProcessesArgs,
+) -> Vec<&'a GpuProcess> {
+    let sort = match args.sort {
+        Some(sort) => sort,
+        None if args.sort_pid => ProcessSort::Pid,
+        None => ProcessSort::Memory,
+    };
+
+    let mut processes: Vec<&GpuProcess> = match sort {
+        ProcessSort::Memory => process_list.sorted_by_memory(),
+        ProcessSort::Pid => {
+            let mut processes: Vec<&GpuProcess> = process_list.processes.iter().collect();
+            processes.sort_by_key(|p| p.pid);
+            processes
+        }
+        ProcessSort::Util => process_list.sorted_by_utilization(),
+    };
+
+    // Filter by process type if specified
+    if let Some(filter) = args.process_type {
+        processes.retain(|p| match filter {
+            ProcessTypeFilter::Graphics => p.process_type.is_graphics(),
+            ProcessTypeFilter::Compute => p.process_type.is_compute(),
+            ProcessTypeFilter::Both => p.process_type == ProcessType::GraphicsCompute,
+        });
+    }
+    if let Some(user) = &args.user {
+        processes.retain(|p| p.user.as_ref() == Some(user));
+    }
+    if let Some(prefix) = &args.container {
+        processes.retain(|p| p.container.as_ref().is_some_and(|c| c.matches(prefix)));
+    }
+
+    processes
+}
+
+/// Sum VRAM by a grouping key, largest first; processes without a key are skipped
+fn vram_totals(
+    processes: &[&GpuProcess],
+    key: impl Fn(&GpuProcess) -> Option<String>,
+) -> Vec<VramTotal> {
+    let mut groups: BTreeMap<String, (usize, u64)> = BTreeMap::new();
+    for process in processes {
+        if let Some(name) = key(process) {
+            let entry = groups.entry(name).or_default();
+            entry.0 += 1;
+            entry.1 += process.used_memory;
+        }
+    }
+
+    let mut totals: Vec<VramTotal> = groups
+        .into_iter()
+        .map(|(name, (process_count, bytes))| VramTotal {
+            name,
+            process_count,
+            memory_mb: bytes as f64 / 1024.0 / 1024.0,
+        })
+        .collect();
+    totals.sort_by(|a, b| b.memory_mb.total_cmp(&a.memory_mb));
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ContainerInfo, ContainerRuntime, ProcessUtilization};
+    use clap::Parser;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_select_processes_by_util() {
+        let mut list = ProcessList::new(vec![
+            GpuProcess::new(10, 8 << 30, ProcessType::Compute),
+            GpuProcess::new(20, 1 << 30, ProcessType::Graphics),
+            GpuProcess::new(30, 2 << 30, ProcessType::Compute),
+        ]);
+        list.apply_utilization(&HashMap::from([(
+            30,
+            ProcessUtilization {
+                sm: 95,
+                ..Default::default()
+            },
+        )]));
+
+        let args = ProcessesArgs::parse_from(["processes", "--sort", "util", "-t", "compute"]);
+        let pids: Vec<u32> = select_processes(&list, &args)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![30, 10]);
+
+        let args = ProcessesArgs::parse_from(["processes", "--sort-pid"]);
+        let pids: Vec<u32> = select_processes(&list, &args)
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        assert_eq!(pids, vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn test_process_history_sources() {
+        use crate::domain::ProcessAccounting;
+        use crate::mock::MockDevice;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("processes.jsonl");
+        let mut history = ProcessHistory::new(&path, 10);
+        let running = [GpuProcess::new(5, 2 << 20, ProcessType::Compute)];
+        history.observe("GPU-MOCK-0000", &running, 100).unwrap();
+        history.observe("GPU-MOCK-0000", &running, 160).unwrap();
+        history.observe("GPU-MOCK-0000", &[], 165).unwrap();
+        let args = ProcessesArgs::parse_from(["processes", "--history"]);
+
+        // Without accounting mode the history file is used
+        let output = process_history(&MockDevice::new(0), &path, &args).unwrap();
+        assert_eq!(output.source, "history file");
+        assert_eq!(output.entries.len(), 1);
+        assert_eq!(output.entries[0].run_time_secs, Some(60));
+        assert_eq!(output.entries[0].max_memory_mb, Some(2.0));
+
+        let device = MockDevice::new(0).with_accounting(vec![
+            ProcessAccounting {
+                pid: 7,
+                is_running: false,
+                max_memory: Some(4 << 20),
+                gpu_utilization: Some(75),
+                memory_utilization: Some(30),
+                start_time: 1000,
+                run_time_ms: 90_500,
+            },
+            ProcessAccounting {
+                pid: 8,
+                is_running: true,
+                max_memory: None,
+                gpu_utilization: None,
+                memory_utilization: None,
+                start_time: 2000,
+                run_time_ms: 0,
+            },
+        ]);
+        let output = process_history(&device, &path, &args).unwrap();
+        assert_eq!(output.source, "accounting");
+        assert_eq!(output.entries[0].pid, 8);
+        assert_eq!(output.entries[0].run_time_secs, None);
+        assert_eq!(output.entries[1].run_time_secs, Some(90));
+        assert_eq!(output.entries[1].gpu_utilization, Some(75));
+    }
+
+    #[test]
+    fn test_user_and_container_filters_and_totals() {
+        let container = ContainerInfo {
+            runtime: ContainerRuntime::Docker,
+            id: Some("abcdef0123456789".to_string()),
+            pod: None,
+        };
+        let mut processes = vec![
+            GpuProcess::new(1, 3 << 20, ProcessType::Compute),
+            GpuProcess::new(2, 1 << 20, ProcessType::Compute),
+            GpuProcess::new(3, 4 << 20, ProcessType::Graphics),
+        ];
+        processes[0].user = Some("alice".to_string());
+        processes[0].container = Some(container.clone());
+        processes[1].user = Some("alice".to_string());
+        processes[2].user = Some("bob".to_string());
+        let list = ProcessList::new(processes);
+
+        let args = ProcessesArgs::parse_from(["processes", "--user", "alice"]);
+        let selected = select_processes(&list, &args);
+        assert_eq!(selected.len(), 2);
+
+        let args = ProcessesArgs::parse_from(["processes", "--container", "abcdef"]);
+        let selected = select_processes(&list, &args);
+        assert_eq!(selected.iter().map(|p| p.pid).collect::<Vec<_>>(), vec![1]);
+
+        let all: Vec<&GpuProcess> = list.processes.iter().collect();
+        let users = vram_totals(&all, |p| p.user.clone());
+        assert_eq!(users[0].name, "alice");
+        assert_eq!(users[0].process_count, 2);
+        assert_eq!(users[0].memory_mb, 4.0);
+        let containers = vram_totals(&all, |p| p.container.as_ref().map(|c| c.short_id()));
+        assert_eq!(containers.len(), 1);
+        assert_eq!(containers[0].name, "abcdef012345");
+    }
+}
@@ -0,0 +1,101 @@
+//! Push command implementation
+//!
+//! Sends GPU metrics to InfluxDB or StatsD at a fixed interval.
+
+use crate::cli::args::PushArgs;
+use crate::config::{ConfigFile, PushConfig, SinkConfig};
+use crate::error::{ConfigError, Result};
+use crate::health::HealthCalculator;
+use crate::metrics;
+use crate::nvml::{Backend, GpuDevice, GpuManager, GpuSnapshot};
+use crate::services::MetricsPusher;
+
+use std::time::{Duration, Instant, SystemTime};
+
+/// Execute the push command
+pub fn run_push(args: &PushArgs, gpu_index: Option<u32>, config_path: Option<&str>) -> Result<()> {
+    let config = ConfigFile::load_or_default(config_path)?;
+    let interval = Duration::from_secs(
+        args.interval
+            .unwrap_or(config.general.interval_seconds)
+            .max(1),
+    );
+
+    let push_config = push_config(args, config.push);
+    if push_config.sinks.is_empty() {
+        return Err(ConfigError::MissingField(
+            "push.sinks (or --influx, --influx-file, --statsd)".to_string(),
+        )
+        .into());
+    }
+    let pusher = MetricsPusher::from_config(&push_config)?;
+
+    let manager = Backend::open(None)?;
+    push_loop(&manager, pusher, gpu_index, interval, args.once)
+}
+
+/// Config `[push]` section with sinks from the command line, if any
+fn push_config(args: &PushArgs, mut config: PushConfig) -> PushConfig {
+    let mut sinks = Vec::new();
+    if let Some(url) = &args.influx {
+        sinks.push(SinkConfig::Influx {
+            url: url.clone(),
+            token: args.token.clone(),
+        });
+    }
+    if let Some(path) = &args.influx_file {
+        sinks.push(SinkConfig::InfluxFile { path: path.clone() });
+    }
+    if let Some(address) = &args.statsd {
+        sinks.push(SinkConfig::Statsd {
+            address: address.clone(),
+            prefix: args.prefix.clone(),
+        });
+    }
+    if !sinks.is_empty() {
+        config.sinks = sinks;
+    }
+    config
+}
+
+fn push_loop<M: GpuManager>(
+    manager: &M,
+    mut pusher: MetricsPusher,
+    gpu_index: Option<u32>,
+    interval: Duration,
+    once: bool,
+) -> Result<()> {
+    let indices: Vec<u32> = if let Some(idx) = gpu_index {
+        vec![idx]
+    } else {
+        (0..manager.device_count()?).collect()
+    };
+    let calculator = HealthCalculator::default();
+
+    loop {
+        let started = Instant::now();
+        let timestamp = SystemTime::now();
+        for &index in &indices {
+            let device = match manager.device_by_index(index) {
+                Ok(device) => device,
+                Err(e) => {
+                    log::warn!("Failed to open GPU {}: {}", index, e);
+                    continue;
+                }
+            };
+            let snapshot = GpuSnapshot::capture(&device);
+            let throttle = device.throttle_reasons().ok();
+            let health = calculator.calculate_snapshot(&snapshot);
+            pusher.record(
+                timestamp,
+                &metrics::gpu_samples(&snapshot, throttle.as_ref(), health.as_ref()),
+            );
+        }
+        pusher.flush(Instant::now());
+
+        if once {
+            return Ok(());
+        }
+        std::thread::sleep(interval.saturating_sub(started.elapsed()));
+    }
+}
@@ -0,0 +1,79 @@
+//! Record command implementation
+//!
+//! Writes raw GPU time series to CSV or NDJSON files.
+
+use crate::cli::args::{OutputFormat, RecordArgs};
+use crate::cli::output::{print_output, Message};
+use crate::error::Result;
+use crate::nvml::{Backend, GpuManager};
+use crate::recorder::{RecordFormat, RecordWriter, Recorder, Rotation};
+
+use std::time::Instant;
+
+/// Execute the record command
+pub fn run_record(args: &RecordArgs, format: OutputFormat, gpu_index: Option<u32>) -> Result<()> {
+    let manager = Backend::open(None)?;
+    record(&manager, args, format, gpu_index)
+}
+
+fn record<M: GpuManager>(
+    manager: &M,
+    args: &RecordArgs,
+    format: OutputFormat,
+    gpu_index: Option<u32>,
+) -> Result<()> {
+    let indices: Vec<u32> = if let Some(idx) = gpu_index {
+        vec![idx]
+    } else {
+        (0..manager.device_count()?).collect()
+    };
+
+    let gzip = args.gzip || args.output.extension().is_some_and(|ext| ext == "gz");
+    let file_format = args
+        .file_format
+        .unwrap_or_else(|| RecordFormat::from_path(&args.output));
+    let recorder = Recorder::new(indices, args.fields.clone());
+    let mut writer = RecordWriter::new(args.output.clone(), file_format, args.fields.clone())
+        .with_gzip(gzip)
+        .with_rotation(Rotation {
+            max_bytes: args.rotate_size,
+            max_age: args.rotate_interval,
+        });
+
+    eprintln!(
+        "Recording {} every {:?} to {} (Ctrl-C to stop)",
+        args.fields
+            .iter()
+            .map(|f| f.name())
+            .collect::<Vec<_>>()
+            .join(","),
+        args.interval,
+        args.output.display()
+    );
+
+    let started = Instant::now();
+    let mut samples = 0u64;
+    loop {
+        let tick = Instant::now();
+        writer.write_rows(&recorder.sample(manager))?;
+        samples += 1;
+
+        if args.duration.is_some_and(|d| started.elapsed() >= d) {
+            break;
+        }
+        std::thread::sleep(args.interval.saturating_sub(tick.elapsed()));
+    }
+    writer.finish()?;
+
+    let files: Vec<String> = writer
+        .files()
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect();
+    let msg = Message {
+        message: format!("Recorded {} samples to {}", samples, files.join(", ")),
+        success: true,
+    };
+    print_output(&msg, format)?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! Schedule command implementation
+//!
+//! Shows which scheduled profile is active and what comes next.
+
+use crate::cli::args::{OutputFormat, ScheduleArgs, ScheduleCommands};
+use crate::cli::output::{print_output, ScheduleEntryInfo, ScheduleRuleInfo, ScheduleStatus};
+use crate::config::ConfigFile;
+use crate::error::Result;
+use crate::services::{ScheduleEntry, Scheduler};
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M %a";
+
+/// Execute schedule commands
+pub fn run_schedule(
+    args: &ScheduleArgs,
+    format: OutputFormat,
+    config_path: Option<&str>,
+) -> Result<()> {
+    match args.command {
+        ScheduleCommands::Show => {
+            let config = ConfigFile::load_or_default(config_path)?;
+            let scheduler = Scheduler::new(&config.schedule)?;
+            print_output(&schedule_status(&scheduler), format)?;
+            Ok(())
+        }
+    }
+}
+
+/// Describe the active and next entries of a schedule
+fn schedule_status(scheduler: &Scheduler) -> ScheduleStatus {
+    let now = scheduler.now();
+    let active = scheduler.entry_at(now.timestamp());
+    let next = scheduler.next_change(now.timestamp());
+
+    let entry_info = |entry: &ScheduleEntry, at: Option<String>| ScheduleEntryInfo {
+        rule: entry
+            .rule
+            .map(|pos| scheduler.rules()[pos].display_name(pos)),
+        profile: entry.profile.clone(),
+        at,
+    };
+
+    ScheduleStatus {
+        timezone: scheduler
+            .timezone()
+            .iana_name()
+            .unwrap_or("local")
+            .to_string(),
+        now: now.strftime(TIME_FORMAT).to_string(),
+        active: entry_info(&active, None),
+        next: next
+            .map(|(at, entry)| entry_info(&entry, Some(at.strftime(TIME_FORMAT).to_string()))),
+        rules: scheduler
+            .rules()
+            .iter()
+            .enumerate()
+            .map(|(pos, rule)| ScheduleRuleInfo {
+                name: rule.display_name(pos),
+                days: rule.days.to_string(),
+                start: rule.start.to_string(),
+                end: rule.end.to_string(),
+                profile: rule.profile.clone(),
+                active: active.rule == Some(pos),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ScheduleConfig;
+    use crate::services::ManualClock;
+
+    #[test]
+    fn test_schedule_status() {
+        let config: ScheduleConfig = toml::from_str(
+            r#"
+            timezone = "UTC"
+
+            [[rules]]
+            label = "night"
+            profile = "quiet"
+            start = "22:00"
+            end = "07:00"
+            "#,
+        )
+        .unwrap();
+        // Wednesday night
+        let clock = ManualClock::new("2024-06-12T23:15:00Z".parse().unwrap());
+        let scheduler = Scheduler::new(&config).unwrap().with_clock(clock);
+
+        let status = schedule_status(&scheduler);
+        assert_eq!(status.timezone, "UTC");
+        assert_eq!(status.now, "2024-06-12 23:15 Wed");
+        assert_eq!(status.active.rule.as_deref(), Some("night"));
+        assert_eq!(status.active.profile.as_deref(), Some("quiet"));
+        assert!(status.rules[0].active);
+
+        let next = status.next.unwrap();
+        assert_eq!(next.profile, None);
+        assert_eq!(next.at.as_deref(), Some("2024-06-13 07:00 Thu"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Stats command implementation
+//!
+//! Summarizes GPU telemetry sampled live or read from a recording.
+
+use crate::cli::args::{OutputFormat, StatsArgs};
+use crate::cli::output::print_output;
+use crate::error::{AppError, Result};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
+use crate::recorder::RecordField;
+use crate::replay::Trace;
+use crate::stats::{StatsCollector, StatsReport};
+
+use std::path::Path;
+use std::time::Instant;
+
+/// Execute the stats command
+pub fn run_stats(args: &StatsArgs, format: OutputFormat, gpu_index: Option<u32>) -> Result<()> {
+    let report = match &args.input {
+        Some(path) => from_recording(path, args, gpu_index)?,
+        None => {
+            let manager = Backend::open(None)?;
+            sample_live(&manager, args, gpu_index)?
+        }
+    };
+
+    print_output(&report, format)?;
+    Ok(())
+}
+
+fn from_recording(path: &Path, args: &StatsArgs, gpu_index: Option<u32>) -> Result<StatsReport> {
+    let trace = Trace::load(path)?;
+    let gpus = trace
+        .gpus
+        .iter()
+        .filter(|gpu| gpu_index.is_none_or(|idx| gpu.index == idx))
+        .map(|gpu| {
+            let mut collector = StatsCollector::new(gpu.index, gpu.uuid.clone(), &args.temp_bands)
+                .with_slowdown(args.slowdown_temp);
+            for sample in &gpu.samples {
+                let values: Vec<Option<f64>> =
+                    RecordField::ALL.iter().map(|&f| sample.get(f)).collect();
+                let throttled = sample.get(RecordField::Throttled).map(|v| v != 0.0);
+                collector.add(sample.offset, &values, throttled);
+            }
+            collector.finish()
+        })
+        .collect::<Vec<_>>();
+
+    if gpus.is_empty() {
+        return Err(match gpu_index {
+            Some(idx) => AppError::GpuNotFound(format!("GPU {} in {}", idx, path.display())),
+            None => AppError::NoGpusFound,
+        });
+    }
+
+    Ok(StatsReport {
+        source: path.display().to_string(),
+        gpus,
+    })
+}
+
+fn sample_live<M: GpuManager>(
+    manager: &M,
+    args: &StatsArgs,
+    gpu_index: Option<u32>,
+) -> Result<StatsReport> {
+    let devices: Vec<M::Device> = match gpu_index {
+        Some(idx) => vec![manager.device_by_index(idx)?],
+        None => manager.all_devices()?,
+    };
+
+    let mut collectors: Vec<StatsCollector> = devices
+        .iter()
+        .map(|device| {
+            let slowdown = args.slowdown_temp.or_else(|| {
+                device
+                    .thermal_thresholds()
+                    .ok()
+                    .and_then(|t| t.slowdown)
+                    .map(|t| f64::from(t.as_celsius()))
+            });
+            StatsCollector::new(
+                device.index(),
+                device.uuid().unwrap_or_default(),
+                &args.temp_bands,
+            )
+            .with_slowdown(slowdown)
+        })
+        .collect();
+
+    eprintln!(
+        "Sampling {} GPU(s) every {:?} for {:?}",
+        devices.len(),
+        args.interval,
+        args.duration
+    );
+
+    let started = Instant::now();
+    loop {
+        let tick = Instant::now();
+        let offset = started.elapsed().as_secs_f64();
+        for (device, collector) in devices.iter().zip(&mut collectors) {
+            let values: Vec<Option<f64>> =
+                RecordField::ALL.iter().map(|f| f.read(device)).collect();
+            let throttled = device.throttle_reasons().ok().map(|r| r.is_throttling());
+            collector.add(offset, &values, throttled);
+        }
+
+        if started.elapsed() >= args.duration {
+            break;
+        }
+        std::thread::sleep(args.interval.saturating_sub(tick.elapsed()));
+    }
+
+    Ok(StatsReport {
+        source: "live".to_string(),
+        gpus: collectors.iter().map(StatsCollector::finish).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_recording_reports_throttle_share() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.csv");
+        std::fs::write(
+            &path,
+            "wall_time,monotonic_s,gpu,uuid,temp,throttled\n\
+             t,0.0,0,GPU-A,70,0\n\
+             t,1.0,0,GPU-A,85,1\n\
+             t,2.0,0,GPU-A,86,1\n\
+             t,3.0,0,GPU-A,70,0\n",
+        )
+        .unwrap();
+        let args = StatsArgs {
+            input: Some(path.clone()),
+            duration: Duration::from_secs(60),
+            interval: Duration::from_secs(1),
+            temp_bands: vec![80.0],
+            slowdown_temp: None,
+        };
+
+        let report = from_recording(&path, &args, None).unwrap();
+        assert_eq!(report.gpus[0].throttle_share, Some(2.0 / 3.0));
+    }
+}
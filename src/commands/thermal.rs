@@ -6,7 +6,7 @@ use crate::cli::args::{OutputFormat, ThermalArgs, ThermalCommands};
 use crate::cli::output::{print_output, AcousticStatus, Message};
 use crate::domain::Temperature;
 use crate::error::Result;
-use crate::nvml::{GpuDevice, GpuManager, NvmlManager};
+use crate::nvml::{Backend, GpuDevice, GpuManager};
 
 /// Execute thermal commands
 pub fn run_thermal(
@@ -15,7 +15,7 @@ pub fn run_thermal(
     gpu_index: Option<u32>,
     dry_run: bool,
 ) -> Result<()> {
-    let manager = NvmlManager::new()?;
+    let manager = Backend::open(None)?;
 
     match &args.command {
         ThermalCommands::Status => run_thermal_status(&manager, format, gpu_index),
@@ -26,7 +26,7 @@ pub fn run_thermal(
 }
 
 fn run_thermal_status(
-    manager: &NvmlManager,
+    manager: &Backend,
     format: OutputFormat,
     gpu_index: Option<u32>,
 ) -> Result<()> {
@@ -63,7 +63,7 @@ fn run_thermal_status(
 }
 
 fn run_thermal_limit(
-    manager: &NvmlManager,
+    manager: &Backend,
     celsius: i32,
     format: OutputFormat,
     gpu_index: Option<u32>,
@@ -0,0 +1,46 @@
+//! Top command implementation
+//!
+//! Runs the full-screen terminal dashboard.
+
+use crate::cli::args::TopArgs;
+use crate::error::Result;
+use crate::nvml::{Backend, GpuDevice, GpuManager};
+use crate::replay::ReplayOptions;
+use crate::tui::{self, TopApp};
+
+use crate::alerts::metric_rules;
+
+use std::time::Duration;
+
+/// Execute the top command
+pub fn run_top(
+    args: &TopArgs,
+    gpu_index: Option<u32>,
+    dry_run: bool,
+    replay: Option<&ReplayOptions>,
+) -> Result<()> {
+    let interval = Duration::from_secs(args.interval.max(1));
+
+    let manager = Backend::open(replay)?;
+    run_with(&manager, gpu_index, interval, dry_run)
+}
+
+fn run_with<M: GpuManager>(
+    manager: &M,
+    gpu_index: Option<u32>,
+    interval: Duration,
+    dry_run: bool,
+) -> Result<()> {
+    let indices: Vec<u32> = if let Some(idx) = gpu_index {
+        vec![idx]
+    } else {
+        manager
+            .all_devices()?
+            .iter()
+            .map(GpuDevice::index)
+            .collect()
+    };
+
+    let app = TopApp::new(&indices, metric_rules()?, dry_run);
+    tui::run(manager, app, interval)
+}
@@ -19,9 +19,20 @@ impl ConfigFile {
             .map_err(|_| ConfigError::FileNotFound(path.display().to_string()))?;
 
         let config: Config = toml::from_str(&content)?;
+        config.validate()?;
         Ok(config)
     }
 
+    /// Load configuration from an explicit path, or from default locations
+    ///
+    /// An explicit path must exist; default locations fall back to built-in defaults.
+    pub fn load_or_default(path: Option<&str>) -> Result<Config, ConfigError> {
+        match path {
+            Some(path) => Self::load(path),
+            None => Ok(Self::load_default().unwrap_or_default()),
+        }
+    }
+
     /// Load configuration from default locations
     pub fn load_default() -> Option<Config> {
         for path in Self::default_paths() {
@@ -0,0 +1,287 @@
+//! Per-GPU configuration sections
+//!
+//! `[[gpus]]` entries override the global fan, power and thermal settings for
+//! GPUs matched by UUID, name pattern or PCI bus id.
+
+use crate::config::{Config, FanConfig, PowerConfig, ThermalConfig};
+use crate::domain::{FanCurve, PowerLimit, Temperature};
+use crate::error::{DomainError, NvmlError};
+use crate::nvml::GpuDevice;
+use serde::{Deserialize, Serialize};
+
+/// Identifying attributes of a GPU used for section matching
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GpuIdentity {
+    /// NVML index
+    pub index: u32,
+    /// GPU UUID
+    pub uuid: String,
+    /// Product name
+    pub name: String,
+    /// PCI bus id, if available
+    pub pci_bus_id: Option<String>,
+}
+
+impl GpuIdentity {
+    /// Read the identity of a device
+    pub fn from_device<D: GpuDevice>(device: &D) -> Result<Self, NvmlError> {
+        let info = device.info()?;
+        Ok(Self {
+            index: info.index,
+            uuid: info.uuid,
+            name: info.name,
+            pci_bus_id: info.pci_bus_id,
+        })
+    }
+}
+
+/// Selector for a `[[gpus]]` section
+///
+/// All selectors that are set must match.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpuSelector {
+    /// Exact GPU UUID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Name pattern; supports `*` and `?`, otherwise a case-insensitive partial match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// PCI bus id, with or without the domain (e.g. `01:00.0`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pci_bus_id: Option<String>,
+}
+
+impl GpuSelector {
+    /// Check if no selector is set
+    pub fn is_empty(&self) -> bool {
+        self.uuid.is_none() && self.name.is_none() && self.pci_bus_id.is_none()
+    }
+
+    /// Check if the selector matches a GPU
+    pub fn matches(&self, gpu: &GpuIdentity) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+
+        let uuid_ok = self
+            .uuid
+            .as_ref()
+            .is_none_or(|uuid| uuid.eq_ignore_ascii_case(&gpu.uuid));
+        let name_ok = self
+            .name
+            .as_ref()
+            .is_none_or(|pattern| name_matches(pattern, &gpu.name));
+        let bus_ok = self.pci_bus_id.as_ref().is_none_or(|bus_id| {
+            gpu.pci_bus_id
+                .as_ref()
+                .is_some_and(|actual| bus_id_matches(bus_id, actual))
+        });
+
+        uuid_ok && name_ok && bus_ok
+    }
+}
+
+/// A `[[gpus]]` configuration section
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GpuSection {
+    /// Optional label shown when reporting matches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Which GPUs this section applies to
+    #[serde(flatten)]
+    pub selector: GpuSelector,
+    /// Fan settings replacing the global `[fan]` section
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan: Option<FanConfig>,
+    /// Power settings replacing the global `[power]` section
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerConfig>,
+    /// Thermal settings replacing the global `[thermal]` section
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermal: Option<ThermalConfig>,
+}
+
+impl GpuSection {
+    /// Display name for the section at position `pos`
+    pub fn display_name(&self, pos: usize) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("gpus[{}]", pos))
+    }
+}
+
+/// Control settings resolved for a GPU
+#[derive(Debug, Clone)]
+pub struct DeviceSettings {
+    /// Fan curve
+    pub fan_curve: FanCurve,
+    /// Power limit
+    pub power_limit: Option<PowerLimit>,
+    /// Acoustic temperature limit
+    pub acoustic_limit: Option<Temperature>,
+}
+
+/// A per-GPU override ready for use by the control loop
+#[derive(Debug, Clone)]
+pub struct GpuOverride {
+    /// Section display name
+    pub name: String,
+    /// Which GPUs it applies to
+    pub selector: GpuSelector,
+    /// Resolved settings
+    pub settings: DeviceSettings,
+}
+
+impl Config {
+    /// Find the first `[[gpus]]` section matching a GPU
+    pub fn match_gpu(&self, gpu: &GpuIdentity) -> Option<(usize, &GpuSection)> {
+        self.gpus
+            .iter()
+            .enumerate()
+            .find(|(_, section)| section.selector.matches(gpu))
+    }
+
+    /// Settings from the global sections
+    pub fn base_settings(&self) -> Result<DeviceSettings, DomainError> {
+        self.settings_with(None, None)
+    }
+
+    /// Resolve every `[[gpus]]` section against the global settings
+    pub fn gpu_overrides(&self) -> Result<Vec<GpuOverride>, DomainError> {
+        Ok(self.layered_settings(None)?.gpu_overrides)
+    }
+}
+
+/// Match a GPU name against a pattern
+///
+/// Patterns containing `*` or `?` are matched as globs against the full name;
+/// anything else is a partial match, like `--gpu-name`.
+fn name_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let name = name.to_lowercase();
+
+    if pattern.contains(['*', '?']) {
+        glob_match(pattern.as_bytes(), name.as_bytes())
+    } else {
+        name.contains(&pattern)
+    }
+}
+
+pub(crate) fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        Some((b'?', rest)) => !text.is_empty() && glob_match(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// Compare PCI bus ids, ignoring case and an omitted domain
+fn bus_id_matches(wanted: &str, actual: &str) -> bool {
+    let wanted = wanted.to_lowercase();
+    let actual = actual.to_lowercase();
+    if wanted.matches(':').count() >= 2 {
+        // Full id; NVML pads the domain to 8 digits
+        let strip = |id: &str| id.trim_start_matches('0').to_string();
+        strip(&wanted) == strip(&actual)
+    } else {
+        actual.ends_with(&format!(":{}", wanted)) || actual == wanted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity() -> GpuIdentity {
+        GpuIdentity {
+            index: 0,
+            uuid: "GPU-1234".to_string(),
+            name: "NVIDIA GeForce RTX 4090".to_string(),
+            pci_bus_id: Some("00000000:01:00.0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_selector_matching() {
+        let gpu = identity();
+
+        let by_uuid = GpuSelector {
+            uuid: Some("gpu-1234".to_string()),
+            ..Default::default()
+        };
+        assert!(by_uuid.matches(&gpu));
+
+        let by_glob = GpuSelector {
+            name: Some("*RTX 40?0".to_string()),
+            ..Default::default()
+        };
+        assert!(by_glob.matches(&gpu));
+
+        let by_partial = GpuSelector {
+            name: Some("4090".to_string()),
+            ..Default::default()
+        };
+        assert!(by_partial.matches(&gpu));
+
+        let by_bus = GpuSelector {
+            pci_bus_id: Some("01:00.0".to_string()),
+            ..Default::default()
+        };
+        assert!(by_bus.matches(&gpu));
+
+        let full_bus = GpuSelector {
+            pci_bus_id: Some("0000:01:00.0".to_string()),
+            ..Default::default()
+        };
+        assert!(full_bus.matches(&gpu));
+
+        let mismatch = GpuSelector {
+            uuid: Some("GPU-1234".to_string()),
+            name: Some("*3090".to_string()),
+            pci_bus_id: None,
+        };
+        assert!(!mismatch.matches(&gpu));
+
+        assert!(!GpuSelector::default().matches(&gpu));
+    }
+
+    #[test]
+    fn test_parse_gpu_sections() {
+        let config: Config = toml::from_str(
+            r#"
+            [power]
+            limit_watts = 250
+
+            [[gpus]]
+            label = "big"
+            name = "*4090*"
+            [gpus.power]
+            limit_watts = 400
+            [gpus.thermal]
+            acoustic_limit_celsius = 78
+
+            [[gpus]]
+            pci_bus_id = "02:00.0"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.gpus.len(), 2);
+        let (pos, section) = config.match_gpu(&identity()).unwrap();
+        assert_eq!(pos, 0);
+        assert_eq!(section.display_name(pos), "big");
+
+        let overrides = config.gpu_overrides().unwrap();
+        assert_eq!(overrides[0].settings.power_limit.unwrap().as_watts(), 400);
+        assert_eq!(
+            overrides[0].settings.acoustic_limit,
+            Some(Temperature::new(78))
+        );
+        // Sections without overrides inherit the global settings
+        assert_eq!(overrides[1].name, "gpus[1]");
+        assert_eq!(overrides[1].settings.power_limit.unwrap().as_watts(), 250);
+    }
+}
@@ -4,12 +4,24 @@
 
 pub mod builder;
 pub mod file;
+pub mod gpus;
+pub mod schedule;
+pub mod triggers;
 
 pub use builder::ConfigBuilder;
 pub use file::ConfigFile;
+pub use gpus::{DeviceSettings, GpuIdentity, GpuOverride, GpuSection, GpuSelector};
+pub use schedule::{
+    DaySet, ProfileConfig, ProfileSettings, ScheduleConfig, ScheduleRule, TimeOfDay,
+};
+pub use triggers::{ProcessKind, ProcessRule, ProcessTriggerConfig};
 
 use crate::domain::{FanCurve, FanCurvePoint, FanSpeed, PowerLimit};
+use crate::error::ConfigError;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +38,93 @@ pub struct Config {
     pub power: PowerConfig,
     /// Thermal settings
     pub thermal: ThermalConfig,
+    /// Per-GPU overrides (`[[gpus]]`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub gpus: Vec<GpuSection>,
+    /// Named settings sets (`[profiles.<name>]`)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Time-of-day profile schedule
+    #[serde(skip_serializing_if = "ScheduleConfig::is_empty")]
+    pub schedule: ScheduleConfig,
+    /// Process-triggered profile rules
+    #[serde(skip_serializing_if = "ProcessTriggerConfig::is_empty")]
+    pub process_triggers: ProcessTriggerConfig,
+    /// Rolling history of finished GPU processes
+    pub process_history: ProcessHistoryConfig,
+    /// Prometheus exporter settings
+    pub exporter: ExporterConfig,
+    /// Metrics push settings
+    pub push: PushConfig,
+    /// On-disk metrics history
+    pub history: HistoryConfig,
+    /// MQTT publisher and Home Assistant discovery
+    pub mqtt: MqttConfig,
+    /// REST API settings
+    pub api: ApiConfig,
+}
+
+impl Config {
+    /// Validate settings that TOML parsing cannot check
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        for (pos, section) in self.gpus.iter().enumerate() {
+            let key = section.display_name(pos);
+            if section.selector.is_empty() {
+                return Err(ConfigError::InvalidValue {
+                    key,
+                    message: "expected at least one of uuid, name or pci_bus_id".to_string(),
+                });
+            }
+        }
+
+        if let Some(tz) = &self.schedule.timezone {
+            jiff::tz::TimeZone::get(tz).map_err(|e| ConfigError::InvalidValue {
+                key: "schedule.timezone".to_string(),
+                message: e.to_string(),
+            })?;
+        }
+
+        for (pos, rule) in self.process_triggers.rules.iter().enumerate() {
+            let key = rule.display_name(pos);
+            if rule.is_empty() {
+                return Err(ConfigError::InvalidValue {
+                    key,
+                    message: "expected at least one of name, cmdline, user or process_type"
+                        .to_string(),
+                });
+            }
+            if let Some(pattern) = &rule.cmdline {
+                regex::Regex::new(pattern).map_err(|e| ConfigError::InvalidValue {
+                    key,
+                    message: e.to_string(),
+                })?;
+            }
+        }
+
+        for (name, profile) in &self.profiles {
+            self.layered_settings(Some(profile))
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: format!("profiles.{}", name),
+                    message: e.to_string(),
+                })?;
+        }
+
+        self.base_settings()
+            .map_err(|e| ConfigError::InvalidValue {
+                key: "fan.curve".to_string(),
+                message: e.to_string(),
+            })?;
+
+        for (pos, section) in self.gpus.iter().enumerate() {
+            self.settings_with(Some(section), None)
+                .map_err(|e| ConfigError::InvalidValue {
+                    key: section.display_name(pos),
+                    message: e.to_string(),
+                })?;
+        }
+
+        Ok(())
+    }
 }
 
 /// General configuration
@@ -154,6 +253,306 @@ pub struct ThermalConfig {
     pub acoustic_limit_celsius: Option<i32>,
 }
 
+/// Process history configuration (`[process_history]`)
+///
+/// The control loop records finished GPU processes when NVML accounting mode
+/// isn't available.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessHistoryConfig {
+    /// Record finished processes
+    pub enabled: bool,
+    /// History file (JSON lines)
+    pub path: Option<PathBuf>,
+    /// Number of finished processes to keep
+    pub max_entries: usize,
+}
+
+impl Default for ProcessHistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            max_entries: 1000,
+        }
+    }
+}
+
+impl ProcessHistoryConfig {
+    /// Get the history file path
+    pub fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::data_local_dir()
+                .map(|dir| dir.join("nvctl"))
+                .unwrap_or_default()
+                .join("process-history.jsonl")
+        })
+    }
+}
+
+/// Prometheus exporter configuration (`[exporter]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExporterConfig {
+    /// Address to listen on
+    pub listen: String,
+    /// Seconds a scrape result is reused for
+    pub cache_seconds: u64,
+}
+
+impl Default for ExporterConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:9835".to_string(),
+            cache_seconds: 2,
+        }
+    }
+}
+
+/// Metrics push configuration (`[push]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PushConfig {
+    /// Maximum lines per write
+    pub batch_size: usize,
+    /// Longest wait between retries of a failing sink
+    pub max_backoff_seconds: u64,
+    /// Keep undelivered lines on disk while a sink is down
+    pub spool: bool,
+    /// Spool directory
+    pub spool_dir: Option<PathBuf>,
+    /// Lines kept per sink before the oldest are dropped
+    pub max_spool_lines: usize,
+    /// Push targets (`[[push.sinks]]`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub sinks: Vec<SinkConfig>,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 5000,
+            max_backoff_seconds: 300,
+            spool: true,
+            spool_dir: None,
+            max_spool_lines: 1_000_000,
+            sinks: Vec::new(),
+        }
+    }
+}
+
+impl PushConfig {
+    /// Get the spool directory
+    pub fn spool_dir(&self) -> PathBuf {
+        self.spool_dir.clone().unwrap_or_else(|| {
+            dirs::data_local_dir()
+                .map(|dir| dir.join("nvctl"))
+                .unwrap_or_default()
+                .join("spool")
+        })
+    }
+}
+
+/// A metrics push target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// InfluxDB HTTP write endpoint
+    Influx {
+        /// Write URL including org/bucket or db parameters
+        url: String,
+        /// API token
+        token: Option<String>,
+    },
+    /// File of InfluxDB line protocol
+    InfluxFile {
+        /// File to append to
+        path: PathBuf,
+    },
+    /// StatsD server over UDP
+    Statsd {
+        /// Server address (`host:port`)
+        address: String,
+        /// Prefix for metric names
+        prefix: Option<String>,
+    },
+}
+
+/// On-disk metrics history configuration (`[history]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Record metrics from the control loop
+    pub enabled: bool,
+    /// History directory
+    pub path: Option<PathBuf>,
+    /// How long to keep every sample
+    pub raw_retention_hours: u64,
+    /// How long to keep 1-minute averages
+    pub minute_retention_days: u64,
+    /// How long to keep 1-hour averages
+    pub hour_retention_days: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            raw_retention_hours: 24,
+            minute_retention_days: 30,
+            hour_retention_days: 365,
+        }
+    }
+}
+
+impl HistoryConfig {
+    /// Get the history directory
+    pub fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            dirs::data_local_dir()
+                .map(|dir| dir.join("nvctl"))
+                .unwrap_or_default()
+                .join("history")
+        })
+    }
+}
+
+/// MQTT publisher configuration (`[mqtt]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// Publish from the control loop
+    pub enabled: bool,
+    /// Broker host
+    pub host: String,
+    /// Broker port
+    pub port: u16,
+    /// Client identifier, defaults to `nvctl-<hostname>`
+    pub client_id: Option<String>,
+    /// Username for broker authentication
+    pub username: Option<String>,
+    /// Password for broker authentication
+    pub password: Option<String>,
+    /// Connect over TLS
+    pub tls: bool,
+    /// PEM CA bundle used to verify the broker, platform roots otherwise
+    pub ca_file: Option<PathBuf>,
+    /// PEM client certificate for mutual TLS
+    pub client_cert: Option<PathBuf>,
+    /// PEM client key for mutual TLS
+    pub client_key: Option<PathBuf>,
+    /// Prefix of state, availability and command topics
+    pub base_topic: String,
+    /// Publish Home Assistant discovery configs
+    pub discovery: bool,
+    /// Home Assistant discovery prefix
+    pub discovery_prefix: String,
+    /// MQTT keep-alive interval
+    pub keep_alive_seconds: u64,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: None,
+            username: None,
+            password: None,
+            tls: false,
+            ca_file: None,
+            client_cert: None,
+            client_key: None,
+            base_topic: "nvctl".to_string(),
+            discovery: true,
+            discovery_prefix: "homeassistant".to_string(),
+            keep_alive_seconds: 30,
+        }
+    }
+}
+
+impl MqttConfig {
+    /// Get the client identifier
+    pub fn client_id(&self) -> String {
+        self.client_id.clone().unwrap_or_else(|| {
+            let host = fs::read_to_string("/proc/sys/kernel/hostname")
+                .map(|name| name.trim().to_string())
+                .unwrap_or_default();
+            if host.is_empty() {
+                "nvctl".to_string()
+            } else {
+                format!("nvctl-{}", host)
+            }
+        })
+    }
+}
+
+/// REST API configuration (`[api]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Address to listen on
+    pub listen: String,
+    /// Audit log file, one JSON line per request
+    pub audit_log: Option<PathBuf>,
+    /// Serve the live web dashboard from `/`
+    pub dashboard: bool,
+    /// Let the dashboard read the event stream without a token
+    pub public_dashboard: bool,
+    /// Bearer tokens (`[[api.tokens]]`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<ApiToken>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:9836".to_string(),
+            audit_log: None,
+            dashboard: true,
+            public_dashboard: false,
+            tokens: Vec::new(),
+        }
+    }
+}
+
+impl ApiConfig {
+    /// Get the audit log path
+    pub fn audit_log(&self) -> PathBuf {
+        self.audit_log.clone().unwrap_or_else(|| {
+            dirs::data_local_dir()
+                .map(|dir| dir.join("nvctl"))
+                .unwrap_or_default()
+                .join("api-audit.log")
+        })
+    }
+}
+
+/// A bearer token accepted by the REST API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiToken {
+    /// Name recorded in the audit log
+    pub name: String,
+    /// Secret sent as `Authorization: Bearer <token>`
+    pub token: String,
+    /// What the token may do
+    #[serde(default)]
+    pub scope: ApiScope,
+}
+
+/// Permissions of an API token
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScope {
+    /// Read GPU state
+    #[default]
+    Read,
+    /// Read GPU state and change settings
+    Control,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,4 +571,37 @@ mod tests {
         let curve = config.to_fan_curve().unwrap();
         assert_eq!(curve.points().len(), 4);
     }
+
+    #[test]
+    fn test_validate_rejects_section_without_selector() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+
+        config.gpus.push(GpuSection::default());
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_names_invalid_section() {
+        let mut config = Config::default();
+        config.gpus.push(GpuSection {
+            selector: GpuSelector {
+                name: Some("*4090*".to_string()),
+                ..Default::default()
+            },
+            fan: Some(FanConfig {
+                default_speed: 150,
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        match config.validate() {
+            Err(ConfigError::InvalidValue { key, .. }) => assert_eq!(key, "gpus[0]"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,432 @@
+//! Named profiles and time-of-day schedules
+//!
+//! `[profiles.<name>]` sections define settings sets, and `[schedule]` rules
+//! select one of them by weekday and time window.
+
+use crate::config::{
+    Config, DeviceSettings, FanConfig, GpuIdentity, GpuOverride, GpuSection, PowerConfig,
+    ThermalConfig,
+};
+use crate::error::DomainError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A named settings set (`[profiles.<name>]`)
+///
+/// Unset sections fall back to the `[[gpus]]` section or global settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileConfig {
+    /// Fan settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fan: Option<FanConfig>,
+    /// Power settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<PowerConfig>,
+    /// Thermal settings
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermal: Option<ThermalConfig>,
+}
+
+/// Settings resolved for a profile, including its `[[gpus]]` overrides
+#[derive(Debug, Clone)]
+pub struct ProfileSettings {
+    /// Settings for GPUs without a matching section
+    pub settings: DeviceSettings,
+    /// Per-GPU overrides, first match wins
+    pub gpu_overrides: Vec<GpuOverride>,
+}
+
+impl ProfileSettings {
+    /// Settings for a GPU: its first matching override, or the defaults
+    pub fn settings_for(&self, identity: &GpuIdentity) -> &DeviceSettings {
+        self.gpu_overrides
+            .iter()
+            .find(|o| o.selector.matches(identity))
+            .map_or(&self.settings, |o| &o.settings)
+    }
+}
+
+/// Schedule configuration (`[schedule]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    /// IANA timezone name; the system timezone if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    /// Profile used when no rule matches; the global settings if unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Rules, first match wins
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ScheduleRule>,
+}
+
+impl ScheduleConfig {
+    /// Check if no rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Names of all profiles referenced by the schedule
+    pub fn profile_names(&self) -> impl Iterator<Item = &str> {
+        self.default
+            .as_deref()
+            .into_iter()
+            .chain(self.rules.iter().map(|r| r.profile.as_str()))
+    }
+}
+
+/// A schedule rule (`[[schedule.rules]]`)
+///
+/// Windows with `end <= start` run past midnight into the next day.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleRule {
+    /// Optional label shown in `nvctl schedule show`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Profile to activate
+    pub profile: String,
+    /// Days the window starts on
+    #[serde(default)]
+    pub days: DaySet,
+    /// Window start
+    pub start: TimeOfDay,
+    /// Window end (exclusive)
+    pub end: TimeOfDay,
+}
+
+impl ScheduleRule {
+    /// Display name for the rule at position `pos`
+    pub fn display_name(&self, pos: usize) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("rules[{}]", pos))
+    }
+
+    /// Check if the rule is active on `weekday` (0 = Monday) at `minute` of the day
+    pub fn matches(&self, weekday: u8, minute: u16) -> bool {
+        let start = self.start.minutes();
+        let end = self.end.minutes();
+        let yesterday = (weekday + 6) % 7;
+
+        if start < end {
+            self.days.contains(weekday) && minute >= start && minute < end
+        } else {
+            (self.days.contains(weekday) && minute >= start)
+                || (self.days.contains(yesterday) && minute < end)
+        }
+    }
+}
+
+impl fmt::Display for ScheduleRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}-{} -> {}",
+            self.days, self.start, self.end, self.profile
+        )
+    }
+}
+
+const DAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Full names and other common abbreviations, by weekday
+const DAY_ALIASES: [&[&str]; 7] = [
+    &["monday"],
+    &["tues", "tuesday"],
+    &["wednesday"],
+    &["thur", "thurs", "thursday"],
+    &["friday"],
+    &["saturday"],
+    &["sunday"],
+];
+
+/// A set of weekdays in cron-like syntax
+///
+/// Accepts `*`, `weekdays`, `weekends`, names (`mon`), numbers (`1` = Monday,
+/// `0`/`7` = Sunday), ranges (`mon-fri`) and comma-separated lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DaySet(u8);
+
+impl DaySet {
+    /// Every day of the week
+    pub const ALL: Self = Self(0x7f);
+
+    /// Check if the set contains `weekday` (0 = Monday)
+    pub fn contains(&self, weekday: u8) -> bool {
+        weekday < 7 && self.0 & (1 << weekday) != 0
+    }
+
+    fn parse_day(s: &str) -> Result<u8, String> {
+        if let Some(pos) = (0..7).find(|&d| DAY_NAMES[d] == s || DAY_ALIASES[d].contains(&s)) {
+            return Ok(pos as u8);
+        }
+        match s.parse::<u8>() {
+            Ok(0) | Ok(7) => Ok(6),
+            Ok(n @ 1..=6) => Ok(n - 1),
+            _ => Err(format!("invalid day '{}'", s)),
+        }
+    }
+}
+
+impl Default for DaySet {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl TryFrom<String> for DaySet {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut mask = 0u8;
+        for part in value.to_lowercase().split(',').map(str::trim) {
+            mask |= match part {
+                "*" => 0x7f,
+                "weekdays" => 0x1f,
+                "weekends" => 0x60,
+                _ => match part.split_once('-') {
+                    Some((from, to)) => {
+                        let (from, to) = (Self::parse_day(from)?, Self::parse_day(to)?);
+                        // Ranges may wrap around the week, e.g. fri-mon
+                        let len = (to + 7 - from) % 7;
+                        (0..=len).fold(0, |m, i| m | (1 << ((from + i) % 7)))
+                    }
+                    None => 1 << Self::parse_day(part)?,
+                },
+            };
+        }
+        Ok(Self(mask))
+    }
+}
+
+impl From<DaySet> for String {
+    fn from(days: DaySet) -> Self {
+        days.to_string()
+    }
+}
+
+impl fmt::Display for DaySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0x7f => write!(f, "*"),
+            0x1f => write!(f, "mon-fri"),
+            0x60 => write!(f, "sat,sun"),
+            _ => {
+                let days: Vec<&str> = (0..7)
+                    .filter(|d| self.contains(*d))
+                    .map(|d| DAY_NAMES[d as usize])
+                    .collect();
+                write!(f, "{}", days.join(","))
+            }
+        }
+    }
+}
+
+/// A time of day as `HH:MM`, from `00:00` to `24:00`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TimeOfDay(u16);
+
+impl TimeOfDay {
+    /// Create from hours and minutes
+    pub fn new(hour: u16, minute: u16) -> Option<Self> {
+        let minutes = hour.checked_mul(60)?.checked_add(minute)?;
+        (minute < 60 && minutes <= 24 * 60).then_some(Self(minutes))
+    }
+
+    /// Minutes since midnight
+    pub fn minutes(&self) -> u16 {
+        self.0
+    }
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value
+            .split_once(':')
+            .and_then(|(h, m)| Self::new(h.trim().parse().ok()?, m.trim().parse().ok()?))
+            .ok_or_else(|| format!("invalid time '{}', expected HH:MM", value))
+    }
+}
+
+impl From<TimeOfDay> for String {
+    fn from(time: TimeOfDay) -> Self {
+        time.to_string()
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}", self.0 / 60, self.0 % 60)
+    }
+}
+
+impl Config {
+    /// Resolve a `[profiles.<name>]` section on top of the configured settings
+    ///
+    /// Returns `None` if the profile does not exist.
+    pub fn profile_settings(&self, name: &str) -> Option<Result<ProfileSettings, DomainError>> {
+        let profile = self.profiles.get(name)?;
+        Some(self.layered_settings(Some(profile)))
+    }
+
+    /// Settings and `[[gpus]]` overrides with an optional profile on top
+    pub(crate) fn layered_settings(
+        &self,
+        profile: Option<&ProfileConfig>,
+    ) -> Result<ProfileSettings, DomainError> {
+        let gpu_overrides = self
+            .gpus
+            .iter()
+            .enumerate()
+            .map(|(pos, section)| {
+                Ok(GpuOverride {
+                    name: section.display_name(pos),
+                    selector: section.selector.clone(),
+                    settings: self.settings_with(Some(section), profile)?,
+                })
+            })
+            .collect::<Result<_, DomainError>>()?;
+
+        Ok(ProfileSettings {
+            settings: self.settings_with(None, profile)?,
+            gpu_overrides,
+        })
+    }
+
+    pub(crate) fn settings_with(
+        &self,
+        section: Option<&GpuSection>,
+        profile: Option<&ProfileConfig>,
+    ) -> Result<DeviceSettings, DomainError> {
+        let fan = profile
+            .and_then(|p| p.fan.as_ref())
+            .or_else(|| section.and_then(|s| s.fan.as_ref()))
+            .unwrap_or(&self.fan);
+        let power = profile
+            .and_then(|p| p.power.as_ref())
+            .or_else(|| section.and_then(|s| s.power.as_ref()))
+            .unwrap_or(&self.power);
+        let thermal = profile
+            .and_then(|p| p.thermal.as_ref())
+            .or_else(|| section.and_then(|s| s.thermal.as_ref()))
+            .unwrap_or(&self.thermal);
+
+        Ok(DeviceSettings {
+            fan_curve: fan.to_fan_curve()?,
+            power_limit: power.to_power_limit(),
+            acoustic_limit: thermal
+                .acoustic_limit_celsius
+                .map(crate::domain::Temperature::new),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(s: &str) -> DaySet {
+        DaySet::try_from(s.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(days("*"), DaySet::ALL);
+        assert_eq!(days("mon-fri"), days("weekdays"));
+        assert_eq!(days("1-5"), days("weekdays"));
+        assert_eq!(days("sat,sun"), days("weekends"));
+        assert_eq!(days("0"), days("sunday"));
+
+        let wrapped = days("fri-mon");
+        assert!(wrapped.contains(4) && wrapped.contains(6) && wrapped.contains(0));
+        assert!(!wrapped.contains(2));
+        assert_eq!(wrapped.to_string(), "mon,fri,sat,sun");
+
+        assert!(DaySet::try_from("someday".to_string()).is_err());
+        assert_eq!(days("Tues-Thurs"), days("tue-thu"));
+        assert!(DaySet::try_from("monkey".to_string()).is_err());
+        assert!(DaySet::try_from("sunshine".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let time = TimeOfDay::try_from("07:30".to_string()).unwrap();
+        assert_eq!(time.minutes(), 450);
+        assert_eq!(time.to_string(), "07:30");
+        assert!(TimeOfDay::try_from("24:00".to_string()).is_ok());
+        assert!(TimeOfDay::try_from("24:30".to_string()).is_err());
+        assert!(TimeOfDay::try_from("7".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_overnight_rule() {
+        let rule = ScheduleRule {
+            label: None,
+            profile: "quiet".to_string(),
+            days: days("mon-fri"),
+            start: TimeOfDay::new(22, 0).unwrap(),
+            end: TimeOfDay::new(7, 0).unwrap(),
+        };
+
+        // Friday night and early Saturday morning
+        assert!(rule.matches(4, 23 * 60));
+        assert!(rule.matches(5, 6 * 60));
+        // Not Saturday night, nor early Monday morning
+        assert!(!rule.matches(5, 23 * 60));
+        assert!(!rule.matches(0, 6 * 60));
+        assert!(!rule.matches(2, 12 * 60));
+    }
+
+    #[test]
+    fn test_parse_profiles_and_schedule() {
+        let config: Config = toml::from_str(
+            r#"
+            [power]
+            limit_watts = 300
+
+            [profiles.quiet.power]
+            limit_watts = 200
+
+            [schedule]
+            timezone = "Europe/Berlin"
+
+            [[schedule.rules]]
+            profile = "quiet"
+            days = "mon-fri"
+            start = "22:00"
+            end = "07:00"
+
+            [[gpus]]
+            name = "*4090"
+            [gpus.power]
+            limit_watts = 450
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.schedule.rules.len(), 1);
+        assert_eq!(
+            config.schedule.rules[0].to_string(),
+            "mon-fri 22:00-07:00 -> quiet"
+        );
+
+        // The profile wins over both the global and [[gpus]] settings
+        let quiet = config.profile_settings("quiet").unwrap().unwrap();
+        assert_eq!(quiet.settings.power_limit.unwrap().as_watts(), 200);
+        assert_eq!(
+            quiet.gpu_overrides[0]
+                .settings
+                .power_limit
+                .unwrap()
+                .as_watts(),
+            200
+        );
+        assert!(config.profile_settings("loud").is_none());
+    }
+}
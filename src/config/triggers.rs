@@ -0,0 +1,195 @@
+//! Process-triggered profile rules
+//!
+//! `[[process_triggers.rules]]` entries apply a profile while a matching GPU
+//! process is running.
+
+use crate::config::gpus::glob_match;
+use crate::domain::{GpuProcess, ProcessType};
+use serde::{Deserialize, Serialize};
+
+/// Process trigger configuration (`[process_triggers]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessTriggerConfig {
+    /// Seconds a match (or its absence) must last before switching
+    pub debounce_secs: u64,
+    /// Rules; the highest priority match wins
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ProcessRule>,
+}
+
+impl Default for ProcessTriggerConfig {
+    fn default() -> Self {
+        Self {
+            debounce_secs: 5,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl ProcessTriggerConfig {
+    /// Check if no rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// Process type selector for trigger rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessKind {
+    /// Processes using graphics (including graphics+compute)
+    Graphics,
+    /// Processes using compute (including graphics+compute)
+    Compute,
+    /// Processes using both graphics and compute
+    Both,
+}
+
+impl ProcessKind {
+    /// Check if a process type is selected
+    pub fn matches(&self, process_type: ProcessType) -> bool {
+        match self {
+            Self::Graphics => process_type.is_graphics(),
+            Self::Compute => process_type.is_compute(),
+            Self::Both => process_type == ProcessType::GraphicsCompute,
+        }
+    }
+}
+
+/// A process trigger rule
+///
+/// All selectors that are set must match the same process.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessRule {
+    /// Optional label used in logs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Profile to apply while a process matches
+    pub profile: String,
+    /// Executable name; supports `*` and `?`, otherwise an exact case-insensitive match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Regular expression searched in the full command line
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cmdline: Option<String>,
+    /// Owning user name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Process type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_type: Option<ProcessKind>,
+    /// Higher priority rules win when several match
+    #[serde(default)]
+    pub priority: i32,
+}
+
+impl ProcessRule {
+    /// Display name for the rule at position `pos`
+    pub fn display_name(&self, pos: usize) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| format!("process_triggers.rules[{}]", pos))
+    }
+
+    /// Check if no selector is set
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none()
+            && self.cmdline.is_none()
+            && self.user.is_none()
+            && self.process_type.is_none()
+    }
+
+    /// Check the name, user and type selectors against a process
+    ///
+    /// The command line regex is checked by the caller, which compiles it once.
+    pub fn matches_fields(&self, process: &GpuProcess) -> bool {
+        let name_ok = self.name.as_ref().is_none_or(|pattern| {
+            process.name.as_ref().is_some_and(|name| {
+                let (pattern, name) = (pattern.to_lowercase(), name.to_lowercase());
+                if pattern.contains(['*', '?']) {
+                    glob_match(pattern.as_bytes(), name.as_bytes())
+                } else {
+                    pattern == name
+                }
+            })
+        });
+        let user_ok = self
+            .user
+            .as_ref()
+            .is_none_or(|user| process.user.as_ref() == Some(user));
+        let type_ok = self
+            .process_type
+            .is_none_or(|kind| kind.matches(process.process_type));
+
+        name_ok && user_ok && type_ok
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_parse_process_triggers() {
+        let config: Config = toml::from_str(
+            r#"
+            [process_triggers]
+            debounce_secs = 10
+
+            [[process_triggers.rules]]
+            profile = "performance"
+            name = "blender*"
+            priority = 10
+
+            [[process_triggers.rules]]
+            profile = "training"
+            cmdline = "python.*train"
+            process_type = "compute"
+            "#,
+        )
+        .unwrap();
+
+        let triggers = &config.process_triggers;
+        assert_eq!(triggers.debounce_secs, 10);
+        assert_eq!(triggers.rules.len(), 2);
+        assert_eq!(triggers.rules[1].process_type, Some(ProcessKind::Compute));
+        assert_eq!(triggers.rules[1].priority, 0);
+    }
+
+    #[test]
+    fn test_matches_fields() {
+        let mut process = GpuProcess::with_name(
+            42,
+            "Blender".to_string(),
+            1 << 30,
+            ProcessType::GraphicsCompute,
+        );
+        process.user = Some("alice".to_string());
+
+        let rule = ProcessRule {
+            label: None,
+            profile: "performance".to_string(),
+            name: Some("blender".to_string()),
+            cmdline: None,
+            user: Some("alice".to_string()),
+            process_type: Some(ProcessKind::Graphics),
+            priority: 0,
+        };
+        assert!(rule.matches_fields(&process));
+
+        let other_user = ProcessRule {
+            user: Some("bob".to_string()),
+            ..rule.clone()
+        };
+        assert!(!other_user.matches_fields(&process));
+
+        // Names are exact unless a glob is used
+        let partial = ProcessRule {
+            name: Some("blend".to_string()),
+            ..rule
+        };
+        assert!(!partial.matches_fields(&process));
+    }
+}
@@ -23,7 +23,10 @@ pub use performance::{
     ThrottleReasons, Utilization,
 };
 pub use power::{PowerConstraints, PowerLimit};
-pub use process::{GpuProcess, ProcessList, ProcessType};
+pub use process::{
+    ContainerInfo, ContainerRuntime, GpuProcess, ProcessAccounting, ProcessList, ProcessType,
+    ProcessUtilization,
+};
 pub use thermal::{
     AcousticLimits, Temperature, TemperatureReading, TemperatureSensor, ThermalThresholds,
 };
@@ -3,6 +3,7 @@
 //! Types for tracking GPU processes and their resource usage.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
 /// Information about a process using the GPU
@@ -16,6 +17,27 @@ pub struct GpuProcess {
     pub used_memory: u64,
     /// Process type (graphics, compute, both)
     pub process_type: ProcessType,
+    /// Full command line (if available)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cmdline: Option<String>,
+    /// Owning user name, or UID if it has no name (if available)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Recent utilization (if sampled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utilization: Option<ProcessUtilization>,
+    /// Start time in seconds since the Unix epoch (if available)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<u64>,
+    /// Parent process ID (if available)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_pid: Option<u32>,
+    /// Control group path (if available)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cgroup: Option<String>,
+    /// Container the process runs in (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerInfo>,
 }
 
 impl GpuProcess {
@@ -26,6 +48,13 @@ impl GpuProcess {
             name: None,
             used_memory,
             process_type,
+            cmdline: None,
+            user: None,
+            utilization: None,
+            start_time: None,
+            parent_pid: None,
+            cgroup: None,
+            container: None,
         }
     }
 
@@ -36,6 +65,13 @@ impl GpuProcess {
             name: Some(name),
             used_memory,
             process_type,
+            cmdline: None,
+            user: None,
+            utilization: None,
+            start_time: None,
+            parent_pid: None,
+            cgroup: None,
+            container: None,
         }
     }
 
@@ -69,6 +105,117 @@ impl fmt::Display for GpuProcess {
     }
 }
 
+/// Container runtime a process was started by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContainerRuntime {
+    /// Docker
+    Docker,
+    /// Podman
+    Podman,
+    /// containerd (CRI)
+    Containerd,
+    /// CRI-O
+    CriO,
+    /// Runtime couldn't be determined (e.g. cgroupfs Kubernetes paths)
+    Unknown,
+}
+
+impl fmt::Display for ContainerRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Docker => write!(f, "docker"),
+            Self::Podman => write!(f, "podman"),
+            Self::Containerd => write!(f, "containerd"),
+            Self::CriO => write!(f, "cri-o"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Container or Kubernetes pod a process belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerInfo {
+    /// Container runtime
+    pub runtime: ContainerRuntime,
+    /// Full container ID (if known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Kubernetes pod UID (if running in a pod)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pod: Option<String>,
+}
+
+impl ContainerInfo {
+    /// Short name: the 12 character container ID, or the pod UID
+    pub fn short_id(&self) -> String {
+        match (&self.id, &self.pod) {
+            (Some(id), _) => id.chars().take(12).collect(),
+            (None, Some(pod)) => format!("pod-{}", pod),
+            (None, None) => self.runtime.to_string(),
+        }
+    }
+
+    /// Check if a container ID or pod UID starts with `prefix`
+    pub fn matches(&self, prefix: &str) -> bool {
+        let prefix = prefix.to_lowercase();
+        self.id.as_ref().is_some_and(|id| id.starts_with(&prefix))
+            || self
+                .pod
+                .as_ref()
+                .is_some_and(|pod| pod.starts_with(&prefix))
+    }
+}
+
+/// Per-process utilization percentages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessUtilization {
+    /// SM (3D/compute) utilization
+    pub sm: u32,
+    /// Frame buffer memory utilization
+    pub memory: u32,
+    /// Video encoder utilization
+    pub encoder: u32,
+    /// Video decoder utilization
+    pub decoder: u32,
+}
+
+impl ProcessUtilization {
+    /// Average a set of samples, or None if there are none
+    pub fn average(samples: &[ProcessUtilization]) -> Option<Self> {
+        let n = samples.len() as u32;
+        if n == 0 {
+            return None;
+        }
+        let sum = |f: fn(&ProcessUtilization) -> u32| samples.iter().map(f).sum::<u32>() / n;
+        Some(Self {
+            sm: sum(|s| s.sm),
+            memory: sum(|s| s.memory),
+            encoder: sum(|s| s.encoder),
+            decoder: sum(|s| s.decoder),
+        })
+    }
+}
+
+/// Lifetime statistics of a process from NVML accounting mode
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessAccounting {
+    /// Process ID
+    pub pid: u32,
+    /// Whether the process is still running
+    pub is_running: bool,
+    /// Peak GPU memory allocated (bytes, if supported)
+    pub max_memory: Option<u64>,
+    /// Average GPU utilization over the process lifetime (if supported)
+    pub gpu_utilization: Option<u32>,
+    /// Average memory controller utilization over the process lifetime (if supported)
+    pub memory_utilization: Option<u32>,
+    /// Start time in seconds since the Unix epoch
+    pub start_time: u64,
+    /// Time the compute context was active in milliseconds (0 while running)
+    pub run_time_ms: u64,
+}
+
 /// Type of GPU process
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ProcessType {
@@ -124,6 +271,26 @@ impl ProcessList {
         }
     }
 
+    /// Attach utilization sampled by PID
+    ///
+    /// NVML only reports processes that were busy, so processes without a
+    /// sample are recorded as idle.
+    pub fn apply_utilization(&mut self, utilization: &HashMap<u32, ProcessUtilization>) {
+        for process in &mut self.processes {
+            process.utilization = Some(utilization.get(&process.pid).copied().unwrap_or_default());
+        }
+    }
+
+    /// Get processes sorted by SM utilization, then memory (descending)
+    pub fn sorted_by_utilization(&self) -> Vec<&GpuProcess> {
+        let mut sorted: Vec<&GpuProcess> = self.processes.iter().collect();
+        sorted.sort_by_key(|p| {
+            let util = p.utilization.unwrap_or_default();
+            std::cmp::Reverse((util.sm, util.memory, p.used_memory))
+        });
+        sorted
+    }
+
     /// Get number of processes
     pub fn count(&self) -> usize {
         self.processes.len()
@@ -142,7 +309,7 @@ impl ProcessList {
     /// Get processes sorted by memory usage (descending)
     pub fn sorted_by_memory(&self) -> Vec<&GpuProcess> {
         let mut sorted: Vec<&GpuProcess> = self.processes.iter().collect();
-        sorted.sort_by(|a, b| b.used_memory.cmp(&a.used_memory));
+        sorted.sort_by_key(|p| std::cmp::Reverse(p.used_memory));
         sorted
     }
 
@@ -190,6 +357,41 @@ mod tests {
         assert!(process.name.is_none());
     }
 
+    #[test]
+    fn test_apply_utilization_and_sort() {
+        let mut list = ProcessList::new(vec![
+            GpuProcess::new(1, 4 << 30, ProcessType::Compute),
+            GpuProcess::new(2, 1 << 30, ProcessType::Compute),
+            GpuProcess::new(3, 2 << 30, ProcessType::Graphics),
+        ]);
+        let samples = [
+            ProcessUtilization {
+                sm: 80,
+                memory: 40,
+                ..Default::default()
+            },
+            ProcessUtilization {
+                sm: 60,
+                memory: 20,
+                encoder: 10,
+                ..Default::default()
+            },
+        ];
+        let averaged = ProcessUtilization::average(&samples).unwrap();
+        assert_eq!(averaged.sm, 70);
+        assert_eq!(averaged.encoder, 5);
+        assert!(ProcessUtilization::average(&[]).is_none());
+
+        list.apply_utilization(&HashMap::from([(2, averaged)]));
+        let sorted: Vec<u32> = list.sorted_by_utilization().iter().map(|p| p.pid).collect();
+        // Idle processes fall back to memory order
+        assert_eq!(sorted, vec![2, 1, 3]);
+        assert_eq!(
+            list.processes[0].utilization,
+            Some(ProcessUtilization::default())
+        );
+    }
+
     #[test]
     fn test_gpu_process_with_name() {
         let process = GpuProcess::with_name(
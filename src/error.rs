@@ -35,10 +35,32 @@ pub enum AppError {
     /// IO error (file operations)
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    /// Error from a metrics push target
+    #[error("Metrics sink error: {0}")]
+    Sink(#[from] SinkError),
+
+    /// Error from the MQTT publisher
+    #[error("MQTT error: {0}")]
+    Mqtt(#[from] MqttError),
+
+    /// Error from an alert notification channel
+    #[error("Notification error: {0}")]
+    Notify(#[from] NotifyError),
+}
+
+impl AppError {
+    /// Get the underlying NVML error, if any
+    pub fn as_nvml(&self) -> Option<&NvmlError> {
+        match self {
+            Self::Nvml(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 /// Errors from NVML wrapper operations
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum NvmlError {
     /// Failed to initialize NVML library
     #[error("Failed to initialize NVML: {0}")]
@@ -72,6 +94,10 @@ pub enum NvmlError {
     #[error("GPU is lost or has become inaccessible")]
     GpuLost,
 
+    /// NVML handle is no longer valid (driver reloaded or unloaded)
+    #[error("NVML is not initialized: {0}")]
+    Uninitialized(String),
+
     /// Invalid argument passed to NVML
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
@@ -81,6 +107,24 @@ pub enum NvmlError {
     FanControlNotAvailable,
 }
 
+impl NvmlError {
+    /// Check if the error means the GPU has disappeared from its last known index
+    ///
+    /// Covers GPUs that fell off the bus as well as GPUs that are no longer
+    /// found after a hot-plug or driver reload reordered the device list.
+    pub fn is_gpu_lost(&self) -> bool {
+        matches!(
+            self,
+            Self::GpuLost | Self::DeviceNotFound(_) | Self::DeviceNotFoundByUuid(_)
+        )
+    }
+
+    /// Check if NVML must be re-initialized before it can be used again
+    pub fn requires_reinit(&self) -> bool {
+        matches!(self, Self::Uninitialized(_) | Self::GpuLost)
+    }
+}
+
 /// Errors from domain type validation
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum DomainError {
@@ -141,6 +185,80 @@ pub enum ConfigError {
     JsonError(#[from] serde_json::Error),
 }
 
+/// Errors from metrics push targets
+#[derive(Error, Debug)]
+pub enum SinkError {
+    /// Socket or file error
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// HTTP request could not be sent
+    #[error("HTTP request failed: {0}")]
+    Http(String),
+
+    /// Endpoint answered with an error status
+    #[error("Endpoint rejected write with HTTP {status}: {body}")]
+    Rejected { status: u16, body: String },
+}
+
+/// Errors from the MQTT publisher
+#[derive(Error, Debug)]
+pub enum MqttError {
+    /// TLS material could not be read
+    #[error("Failed to read {path}: {source}")]
+    Tls {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+
+    /// Invalid client settings
+    #[error("Invalid MQTT settings: {0}")]
+    Settings(String),
+
+    /// The client request queue is closed
+    #[error("MQTT client error: {0}")]
+    Client(String),
+}
+
+/// Errors from alert notification channels
+#[derive(Error, Debug)]
+pub enum NotifyError {
+    /// HTTP request could not be sent
+    #[error("HTTP request failed: {0}")]
+    Http(String),
+
+    /// Endpoint answered with an error status
+    #[error("Webhook rejected notification with HTTP {status}: {body}")]
+    Rejected { status: u16, body: String },
+
+    /// Session bus or notification service error
+    #[error("D-Bus error: {0}")]
+    Dbus(#[from] zbus::Error),
+
+    /// Email could not be built or sent
+    #[error("Email error: {0}")]
+    Email(String),
+
+    /// Hook command could not be run or failed
+    #[error("Command failed: {0}")]
+    Exec(String),
+
+    /// Journal or syslog socket error
+    #[error("Log socket error: {0}")]
+    Socket(#[from] std::io::Error),
+}
+
+impl NotifyError {
+    /// Check if a failed notification may succeed when retried
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Http(_) => true,
+            Self::Rejected { status, .. } => *status == 429 || *status >= 500,
+            Self::Dbus(_) | Self::Email(_) | Self::Exec(_) | Self::Socket(_) => false,
+        }
+    }
+}
+
 /// Errors from service operations
 #[derive(Error, Debug)]
 pub enum ServiceError {
@@ -188,6 +306,16 @@ mod tests {
         assert!(err.to_string().contains("NVIDIA driver"));
     }
 
+    #[test]
+    fn test_nvml_error_recovery_classification() {
+        assert!(NvmlError::GpuLost.is_gpu_lost());
+        assert!(NvmlError::GpuLost.requires_reinit());
+        assert!(NvmlError::DeviceNotFound(1).is_gpu_lost());
+        assert!(!NvmlError::DeviceNotFound(1).requires_reinit());
+        assert!(NvmlError::Uninitialized("driver reloaded".to_string()).requires_reinit());
+        assert!(!NvmlError::NotSupported("fan".to_string()).is_gpu_lost());
+    }
+
     #[test]
     fn test_power_limit_error_display() {
         let err = DomainError::InvalidPowerLimit {
@@ -0,0 +1,152 @@
+//! Background event listener
+//!
+//! Runs an [`EventSource`] on its own thread and forwards events over a channel.
+
+use super::{EventSource, GpuEvent};
+use crate::error::NvmlError;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// How long the event thread blocks before checking for shutdown
+const POLL_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Delay before reopening a source that failed
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+
+/// Handle to a running event thread
+pub struct EventListener {
+    receiver: Receiver<GpuEvent>,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl EventListener {
+    /// Spawn an event thread
+    ///
+    /// The source is opened on the event thread by `open`, and reopened after
+    /// errors such as a driver reload.
+    pub fn spawn<S, F>(open: F) -> Self
+    where
+        S: EventSource,
+        F: Fn() -> Result<S, NvmlError> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+
+        let handle = thread::Builder::new()
+            .name("nvctl-events".to_string())
+            .spawn(move || {
+                while thread_running.load(Ordering::SeqCst) {
+                    let mut source = match open() {
+                        Ok(source) => source,
+                        Err(e) => {
+                            log::warn!("Failed to open event source: {}", e);
+                            sleep_while(&thread_running, RESTART_DELAY);
+                            continue;
+                        }
+                    };
+
+                    while thread_running.load(Ordering::SeqCst) {
+                        match source.next_event(POLL_TIMEOUT) {
+                            Ok(Some(event)) => {
+                                if sender.send(event).is_err() {
+                                    // Receiver dropped
+                                    return;
+                                }
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                log::warn!("Event source failed, reopening: {}", e);
+                                sleep_while(&thread_running, RESTART_DELAY);
+                                break;
+                            }
+                        }
+                    }
+                }
+            })
+            .expect("failed to spawn event thread");
+
+        Self {
+            receiver,
+            running,
+            handle: Some(handle),
+        }
+    }
+
+    /// Get all events received so far without blocking
+    pub fn drain(&self) -> Vec<GpuEvent> {
+        self.receiver.try_iter().collect()
+    }
+
+    /// Wait up to `timeout` for the next event
+    ///
+    /// Returns None on timeout or if the event thread has exited.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<GpuEvent> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => Some(event),
+            Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => None,
+        }
+    }
+
+    /// Stop the event thread and wait for it to exit
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for EventListener {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn sleep_while(running: &AtomicBool, duration: Duration) {
+    let step = Duration::from_millis(100);
+    let mut waited = Duration::ZERO;
+    while waited < duration && running.load(Ordering::SeqCst) {
+        thread::sleep(step);
+        waited += step;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::GpuEventKind;
+    use crate::mock::MockEventSource;
+
+    #[test]
+    fn test_listener_forwards_events() {
+        let (sender, source) = MockEventSource::channel();
+        let source = std::sync::Mutex::new(Some(source));
+        let mut listener = EventListener::spawn(move || {
+            source
+                .lock()
+                .unwrap()
+                .take()
+                .ok_or_else(|| NvmlError::Unknown("already opened".to_string()))
+        });
+
+        sender
+            .send(GpuEvent::new(
+                0,
+                "GPU-MOCK-0000",
+                GpuEventKind::XidError(Some(48)),
+            ))
+            .unwrap();
+
+        let event = listener.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(event.kind, GpuEventKind::XidError(Some(48)));
+
+        listener.stop();
+        assert!(listener.drain().is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+//! GPU event subsystem
+//!
+//! Delivers NVML events (Xid errors, ECC errors, P-state, clock and power
+//! source changes) from a background thread as typed [`GpuEvent`]s.
+
+pub mod listener;
+pub mod nvml;
+
+pub use listener::EventListener;
+pub use nvml::NvmlEventSource;
+
+use crate::alerts::MetricType;
+use crate::error::NvmlError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// Kind of GPU event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "xid", rename_all = "snake_case")]
+pub enum GpuEventKind {
+    /// Critical Xid error, with the Xid code if known
+    XidError(Option<u64>),
+    /// Single-bit (correctable) ECC error
+    SingleBitEccError,
+    /// Double-bit (uncorrectable) ECC error
+    DoubleBitEccError,
+    /// Performance state changed
+    PStateChange,
+    /// Clock frequency changed
+    ClockChange,
+    /// Power source changed (AC/battery)
+    PowerSourceChange,
+}
+
+impl GpuEventKind {
+    /// Alert metric this event is evaluated against
+    pub fn metric(&self) -> MetricType {
+        match self {
+            Self::XidError(_) => MetricType::XidError,
+            Self::SingleBitEccError => MetricType::SingleBitEccEvent,
+            Self::DoubleBitEccError => MetricType::DoubleBitEccEvent,
+            Self::PStateChange => MetricType::PStateChange,
+            Self::ClockChange => MetricType::ClockChange,
+            Self::PowerSourceChange => MetricType::PowerSourceChange,
+        }
+    }
+
+    /// Value used for alert conditions: the Xid code for Xid errors, 1 otherwise
+    pub fn value(&self) -> f64 {
+        match self {
+            Self::XidError(Some(xid)) => *xid as f64,
+            _ => 1.0,
+        }
+    }
+}
+
+impl fmt::Display for GpuEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::XidError(Some(xid)) => write!(f, "Xid {}", xid),
+            Self::XidError(None) => write!(f, "Xid (unknown)"),
+            Self::SingleBitEccError => write!(f, "single-bit ECC error"),
+            Self::DoubleBitEccError => write!(f, "double-bit ECC error"),
+            Self::PStateChange => write!(f, "P-state change"),
+            Self::ClockChange => write!(f, "clock change"),
+            Self::PowerSourceChange => write!(f, "power source change"),
+        }
+    }
+}
+
+/// An event reported by a GPU
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuEvent {
+    /// GPU index
+    pub gpu_index: u32,
+    /// GPU UUID
+    pub uuid: String,
+    /// What happened
+    pub kind: GpuEventKind,
+    /// When the event was received
+    pub timestamp: SystemTime,
+}
+
+impl GpuEvent {
+    /// Create an event received now
+    pub fn new(gpu_index: u32, uuid: impl Into<String>, kind: GpuEventKind) -> Self {
+        Self {
+            gpu_index,
+            uuid: uuid.into(),
+            kind,
+            timestamp: SystemTime::now(),
+        }
+    }
+}
+
+impl fmt::Display for GpuEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self
+            .timestamp
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        write!(
+            f,
+            "{:02}:{:02}:{:02} UTC GPU {} ({}): {}",
+            (secs / 3600) % 24,
+            (secs / 60) % 60,
+            secs % 60,
+            self.gpu_index,
+            self.uuid,
+            self.kind
+        )
+    }
+}
+
+/// A source of GPU events
+pub trait EventSource {
+    /// Wait up to `timeout` for the next event
+    ///
+    /// Returns `Ok(None)` if no event arrived in time.
+    fn next_event(&mut self, timeout: Duration) -> Result<Option<GpuEvent>, NvmlError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_kind_metric_and_value() {
+        assert_eq!(GpuEventKind::XidError(Some(79)).value(), 79.0);
+        assert_eq!(GpuEventKind::XidError(None).metric(), MetricType::XidError);
+        assert_eq!(GpuEventKind::ClockChange.value(), 1.0);
+        assert_eq!(
+            GpuEventKind::DoubleBitEccError.metric(),
+            MetricType::DoubleBitEccEvent
+        );
+    }
+
+    #[test]
+    fn test_event_display() {
+        let mut event = GpuEvent::new(0, "GPU-MOCK-0000", GpuEventKind::XidError(Some(79)));
+        event.timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(3661);
+        assert_eq!(
+            event.to_string(),
+            "01:01:01 UTC GPU 0 (GPU-MOCK-0000): Xid 79"
+        );
+    }
+}
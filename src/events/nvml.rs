@@ -0,0 +1,132 @@
+//! NVML event set source
+//!
+//! Registers every GPU in an NVML event set and translates event data.
+
+use super::{EventSource, GpuEvent, GpuEventKind};
+use crate::error::NvmlError;
+use crate::nvml::NvmlDevice;
+
+use nvml_wrapper::bitmasks::event::EventTypes;
+use nvml_wrapper::enums::event::XidError;
+use nvml_wrapper::{EventSet, Nvml};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Event types requested from each GPU (filtered by what it supports)
+fn wanted_events() -> EventTypes {
+    EventTypes::CRITICAL_XID_ERROR
+        | EventTypes::SINGLE_BIT_ECC_ERROR
+        | EventTypes::DOUBLE_BIT_ECC_ERROR
+        | EventTypes::PSTATE_CHANGE
+        | EventTypes::CLOCK_CHANGE
+        | EventTypes::POWER_SOURCE_CHANGE
+}
+
+/// Event source backed by an NVML event set
+pub struct NvmlEventSource {
+    // Declared before `nvml` so it is dropped first
+    set: EventSet<'static>,
+    uuids: HashMap<u32, String>,
+    // Owns the library instance `set` borrows from
+    _nvml: Box<Nvml>,
+}
+
+impl NvmlEventSource {
+    /// Initialize NVML and register all GPUs for events
+    pub fn new() -> Result<Self, NvmlError> {
+        let nvml = Box::new(Nvml::init().map_err(NvmlDevice::convert_error)?);
+
+        // SAFETY: The event set borrows the boxed Nvml instance, which has a
+        // stable address and is dropped after the set (see field order).
+        let nvml_ref: &'static Nvml = unsafe { std::mem::transmute(nvml.as_ref()) };
+
+        let mut uuids = HashMap::new();
+        let mut set = nvml_ref
+            .create_event_set()
+            .map_err(NvmlDevice::convert_error)?;
+
+        for index in 0..nvml_ref.device_count().map_err(NvmlDevice::convert_error)? {
+            let device = nvml_ref
+                .device_by_index(index)
+                .map_err(NvmlDevice::convert_error)?;
+            if let Ok(uuid) = device.uuid() {
+                uuids.insert(index, uuid);
+            }
+
+            let events = device
+                .supported_event_types()
+                .map(|supported| supported & wanted_events())
+                .unwrap_or_else(|_| EventTypes::empty());
+            if events.is_empty() {
+                log::debug!("GPU {} supports none of the requested events", index);
+                continue;
+            }
+
+            // A failed registration consumes the set; the listener reopens the source
+            set = device.register_events(events, set).map_err(|e| {
+                log::warn!("Failed to register events on GPU {}: {}", index, e.error);
+                NvmlDevice::convert_error(e.error)
+            })?;
+        }
+
+        Ok(Self {
+            set,
+            uuids,
+            _nvml: nvml,
+        })
+    }
+
+    fn kind_for(event_type: EventTypes, xid: Option<XidError>) -> Option<GpuEventKind> {
+        if event_type.contains(EventTypes::CRITICAL_XID_ERROR) {
+            Some(GpuEventKind::XidError(match xid {
+                Some(XidError::Value(v)) => Some(v),
+                _ => None,
+            }))
+        } else if event_type.contains(EventTypes::DOUBLE_BIT_ECC_ERROR) {
+            Some(GpuEventKind::DoubleBitEccError)
+        } else if event_type.contains(EventTypes::SINGLE_BIT_ECC_ERROR) {
+            Some(GpuEventKind::SingleBitEccError)
+        } else if event_type.contains(EventTypes::PSTATE_CHANGE) {
+            Some(GpuEventKind::PStateChange)
+        } else if event_type.contains(EventTypes::CLOCK_CHANGE) {
+            Some(GpuEventKind::ClockChange)
+        } else if event_type.contains(EventTypes::POWER_SOURCE_CHANGE) {
+            Some(GpuEventKind::PowerSourceChange)
+        } else {
+            None
+        }
+    }
+}
+
+impl EventSource for NvmlEventSource {
+    fn next_event(&mut self, timeout: Duration) -> Result<Option<GpuEvent>, NvmlError> {
+        let data = match self
+            .set
+            .wait(timeout.as_millis().min(u32::MAX as u128) as u32)
+        {
+            Ok(data) => data,
+            Err(nvml_wrapper::error::NvmlError::Timeout) => return Ok(None),
+            Err(e) => return Err(NvmlDevice::convert_error(e)),
+        };
+
+        let Some(kind) = Self::kind_for(data.event_type, data.event_data) else {
+            return Ok(None);
+        };
+
+        let index = match data.device.index() {
+            Ok(index) => index,
+            Err(e) => {
+                log::warn!("Dropping {:?} event from an unknown GPU: {}", kind, e);
+                return Ok(None);
+            }
+        };
+        let uuid = self
+            .uuids
+            .get(&index)
+            .cloned()
+            .or_else(|| data.device.uuid().ok())
+            .unwrap_or_default();
+
+        Ok(Some(GpuEvent::new(index, uuid, kind)))
+    }
+}
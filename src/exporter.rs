@@ -0,0 +1,233 @@
+//! Prometheus/OpenMetrics exporter
+//!
+//! Serves `/metrics` in the Prometheus text format and `/healthz` for
+//! liveness probes. Scrapes inside the cache window reuse the last result so
+//! frequent scrapers don't hammer NVML.
+
+use crate::alerts::{AlertManager, AlertManagerConfig, AlertRule, AlertState, RuleKind};
+use crate::error::Result;
+use crate::health::HealthCalculator;
+use crate::metrics::{self, GpuLabels, Sample};
+use crate::nvml::{GpuDevice, GpuManager, GpuSnapshot};
+
+use std::io;
+use std::time::{Duration, Instant};
+use tiny_http::{Header, Method, Response, Server};
+
+/// Content type of the Prometheus text format
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// HTTP response produced by the exporter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExporterResponse {
+    /// Status code
+    pub status: u16,
+    /// Content type
+    pub content_type: &'static str,
+    /// Response body
+    pub body: String,
+}
+
+impl ExporterResponse {
+    fn text(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: body.into(),
+        }
+    }
+}
+
+/// Metrics exporter for all GPUs of a manager
+pub struct Exporter<'a, M: GpuManager> {
+    manager: &'a M,
+    alerts: AlertManager,
+    health: HealthCalculator,
+    cache_ttl: Duration,
+    cache: Option<(Instant, String)>,
+}
+
+impl<'a, M: GpuManager> Exporter<'a, M> {
+    /// Create an exporter with a scrape cache window
+    pub fn new(manager: &'a M, cache_ttl: Duration) -> Self {
+        Self {
+            manager,
+            alerts: AlertManager::new(AlertManagerConfig::default()),
+            health: HealthCalculator::default(),
+            cache_ttl,
+            cache: None,
+        }
+    }
+
+    /// Evaluate these alert rules on every scrape
+    pub fn with_alert_rules(mut self, rules: Vec<AlertRule>) -> Self {
+        self.alerts.add_rules(rules);
+        self
+    }
+
+    /// Get the metrics text, from the cache if it is fresh
+    pub fn metrics(&mut self) -> String {
+        if let Some((at, text)) = &self.cache {
+            if at.elapsed() < self.cache_ttl {
+                return text.clone();
+            }
+        }
+
+        let text = metrics::encode_prometheus(&self.collect());
+        self.cache = Some((Instant::now(), text.clone()));
+        text
+    }
+
+    /// Check that NVML still answers
+    pub fn healthy(&self) -> bool {
+        self.manager.device_count().is_ok()
+    }
+
+    /// Handle a request for `path`
+    pub fn handle(&mut self, method: &Method, path: &str) -> ExporterResponse {
+        if !matches!(method, Method::Get | Method::Head) {
+            return ExporterResponse::text(405, "method not allowed\n");
+        }
+
+        match path.split('?').next().unwrap_or_default() {
+            "/metrics" => ExporterResponse {
+                status: 200,
+                content_type: METRICS_CONTENT_TYPE,
+                body: self.metrics(),
+            },
+            "/healthz" if self.healthy() => ExporterResponse::text(200, "ok\n"),
+            "/healthz" => ExporterResponse::text(503, "NVML unavailable\n"),
+            "/" => ExporterResponse::text(200, "nvctl exporter: see /metrics and /healthz\n"),
+            _ => ExporterResponse::text(404, "not found\n"),
+        }
+    }
+
+    /// Serve HTTP requests until the process exits
+    pub fn serve(&mut self, listen: &str) -> Result<()> {
+        let server = Server::http(listen).map_err(io::Error::other)?;
+        log::info!("Serving metrics on http://{}/metrics", listen);
+
+        for request in server.incoming_requests() {
+            let response = self.handle(request.method(), request.url());
+            log::debug!(
+                "{} {} -> {}",
+                request.method(),
+                request.url(),
+                response.status
+            );
+
+            let header = Header::from_bytes("Content-Type", response.content_type)
+                .expect("static header is valid");
+            let result = request.respond(
+                Response::from_string(response.body)
+                    .with_status_code(response.status)
+                    .with_header(header),
+            );
+            if let Err(e) = result {
+                log::warn!("Failed to send response: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read every GPU and build the sample list
+    fn collect(&mut self) -> Vec<Sample> {
+        let count = match self.manager.device_count() {
+            Ok(count) => count,
+            Err(e) => {
+                log::warn!("Failed to query GPUs: {}", e);
+                return vec![Sample::new(&metrics::UP, 0.0)];
+            }
+        };
+
+        let mut samples = vec![Sample::new(&metrics::UP, 1.0)];
+        for index in 0..count {
+            let device = match self.manager.device_by_index(index) {
+                Ok(device) => device,
+                Err(e) => {
+                    log::warn!("Failed to open GPU {}: {}", index, e);
+                    samples.push(
+                        Sample::new(&metrics::GPU_UP, 0.0).with_label("gpu", index.to_string()),
+                    );
+                    continue;
+                }
+            };
+
+            let snapshot = GpuSnapshot::capture(&device);
+            let throttle = device.throttle_reasons().ok();
+            let health = self.health.calculate_snapshot(&snapshot);
+            samples.extend(metrics::gpu_samples(
+                &snapshot,
+                throttle.as_ref(),
+                health.as_ref(),
+            ));
+            samples.extend(self.alert_samples(&snapshot));
+        }
+        samples
+    }
+
+    /// One sample per metric alert rule that applies to the GPU
+    fn alert_samples(&mut self, snapshot: &GpuSnapshot) -> Vec<Sample> {
+        if let Err(e) = self.alerts.evaluate(snapshot, snapshot.index) {
+            log::warn!(
+                "Failed to evaluate alerts for GPU {}: {}",
+                snapshot.index,
+                e
+            );
+        }
+
+        let gpu = GpuLabels::from_snapshot(snapshot);
+        let active = self.alerts.active_alerts();
+        self.alerts
+            .rules()
+            .iter()
+            .filter(|rule| {
+                rule.enabled && RuleKind::Metric.matches(rule) && rule.gpu_filter.matches(gpu.index)
+            })
+            .map(|rule| {
+                let firing = active.iter().any(|alert| {
+                    alert.rule_id == rule.id
+                        && alert.gpu_index == gpu.index
+                        && matches!(alert.state, AlertState::Firing | AlertState::Acknowledged)
+                });
+                gpu.sample(&metrics::ALERT_FIRING, if firing { 1.0 } else { 0.0 })
+                    .with_label("rule", rule.id.clone())
+                    .with_label("severity", rule.severity.to_string().to_lowercase())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockManager;
+
+    #[test]
+    fn test_metrics_endpoint_and_cache() {
+        let manager = MockManager::new(2);
+        let mut exporter = Exporter::new(&manager, Duration::from_secs(60));
+
+        let response = exporter.handle(&Method::Get, "/metrics");
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("nvctl_up 1"));
+        assert!(response
+            .body
+            .contains("nvctl_gpu_temperature_celsius{gpu=\"1\""));
+        assert!(response.body.contains("nvctl_gpu_health_score{gpu=\"0\""));
+
+        let (cached_at, _) = exporter.cache.clone().unwrap();
+        exporter.handle(&Method::Get, "/metrics?x=1");
+        assert_eq!(exporter.cache.as_ref().unwrap().0, cached_at);
+    }
+
+    #[test]
+    fn test_healthz_and_unknown_paths() {
+        let manager = MockManager::new(1);
+        let mut exporter = Exporter::new(&manager, Duration::ZERO);
+
+        assert_eq!(exporter.handle(&Method::Get, "/healthz").status, 200);
+        assert_eq!(exporter.handle(&Method::Get, "/nope").status, 404);
+        assert_eq!(exporter.handle(&Method::Post, "/metrics").status, 405);
+    }
+}
@@ -11,6 +11,7 @@ use crate::domain::{
     memory::EccErrors, pcie::PcieMetrics, performance::Utilization, thermal::Temperature,
     PowerLimit, ThermalThresholds,
 };
+use crate::nvml::GpuSnapshot;
 use serde::{Deserialize, Serialize};
 
 /// GPU health score (0-100)
@@ -174,6 +175,28 @@ impl HealthCalculator {
         }
     }
 
+    /// Calculate health from a device snapshot
+    ///
+    /// Returns None if temperature, thermal thresholds or power readings are missing.
+    pub fn calculate_snapshot(&self, snapshot: &GpuSnapshot) -> Option<HealthBreakdown> {
+        let thresholds = snapshot.thermal_thresholds.as_ref().ok()?;
+        let params = HealthParams {
+            temperature: *snapshot.temperature.as_ref().ok()?,
+            thresholds,
+            power_usage: *snapshot.power_usage.as_ref().ok()?,
+            power_limit: *snapshot.power_limit.as_ref().ok()?,
+            is_thermal_throttling: snapshot.is_thermal_throttling(),
+            is_power_throttling: snapshot.is_power_throttling(),
+            ecc_errors: snapshot.ecc_errors.as_ref().ok().and_then(|e| e.as_ref()),
+            vram_usage_ratio: snapshot.vram_usage_ratio(),
+            utilization: snapshot.utilization.as_ref().ok(),
+            pcie_metrics: snapshot.pcie_metrics.as_ref().ok(),
+            // Uptime is not tracked; estimate 1 hour for ECC error rates
+            uptime_seconds: 3600,
+        };
+        Some(self.calculate(&params))
+    }
+
     /// Calculate overall health score
     pub fn calculate(&self, params: &HealthParams) -> HealthBreakdown {
         let thermal_score = self.calculate_thermal_health(params);
@@ -619,4 +642,16 @@ mod tests {
             .iter()
             .any(|i| matches!(i.severity, IssueSeverity::Critical)));
     }
+
+    #[test]
+    fn test_calculate_snapshot() {
+        use crate::mock::MockDevice;
+
+        let device = MockDevice::new(0);
+        let snapshot = GpuSnapshot::capture(&device);
+        let breakdown = HealthCalculator::default()
+            .calculate_snapshot(&snapshot)
+            .unwrap();
+        assert!(breakdown.overall.score() >= 75);
+    }
 }
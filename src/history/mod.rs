@@ -0,0 +1,486 @@
+//! On-disk metrics history
+//!
+//! The control loop appends every GPU's readings to segment files per GPU and
+//! tier: raw samples for a day, 1-minute aggregates for a month and 1-hour
+//! aggregates for a year by default. Queries read the finest tier whose
+//! retention still covers the requested range.
+
+mod segment;
+
+pub use segment::{Aggregate, Record, SegmentKind};
+
+use crate::config::HistoryConfig;
+use crate::recorder::RecordField;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often expired segments are removed
+const PRUNE_INTERVAL_SECS: i64 = 3600;
+
+/// Storage resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Tier {
+    /// Every sample
+    Raw,
+    /// 1-minute aggregates
+    Minute,
+    /// 1-hour aggregates
+    Hour,
+}
+
+impl Tier {
+    /// Every tier, finest first
+    pub const ALL: [Tier; 3] = [Self::Raw, Self::Minute, Self::Hour];
+
+    /// Directory name of the tier
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Raw => "raw",
+            Self::Minute => "1m",
+            Self::Hour => "1h",
+        }
+    }
+
+    /// Aggregation period in seconds, `None` for raw samples
+    pub fn resolution(&self) -> Option<i64> {
+        match self {
+            Self::Raw => None,
+            Self::Minute => Some(60),
+            Self::Hour => Some(3600),
+        }
+    }
+
+    /// Time covered by one segment file
+    fn segment_secs(&self) -> i64 {
+        match self {
+            Self::Raw => 3600,
+            Self::Minute => 86_400,
+            Self::Hour => 30 * 86_400,
+        }
+    }
+
+    fn kind(&self) -> SegmentKind {
+        match self {
+            Self::Raw => SegmentKind::Raw,
+            Self::Minute | Self::Hour => SegmentKind::Aggregated,
+        }
+    }
+
+    fn position(&self) -> usize {
+        Self::ALL.iter().position(|t| t == self).unwrap_or_default()
+    }
+}
+
+/// A GPU with recorded history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryGpu {
+    pub uuid: String,
+    /// Index when last recorded
+    pub index: u32,
+    pub name: String,
+}
+
+/// One point of a queried series
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HistoryPoint {
+    /// Unix time in seconds
+    pub timestamp: i64,
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Read access to a history directory
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    dir: PathBuf,
+    retention: [Duration; 3],
+}
+
+impl HistoryStore {
+    /// Open a history directory with the default retention
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self::from_config(&HistoryConfig {
+            path: Some(dir.into()),
+            ..HistoryConfig::default()
+        })
+    }
+
+    /// Open the history described by the `[history]` configuration
+    pub fn from_config(config: &HistoryConfig) -> Self {
+        let hours = |h: u64| Duration::from_secs(h * 3600);
+        Self {
+            dir: config.path(),
+            retention: [
+                hours(config.raw_retention_hours),
+                hours(config.minute_retention_days * 24),
+                hours(config.hour_retention_days * 24),
+            ],
+        }
+    }
+
+    /// Get the history directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// How long a tier is kept
+    pub fn retention(&self, tier: Tier) -> Duration {
+        self.retention[tier.position()]
+    }
+
+    /// GPUs with recorded history; empty if nothing was recorded yet
+    pub fn gpus(&self) -> io::Result<Vec<HistoryGpu>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut gpus = Vec::new();
+        for entry in entries {
+            let info = entry?.path().join("gpu.json");
+            if let Ok(content) = fs::read_to_string(&info) {
+                match serde_json::from_str(&content) {
+                    Ok(gpu) => gpus.push(gpu),
+                    Err(e) => log::warn!("Ignoring {}: {}", info.display(), e),
+                }
+            }
+        }
+        gpus.sort_by_key(|gpu: &HistoryGpu| gpu.index);
+        Ok(gpus)
+    }
+
+    /// Finest tier that still holds data from `since`
+    pub fn tier_for(&self, since: i64, now: i64) -> Tier {
+        let age = Duration::from_secs(now.saturating_sub(since).max(0) as u64);
+        Tier::ALL
+            .into_iter()
+            .find(|&tier| self.retention(tier) >= age)
+            .unwrap_or(Tier::Hour)
+    }
+
+    /// Read a field of a GPU between two Unix times from one tier
+    pub fn query(
+        &self,
+        uuid: &str,
+        tier: Tier,
+        field: RecordField,
+        since: i64,
+        until: i64,
+    ) -> io::Result<Vec<HistoryPoint>> {
+        let mut points = Vec::new();
+        for (start, path) in self.segments(uuid, tier)? {
+            if start + tier.segment_secs() <= since || start > until {
+                continue;
+            }
+            for record in segment::read(&path)? {
+                if record.timestamp < since || record.timestamp > until {
+                    continue;
+                }
+                if let Some(Some(agg)) = record.values.get(field.position()) {
+                    // Values are stored as f32; drop the noise of widening them
+                    let round = |v: f64| (v * 1000.0).round() / 1000.0;
+                    points.push(HistoryPoint {
+                        timestamp: record.timestamp,
+                        avg: round(agg.avg),
+                        min: round(agg.min),
+                        max: round(agg.max),
+                    });
+                }
+            }
+        }
+        Ok(points)
+    }
+
+    /// Remove segments older than their tier's retention
+    pub fn prune(&self, now: i64) -> io::Result<usize> {
+        let mut removed = 0;
+        for gpu in self.gpus()? {
+            for tier in Tier::ALL {
+                let cutoff = now - self.retention(tier).as_secs() as i64;
+                for (start, path) in self.segments(&gpu.uuid, tier)? {
+                    if start + tier.segment_secs() <= cutoff {
+                        fs::remove_file(&path)?;
+                        removed += 1;
+                    }
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    fn gpu_dir(&self, uuid: &str) -> PathBuf {
+        self.dir.join(uuid)
+    }
+
+    fn segment_path(&self, uuid: &str, tier: Tier, timestamp: i64) -> PathBuf {
+        let start = timestamp - timestamp.rem_euclid(tier.segment_secs());
+        self.gpu_dir(uuid)
+            .join(tier.name())
+            .join(format!("{}.seg", start))
+    }
+
+    /// Segment files of a tier, oldest first
+    fn segments(&self, uuid: &str, tier: Tier) -> io::Result<Vec<(i64, PathBuf)>> {
+        let entries = match fs::read_dir(self.gpu_dir(uuid).join(tier.name())) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut segments: Vec<(i64, PathBuf)> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let start = path.file_stem()?.to_str()?.parse().ok()?;
+                (path.extension()? == "seg").then_some((start, path))
+            })
+            .collect();
+        segments.sort();
+        Ok(segments)
+    }
+}
+
+/// Running aggregate of one field
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    sum: f64,
+    count: u32,
+    min: f64,
+    max: f64,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        }
+        self.sum += value;
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn aggregate(&self) -> Option<Aggregate> {
+        (self.count > 0).then(|| Aggregate {
+            avg: self.sum / f64::from(self.count),
+            min: self.min,
+            max: self.max,
+        })
+    }
+}
+
+/// Samples of one aggregation period
+#[derive(Debug, Clone)]
+struct Bucket {
+    start: i64,
+    fields: Vec<Accumulator>,
+}
+
+/// Appends samples to a history and maintains the aggregate tiers
+///
+/// The aggregate of the current minute and hour is written once the period
+/// is over, so it is lost if the process stops before then.
+pub struct HistoryWriter {
+    store: HistoryStore,
+    buckets: HashMap<(String, Tier), Bucket>,
+    known: HashMap<String, HistoryGpu>,
+    last_prune: Option<i64>,
+}
+
+impl HistoryWriter {
+    /// Create a writer for a history directory
+    pub fn new(store: HistoryStore) -> Self {
+        Self {
+            store,
+            buckets: HashMap::new(),
+            known: HashMap::new(),
+            last_prune: None,
+        }
+    }
+
+    /// Get the underlying store
+    pub fn store(&self) -> &HistoryStore {
+        &self.store
+    }
+
+    /// Record one sample of a GPU
+    ///
+    /// `values` follows [`RecordField::ALL`].
+    pub fn record(
+        &mut self,
+        gpu: &HistoryGpu,
+        timestamp: i64,
+        values: &[Option<f64>],
+    ) -> io::Result<()> {
+        if self.known.get(&gpu.uuid) != Some(gpu) {
+            let dir = self.store.gpu_dir(&gpu.uuid);
+            fs::create_dir_all(&dir)?;
+            fs::write(dir.join("gpu.json"), serde_json::to_string(gpu)?)?;
+            self.known.insert(gpu.uuid.clone(), gpu.clone());
+        }
+
+        let raw = Record {
+            timestamp,
+            values: values.iter().map(|v| v.map(Aggregate::single)).collect(),
+        };
+        segment::append(
+            &self.store.segment_path(&gpu.uuid, Tier::Raw, timestamp),
+            SegmentKind::Raw,
+            &[raw],
+        )?;
+
+        for tier in [Tier::Minute, Tier::Hour] {
+            let resolution = tier.resolution().unwrap_or(1);
+            let start = timestamp - timestamp.rem_euclid(resolution);
+            let key = (gpu.uuid.clone(), tier);
+
+            let finished = match self.buckets.get(&key) {
+                Some(bucket) if bucket.start != start => self.buckets.remove(&key),
+                _ => None,
+            };
+            if let Some(bucket) = finished {
+                let record = Record {
+                    timestamp: bucket.start,
+                    values: bucket.fields.iter().map(Accumulator::aggregate).collect(),
+                };
+                segment::append(
+                    &self.store.segment_path(&gpu.uuid, tier, bucket.start),
+                    tier.kind(),
+                    &[record],
+                )?;
+            }
+
+            let bucket = self.buckets.entry(key).or_insert_with(|| Bucket {
+                start,
+                fields: vec![
+                    Accumulator {
+                        sum: 0.0,
+                        count: 0,
+                        min: 0.0,
+                        max: 0.0,
+                    };
+                    values.len()
+                ],
+            });
+            for (acc, value) in bucket.fields.iter_mut().zip(values) {
+                if let Some(value) = value {
+                    acc.add(*value);
+                }
+            }
+        }
+
+        if self
+            .last_prune
+            .is_none_or(|last| timestamp - last >= PRUNE_INTERVAL_SECS)
+        {
+            self.last_prune = Some(timestamp);
+            let removed = self.store.prune(timestamp)?;
+            if removed > 0 {
+                log::debug!("Removed {} expired history segments", removed);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Merge neighbouring points so that at most `max_points` remain
+pub fn downsample(points: Vec<HistoryPoint>, max_points: usize) -> Vec<HistoryPoint> {
+    if max_points == 0 || points.len() <= max_points {
+        return points;
+    }
+    let chunk = points.len().div_ceil(max_points);
+    points
+        .chunks(chunk)
+        .map(|chunk| HistoryPoint {
+            timestamp: chunk[0].timestamp,
+            avg: chunk.iter().map(|p| p.avg).sum::<f64>() / chunk.len() as f64,
+            min: chunk.iter().map(|p| p.min).fold(f64::INFINITY, f64::min),
+            max: chunk
+                .iter()
+                .map(|p| p.max)
+                .fold(f64::NEG_INFINITY, f64::max),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temps(temp: f64) -> Vec<Option<f64>> {
+        RecordField::ALL
+            .iter()
+            .map(|&f| (f == RecordField::Temp).then_some(temp))
+            .collect()
+    }
+
+    #[test]
+    fn test_writer_fills_tiers_and_query_picks_tier() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = HistoryStore::new(dir.path());
+        let mut writer = HistoryWriter::new(store.clone());
+        let gpu = HistoryGpu {
+            uuid: "GPU-A".to_string(),
+            index: 0,
+            name: "Test GPU".to_string(),
+        };
+
+        // Two minutes of samples every 10s, starting on a minute boundary
+        let t0 = 1_700_000_040;
+        for i in 0..13 {
+            writer
+                .record(&gpu, t0 + i * 10, &temps(50.0 + i as f64))
+                .unwrap();
+        }
+
+        assert_eq!(store.gpus().unwrap(), vec![gpu]);
+
+        let raw = store
+            .query("GPU-A", Tier::Raw, RecordField::Temp, t0, t0 + 120)
+            .unwrap();
+        assert_eq!(raw.len(), 13);
+
+        let minutes = store
+            .query("GPU-A", Tier::Minute, RecordField::Temp, t0, t0 + 120)
+            .unwrap();
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(minutes[0].timestamp, t0);
+        assert_eq!(minutes[0].min, 50.0);
+        assert_eq!(minutes[0].max, 55.0);
+        assert_eq!(minutes[0].avg, 52.5);
+
+        // Power was never recorded
+        assert!(store
+            .query("GPU-A", Tier::Raw, RecordField::Power, t0, t0 + 120)
+            .unwrap()
+            .is_empty());
+
+        let now = t0 + 200;
+        assert_eq!(store.tier_for(now - 3600, now), Tier::Raw);
+        assert_eq!(store.tier_for(now - 7 * 86_400, now), Tier::Minute);
+        assert_eq!(store.tier_for(now - 90 * 86_400, now), Tier::Hour);
+    }
+
+    #[test]
+    fn test_downsample() {
+        let points: Vec<HistoryPoint> = (0..10)
+            .map(|i| HistoryPoint {
+                timestamp: i,
+                avg: i as f64,
+                min: i as f64,
+                max: i as f64,
+            })
+            .collect();
+        let merged = downsample(points, 5);
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged[1].timestamp, 2);
+        assert_eq!(merged[1].avg, 2.5);
+        assert_eq!(merged[1].max, 3.0);
+    }
+}
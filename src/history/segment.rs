@@ -0,0 +1,229 @@
+//! Segment file encoding
+//!
+//! A segment is an 8-byte header followed by fixed-size little-endian
+//! records. Raw records hold one `f32` per field, aggregated records hold
+//! average, minimum and maximum. Missing values are stored as NaN. A record
+//! cut short by a crash is ignored when reading.
+//!
+//! Appends keep the field count of an existing segment, so segments written
+//! before a field was added stay readable.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"NVH1";
+const HEADER_LEN: usize = 8;
+
+/// Layout of the records in a segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SegmentKind {
+    /// One value per field
+    Raw,
+    /// Average, minimum and maximum per field
+    Aggregated,
+}
+
+impl SegmentKind {
+    fn code(self) -> u8 {
+        match self {
+            Self::Raw => 0,
+            Self::Aggregated => 1,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(Self::Raw),
+            1 => Some(Self::Aggregated),
+            _ => None,
+        }
+    }
+
+    fn values_per_field(self) -> usize {
+        match self {
+            Self::Raw => 1,
+            Self::Aggregated => 3,
+        }
+    }
+
+    fn record_len(self, fields: usize) -> usize {
+        8 + fields * self.values_per_field() * 4
+    }
+}
+
+/// Average, minimum and maximum of one field over a record's period
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aggregate {
+    pub avg: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Aggregate {
+    /// A single value
+    pub fn single(value: f64) -> Self {
+        Self {
+            avg: value,
+            min: value,
+            max: value,
+        }
+    }
+}
+
+/// One stored record
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    /// Unix time in seconds (start of the period for aggregated records)
+    pub timestamp: i64,
+    /// Per-field values in [`crate::recorder::RecordField::ALL`] order
+    pub values: Vec<Option<Aggregate>>,
+}
+
+/// Append records to a segment, writing the header if the file is new
+pub fn append(path: &Path, kind: SegmentKind, records: &[Record]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)?;
+
+    let mut buf = Vec::new();
+    let fields = if file.metadata()?.len() == 0 {
+        let fields = records.first().map_or(0, |r| r.values.len());
+        buf.extend_from_slice(MAGIC);
+        buf.push(kind.code());
+        buf.push(fields as u8);
+        buf.extend_from_slice(&[0, 0]);
+        fields
+    } else {
+        let mut header = [0; HEADER_LEN];
+        file.read_exact(&mut header)?;
+        header[5] as usize
+    };
+    for record in records {
+        buf.extend_from_slice(&record.timestamp.to_le_bytes());
+        for pos in 0..fields {
+            let value = record.values.get(pos).copied().flatten();
+            let agg = value.unwrap_or(Aggregate::single(f64::NAN));
+            match kind {
+                SegmentKind::Raw => buf.extend_from_slice(&(agg.avg as f32).to_le_bytes()),
+                SegmentKind::Aggregated => {
+                    for v in [agg.avg, agg.min, agg.max] {
+                        buf.extend_from_slice(&(v as f32).to_le_bytes());
+                    }
+                }
+            }
+        }
+    }
+    // One write per call keeps concurrent readers from seeing half a batch
+    file.write_all(&buf)
+}
+
+/// Read every complete record of a segment
+pub fn read(path: &Path) -> io::Result<Vec<Record>> {
+    let data = fs::read(path)?;
+    if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+        return Err(invalid(path, "not a history segment"));
+    }
+    let kind = SegmentKind::from_code(data[4]).ok_or_else(|| invalid(path, "unknown kind"))?;
+    let fields = data[5] as usize;
+    let record_len = kind.record_len(fields);
+
+    let f32_at = |chunk: &[u8], pos: usize| {
+        let bytes: [u8; 4] = chunk[pos..pos + 4].try_into().unwrap_or_default();
+        f64::from(f32::from_le_bytes(bytes))
+    };
+
+    Ok(data[HEADER_LEN..]
+        .chunks_exact(record_len)
+        .map(|chunk| {
+            let timestamp = i64::from_le_bytes(chunk[..8].try_into().unwrap_or_default());
+            let values = (0..fields)
+                .map(|field| {
+                    let pos = 8 + field * kind.values_per_field() * 4;
+                    let agg = match kind {
+                        SegmentKind::Raw => Aggregate::single(f32_at(chunk, pos)),
+                        SegmentKind::Aggregated => Aggregate {
+                            avg: f32_at(chunk, pos),
+                            min: f32_at(chunk, pos + 4),
+                            max: f32_at(chunk, pos + 8),
+                        },
+                    };
+                    (!agg.avg.is_nan()).then_some(agg)
+                })
+                .collect();
+            Record { timestamp, values }
+        })
+        .collect())
+}
+
+fn invalid(path: &Path, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {}", path.display(), message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_ignores_truncated_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seg");
+        let record = Record {
+            timestamp: 1_700_000_000,
+            values: vec![
+                Some(Aggregate {
+                    avg: 60.0,
+                    min: 50.0,
+                    max: 70.0,
+                }),
+                None,
+            ],
+        };
+        append(
+            &path,
+            SegmentKind::Aggregated,
+            std::slice::from_ref(&record),
+        )
+        .unwrap();
+        append(
+            &path,
+            SegmentKind::Aggregated,
+            std::slice::from_ref(&record),
+        )
+        .unwrap();
+
+        // Simulate a crash in the middle of a write
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[1, 2, 3]).unwrap();
+
+        assert_eq!(read(&path).unwrap(), vec![record.clone(), record]);
+    }
+
+    #[test]
+    fn test_append_keeps_field_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seg");
+        let old = Record {
+            timestamp: 1,
+            values: vec![Some(Aggregate::single(60.0))],
+        };
+        let new = Record {
+            timestamp: 2,
+            values: vec![Some(Aggregate::single(61.0)), Some(Aggregate::single(1.0))],
+        };
+        append(&path, SegmentKind::Raw, std::slice::from_ref(&old)).unwrap();
+        append(&path, SegmentKind::Raw, std::slice::from_ref(&new)).unwrap();
+
+        let records = read(&path).unwrap();
+        assert_eq!(records[0], old);
+        assert_eq!(records[1].values, vec![Some(Aggregate::single(61.0))]);
+    }
+}
@@ -6,24 +6,48 @@
 //! # Modules
 //!
 //! - [`alerts`]: Alerting and notification system
+//! - [`api`]: Authenticated REST API (`nvctl api`)
 //! - [`cli`]: Command-line interface definitions
 //! - [`commands`]: Command handlers
 //! - [`config`]: Configuration system
 //! - [`domain`]: Domain models with validation
 //! - [`error`]: Error types
+//! - [`events`]: NVML event subscription (Xid, ECC, clock and power events)
+//! - [`exporter`]: Prometheus metrics endpoint (`nvctl exporter`)
 //! - [`health`]: GPU health scoring and monitoring
+//! - [`history`]: On-disk metrics history with downsampling tiers
+//! - [`metrics`]: Flat metric samples shared by exporters
+//! - [`mqtt`]: MQTT topics and Home Assistant discovery payloads
 //! - [`nvml`]: NVML abstraction layer
+//! - [`procfs`]: Process metadata from `/proc`
+//! - [`recorder`]: Telemetry recording to CSV/NDJSON (`nvctl record`)
+//! - [`replay`]: Replay backend driven by recorded telemetry (`--replay`)
 //! - [`services`]: Business logic services
+//! - [`sinks`]: Push targets for metrics (InfluxDB, StatsD)
+//! - [`stats`]: Session statistics (`nvctl stats`)
+//! - [`tui`]: Terminal dashboard (`nvctl top`)
 
 pub mod alerts;
+pub mod api;
 pub mod cli;
 pub mod commands;
 pub mod config;
 pub mod domain;
 pub mod error;
+pub mod events;
+pub mod exporter;
 pub mod health;
+pub mod history;
+pub mod metrics;
+pub mod mqtt;
 pub mod nvml;
+pub mod procfs;
+pub mod recorder;
+pub mod replay;
 pub mod services;
+pub mod sinks;
+pub mod stats;
+pub mod tui;
 
 /// Mock implementations for testing
 /// Available when the "mock" feature is enabled or during tests
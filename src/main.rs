@@ -3,11 +3,14 @@
 //! A command-line tool for controlling NVIDIA GPU fan speeds, power limits,
 //! and thermal settings.
 
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 use nvctl::cli::args::{generate_completions, Cli, Commands};
+use nvctl::cli::watch::{run_watch, WatchOptions};
 use nvctl::commands::{
-    run_alerts, run_control, run_fan, run_health, run_info, run_list, run_power, run_processes,
-    run_thermal,
+    run_alerts, run_api, run_config, run_control, run_events, run_exporter, run_fan, run_health,
+    run_history, run_info, run_list, run_power, run_processes, run_push, run_record, run_schedule,
+    run_stats, run_thermal, run_top,
 };
 use nvctl::error::AppError;
 
@@ -25,8 +28,26 @@ fn main() {
         log::set_max_level(log::LevelFilter::Debug);
     }
 
-    // Run the appropriate command
-    let result = run(&cli);
+    if cli.replay.is_some() {
+        check_replay(&cli);
+    }
+    #[cfg(feature = "mock")]
+    if let Some(count) = cli.mock {
+        nvctl::nvml::set_mock(count);
+    }
+
+    // Run the appropriate command, repeatedly in watch mode
+    let result = match cli.watch {
+        Some(secs) => {
+            let interval = watch_interval(&cli, secs);
+            let options = WatchOptions {
+                interval,
+                count: cli.count,
+            };
+            run_watch(options, cli.format, cli.command.name(), || run(&cli))
+        }
+        None => run(&cli),
+    };
 
     if let Err(e) = result {
         log::error!("{}", e);
@@ -35,11 +56,73 @@ fn main() {
     }
 }
 
+/// Validate `--watch` against the command, exiting with a usage error if invalid
+fn watch_interval(cli: &Cli, secs: f64) -> std::time::Duration {
+    let mut command = Cli::command();
+    if !cli.command.supports_watch() {
+        command
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "--watch is only supported by read commands \
+                     (info, health, history, power status, fan status, processes), not '{}'",
+                    cli.command.name()
+                ),
+            )
+            .exit();
+    }
+    match std::time::Duration::try_from_secs_f64(secs) {
+        Ok(interval) if !interval.is_zero() => interval,
+        _ => command
+            .error(
+                ErrorKind::InvalidValue,
+                "--watch interval must be a positive number of seconds",
+            )
+            .exit(),
+    }
+}
+
+/// Validate `--replay` against the command, exiting with a usage error if invalid
+fn check_replay(cli: &Cli) {
+    let mut command = Cli::command();
+    if !cli.command.supports_replay() {
+        command
+            .error(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "--replay is only supported by info, health, control --dry-run, \
+                     alerts start, top and exporter, not '{}'",
+                    cli.command.name()
+                ),
+            )
+            .exit();
+    }
+    if matches!(cli.command, Commands::Control(_)) && !cli.dry_run {
+        command
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "control --replay requires --dry-run (recorded GPUs cannot be changed)",
+            )
+            .exit();
+    }
+    if !(cli.replay_speed.is_finite() && cli.replay_speed > 0.0) {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "--replay-speed must be a positive number",
+            )
+            .exit();
+    }
+}
+
 fn run(cli: &Cli) -> Result<(), AppError> {
+    let replay = cli.replay_options();
+    let replay = replay.as_ref();
+
     match &cli.command {
         Commands::List => run_list(cli.format),
 
-        Commands::Info(args) => run_info(args, cli.format, cli.gpu),
+        Commands::Info(args) => run_info(args, cli.format, cli.gpu, replay),
 
         Commands::Fan(args) => run_fan(args, cli.format, cli.gpu, cli.dry_run),
 
@@ -47,13 +130,47 @@ fn run(cli: &Cli) -> Result<(), AppError> {
 
         Commands::Thermal(args) => run_thermal(args, cli.format, cli.gpu, cli.dry_run),
 
-        Commands::Control(args) => run_control(args, cli.format, cli.gpu, cli.dry_run, cli.verbose),
+        Commands::Control(args) => run_control(
+            args,
+            cli.format,
+            cli.gpu,
+            cli.config.as_deref(),
+            cli.dry_run,
+            cli.verbose,
+            replay,
+        ),
+
+        Commands::Alerts(args) => run_alerts(&args.command, cli.format, replay),
+
+        Commands::Health => run_health(cli.format, cli.gpu, replay),
+
+        Commands::Processes(args) => run_processes(
+            args,
+            cli.format,
+            cli.gpu,
+            cli.config.as_deref(),
+            cli.dry_run,
+        ),
+
+        Commands::Config(args) => run_config(args, cli.format, cli.config.as_deref()),
+
+        Commands::Events(args) => run_events(args, cli.format, cli.gpu),
+
+        Commands::Schedule(args) => run_schedule(args, cli.format, cli.config.as_deref()),
+
+        Commands::Top(args) => run_top(args, cli.gpu, cli.dry_run, replay),
+
+        Commands::Exporter(args) => run_exporter(args, cli.config.as_deref(), replay),
+
+        Commands::Push(args) => run_push(args, cli.gpu, cli.config.as_deref()),
+
+        Commands::Record(args) => run_record(args, cli.format, cli.gpu),
 
-        Commands::Alerts(args) => run_alerts(&args.command, cli.format),
+        Commands::Stats(args) => run_stats(args, cli.format, cli.gpu),
 
-        Commands::Health => run_health(cli.format, cli.gpu),
+        Commands::History(args) => run_history(args, cli.format, cli.gpu, cli.config.as_deref()),
 
-        Commands::Processes(args) => run_processes(args, cli.format, cli.gpu),
+        Commands::Api(args) => run_api(args, cli.config.as_deref(), cli.dry_run),
 
         Commands::Completions { shell } => {
             generate_completions(*shell);
@@ -0,0 +1,384 @@
+//! Flat metric samples for exporters
+//!
+//! Converts snapshots, throttle reasons and health scores into labeled
+//! samples that the Prometheus exporter and push targets encode.
+
+use crate::domain::ThrottleReasons;
+use crate::health::HealthBreakdown;
+use crate::nvml::GpuSnapshot;
+
+use std::fmt::Write;
+
+/// Prometheus metric type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    /// Value that can go up and down
+    Gauge,
+    /// Monotonically increasing value
+    Counter,
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gauge => "gauge",
+            Self::Counter => "counter",
+        }
+    }
+}
+
+/// Metric name, help text and type
+#[derive(Debug, PartialEq, Eq)]
+pub struct Metric {
+    /// Metric name
+    pub name: &'static str,
+    /// Help text
+    pub help: &'static str,
+    /// Metric type
+    pub kind: MetricKind,
+}
+
+macro_rules! metrics {
+    ($($ident:ident: $kind:ident $name:literal $help:literal;)*) => {
+        $(
+            #[doc = $help]
+            pub static $ident: Metric = Metric {
+                name: $name,
+                help: $help,
+                kind: MetricKind::$kind,
+            };
+        )*
+    };
+}
+
+metrics! {
+    UP: Gauge "nvctl_up" "Whether NVML could be queried";
+    GPU_UP: Gauge "nvctl_gpu_up" "Whether the GPU could be read";
+    TEMPERATURE: Gauge "nvctl_gpu_temperature_celsius" "Core temperature";
+    MEMORY_TEMPERATURE: Gauge "nvctl_gpu_memory_temperature_celsius" "Memory temperature";
+    SLOWDOWN_TEMPERATURE: Gauge "nvctl_gpu_slowdown_temperature_celsius" "Temperature at which the GPU slows down";
+    SHUTDOWN_TEMPERATURE: Gauge "nvctl_gpu_shutdown_temperature_celsius" "Temperature at which the GPU shuts down";
+    FAN_SPEED: Gauge "nvctl_gpu_fan_speed_percent" "Fan speed";
+    POWER_USAGE: Gauge "nvctl_gpu_power_usage_watts" "Current power draw";
+    POWER_LIMIT: Gauge "nvctl_gpu_power_limit_watts" "Current power limit";
+    POWER_LIMIT_MIN: Gauge "nvctl_gpu_power_limit_min_watts" "Minimum allowed power limit";
+    POWER_LIMIT_MAX: Gauge "nvctl_gpu_power_limit_max_watts" "Maximum allowed power limit";
+    CLOCK: Gauge "nvctl_gpu_clock_mhz" "Current clock speed";
+    UTILIZATION: Gauge "nvctl_gpu_utilization_percent" "Engine utilization";
+    MEMORY_USED: Gauge "nvctl_gpu_memory_used_bytes" "Used VRAM";
+    MEMORY_FREE: Gauge "nvctl_gpu_memory_free_bytes" "Free VRAM";
+    MEMORY_TOTAL: Gauge "nvctl_gpu_memory_total_bytes" "Total VRAM";
+    PERFORMANCE_STATE: Gauge "nvctl_gpu_performance_state" "Performance state (0 = P0, maximum performance)";
+    ECC_ERRORS: Counter "nvctl_gpu_ecc_errors_total" "ECC memory errors";
+    PCIE_LINK_GENERATION: Gauge "nvctl_gpu_pcie_link_generation" "Current PCIe link generation";
+    PCIE_LINK_WIDTH: Gauge "nvctl_gpu_pcie_link_width" "Current PCIe link width in lanes";
+    PCIE_TX: Gauge "nvctl_gpu_pcie_tx_bytes_per_second" "PCIe transmit throughput";
+    PCIE_RX: Gauge "nvctl_gpu_pcie_rx_bytes_per_second" "PCIe receive throughput";
+    PCIE_REPLAYS: Counter "nvctl_gpu_pcie_replays_total" "PCIe replay counter";
+    THROTTLE_REASON: Gauge "nvctl_gpu_throttle_reason" "Whether a clock throttle reason is active";
+    HEALTH_SCORE: Gauge "nvctl_gpu_health_score" "Health score (0-100)";
+    ALERT_FIRING: Gauge "nvctl_alert_firing" "Whether an alert rule is firing for the GPU";
+}
+
+/// A single labeled value
+#[derive(Debug, Clone)]
+pub struct Sample {
+    /// Metric this sample belongs to
+    pub metric: &'static Metric,
+    /// Label pairs, GPU labels first
+    pub labels: Vec<(&'static str, String)>,
+    /// Sample value
+    pub value: f64,
+}
+
+impl Sample {
+    /// Create a sample without labels
+    pub fn new(metric: &'static Metric, value: f64) -> Self {
+        Self {
+            metric,
+            labels: Vec::new(),
+            value,
+        }
+    }
+
+    /// Add a label
+    pub fn with_label(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.labels.push((name, value.into()));
+        self
+    }
+
+    /// Get a label value
+    pub fn label(&self, name: &str) -> Option<&str> {
+        self.labels
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Labels identifying a GPU
+#[derive(Debug, Clone)]
+pub struct GpuLabels {
+    /// Device index
+    pub index: u32,
+    /// GPU UUID
+    pub uuid: String,
+    /// Product name
+    pub name: String,
+}
+
+impl GpuLabels {
+    /// Labels from a snapshot, with empty strings for unreadable fields
+    pub fn from_snapshot(snapshot: &GpuSnapshot) -> Self {
+        Self {
+            index: snapshot.index,
+            uuid: snapshot.uuid.clone().unwrap_or_default(),
+            name: snapshot.name.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Create a sample carrying these labels
+    pub fn sample(&self, metric: &'static Metric, value: f64) -> Sample {
+        Sample::new(metric, value)
+            .with_label("gpu", self.index.to_string())
+            .with_label("uuid", self.uuid.clone())
+            .with_label("name", self.name.clone())
+    }
+}
+
+/// Collect every readable metric of a GPU
+pub fn gpu_samples(
+    snapshot: &GpuSnapshot,
+    throttle_reasons: Option<&ThrottleReasons>,
+    health: Option<&HealthBreakdown>,
+) -> Vec<Sample> {
+    let gpu = GpuLabels::from_snapshot(snapshot);
+    let mut samples = vec![gpu.sample(&GPU_UP, 1.0)];
+    let mut push = |metric, value: f64| samples.push(gpu.sample(metric, value));
+
+    if let Ok(temp) = &snapshot.temperature {
+        push(&TEMPERATURE, temp.as_celsius() as f64);
+    }
+    if let Ok(Some(temp)) = &snapshot.memory_temperature {
+        push(&MEMORY_TEMPERATURE, temp.as_celsius() as f64);
+    }
+    if let Ok(thresholds) = &snapshot.thermal_thresholds {
+        if let Some(temp) = thresholds.slowdown {
+            push(&SLOWDOWN_TEMPERATURE, temp.as_celsius() as f64);
+        }
+        if let Some(temp) = thresholds.shutdown {
+            push(&SHUTDOWN_TEMPERATURE, temp.as_celsius() as f64);
+        }
+    }
+    let watts = |limit: &crate::domain::PowerLimit| limit.as_milliwatts() as f64 / 1000.0;
+    if let Ok(usage) = &snapshot.power_usage {
+        push(&POWER_USAGE, watts(usage));
+    }
+    if let Ok(limit) = &snapshot.power_limit {
+        push(&POWER_LIMIT, watts(limit));
+    }
+    if let Ok(constraints) = &snapshot.power_constraints {
+        push(&POWER_LIMIT_MIN, watts(&constraints.min));
+        push(&POWER_LIMIT_MAX, watts(&constraints.max));
+    }
+    if let Ok(info) = &snapshot.memory_info {
+        push(&MEMORY_USED, info.used as f64);
+        push(&MEMORY_FREE, info.free as f64);
+        push(&MEMORY_TOTAL, info.total as f64);
+    }
+    if let Ok(state) = &snapshot.performance_state {
+        push(&PERFORMANCE_STATE, state.as_raw() as f64);
+    }
+    if let Ok(pcie) = &snapshot.pcie_metrics {
+        push(
+            &PCIE_LINK_GENERATION,
+            pcie.link_status.current_generation.generation_number() as f64,
+        );
+        push(
+            &PCIE_LINK_WIDTH,
+            pcie.link_status.current_width.lanes() as f64,
+        );
+        push(&PCIE_TX, pcie.throughput.tx_bytes_per_sec() as f64);
+        push(&PCIE_RX, pcie.throughput.rx_bytes_per_sec() as f64);
+        push(&PCIE_REPLAYS, pcie.replay_counter.count() as f64);
+    }
+
+    for (fan, speed) in snapshot.fan_speeds.iter().enumerate() {
+        if let Ok(speed) = speed {
+            samples.push(
+                gpu.sample(&FAN_SPEED, speed.as_percentage() as f64)
+                    .with_label("fan", fan.to_string()),
+            );
+        }
+    }
+
+    let clocks = [
+        ("graphics", &snapshot.graphics_clock),
+        ("memory", &snapshot.memory_clock),
+    ];
+    for (clock, speed) in clocks {
+        if let Ok(speed) = speed {
+            samples.push(
+                gpu.sample(&CLOCK, speed.as_mhz() as f64)
+                    .with_label("clock", clock),
+            );
+        }
+    }
+
+    let mut engines = Vec::new();
+    if let Ok(util) = &snapshot.utilization {
+        engines.push(("gpu", util.gpu_percent()));
+        engines.push(("memory", util.memory_percent()));
+    }
+    if let Ok(Some(util)) = &snapshot.encoder_utilization {
+        engines.push(("encoder", util.percent()));
+    }
+    if let Ok(Some(util)) = &snapshot.decoder_utilization {
+        engines.push(("decoder", util.percent()));
+    }
+    for (engine, percent) in engines {
+        samples.push(
+            gpu.sample(&UTILIZATION, percent as f64)
+                .with_label("engine", engine),
+        );
+    }
+
+    if let Ok(Some(ecc)) = &snapshot.ecc_errors {
+        let counts = [
+            ("correctable", "volatile", ecc.correctable_current),
+            ("correctable", "aggregate", ecc.correctable_lifetime),
+            ("uncorrectable", "volatile", ecc.uncorrectable_current),
+            ("uncorrectable", "aggregate", ecc.uncorrectable_lifetime),
+        ];
+        for (kind, scope, count) in counts {
+            samples.push(
+                gpu.sample(&ECC_ERRORS, count as f64)
+                    .with_label("type", kind)
+                    .with_label("scope", scope),
+            );
+        }
+    }
+
+    if let Some(reasons) = throttle_reasons {
+        let active = [
+            ("idle", reasons.idle),
+            ("sw_power_cap", reasons.sw_power_cap),
+            ("hw_slowdown", reasons.hw_slowdown),
+            ("sync_boost", reasons.sync_boost),
+            ("sw_thermal", reasons.sw_thermal),
+            ("hw_thermal", reasons.hw_thermal),
+            ("hw_power_brake", reasons.hw_power_brake),
+            ("display_clocks", reasons.display_clocks),
+        ];
+        for (reason, active) in active {
+            samples.push(
+                gpu.sample(&THROTTLE_REASON, if active { 1.0 } else { 0.0 })
+                    .with_label("reason", reason),
+            );
+        }
+    }
+
+    if let Some(health) = health {
+        let scores = [
+            ("overall", health.overall),
+            ("thermal", health.thermal),
+            ("power", health.power),
+            ("memory", health.memory),
+            ("performance", health.performance),
+            ("pcie", health.pcie),
+        ];
+        for (category, score) in scores {
+            samples.push(
+                gpu.sample(&HEALTH_SCORE, score.score() as f64)
+                    .with_label("category", category),
+            );
+        }
+    }
+
+    samples
+}
+
+/// Encode samples in the Prometheus text exposition format
+///
+/// Samples of the same metric are grouped under one `# HELP`/`# TYPE` header
+/// in order of first appearance.
+pub fn encode_prometheus(samples: &[Sample]) -> String {
+    let mut order: Vec<&'static Metric> = Vec::new();
+    for sample in samples {
+        if !order.iter().any(|m| std::ptr::eq(*m, sample.metric)) {
+            order.push(sample.metric);
+        }
+    }
+
+    let mut output = String::new();
+    for metric in order {
+        let _ = writeln!(output, "# HELP {} {}", metric.name, metric.help);
+        let _ = writeln!(output, "# TYPE {} {}", metric.name, metric.kind.as_str());
+        for sample in samples.iter().filter(|s| std::ptr::eq(s.metric, metric)) {
+            output.push_str(metric.name);
+            if !sample.labels.is_empty() {
+                let labels: Vec<String> = sample
+                    .labels
+                    .iter()
+                    .map(|(name, value)| format!("{}=\"{}\"", name, escape_label(value)))
+                    .collect();
+                let _ = write!(output, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(output, " {}", sample.value);
+        }
+    }
+    output
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+
+    #[test]
+    fn test_gpu_samples_from_snapshot() {
+        let snapshot = GpuSnapshot::capture(&MockDevice::new(0));
+        let samples = gpu_samples(&snapshot, Some(&ThrottleReasons::default()), None);
+
+        let temp = samples
+            .iter()
+            .find(|s| std::ptr::eq(s.metric, &TEMPERATURE))
+            .unwrap();
+        assert_eq!(temp.label("gpu"), Some("0"));
+        assert!(temp.label("uuid").is_some_and(|uuid| !uuid.is_empty()));
+        assert!(
+            samples
+                .iter()
+                .any(|s| std::ptr::eq(s.metric, &THROTTLE_REASON)
+                    && s.label("reason") == Some("idle"))
+        );
+    }
+
+    #[test]
+    fn test_encode_prometheus_groups_and_escapes() {
+        let samples = vec![
+            Sample::new(&UP, 1.0),
+            Sample::new(&FAN_SPEED, 40.0).with_label("name", "GPU \"A\"\\"),
+            Sample::new(&FAN_SPEED, 55.5).with_label("name", "B"),
+        ];
+        let text = encode_prometheus(&samples);
+
+        assert_eq!(
+            text,
+            "# HELP nvctl_up Whether NVML could be queried\n\
+             # TYPE nvctl_up gauge\n\
+             nvctl_up 1\n\
+             # HELP nvctl_gpu_fan_speed_percent Fan speed\n\
+             # TYPE nvctl_gpu_fan_speed_percent gauge\n\
+             nvctl_gpu_fan_speed_percent{name=\"GPU \\\"A\\\"\\\\\"} 40\n\
+             nvctl_gpu_fan_speed_percent{name=\"B\"} 55.5\n"
+        );
+    }
+}
@@ -4,16 +4,19 @@
 
 use crate::domain::{
     AcousticLimits, ClockSpeed, ClockType, CoolerTarget, DecoderUtilization, EccErrors, EccMode,
-    EncoderUtilization, FanPolicy, FanSpeed, GpuInfo, MemoryInfo, PcieGeneration, PcieLinkStatus,
-    PcieLinkWidth, PcieMetrics, PcieReplayCounter, PcieThroughput, PerformanceState,
-    PowerConstraints, PowerLimit, ProcessList, Temperature, ThermalThresholds, ThrottleReasons,
-    Utilization,
+    EncoderUtilization, FanPolicy, FanSpeed, GpuInfo, GpuProcess, MemoryInfo, PcieGeneration,
+    PcieLinkStatus, PcieLinkWidth, PcieMetrics, PcieReplayCounter, PcieThroughput,
+    PerformanceState, PowerConstraints, PowerLimit, ProcessAccounting, ProcessList,
+    ProcessUtilization, Temperature, ThermalThresholds, ThrottleReasons, Utilization,
 };
 use crate::error::NvmlError;
+use crate::events::{EventSource, GpuEvent};
 use crate::nvml::{GpuDevice, GpuManager};
 
 use std::collections::HashMap;
-use std::sync::{Mutex, RwLock};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
 /// Mock GPU device for testing
 #[derive(Debug)]
@@ -21,15 +24,20 @@ pub struct MockDevice {
     index: u32,
     name: String,
     uuid: String,
+    pci_bus_id: Option<String>,
     temperature: RwLock<Temperature>,
     fan_speeds: Mutex<HashMap<u32, FanSpeed>>,
     fan_policies: Mutex<HashMap<u32, FanPolicy>>,
     fan_count: u32,
-    power_limit: Mutex<PowerLimit>,
+    power_limit: Arc<Mutex<PowerLimit>>,
     power_constraints: PowerConstraints,
     power_usage: PowerLimit,
     thermal_thresholds: ThermalThresholds,
-    acoustic_limits: RwLock<AcousticLimits>,
+    acoustic_limits: Arc<RwLock<AcousticLimits>>,
+    processes: Vec<GpuProcess>,
+    process_utilization: HashMap<u32, ProcessUtilization>,
+    accounting_enabled: Mutex<bool>,
+    accounting: Vec<ProcessAccounting>,
 }
 
 impl MockDevice {
@@ -47,11 +55,12 @@ impl MockDevice {
             index,
             name: format!("Mock GPU {}", index),
             uuid: format!("GPU-MOCK-{:04}", index),
+            pci_bus_id: Some(format!("00000000:{:02X}:00.0", index + 1)),
             temperature: RwLock::new(Temperature::new(45)),
             fan_speeds: Mutex::new(fan_speeds),
             fan_policies: Mutex::new(fan_policies),
             fan_count: 2,
-            power_limit: Mutex::new(PowerLimit::from_watts(300)),
+            power_limit: Arc::new(Mutex::new(PowerLimit::from_watts(300))),
             power_constraints: PowerConstraints::new(
                 PowerLimit::from_watts(100),
                 PowerLimit::from_watts(400),
@@ -63,11 +72,15 @@ impl MockDevice {
                 Some(Temperature::new(95)),
                 Some(Temperature::new(83)),
             ),
-            acoustic_limits: RwLock::new(AcousticLimits::new(
+            acoustic_limits: Arc::new(RwLock::new(AcousticLimits::new(
                 Some(Temperature::new(80)),
                 Some(Temperature::new(60)),
                 Some(Temperature::new(90)),
-            )),
+            ))),
+            processes: Vec::new(),
+            process_utilization: HashMap::new(),
+            accounting_enabled: Mutex::new(false),
+            accounting: Vec::new(),
         }
     }
 
@@ -88,12 +101,40 @@ impl MockDevice {
         self
     }
 
+    /// Builder: set PCI bus id
+    pub fn with_pci_bus_id(mut self, bus_id: impl Into<String>) -> Self {
+        self.pci_bus_id = Some(bus_id.into());
+        self
+    }
+
     /// Builder: set fan count
     pub fn with_fan_count(mut self, count: u32) -> Self {
         self.fan_count = count;
         self
     }
 
+    /// Builder: set running processes
+    pub fn with_processes(mut self, processes: Vec<GpuProcess>) -> Self {
+        self.processes = processes;
+        self
+    }
+
+    /// Builder: set per-process utilization samples
+    pub fn with_process_utilization(
+        mut self,
+        utilization: HashMap<u32, ProcessUtilization>,
+    ) -> Self {
+        self.process_utilization = utilization;
+        self
+    }
+
+    /// Builder: enable accounting mode with the given statistics
+    pub fn with_accounting(mut self, stats: Vec<ProcessAccounting>) -> Self {
+        self.accounting_enabled = Mutex::new(true);
+        self.accounting = stats;
+        self
+    }
+
     /// Builder: set power constraints
     pub fn with_power_constraints(mut self, constraints: PowerConstraints) -> Self {
         self.power_constraints = constraints;
@@ -106,10 +147,12 @@ unsafe impl Sync for MockDevice {}
 
 impl GpuDevice for MockDevice {
     fn info(&self) -> Result<GpuInfo, NvmlError> {
-        Ok(
-            GpuInfo::new(self.index, self.name.clone(), self.uuid.clone())
-                .with_fan_count(self.fan_count),
-        )
+        let info = GpuInfo::new(self.index, self.name.clone(), self.uuid.clone())
+            .with_fan_count(self.fan_count);
+        Ok(match &self.pci_bus_id {
+            Some(bus_id) => info.with_pci_bus_id(bus_id.clone()),
+            None => info,
+        })
     }
 
     fn name(&self) -> Result<String, NvmlError> {
@@ -305,8 +348,35 @@ impl GpuDevice for MockDevice {
     }
 
     fn running_processes(&self) -> Result<ProcessList, NvmlError> {
-        // Return empty process list for mock device
-        Ok(ProcessList::default())
+        Ok(ProcessList::new(self.processes.clone()))
+    }
+
+    fn process_utilization(
+        &self,
+        _window: Duration,
+    ) -> Result<HashMap<u32, ProcessUtilization>, NvmlError> {
+        Ok(self.process_utilization.clone())
+    }
+
+    fn accounting_enabled(&self) -> Result<bool, NvmlError> {
+        Ok(*self.accounting_enabled.lock().unwrap())
+    }
+
+    fn set_accounting(&mut self, enabled: bool) -> Result<(), NvmlError> {
+        *self.accounting_enabled.lock().unwrap() = enabled;
+        if !enabled {
+            self.accounting.clear();
+        }
+        Ok(())
+    }
+
+    fn accounting_stats(&self) -> Result<Vec<ProcessAccounting>, NvmlError> {
+        if !*self.accounting_enabled.lock().unwrap() {
+            return Err(NvmlError::NotSupported(
+                "Accounting mode is disabled".to_string(),
+            ));
+        }
+        Ok(self.accounting.clone())
     }
 }
 
@@ -315,28 +385,69 @@ pub struct MockManager {
     devices: Vec<MockDevice>,
     driver_version: String,
     nvml_version: String,
+    lost: Vec<bool>,
+    uninitialized: bool,
+    reinit_count: u32,
 }
 
 impl MockManager {
     /// Create a new mock manager with the specified number of devices
     pub fn new(device_count: u32) -> Self {
         let devices = (0..device_count).map(MockDevice::new).collect();
-
-        Self {
-            devices,
-            driver_version: "535.154.05".to_string(),
-            nvml_version: "12.535.154.05".to_string(),
-        }
+        Self::with_devices(devices)
     }
 
     /// Create a mock manager with custom devices
     pub fn with_devices(devices: Vec<MockDevice>) -> Self {
+        let lost = vec![false; devices.len()];
         Self {
             devices,
             driver_version: "535.154.05".to_string(),
             nvml_version: "12.535.154.05".to_string(),
+            lost,
+            uninitialized: false,
+            reinit_count: 0,
         }
     }
+
+    /// Simulate a GPU falling off the bus (or coming back)
+    pub fn set_gpu_lost(&mut self, index: u32, lost: bool) {
+        if let Some(flag) = self.lost.get_mut(index as usize) {
+            *flag = lost;
+        }
+    }
+
+    /// Simulate a driver reload; all lookups fail until `reinitialize` is called
+    pub fn invalidate(&mut self) {
+        self.uninitialized = true;
+    }
+
+    /// Swap the positions of two devices, as a hot-plug reorder would
+    pub fn swap_devices(&mut self, a: u32, b: u32) {
+        self.devices.swap(a as usize, b as usize);
+        self.lost.swap(a as usize, b as usize);
+        self.devices[a as usize].index = a;
+        self.devices[b as usize].index = b;
+    }
+
+    /// Replace the processes running on a device
+    pub fn set_processes(&mut self, index: u32, processes: Vec<GpuProcess>) {
+        if let Some(device) = self.devices.get_mut(index as usize) {
+            device.processes = processes;
+        }
+    }
+
+    /// Number of times `reinitialize` has been called
+    pub fn reinit_count(&self) -> u32 {
+        self.reinit_count
+    }
+
+    fn check_initialized(&self) -> Result<(), NvmlError> {
+        if self.uninitialized {
+            return Err(NvmlError::Uninitialized("mock driver reloaded".to_string()));
+        }
+        Ok(())
+    }
 }
 
 // SAFETY: MockManager only contains MockDevice which is Sync
@@ -346,30 +457,42 @@ impl GpuManager for MockManager {
     type Device = MockDevice;
 
     fn device_count(&self) -> Result<u32, NvmlError> {
+        self.check_initialized()?;
         Ok(self.devices.len() as u32)
     }
 
     fn device_by_index(&self, index: u32) -> Result<Self::Device, NvmlError> {
+        self.check_initialized()?;
+        if self.lost.get(index as usize).copied().unwrap_or(false) {
+            return Err(NvmlError::GpuLost);
+        }
         self.devices
             .get(index as usize)
             .map(|d| MockDevice {
                 index: d.index,
                 name: d.name.clone(),
                 uuid: d.uuid.clone(),
+                pci_bus_id: d.pci_bus_id.clone(),
                 temperature: RwLock::new(*d.temperature.read().unwrap()),
                 fan_speeds: Mutex::new(d.fan_speeds.lock().unwrap().clone()),
                 fan_policies: Mutex::new(d.fan_policies.lock().unwrap().clone()),
                 fan_count: d.fan_count,
-                power_limit: Mutex::new(*d.power_limit.lock().unwrap()),
+                // Limits persist on the card, so every handle shares them
+                power_limit: Arc::clone(&d.power_limit),
                 power_constraints: d.power_constraints,
                 power_usage: d.power_usage,
                 thermal_thresholds: d.thermal_thresholds,
-                acoustic_limits: RwLock::new(*d.acoustic_limits.read().unwrap()),
+                acoustic_limits: Arc::clone(&d.acoustic_limits),
+                processes: d.processes.clone(),
+                process_utilization: d.process_utilization.clone(),
+                accounting_enabled: Mutex::new(*d.accounting_enabled.lock().unwrap()),
+                accounting: d.accounting.clone(),
             })
             .ok_or(NvmlError::DeviceNotFound(index))
     }
 
     fn device_by_uuid(&self, uuid: &str) -> Result<Self::Device, NvmlError> {
+        self.check_initialized()?;
         for d in &self.devices {
             if d.uuid == uuid {
                 return self.device_by_index(d.index);
@@ -398,6 +521,39 @@ impl GpuManager for MockManager {
     fn nvml_version(&self) -> Result<String, NvmlError> {
         Ok(self.nvml_version.clone())
     }
+
+    unsafe fn reinitialize(&mut self) -> Result<(), NvmlError> {
+        self.uninitialized = false;
+        self.reinit_count += 1;
+        Ok(())
+    }
+}
+
+/// Mock event source fed through a channel
+pub struct MockEventSource {
+    receiver: Receiver<GpuEvent>,
+}
+
+impl MockEventSource {
+    /// Create a source and the sender used to inject events into it
+    pub fn channel() -> (Sender<GpuEvent>, Self) {
+        let (sender, receiver) = mpsc::channel();
+        (sender, Self { receiver })
+    }
+}
+
+impl EventSource for MockEventSource {
+    fn next_event(&mut self, timeout: Duration) -> Result<Option<GpuEvent>, NvmlError> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => Ok(Some(event)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => {
+                // Behave like an idle event set once the test drops the sender
+                std::thread::sleep(timeout);
+                Ok(None)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,175 @@
+//! Blocking MQTT client
+//!
+//! Wraps a rumqttc client whose connection is driven on a background thread.
+//! Incoming publishes are queued for the control loop, and connections are
+//! counted so subscriptions and retained topics can be restored after the
+//! broker comes back.
+
+use super::OFFLINE;
+use crate::config::MqttConfig;
+use crate::error::MqttError;
+
+use rumqttc::{Client, Event, LastWill, MqttOptions, Outgoing, Packet, QoS, Transport};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Requests queued while the broker is unreachable before publishes are dropped
+const QUEUE_CAPACITY: usize = 1000;
+
+/// Wait between connection attempts
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// How long dropping the client waits for the offline message to go out
+const DISCONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// MQTT connection with a last will on the availability topic
+pub struct MqttClient {
+    client: Client,
+    incoming: Receiver<(String, Vec<u8>)>,
+    connections: Arc<AtomicU64>,
+    status_topic: String,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MqttClient {
+    /// Start connecting to the configured broker
+    ///
+    /// The broker is told to publish `offline` on `status_topic` if the
+    /// connection drops without a clean disconnect.
+    pub fn connect(
+        config: &MqttConfig,
+        client_id: &str,
+        status_topic: &str,
+    ) -> Result<Self, MqttError> {
+        let mut options = MqttOptions::new(client_id, &config.host, config.port);
+        options
+            .set_keep_alive(Duration::from_secs(config.keep_alive_seconds.max(5)))
+            .set_last_will(LastWill::new(status_topic, OFFLINE, QoS::AtLeastOnce, true));
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.as_deref().unwrap_or_default());
+        }
+        if config.tls {
+            options.set_transport(tls_transport(config)?);
+        }
+
+        let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+        let (tx, incoming) = mpsc::channel();
+        let connections = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&connections);
+        let broker = format!("{}:{}", config.host, config.port);
+
+        let thread = thread::Builder::new()
+            .name("mqtt".to_string())
+            .spawn(move || {
+                for event in connection.iter() {
+                    match event {
+                        Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                            log::info!("Connected to MQTT broker {}", broker);
+                            counter.fetch_add(1, Ordering::SeqCst);
+                        }
+                        Ok(Event::Incoming(Packet::Publish(publish))) => {
+                            if tx.send((publish.topic, publish.payload.to_vec())).is_err() {
+                                break;
+                            }
+                        }
+                        Ok(Event::Outgoing(Outgoing::Disconnect)) => break,
+                        Ok(_) => {}
+                        Err(e) => {
+                            log::warn!("MQTT connection to {} failed: {}", broker, e);
+                            thread::sleep(RECONNECT_DELAY);
+                        }
+                    }
+                }
+            })
+            .map_err(|e| MqttError::Client(e.to_string()))?;
+
+        Ok(Self {
+            client,
+            incoming,
+            connections,
+            status_topic: status_topic.to_string(),
+            thread: Some(thread),
+        })
+    }
+
+    /// Number of successful connections so far
+    pub fn connections(&self) -> u64 {
+        self.connections.load(Ordering::SeqCst)
+    }
+
+    /// Queue a message without blocking
+    ///
+    /// Retained messages are sent at least once, everything else at most once.
+    pub fn publish(&self, topic: &str, payload: &str, retain: bool) -> Result<(), MqttError> {
+        let qos = if retain {
+            QoS::AtLeastOnce
+        } else {
+            QoS::AtMostOnce
+        };
+        self.client
+            .try_publish(topic, qos, retain, payload.as_bytes())
+            .map_err(|e| MqttError::Client(e.to_string()))
+    }
+
+    /// Subscribe to a topic filter without blocking
+    pub fn subscribe(&self, filter: &str) -> Result<(), MqttError> {
+        self.client
+            .try_subscribe(filter, QoS::AtLeastOnce)
+            .map_err(|e| MqttError::Client(e.to_string()))
+    }
+
+    /// Take every message received since the last call, as (topic, payload) pairs
+    pub fn take_messages(&self) -> Vec<(String, Vec<u8>)> {
+        self.incoming.try_iter().collect()
+    }
+}
+
+impl Drop for MqttClient {
+    /// Mark the publisher offline and disconnect cleanly
+    fn drop(&mut self) {
+        let _ = self.publish(&self.status_topic.clone(), OFFLINE, true);
+        if self.client.try_disconnect().is_err() {
+            return;
+        }
+        let Some(thread) = self.thread.take() else {
+            return;
+        };
+        let deadline = Instant::now() + DISCONNECT_TIMEOUT;
+        while !thread.is_finished() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+/// TLS transport verifying the broker against `ca_file` or the platform roots
+fn tls_transport(config: &MqttConfig) -> Result<Transport, MqttError> {
+    let client_auth = match (&config.client_cert, &config.client_key) {
+        (Some(cert), Some(key)) => Some((read_pem(cert)?, read_pem(key)?)),
+        (None, None) => None,
+        _ => {
+            return Err(MqttError::Settings(
+                "client_cert and client_key must be set together".to_string(),
+            ))
+        }
+    };
+
+    match &config.ca_file {
+        Some(ca) => Ok(Transport::tls(read_pem(ca)?, client_auth, None)),
+        None if client_auth.is_none() => Ok(Transport::tls_with_default_config()),
+        None => Err(MqttError::Settings(
+            "client certificates require ca_file".to_string(),
+        )),
+    }
+}
+
+fn read_pem(path: &Path) -> Result<Vec<u8>, MqttError> {
+    fs::read(path).map_err(|source| MqttError::Tls {
+        path: path.to_path_buf(),
+        source,
+    })
+}
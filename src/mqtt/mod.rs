@@ -0,0 +1,370 @@
+//! MQTT publisher with Home Assistant discovery
+//!
+//! Builds the topics and payloads the control loop publishes: one retained
+//! availability topic, a JSON state topic per GPU, Home Assistant discovery
+//! configs, and command topics for the power limit and the active profile.
+
+pub mod client;
+
+pub use client::MqttClient;
+
+use crate::domain::PowerConstraints;
+use crate::health::HealthBreakdown;
+use crate::nvml::GpuSnapshot;
+use crate::recorder::RecordField;
+
+use serde_json::{json, Map, Value};
+
+/// Payload of the availability topic while connected
+pub const ONLINE: &str = "online";
+
+/// Payload of the availability topic after disconnecting, also the last will
+pub const OFFLINE: &str = "offline";
+
+/// Profile option that hands profile selection back to the schedule and process rules
+pub const AUTO_PROFILE: &str = "auto";
+
+/// Topic layout under the base and discovery prefixes
+#[derive(Debug, Clone)]
+pub struct Topics {
+    base: String,
+    discovery_prefix: String,
+}
+
+impl Topics {
+    /// Create the topic layout
+    pub fn new(base: &str, discovery_prefix: &str) -> Self {
+        Self {
+            base: base.trim_end_matches('/').to_string(),
+            discovery_prefix: discovery_prefix.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Availability topic (`online`/`offline`)
+    pub fn status(&self) -> String {
+        format!("{}/status", self.base)
+    }
+
+    /// JSON state of one GPU
+    pub fn state(&self, node: &str) -> String {
+        format!("{}/{}/state", self.base, node)
+    }
+
+    /// Power limit command topic of one GPU
+    pub fn power_limit_command(&self, node: &str) -> String {
+        format!("{}/{}/power_limit/set", self.base, node)
+    }
+
+    /// Selected profile
+    pub fn profile(&self) -> String {
+        format!("{}/profile", self.base)
+    }
+
+    /// Profile command topic
+    pub fn profile_command(&self) -> String {
+        format!("{}/profile/set", self.base)
+    }
+
+    /// Subscriptions covering every command topic
+    pub fn command_filters(&self) -> [String; 2] {
+        [
+            format!("{}/+/power_limit/set", self.base),
+            self.profile_command(),
+        ]
+    }
+
+    /// Home Assistant discovery config topic of one entity
+    pub fn discovery(&self, component: &str, node: &str, object: &str) -> String {
+        format!(
+            "{}/{}/{}/{}/config",
+            self.discovery_prefix, component, node, object
+        )
+    }
+
+    /// Parse a message received on a command topic
+    pub fn parse_command(&self, topic: &str, payload: &[u8]) -> Option<Command> {
+        let payload = std::str::from_utf8(payload).ok()?.trim();
+        if topic == self.profile_command() {
+            return match payload {
+                "" | AUTO_PROFILE => Some(Command::SetProfile(None)),
+                name => Some(Command::SetProfile(Some(name.to_string()))),
+            };
+        }
+
+        let node = topic
+            .strip_prefix(&self.base)?
+            .strip_prefix('/')?
+            .strip_suffix("/power_limit/set")?;
+        let watts: f64 = payload.parse().ok()?;
+        if node.is_empty() || node.contains('/') || !(0.0..=u32::MAX as f64).contains(&watts) {
+            return None;
+        }
+        Some(Command::SetPowerLimit {
+            node: node.to_string(),
+            watts: watts.round() as u32,
+        })
+    }
+}
+
+/// Request received on a command topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Set the power limit of the GPU published under `node`
+    SetPowerLimit { node: String, watts: u32 },
+    /// Select a profile, or `None` to follow the schedule and process rules
+    SetProfile(Option<String>),
+}
+
+/// Topic-safe identifier for a GPU UUID or client ID
+pub fn node_id(id: &str) -> String {
+    id.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// JSON state payload: every recorded field plus the health score
+pub fn state_payload(snapshot: &GpuSnapshot, health: Option<&HealthBreakdown>) -> String {
+    let mut state: Map<String, Value> = RecordField::ALL
+        .iter()
+        .map(|field| {
+            (
+                field.name().to_string(),
+                json!(field.read_snapshot(snapshot)),
+            )
+        })
+        .collect();
+    state.insert(
+        "health".to_string(),
+        json!(health.map(|h| h.overall.score())),
+    );
+    Value::Object(state).to_string()
+}
+
+/// Home Assistant device class of a field, if one fits
+fn device_class(field: RecordField) -> Option<&'static str> {
+    match field {
+        RecordField::Temp | RecordField::MemTemp => Some("temperature"),
+        RecordField::Power | RecordField::PowerLimit => Some("power"),
+        RecordField::ClockGpu | RecordField::ClockMem => Some("frequency"),
+        RecordField::VramUsed => Some("data_size"),
+        _ => None,
+    }
+}
+
+/// Human-readable entity name of a field
+fn entity_name(field: RecordField) -> &'static str {
+    match field {
+        RecordField::Temp => "Temperature",
+        RecordField::MemTemp => "Memory temperature",
+        RecordField::Fan => "Fan speed",
+        RecordField::Power => "Power draw",
+        RecordField::PowerLimit => "Power limit",
+        RecordField::ClockGpu => "Graphics clock",
+        RecordField::ClockMem => "Memory clock",
+        RecordField::Util => "Utilization",
+        RecordField::MemUtil => "Memory utilization",
+        RecordField::VramUsed => "VRAM used",
+        RecordField::Pstate => "Performance state",
+        RecordField::Throttled => "Throttled",
+    }
+}
+
+/// GPU described by discovery configs
+#[derive(Debug, Clone)]
+pub struct DiscoveryGpu<'a> {
+    /// GPU UUID
+    pub uuid: &'a str,
+    /// Product name
+    pub name: &'a str,
+    /// Allowed power limit range, if known
+    pub constraints: Option<&'a PowerConstraints>,
+}
+
+/// Discovery configs for one GPU's sensors and power limit, as (topic, payload) pairs
+///
+/// Sensors are only announced for fields present in the first snapshot.
+pub fn gpu_discovery(
+    topics: &Topics,
+    gpu: &DiscoveryGpu<'_>,
+    snapshot: &GpuSnapshot,
+) -> Vec<(String, String)> {
+    let node = node_id(gpu.uuid);
+    let device = json!({
+        "identifiers": [gpu.uuid],
+        "name": gpu.name,
+        "manufacturer": "NVIDIA",
+        "model": gpu.name,
+    });
+    let mut configs = Vec::new();
+
+    let fields = RecordField::ALL
+        .iter()
+        .map(|f| {
+            (
+                f.name(),
+                entity_name(*f),
+                f.unit(),
+                device_class(*f),
+                f.read_snapshot(snapshot),
+            )
+        })
+        .chain([("health", "Health", "", None, Some(0.0))]);
+    for (key, name, unit, class, value) in fields {
+        if value.is_none() {
+            continue;
+        }
+        let mut config = json!({
+            "name": name,
+            "unique_id": format!("{}_{}", node, key),
+            "object_id": format!("{}_{}", node, key),
+            "state_topic": topics.state(&node),
+            "value_template": format!("{{{{ value_json.{} }}}}", key),
+            "availability_topic": topics.status(),
+            "state_class": "measurement",
+            "device": device,
+        });
+        if !unit.is_empty() {
+            config["unit_of_measurement"] = json!(unit);
+        }
+        if let Some(class) = class {
+            config["device_class"] = json!(class);
+        }
+        configs.push((topics.discovery("sensor", &node, key), config.to_string()));
+    }
+
+    if let Some(constraints) = gpu.constraints {
+        let config = json!({
+            "name": "Power limit",
+            "unique_id": format!("{}_power_limit_set", node),
+            "object_id": format!("{}_power_limit_set", node),
+            "state_topic": topics.state(&node),
+            "value_template": "{{ value_json.power_limit }}",
+            "command_topic": topics.power_limit_command(&node),
+            "availability_topic": topics.status(),
+            "min": constraints.min.as_watts(),
+            "max": constraints.max.as_watts(),
+            "step": 1,
+            "mode": "box",
+            "unit_of_measurement": "W",
+            "device_class": "power",
+            "device": device,
+        });
+        configs.push((
+            topics.discovery("number", &node, "power_limit"),
+            config.to_string(),
+        ));
+    }
+
+    configs
+}
+
+/// Discovery config for the profile selector, as a (topic, payload) pair
+pub fn profile_discovery(
+    topics: &Topics,
+    client_id: &str,
+    profiles: &[String],
+) -> (String, String) {
+    let node = node_id(client_id);
+    let options: Vec<&str> = std::iter::once(AUTO_PROFILE)
+        .chain(profiles.iter().map(String::as_str))
+        .collect();
+    let config = json!({
+        "name": "Profile",
+        "unique_id": format!("{}_profile", node),
+        "object_id": format!("{}_profile", node),
+        "state_topic": topics.profile(),
+        "command_topic": topics.profile_command(),
+        "availability_topic": topics.status(),
+        "options": options,
+        "device": {
+            "identifiers": [node],
+            "name": client_id,
+            "manufacturer": "nvctl",
+        },
+    });
+    (
+        topics.discovery("select", &node, "profile"),
+        config.to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+
+    #[test]
+    fn test_parse_command() {
+        let topics = Topics::new("nvctl/", "homeassistant");
+        assert_eq!(
+            topics.parse_command("nvctl/gpu_0/power_limit/set", b"250.0"),
+            Some(Command::SetPowerLimit {
+                node: "gpu_0".to_string(),
+                watts: 250
+            })
+        );
+        assert_eq!(
+            topics.parse_command("nvctl/profile/set", b"quiet"),
+            Some(Command::SetProfile(Some("quiet".to_string())))
+        );
+        assert_eq!(
+            topics.parse_command("nvctl/profile/set", b"auto"),
+            Some(Command::SetProfile(None))
+        );
+        assert_eq!(
+            topics.parse_command("nvctl/gpu_0/power_limit/set", b"-5"),
+            None
+        );
+        assert_eq!(
+            topics.parse_command("other/gpu_0/power_limit/set", b"250"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_gpu_discovery() {
+        let device = MockDevice::new(0);
+        let snapshot = GpuSnapshot::capture(&device);
+        let uuid = snapshot.uuid.clone().unwrap();
+        let topics = Topics::new("nvctl", "homeassistant");
+        let constraints = snapshot.power_constraints.clone().unwrap();
+        let gpu = DiscoveryGpu {
+            uuid: &uuid,
+            name: "Mock GPU",
+            constraints: Some(&constraints),
+        };
+
+        let configs = gpu_discovery(&topics, &gpu, &snapshot);
+        let node = node_id(&uuid);
+        let (topic, payload) = configs
+            .iter()
+            .find(|(topic, _)| topic.ends_with("/temp/config"))
+            .unwrap();
+        assert_eq!(topic, &format!("homeassistant/sensor/{}/temp/config", node));
+        let config: Value = serde_json::from_str(payload).unwrap();
+        assert_eq!(config["state_topic"], format!("nvctl/{}/state", node));
+        assert_eq!(config["device_class"], "temperature");
+        assert_eq!(config["availability_topic"], "nvctl/status");
+
+        let (_, number) = configs
+            .iter()
+            .find(|(topic, _)| topic.starts_with("homeassistant/number/"))
+            .unwrap();
+        let number: Value = serde_json::from_str(number).unwrap();
+        assert_eq!(
+            number["command_topic"],
+            format!("nvctl/{}/power_limit/set", node)
+        );
+        assert_eq!(number["max"], constraints.max.as_watts());
+
+        let state: Value = serde_json::from_str(&state_payload(&snapshot, None)).unwrap();
+        assert!(state["temp"].is_number());
+        assert!(state["health"].is_null());
+    }
+}
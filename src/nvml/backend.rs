@@ -0,0 +1,306 @@
+//! Runtime-selected GPU backend
+//!
+//! Commands that can run against recorded telemetry open a [`Backend`], which
+//! is either the live NVML manager or a [`ReplayManager`] when `--replay` is set.
+//! Builds with the `mock` feature simulate GPUs instead when `--mock` is set.
+
+use crate::domain::{
+    AcousticLimits, ClockSpeed, ClockType, CoolerTarget, DecoderUtilization, EccErrors, EccMode,
+    EncoderUtilization, FanPolicy, FanSpeed, GpuInfo, MemoryInfo, PcieMetrics, PerformanceState,
+    PowerConstraints, PowerLimit, ProcessAccounting, ProcessList, ProcessUtilization, Temperature,
+    TemperatureReading, ThermalThresholds, ThrottleReasons, Utilization,
+};
+use crate::error::{AppError, NvmlError};
+use crate::nvml::{GpuDevice, GpuManager, NvmlDevice, NvmlManager};
+use crate::replay::{ReplayDevice, ReplayManager, ReplayOptions};
+
+#[cfg(feature = "mock")]
+use crate::mock::{MockDevice, MockManager};
+use std::collections::HashMap;
+#[cfg(feature = "mock")]
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Number of simulated GPUs to use instead of NVML, set once at startup
+#[cfg(feature = "mock")]
+static MOCK_GPUS: OnceLock<u32> = OnceLock::new();
+
+/// Make every backend opened afterwards simulate `count` GPUs
+#[cfg(feature = "mock")]
+pub fn set_mock(count: u32) {
+    let _ = MOCK_GPUS.set(count);
+}
+
+/// Forward a trait method to whichever variant is active
+macro_rules! delegate {
+    ($self:ident, $inner:ident => $call:expr) => {
+        match $self {
+            Self::Nvml($inner) => $call,
+            Self::Replay($inner) => $call,
+            #[cfg(feature = "mock")]
+            Self::Mock($inner) => $call,
+        }
+    };
+}
+
+/// Live NVML or a replayed trace
+pub enum Backend {
+    /// Hardware via NVML
+    Nvml(Box<NvmlManager>),
+    /// Recorded telemetry
+    Replay(ReplayManager),
+    /// Simulated GPUs
+    #[cfg(feature = "mock")]
+    Mock(MockManager),
+}
+
+impl Backend {
+    /// Open the replay if one was requested, NVML otherwise
+    pub fn open(replay: Option<&ReplayOptions>) -> Result<Self, AppError> {
+        #[cfg(feature = "mock")]
+        if let Some(&count) = MOCK_GPUS.get() {
+            return Ok(Self::Mock(MockManager::new(count)));
+        }
+
+        match replay {
+            Some(options) => Ok(Self::Replay(ReplayManager::open(options)?)),
+            None => Ok(Self::Nvml(Box::new(NvmlManager::new()?))),
+        }
+    }
+
+    /// Check if this backend replays recorded telemetry
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Self::Replay(_))
+    }
+}
+
+impl GpuManager for Backend {
+    type Device = BackendDevice;
+
+    fn device_count(&self) -> Result<u32, NvmlError> {
+        delegate!(self, m => m.device_count())
+    }
+
+    fn device_by_index(&self, index: u32) -> Result<Self::Device, NvmlError> {
+        match self {
+            Self::Nvml(m) => m.device_by_index(index).map(BackendDevice::Nvml),
+            Self::Replay(m) => m.device_by_index(index).map(BackendDevice::Replay),
+            #[cfg(feature = "mock")]
+            Self::Mock(m) => m
+                .device_by_index(index)
+                .map(|d| BackendDevice::Mock(Box::new(d))),
+        }
+    }
+
+    fn device_by_uuid(&self, uuid: &str) -> Result<Self::Device, NvmlError> {
+        match self {
+            Self::Nvml(m) => m.device_by_uuid(uuid).map(BackendDevice::Nvml),
+            Self::Replay(m) => m.device_by_uuid(uuid).map(BackendDevice::Replay),
+            #[cfg(feature = "mock")]
+            Self::Mock(m) => m
+                .device_by_uuid(uuid)
+                .map(|d| BackendDevice::Mock(Box::new(d))),
+        }
+    }
+
+    fn device_by_name(&self, name: &str) -> Result<Self::Device, NvmlError> {
+        match self {
+            Self::Nvml(m) => m.device_by_name(name).map(BackendDevice::Nvml),
+            Self::Replay(m) => m.device_by_name(name).map(BackendDevice::Replay),
+            #[cfg(feature = "mock")]
+            Self::Mock(m) => m
+                .device_by_name(name)
+                .map(|d| BackendDevice::Mock(Box::new(d))),
+        }
+    }
+
+    fn all_devices(&self) -> Result<Vec<Self::Device>, NvmlError> {
+        match self {
+            Self::Nvml(m) => Ok(m
+                .all_devices()?
+                .into_iter()
+                .map(BackendDevice::Nvml)
+                .collect()),
+            Self::Replay(m) => Ok(m
+                .all_devices()?
+                .into_iter()
+                .map(BackendDevice::Replay)
+                .collect()),
+            #[cfg(feature = "mock")]
+            Self::Mock(m) => Ok(m
+                .all_devices()?
+                .into_iter()
+                .map(|d| BackendDevice::Mock(Box::new(d)))
+                .collect()),
+        }
+    }
+
+    fn driver_version(&self) -> Result<String, NvmlError> {
+        delegate!(self, m => m.driver_version())
+    }
+
+    fn nvml_version(&self) -> Result<String, NvmlError> {
+        delegate!(self, m => m.nvml_version())
+    }
+
+    unsafe fn reinitialize(&mut self) -> Result<(), NvmlError> {
+        // SAFETY: the caller's guarantee covers the wrapped manager's devices
+        delegate!(self, m => unsafe { m.reinitialize() })
+    }
+}
+
+/// Device handle from a [`Backend`]
+pub enum BackendDevice {
+    /// Hardware via NVML
+    Nvml(NvmlDevice<'static>),
+    /// Recorded telemetry
+    Replay(ReplayDevice),
+    /// Simulated GPU
+    #[cfg(feature = "mock")]
+    Mock(Box<MockDevice>),
+}
+
+impl GpuDevice for BackendDevice {
+    fn info(&self) -> Result<GpuInfo, NvmlError> {
+        delegate!(self, d => d.info())
+    }
+
+    fn name(&self) -> Result<String, NvmlError> {
+        delegate!(self, d => d.name())
+    }
+
+    fn uuid(&self) -> Result<String, NvmlError> {
+        delegate!(self, d => d.uuid())
+    }
+
+    fn index(&self) -> u32 {
+        delegate!(self, d => d.index())
+    }
+
+    fn temperature(&self) -> Result<Temperature, NvmlError> {
+        delegate!(self, d => d.temperature())
+    }
+
+    fn memory_temperature(&self) -> Result<Option<Temperature>, NvmlError> {
+        delegate!(self, d => d.memory_temperature())
+    }
+
+    fn temperature_readings(&self) -> Result<Vec<TemperatureReading>, NvmlError> {
+        delegate!(self, d => d.temperature_readings())
+    }
+
+    fn thermal_thresholds(&self) -> Result<ThermalThresholds, NvmlError> {
+        delegate!(self, d => d.thermal_thresholds())
+    }
+
+    fn acoustic_limits(&self) -> Result<AcousticLimits, NvmlError> {
+        delegate!(self, d => d.acoustic_limits())
+    }
+
+    fn set_acoustic_limit(&mut self, temp: Temperature) -> Result<(), NvmlError> {
+        delegate!(self, d => d.set_acoustic_limit(temp))
+    }
+
+    fn fan_count(&self) -> Result<u32, NvmlError> {
+        delegate!(self, d => d.fan_count())
+    }
+
+    fn fan_speed(&self, fan_idx: u32) -> Result<FanSpeed, NvmlError> {
+        delegate!(self, d => d.fan_speed(fan_idx))
+    }
+
+    fn set_fan_speed(&mut self, fan_idx: u32, speed: FanSpeed) -> Result<(), NvmlError> {
+        delegate!(self, d => d.set_fan_speed(fan_idx, speed))
+    }
+
+    fn fan_policy(&self, fan_idx: u32) -> Result<FanPolicy, NvmlError> {
+        delegate!(self, d => d.fan_policy(fan_idx))
+    }
+
+    fn set_fan_policy(&mut self, fan_idx: u32, policy: FanPolicy) -> Result<(), NvmlError> {
+        delegate!(self, d => d.set_fan_policy(fan_idx, policy))
+    }
+
+    fn cooler_target(&self, fan_idx: u32) -> Result<CoolerTarget, NvmlError> {
+        delegate!(self, d => d.cooler_target(fan_idx))
+    }
+
+    fn power_limit(&self) -> Result<PowerLimit, NvmlError> {
+        delegate!(self, d => d.power_limit())
+    }
+
+    fn power_constraints(&self) -> Result<PowerConstraints, NvmlError> {
+        delegate!(self, d => d.power_constraints())
+    }
+
+    fn set_power_limit(&mut self, limit: PowerLimit) -> Result<(), NvmlError> {
+        delegate!(self, d => d.set_power_limit(limit))
+    }
+
+    fn power_usage(&self) -> Result<PowerLimit, NvmlError> {
+        delegate!(self, d => d.power_usage())
+    }
+
+    fn clock_speed(&self, clock_type: ClockType) -> Result<ClockSpeed, NvmlError> {
+        delegate!(self, d => d.clock_speed(clock_type))
+    }
+
+    fn utilization(&self) -> Result<Utilization, NvmlError> {
+        delegate!(self, d => d.utilization())
+    }
+
+    fn encoder_utilization(&self) -> Result<Option<EncoderUtilization>, NvmlError> {
+        delegate!(self, d => d.encoder_utilization())
+    }
+
+    fn decoder_utilization(&self) -> Result<Option<DecoderUtilization>, NvmlError> {
+        delegate!(self, d => d.decoder_utilization())
+    }
+
+    fn memory_info(&self) -> Result<MemoryInfo, NvmlError> {
+        delegate!(self, d => d.memory_info())
+    }
+
+    fn performance_state(&self) -> Result<PerformanceState, NvmlError> {
+        delegate!(self, d => d.performance_state())
+    }
+
+    fn throttle_reasons(&self) -> Result<ThrottleReasons, NvmlError> {
+        delegate!(self, d => d.throttle_reasons())
+    }
+
+    fn ecc_mode(&self) -> Result<Option<EccMode>, NvmlError> {
+        delegate!(self, d => d.ecc_mode())
+    }
+
+    fn ecc_errors(&self) -> Result<Option<EccErrors>, NvmlError> {
+        delegate!(self, d => d.ecc_errors())
+    }
+
+    fn pcie_metrics(&self) -> Result<PcieMetrics, NvmlError> {
+        delegate!(self, d => d.pcie_metrics())
+    }
+
+    fn running_processes(&self) -> Result<ProcessList, NvmlError> {
+        delegate!(self, d => d.running_processes())
+    }
+
+    fn process_utilization(
+        &self,
+        window: Duration,
+    ) -> Result<HashMap<u32, ProcessUtilization>, NvmlError> {
+        delegate!(self, d => d.process_utilization(window))
+    }
+
+    fn accounting_enabled(&self) -> Result<bool, NvmlError> {
+        delegate!(self, d => d.accounting_enabled())
+    }
+
+    fn set_accounting(&mut self, enabled: bool) -> Result<(), NvmlError> {
+        delegate!(self, d => d.set_accounting(enabled))
+    }
+
+    fn accounting_stats(&self) -> Result<Vec<ProcessAccounting>, NvmlError> {
+        delegate!(self, d => d.accounting_stats())
+    }
+}
@@ -6,14 +6,16 @@ use crate::domain::{
     AcousticLimits, ClockSpeed, ClockType, CoolerTarget, DecoderUtilization, EccErrors, EccMode,
     EncoderUtilization, FanPolicy, FanSpeed, GpuInfo, MemoryInfo, PcieGeneration, PcieLinkStatus,
     PcieLinkWidth, PcieMetrics, PcieReplayCounter, PcieThroughput, PerformanceState,
-    PowerConstraints, PowerLimit, ProcessList, Temperature, ThermalThresholds, ThrottleReasons,
-    Utilization,
+    PowerConstraints, PowerLimit, ProcessAccounting, ProcessList, ProcessUtilization, Temperature,
+    ThermalThresholds, ThrottleReasons, Utilization,
 };
 use crate::error::NvmlError;
 use crate::nvml::traits::GpuDevice;
 
 use nvml_wrapper::enum_wrappers::device::TemperatureSensor;
 use nvml_wrapper::Device;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 // FFI constants for acoustic temperature thresholds
 // These are not exposed by nvml-wrapper's high-level API
@@ -34,7 +36,7 @@ impl<'a> NvmlDevice<'a> {
     }
 
     /// Convert NVML error to our error type
-    fn convert_error(err: nvml_wrapper::error::NvmlError) -> NvmlError {
+    pub(crate) fn convert_error(err: nvml_wrapper::error::NvmlError) -> NvmlError {
         use nvml_wrapper::error::NvmlError as NE;
         match err {
             NE::NotSupported => {
@@ -45,6 +47,15 @@ impl<'a> NvmlDevice<'a> {
             }
             NE::NotFound => NvmlError::DeviceNotFound(0),
             NE::GpuLost => NvmlError::GpuLost,
+            NE::Uninitialized => {
+                NvmlError::Uninitialized("NVML library handle is no longer valid".to_string())
+            }
+            NE::DriverNotLoaded => {
+                NvmlError::Uninitialized("NVIDIA driver is not loaded".to_string())
+            }
+            NE::LibRmVersionMismatch => NvmlError::Uninitialized(
+                "NVML library does not match the loaded driver (driver reloaded?)".to_string(),
+            ),
             NE::InvalidArg => NvmlError::InvalidArgument("Invalid argument".to_string()),
             _ => NvmlError::Unknown(err.to_string()),
         }
@@ -489,26 +500,88 @@ impl GpuDevice for NvmlDevice<'_> {
 
         let mut processes: Vec<GpuProcess> = process_map.into_values().collect();
 
-        // Try to get process names from /proc on Linux
+        // Try to get process names, command lines and users from /proc on Linux
         #[cfg(target_os = "linux")]
-        for process in &mut processes {
-            if let Ok(cmdline) = std::fs::read_to_string(format!("/proc/{}/cmdline", process.pid)) {
-                // cmdline has null-separated args, take first one
-                if let Some(name) = cmdline.split('\0').next() {
-                    if !name.is_empty() {
-                        // Extract just the executable name
-                        let exe_name = std::path::Path::new(name)
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or(name);
-                        process.name = Some(exe_name.to_string());
-                    }
-                }
+        {
+            let procfs = crate::procfs::ProcFs::system();
+            for process in &mut processes {
+                procfs.annotate(process);
             }
         }
 
         Ok(ProcessList::new(processes))
     }
+
+    fn process_utilization(
+        &self,
+        window: Duration,
+    ) -> Result<HashMap<u32, ProcessUtilization>, NvmlError> {
+        // Samples newer than this timestamp (in microseconds) are returned
+        let since = SystemTime::now()
+            .checked_sub(window)
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_micros() as u64);
+
+        let samples = match self.device.process_utilization_stats(since) {
+            Ok(samples) => samples,
+            // No process was busy in the window
+            Err(nvml_wrapper::error::NvmlError::NotFound) => return Ok(HashMap::new()),
+            Err(e) => return Err(Self::convert_error(e)),
+        };
+
+        let mut by_pid: HashMap<u32, Vec<ProcessUtilization>> = HashMap::new();
+        for sample in samples {
+            by_pid
+                .entry(sample.pid)
+                .or_default()
+                .push(ProcessUtilization {
+                    sm: sample.sm_util,
+                    memory: sample.mem_util,
+                    encoder: sample.enc_util,
+                    decoder: sample.dec_util,
+                });
+        }
+
+        Ok(by_pid
+            .into_iter()
+            .filter_map(|(pid, samples)| Some((pid, ProcessUtilization::average(&samples)?)))
+            .collect())
+    }
+
+    fn accounting_enabled(&self) -> Result<bool, NvmlError> {
+        self.device
+            .is_accounting_enabled()
+            .map_err(Self::convert_error)
+    }
+
+    fn set_accounting(&mut self, enabled: bool) -> Result<(), NvmlError> {
+        self.device
+            .set_accounting(enabled)
+            .map_err(Self::convert_error)
+    }
+
+    fn accounting_stats(&self) -> Result<Vec<ProcessAccounting>, NvmlError> {
+        let pids = self.device.accounting_pids().map_err(Self::convert_error)?;
+
+        let mut stats = Vec::with_capacity(pids.len());
+        for pid in pids {
+            match self.device.accounting_stats_for(pid) {
+                Ok(s) => stats.push(ProcessAccounting {
+                    pid,
+                    is_running: s.is_running,
+                    max_memory: s.max_memory_usage,
+                    gpu_utilization: s.gpu_utilization,
+                    memory_utilization: s.memory_utilization,
+                    start_time: s.start_time / 1_000_000,
+                    run_time_ms: s.time,
+                }),
+                // Dropped out of the accounting buffer since listing the PIDs
+                Err(nvml_wrapper::error::NvmlError::NotFound) => {}
+                Err(e) => return Err(Self::convert_error(e)),
+            }
+        }
+        Ok(stats)
+    }
 }
 
 /// Get temperature threshold using raw FFI
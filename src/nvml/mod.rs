@@ -2,10 +2,18 @@
 //!
 //! Provides trait-based abstractions over NVML for testability.
 
+pub mod backend;
 pub mod device;
+pub mod snapshot;
+pub mod tracker;
 pub mod traits;
 pub mod wrapper;
 
+#[cfg(feature = "mock")]
+pub use backend::set_mock;
+pub use backend::{Backend, BackendDevice};
 pub use device::NvmlDevice;
+pub use snapshot::{GpuSnapshot, SnapshotScope};
+pub use tracker::{DeviceState, DeviceTracker, TrackedGpu};
 pub use traits::{GpuDevice, GpuManager};
 pub use wrapper::NvmlManager;
@@ -0,0 +1,252 @@
+//! Per-tick device snapshots
+//!
+//! A `GpuSnapshot` reads the metrics of a device once so that all services
+//! working on the same tick see consistent values without repeating NVML calls.
+//! A [`SnapshotScope`] limits the capture to the readings its consumers use.
+
+use crate::domain::{
+    ClockSpeed, ClockType, DecoderUtilization, EccErrors, EncoderUtilization, FanPolicy, FanSpeed,
+    MemoryInfo, PcieMetrics, PerformanceState, PowerConstraints, PowerLimit, Temperature,
+    ThermalThresholds, ThrottleReasons, Utilization,
+};
+use crate::error::NvmlError;
+use crate::nvml::GpuDevice;
+
+use std::time::Instant;
+
+/// Groups of readings captured on top of the control readings
+///
+/// Name, UUID, temperature, fan count, power limit and power constraints are
+/// always read. Fields of groups that are left out hold a `NotSupported` error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SnapshotScope {
+    /// Fan speeds and policies
+    pub fans: bool,
+    /// Power draw, clocks, utilization, VRAM, performance state and throttle reasons
+    pub activity: bool,
+    /// Memory temperature and thermal thresholds
+    pub thermal: bool,
+    /// ECC error counts
+    pub ecc: bool,
+    /// PCIe link and throughput (throughput is sampled over an interval)
+    pub pcie: bool,
+    /// Video encoder and decoder utilization
+    pub video: bool,
+}
+
+impl SnapshotScope {
+    /// Only the readings needed to apply fan curves and power limits
+    pub const CONTROL: Self = Self {
+        fans: false,
+        activity: false,
+        thermal: false,
+        ecc: false,
+        pcie: false,
+        video: false,
+    };
+
+    /// Every reading
+    pub const ALL: Self = Self {
+        fans: true,
+        activity: true,
+        thermal: true,
+        ecc: true,
+        pcie: true,
+        video: true,
+    };
+
+    /// Readings needed by either scope
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            fans: self.fans || other.fans,
+            activity: self.activity || other.activity,
+            thermal: self.thermal || other.thermal,
+            ecc: self.ecc || other.ecc,
+            pcie: self.pcie || other.pcie,
+            video: self.video || other.video,
+        }
+    }
+}
+
+/// Point-in-time readings of a GPU, with per-field errors
+#[derive(Debug, Clone)]
+pub struct GpuSnapshot {
+    /// Device index at capture time
+    pub index: u32,
+    /// When the snapshot was taken
+    pub captured_at: Instant,
+    /// Product name
+    pub name: Result<String, NvmlError>,
+    /// GPU UUID
+    pub uuid: Result<String, NvmlError>,
+    /// Core temperature
+    pub temperature: Result<Temperature, NvmlError>,
+    /// Memory temperature, if the GPU reports it
+    pub memory_temperature: Result<Option<Temperature>, NvmlError>,
+    /// Thermal thresholds
+    pub thermal_thresholds: Result<ThermalThresholds, NvmlError>,
+    /// Number of fans
+    pub fan_count: Result<u32, NvmlError>,
+    /// Speed of each fan
+    pub fan_speeds: Vec<Result<FanSpeed, NvmlError>>,
+    /// Control policy of each fan
+    pub fan_policies: Vec<Result<FanPolicy, NvmlError>>,
+    /// Current power draw
+    pub power_usage: Result<PowerLimit, NvmlError>,
+    /// Current power limit
+    pub power_limit: Result<PowerLimit, NvmlError>,
+    /// Allowed power limit range
+    pub power_constraints: Result<PowerConstraints, NvmlError>,
+    /// Graphics clock
+    pub graphics_clock: Result<ClockSpeed, NvmlError>,
+    /// Memory clock
+    pub memory_clock: Result<ClockSpeed, NvmlError>,
+    /// GPU and memory utilization
+    pub utilization: Result<Utilization, NvmlError>,
+    /// VRAM usage
+    pub memory_info: Result<MemoryInfo, NvmlError>,
+    /// Performance state
+    pub performance_state: Result<PerformanceState, NvmlError>,
+    /// Reasons the clocks are currently throttled
+    pub throttle_reasons: Result<ThrottleReasons, NvmlError>,
+    /// ECC error counts, if ECC is supported
+    pub ecc_errors: Result<Option<EccErrors>, NvmlError>,
+    /// PCIe link and throughput metrics
+    pub pcie_metrics: Result<PcieMetrics, NvmlError>,
+    /// Video encoder utilization
+    pub encoder_utilization: Result<Option<EncoderUtilization>, NvmlError>,
+    /// Video decoder utilization
+    pub decoder_utilization: Result<Option<DecoderUtilization>, NvmlError>,
+}
+
+impl GpuSnapshot {
+    /// Read all metrics from a device
+    pub fn capture<D: GpuDevice>(device: &D) -> Self {
+        Self::capture_scope(device, SnapshotScope::ALL)
+    }
+
+    /// Read the control readings and the groups of `scope` from a device
+    pub fn capture_scope<D: GpuDevice>(device: &D, scope: SnapshotScope) -> Self {
+        /// Read a group's field only if the scope includes the group
+        fn read<T>(
+            included: bool,
+            f: impl FnOnce() -> Result<T, NvmlError>,
+        ) -> Result<T, NvmlError> {
+            if included {
+                f()
+            } else {
+                Err(NvmlError::NotSupported("not captured".to_string()))
+            }
+        }
+
+        let fan_count = device.fan_count();
+        let fans = if scope.fans {
+            *fan_count.as_ref().unwrap_or(&0)
+        } else {
+            0
+        };
+
+        Self {
+            index: device.index(),
+            captured_at: Instant::now(),
+            name: device.name(),
+            uuid: device.uuid(),
+            temperature: device.temperature(),
+            memory_temperature: read(scope.thermal, || device.memory_temperature()),
+            thermal_thresholds: read(scope.thermal, || device.thermal_thresholds()),
+            fan_speeds: (0..fans).map(|i| device.fan_speed(i)).collect(),
+            fan_policies: (0..fans).map(|i| device.fan_policy(i)).collect(),
+            fan_count,
+            power_usage: read(scope.activity, || device.power_usage()),
+            power_limit: device.power_limit(),
+            power_constraints: device.power_constraints(),
+            graphics_clock: read(scope.activity, || device.clock_speed(ClockType::Graphics)),
+            memory_clock: read(scope.activity, || device.clock_speed(ClockType::Memory)),
+            utilization: read(scope.activity, || device.utilization()),
+            memory_info: read(scope.activity, || device.memory_info()),
+            performance_state: read(scope.activity, || device.performance_state()),
+            throttle_reasons: read(scope.activity, || device.throttle_reasons()),
+            ecc_errors: read(scope.ecc, || device.ecc_errors()),
+            pcie_metrics: read(scope.pcie, || device.pcie_metrics()),
+            encoder_utilization: read(scope.video, || device.encoder_utilization()),
+            decoder_utilization: read(scope.video, || device.decoder_utilization()),
+        }
+    }
+
+    /// Get the first error that indicates the GPU is lost or NVML needs re-init
+    pub fn loss_error(&self) -> Option<&NvmlError> {
+        [
+            self.temperature.as_ref().err(),
+            self.power_limit.as_ref().err(),
+            self.fan_count.as_ref().err(),
+        ]
+        .into_iter()
+        .flatten()
+        .find(|e| e.is_gpu_lost() || e.requires_reinit())
+    }
+
+    /// Check if the GPU is throttling for thermal reasons
+    pub fn is_thermal_throttling(&self) -> bool {
+        match (&self.temperature, &self.thermal_thresholds) {
+            (Ok(temp), Ok(thresholds)) => thresholds
+                .slowdown
+                .is_some_and(|slowdown| temp.as_celsius() >= slowdown.as_celsius()),
+            _ => false,
+        }
+    }
+
+    /// Check if the GPU is drawing at least 99% of its power limit
+    pub fn is_power_throttling(&self) -> bool {
+        match (&self.power_usage, &self.power_limit) {
+            (Ok(usage), Ok(limit)) => usage.as_watts() as f64 >= limit.as_watts() as f64 * 0.99,
+            _ => false,
+        }
+    }
+
+    /// VRAM usage as a ratio of total memory
+    pub fn vram_usage_ratio(&self) -> Option<f64> {
+        self.memory_info
+            .as_ref()
+            .ok()
+            .filter(|info| info.total > 0)
+            .map(|info| info.used as f64 / info.total as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+
+    #[test]
+    fn test_capture_mock_device() {
+        let device = MockDevice::new(1);
+        device.set_temperature(Temperature::new(70));
+
+        let snapshot = GpuSnapshot::capture(&device);
+        assert_eq!(snapshot.index, 1);
+        assert_eq!(snapshot.temperature.as_ref().unwrap().as_celsius(), 70);
+        assert_eq!(snapshot.fan_speeds.len(), 2);
+        assert_eq!(snapshot.power_limit.as_ref().unwrap().as_watts(), 300);
+        assert!(snapshot.loss_error().is_none());
+        assert!(!snapshot.is_power_throttling());
+    }
+
+    #[test]
+    fn test_capture_control_scope() {
+        let device = MockDevice::new(0);
+        let snapshot = GpuSnapshot::capture_scope(&device, SnapshotScope::CONTROL);
+        assert!(snapshot.temperature.is_ok());
+        assert_eq!(*snapshot.fan_count.as_ref().unwrap(), 2);
+        assert!(snapshot.fan_speeds.is_empty());
+        assert!(snapshot.power_usage.is_err());
+        assert!(snapshot.pcie_metrics.is_err());
+
+        let scope = SnapshotScope {
+            fans: true,
+            ..SnapshotScope::CONTROL
+        };
+        let snapshot = GpuSnapshot::capture_scope(&device, scope);
+        assert_eq!(snapshot.fan_speeds.len(), 2);
+    }
+}
@@ -0,0 +1,227 @@
+//! UUID-based device tracking
+//!
+//! Keeps long-running loops pointed at the same physical GPUs across driver
+//! reloads and hot-plug events that reorder NVML device indices.
+
+use crate::error::NvmlError;
+use crate::nvml::{GpuDevice, GpuManager};
+
+use std::time::{Duration, Instant};
+
+/// Delay between re-initializations while a GPU stays lost, doubled per attempt
+const REINIT_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_REINIT_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Connection state of a tracked GPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceState {
+    /// Not opened successfully yet
+    Pending,
+    /// Reachable at its current index
+    Online,
+    /// Lost or no longer found
+    Lost,
+}
+
+/// A GPU tracked by UUID
+#[derive(Debug, Clone)]
+pub struct TrackedGpu {
+    /// Stable GPU UUID
+    pub uuid: String,
+    /// Last known NVML index
+    pub index: u32,
+    /// Current state
+    pub state: DeviceState,
+}
+
+/// Tracks a set of GPUs by UUID and re-resolves their indices when needed
+#[derive(Debug, Clone, Default)]
+pub struct DeviceTracker {
+    gpus: Vec<TrackedGpu>,
+    /// An error required NVML to be re-initialized
+    reinit_requested: bool,
+    /// Re-initializations since all GPUs were last online
+    reinit_attempts: u32,
+    /// Earliest time of the next re-initialization
+    next_reinit: Option<Instant>,
+}
+
+impl DeviceTracker {
+    /// Resolve the UUIDs of the GPUs at the given indices
+    pub fn from_indices<M: GpuManager>(manager: &M, indices: &[u32]) -> Result<Self, NvmlError> {
+        let mut gpus = Vec::with_capacity(indices.len());
+        for &index in indices {
+            let uuid = manager.device_by_index(index)?.uuid()?;
+            gpus.push(TrackedGpu {
+                uuid,
+                index,
+                state: DeviceState::Pending,
+            });
+        }
+        Ok(Self {
+            gpus,
+            ..Self::default()
+        })
+    }
+
+    /// Get all tracked GPUs
+    pub fn gpus(&self) -> &[TrackedGpu] {
+        &self.gpus
+    }
+
+    /// Get the number of tracked GPUs
+    pub fn len(&self) -> usize {
+        self.gpus.len()
+    }
+
+    /// Check if no GPUs are tracked
+    pub fn is_empty(&self) -> bool {
+        self.gpus.is_empty()
+    }
+
+    /// Check if any tracked GPU is currently lost
+    pub fn any_lost(&self) -> bool {
+        self.gpus.iter().any(|g| g.state == DeviceState::Lost)
+    }
+
+    /// Open the tracked GPU at `pos`, following it to a new index if it moved
+    pub fn open<M: GpuManager>(&mut self, manager: &M, pos: usize) -> Result<M::Device, NvmlError> {
+        let gpu = &mut self.gpus[pos];
+
+        match manager.device_by_index(gpu.index) {
+            Ok(device) if device.uuid()? == gpu.uuid => return Ok(device),
+            Ok(_) => {}
+            Err(e) if e.requires_reinit() => return Err(e),
+            Err(_) => {}
+        }
+
+        let device = manager.device_by_uuid(&gpu.uuid)?;
+        if device.index() != gpu.index {
+            log::info!(
+                "GPU {} moved from index {} to {}",
+                gpu.uuid,
+                gpu.index,
+                device.index()
+            );
+            gpu.index = device.index();
+        }
+        Ok(device)
+    }
+
+    /// Mark the GPU at `pos` as online, returning its previous state
+    pub fn mark_online(&mut self, pos: usize) -> DeviceState {
+        let previous = std::mem::replace(&mut self.gpus[pos].state, DeviceState::Online);
+        if !self.any_lost() {
+            self.reinit_attempts = 0;
+            self.next_reinit = None;
+        }
+        previous
+    }
+
+    /// Record an error after which NVML must be re-initialized
+    pub fn request_reinit(&mut self) {
+        self.reinit_requested = true;
+    }
+
+    /// Re-initialize the manager if an error required it and the backoff has elapsed
+    ///
+    /// Attempts are spaced out with exponential backoff while GPUs stay
+    /// lost, so the GPUs that are still healthy are not disturbed every tick.
+    ///
+    /// # Safety
+    ///
+    /// Same as [`GpuManager::reinitialize`]: no device obtained from `manager`
+    /// may be alive.
+    pub unsafe fn reinit_if_due<M: GpuManager>(&mut self, manager: &mut M) {
+        let now = Instant::now();
+        if !self.reinit_requested || self.next_reinit.is_some_and(|next| now < next) {
+            return;
+        }
+
+        self.reinit_requested = false;
+        let backoff = REINIT_BACKOFF
+            .saturating_mul(1 << self.reinit_attempts.min(16))
+            .min(MAX_REINIT_BACKOFF);
+        self.reinit_attempts += 1;
+        self.next_reinit = Some(now + backoff);
+        // SAFETY: forwarded from this function's contract
+        if let Err(e) = unsafe { manager.reinitialize() } {
+            log::warn!("Failed to re-initialize NVML: {}", e);
+        }
+    }
+
+    /// Mark the GPU at `pos` as lost, returning true if it was not lost before
+    pub fn mark_lost(&mut self, pos: usize) -> bool {
+        std::mem::replace(&mut self.gpus[pos].state, DeviceState::Lost) != DeviceState::Lost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockManager;
+
+    #[test]
+    fn test_tracker_follows_reordered_gpu() {
+        let mut manager = MockManager::new(2);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0]).unwrap();
+        assert_eq!(tracker.gpus()[0].uuid, "GPU-MOCK-0000");
+
+        manager.swap_devices(0, 1);
+        let device = tracker.open(&manager, 0).unwrap();
+        assert_eq!(device.uuid().unwrap(), "GPU-MOCK-0000");
+        assert_eq!(tracker.gpus()[0].index, 1);
+    }
+
+    #[test]
+    fn test_tracker_reports_lost_gpu() {
+        let mut manager = MockManager::new(1);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0]).unwrap();
+
+        manager.set_gpu_lost(0, true);
+        let err = tracker.open(&manager, 0).unwrap_err();
+        assert!(err.is_gpu_lost());
+    }
+
+    #[test]
+    fn test_tracker_state_transitions() {
+        let manager = MockManager::new(1);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0]).unwrap();
+
+        assert_eq!(tracker.mark_online(0), DeviceState::Pending);
+        assert!(tracker.mark_lost(0));
+        assert!(!tracker.mark_lost(0));
+        assert!(tracker.any_lost());
+        assert_eq!(tracker.mark_online(0), DeviceState::Lost);
+        assert!(!tracker.any_lost());
+    }
+
+    #[test]
+    fn test_tracker_backs_off_reinit() {
+        let mut manager = MockManager::new(1);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0]).unwrap();
+
+        // Nothing to do until an error asks for it
+        // SAFETY: the test holds no devices
+        unsafe { tracker.reinit_if_due(&mut manager) };
+        assert_eq!(manager.reinit_count(), 0);
+
+        tracker.mark_lost(0);
+        tracker.request_reinit();
+        // SAFETY: the test holds no devices
+        unsafe { tracker.reinit_if_due(&mut manager) };
+        assert_eq!(manager.reinit_count(), 1);
+
+        // Still lost: the next attempt waits for the backoff
+        tracker.request_reinit();
+        // SAFETY: the test holds no devices
+        unsafe { tracker.reinit_if_due(&mut manager) };
+        assert_eq!(manager.reinit_count(), 1);
+
+        // Recovery resets the backoff
+        tracker.mark_online(0);
+        // SAFETY: the test holds no devices
+        unsafe { tracker.reinit_if_due(&mut manager) };
+        assert_eq!(manager.reinit_count(), 2);
+    }
+}
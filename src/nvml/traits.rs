@@ -5,11 +5,14 @@
 use crate::domain::{
     AcousticLimits, ClockSpeed, ClockType, CoolerTarget, DecoderUtilization, EccErrors, EccMode,
     EncoderUtilization, FanPolicy, FanSpeed, GpuInfo, MemoryInfo, PcieMetrics, PerformanceState,
-    PowerConstraints, PowerLimit, ProcessList, Temperature, TemperatureReading, ThermalThresholds,
-    ThrottleReasons, Utilization,
+    PowerConstraints, PowerLimit, ProcessAccounting, ProcessList, ProcessUtilization, Temperature,
+    TemperatureReading, ThermalThresholds, ThrottleReasons, Utilization,
 };
 use crate::error::NvmlError;
 
+use std::collections::HashMap;
+use std::time::Duration;
+
 /// Trait for GPU device operations
 ///
 /// This trait abstracts all GPU operations, allowing for mock implementations
@@ -147,6 +150,27 @@ pub trait GpuDevice: Send + Sync {
     // Process monitoring operations
     /// Get list of processes running on this GPU
     fn running_processes(&self) -> Result<ProcessList, NvmlError>;
+
+    /// Get per-process utilization averaged over samples from the last `window`
+    ///
+    /// NVML only reports processes that were busy during the window.
+    fn process_utilization(
+        &self,
+        window: Duration,
+    ) -> Result<HashMap<u32, ProcessUtilization>, NvmlError>;
+
+    /// Check if accounting mode is enabled
+    fn accounting_enabled(&self) -> Result<bool, NvmlError>;
+
+    /// Enable or disable accounting mode (requires root)
+    ///
+    /// Disabling accounting also clears the recorded statistics.
+    fn set_accounting(&mut self, enabled: bool) -> Result<(), NvmlError>;
+
+    /// Get accounting statistics for running and recently finished processes
+    ///
+    /// NVML keeps a limited buffer, so old processes drop out over time.
+    fn accounting_stats(&self) -> Result<Vec<ProcessAccounting>, NvmlError>;
 }
 
 /// Trait for managing multiple GPUs
@@ -183,4 +207,16 @@ pub trait GpuManager: Send + Sync {
 
     /// Get NVML version
     fn nvml_version(&self) -> Result<String, NvmlError>;
+
+    /// Re-initialize the underlying library after a driver reload or GPU loss
+    ///
+    /// The default implementation is a no-op for backends without library state.
+    ///
+    /// # Safety
+    ///
+    /// No device obtained from this manager may be alive: NVML devices borrow
+    /// the library instance this call replaces.
+    unsafe fn reinitialize(&mut self) -> Result<(), NvmlError> {
+        Ok(())
+    }
 }
@@ -16,15 +16,19 @@ pub struct NvmlManager {
 impl NvmlManager {
     /// Initialize NVML and create a new manager
     pub fn new() -> Result<Self, NvmlError> {
-        let nvml = Nvml::init().map_err(|e| match e {
+        Ok(Self {
+            nvml: Self::init_nvml()?,
+        })
+    }
+
+    fn init_nvml() -> Result<Nvml, NvmlError> {
+        Nvml::init().map_err(|e| match e {
             nvml_wrapper::error::NvmlError::LibloadingError(_) => NvmlError::LibraryNotFound,
             nvml_wrapper::error::NvmlError::DriverNotLoaded => {
                 NvmlError::InitializationFailed("NVIDIA driver not loaded".to_string())
             }
             other => NvmlError::InitializationFailed(other.to_string()),
-        })?;
-
-        Ok(Self { nvml })
+        })
     }
 
     /// Get a reference to the underlying NVML instance
@@ -37,9 +41,7 @@ impl GpuManager for NvmlManager {
     type Device = NvmlDevice<'static>;
 
     fn device_count(&self) -> Result<u32, NvmlError> {
-        self.nvml
-            .device_count()
-            .map_err(|e| NvmlError::Unknown(e.to_string()))
+        self.nvml.device_count().map_err(NvmlDevice::convert_error)
     }
 
     fn device_by_index(&self, index: u32) -> Result<Self::Device, NvmlError> {
@@ -49,8 +51,9 @@ impl GpuManager for NvmlManager {
         let nvml: &'static Nvml = unsafe { std::mem::transmute(&self.nvml) };
 
         let device = nvml.device_by_index(index).map_err(|e| match e {
-            nvml_wrapper::error::NvmlError::NotFound => NvmlError::DeviceNotFound(index),
-            other => NvmlError::Unknown(other.to_string()),
+            nvml_wrapper::error::NvmlError::NotFound
+            | nvml_wrapper::error::NvmlError::InvalidArg => NvmlError::DeviceNotFound(index),
+            other => NvmlDevice::convert_error(other),
         })?;
 
         Ok(NvmlDevice::new(device, index))
@@ -64,7 +67,7 @@ impl GpuManager for NvmlManager {
             nvml_wrapper::error::NvmlError::NotFound => {
                 NvmlError::DeviceNotFoundByUuid(uuid.to_string())
             }
-            other => NvmlError::Unknown(other.to_string()),
+            other => NvmlDevice::convert_error(other),
         })?;
 
         // Find the index by iterating through devices
@@ -114,6 +117,14 @@ impl GpuManager for NvmlManager {
             .sys_nvml_version()
             .map_err(|e| NvmlError::Unknown(e.to_string()))
     }
+
+    unsafe fn reinitialize(&mut self) -> Result<(), NvmlError> {
+        // Devices handed out earlier borrow the old instance (see the SAFETY
+        // notes above); the caller guarantees none are left.
+        log::info!("Re-initializing NVML");
+        self.nvml = Self::init_nvml()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
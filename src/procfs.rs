@@ -0,0 +1,293 @@
+//! Process metadata from procfs
+//!
+//! NVML only reports PIDs; names, command lines, owners, start times and
+//! cgroups come from `/proc`. The procfs and passwd locations are configurable
+//! so tests can use a fake tree.
+
+use crate::domain::{ContainerInfo, ContainerRuntime, GpuProcess};
+
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+/// Clock ticks per second used by `/proc/<pid>/stat` (USER_HZ, 100 on Linux)
+const USER_HZ: u64 = 100;
+
+static CONTAINER_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(docker|libpod|cri-containerd|crio)[-/]([0-9a-f]{64})").expect("valid regex")
+});
+static POD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"pod([0-9a-f]{8}[-_][0-9a-f]{4}[-_][0-9a-f]{4}[-_][0-9a-f]{4}[-_][0-9a-f]{12})")
+        .expect("valid regex")
+});
+static BARE_ID_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"/([0-9a-f]{64})(?:\.scope)?$").expect("valid regex"));
+
+/// Reader for process metadata under a procfs root
+#[derive(Debug, Clone)]
+pub struct ProcFs {
+    root: PathBuf,
+    passwd: PathBuf,
+}
+
+impl ProcFs {
+    /// Create a reader for the given procfs root and passwd file
+    pub fn new(root: impl Into<PathBuf>, passwd: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            passwd: passwd.into(),
+        }
+    }
+
+    /// Reader for the running system
+    pub fn system() -> Self {
+        Self::new("/proc", "/etc/passwd")
+    }
+
+    /// Get the procfs root
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Get the arguments of a process
+    pub fn cmdline(&self, pid: u32) -> Option<Vec<String>> {
+        let raw = fs::read(self.root.join(pid.to_string()).join("cmdline")).ok()?;
+        let args: Vec<String> = raw
+            .split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        (!args.is_empty()).then_some(args)
+    }
+
+    /// Get the real UID of a process
+    pub fn uid(&self, pid: u32) -> Option<u32> {
+        let status = fs::read_to_string(self.root.join(pid.to_string()).join("status")).ok()?;
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("Uid:"))
+            .and_then(|uids| uids.split_whitespace().next())
+            .and_then(|uid| uid.parse().ok())
+    }
+
+    /// Get the fields of `/proc/<pid>/stat` following the command name
+    ///
+    /// The command name may contain spaces and parentheses, so fields are
+    /// counted from the last `)`. The first returned field is the state (field 3).
+    fn stat_fields(&self, pid: u32) -> Option<Vec<String>> {
+        let stat = fs::read_to_string(self.root.join(pid.to_string()).join("stat")).ok()?;
+        let rest = &stat[stat.rfind(')')? + 1..];
+        Some(rest.split_whitespace().map(str::to_string).collect())
+    }
+
+    /// Get the parent PID of a process
+    pub fn parent_pid(&self, pid: u32) -> Option<u32> {
+        self.stat_fields(pid)?.get(1)?.parse().ok()
+    }
+
+    /// Get the system boot time in seconds since the Unix epoch
+    pub fn boot_time(&self) -> Option<u64> {
+        let stat = fs::read_to_string(self.root.join("stat")).ok()?;
+        stat.lines()
+            .find_map(|line| line.strip_prefix("btime "))
+            .and_then(|btime| btime.trim().parse().ok())
+    }
+
+    /// Get the start time of a process in seconds since the Unix epoch
+    pub fn start_time(&self, pid: u32) -> Option<u64> {
+        // starttime is field 22, in clock ticks since boot
+        let ticks: u64 = self.stat_fields(pid)?.get(19)?.parse().ok()?;
+        Some(self.boot_time()? + ticks / USER_HZ)
+    }
+
+    /// Get the control group path of a process
+    ///
+    /// Uses the unified (v2) hierarchy when present, otherwise the first v1 entry.
+    pub fn cgroup(&self, pid: u32) -> Option<String> {
+        let cgroup = fs::read_to_string(self.root.join(pid.to_string()).join("cgroup")).ok()?;
+        let entries: Vec<(&str, &str)> = cgroup
+            .lines()
+            .filter_map(|line| {
+                let (hierarchy, rest) = line.split_once(':')?;
+                let (_, path) = rest.split_once(':')?;
+                Some((hierarchy, path))
+            })
+            .collect();
+        entries
+            .iter()
+            .find(|(hierarchy, _)| *hierarchy == "0")
+            .or(entries.first())
+            .map(|(_, path)| path.to_string())
+    }
+
+    /// Look up a user name by UID
+    pub fn user_name(&self, uid: u32) -> Option<String> {
+        let passwd = fs::read_to_string(&self.passwd).ok()?;
+        passwd.lines().find_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let entry_uid = fields.nth(1)?.parse::<u32>().ok()?;
+            (entry_uid == uid).then(|| name.to_string())
+        })
+    }
+
+    /// Fill in the name, command line and user of a GPU process
+    pub fn annotate(&self, process: &mut GpuProcess) {
+        if let Some(args) = self.cmdline(process.pid) {
+            // Use just the executable name
+            let exe = Path::new(&args[0])
+                .file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or(&args[0]);
+            process.name = Some(exe.to_string());
+            process.cmdline = Some(args.join(" "));
+        }
+
+        if let Some(uid) = self.uid(process.pid) {
+            process.user = Some(self.user_name(uid).unwrap_or_else(|| uid.to_string()));
+        }
+
+        process.parent_pid = self.parent_pid(process.pid);
+        process.start_time = self.start_time(process.pid);
+        process.cgroup = self.cgroup(process.pid);
+        process.container = process.cgroup.as_deref().and_then(parse_container);
+    }
+}
+
+impl Default for ProcFs {
+    fn default() -> Self {
+        Self::system()
+    }
+}
+
+/// Find the Docker, Podman or Kubernetes container in a cgroup path
+///
+/// Handles both the systemd (`docker-<id>.scope`) and cgroupfs
+/// (`/docker/<id>`) layouts.
+pub fn parse_container(cgroup: &str) -> Option<ContainerInfo> {
+    let pod = POD_RE
+        .captures(cgroup)
+        .map(|caps| caps[1].replace('_', "-"));
+
+    let (runtime, id) = match CONTAINER_RE.captures(cgroup) {
+        Some(caps) => {
+            let runtime = match &caps[1] {
+                "docker" => ContainerRuntime::Docker,
+                "libpod" => ContainerRuntime::Podman,
+                "cri-containerd" => ContainerRuntime::Containerd,
+                _ => ContainerRuntime::CriO,
+            };
+            (runtime, Some(caps[2].to_string()))
+        }
+        // cgroupfs pods only have the bare container ID
+        None if pod.is_some() => (
+            ContainerRuntime::Unknown,
+            BARE_ID_RE.captures(cgroup).map(|caps| caps[1].to_string()),
+        ),
+        None => return None,
+    };
+
+    Some(ContainerInfo { runtime, id, pod })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ProcessType;
+
+    const DOCKER_ID: &str = "3f2a9c1b7d4e5f60718293a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4";
+
+    #[test]
+    fn test_annotate_from_fake_procfs() {
+        let dir = tempfile::tempdir().unwrap();
+        let proc_dir = dir.path().join("proc/4242");
+        fs::create_dir_all(&proc_dir).unwrap();
+        fs::write(
+            proc_dir.join("cmdline"),
+            b"/usr/bin/python3\0train.py\0--epochs\x0010\0",
+        )
+        .unwrap();
+        fs::write(
+            proc_dir.join("status"),
+            "Name:\tpython3\nUid:\t1000\t1000\t1000\t1000\n",
+        )
+        .unwrap();
+        fs::write(
+            proc_dir.join("stat"),
+            format!("4242 (python (worker)) S 1 {} 250000 0\n", "0 ".repeat(17)),
+        )
+        .unwrap();
+        fs::write(
+            proc_dir.join("cgroup"),
+            format!("0::/system.slice/docker-{}.scope\n", DOCKER_ID),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("proc/stat"),
+            "cpu 1 2 3\nbtime 1700000000\n",
+        )
+        .unwrap();
+        let passwd = dir.path().join("passwd");
+        fs::write(
+            &passwd,
+            "root:x:0:0:root:/root:/bin/bash\nalice:x:1000:1000::/home/alice:/bin/zsh\n",
+        )
+        .unwrap();
+
+        let procfs = ProcFs::new(dir.path().join("proc"), passwd);
+        let mut process = GpuProcess::new(4242, 1024, ProcessType::Compute);
+        procfs.annotate(&mut process);
+
+        assert_eq!(process.name.as_deref(), Some("python3"));
+        assert_eq!(
+            process.cmdline.as_deref(),
+            Some("/usr/bin/python3 train.py --epochs 10")
+        );
+        assert_eq!(process.user.as_deref(), Some("alice"));
+        assert_eq!(process.parent_pid, Some(1));
+        // btime + 250000 ticks / 100
+        assert_eq!(process.start_time, Some(1_700_002_500));
+        let container = process.container.unwrap();
+        assert_eq!(container.runtime, ContainerRuntime::Docker);
+        assert_eq!(container.short_id(), "3f2a9c1b7d4e");
+
+        // Missing processes are left alone
+        let mut gone = GpuProcess::new(1, 0, ProcessType::Graphics);
+        procfs.annotate(&mut gone);
+        assert!(gone.name.is_none() && gone.user.is_none());
+    }
+
+    #[test]
+    fn test_parse_container() {
+        let podman = parse_container(&format!(
+            "/user.slice/user-1000.slice/user@1000.service/user.slice/libpod-{}.scope/container",
+            DOCKER_ID
+        ))
+        .unwrap();
+        assert_eq!(podman.runtime, ContainerRuntime::Podman);
+        assert!(podman.pod.is_none());
+
+        let k8s = parse_container(&format!(
+            "/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1b2c3d4e_0f1a_4b2c_8d3e_9f0a1b2c3d4e.slice/cri-containerd-{}.scope",
+            DOCKER_ID
+        ))
+        .unwrap();
+        assert_eq!(k8s.runtime, ContainerRuntime::Containerd);
+        assert_eq!(
+            k8s.pod.as_deref(),
+            Some("1b2c3d4e-0f1a-4b2c-8d3e-9f0a1b2c3d4e")
+        );
+        assert!(k8s.matches("1b2c3d4e") && k8s.matches("3f2a9c"));
+
+        let cgroupfs = parse_container(&format!(
+            "/kubepods/besteffort/pod1b2c3d4e-0f1a-4b2c-8d3e-9f0a1b2c3d4e/{}",
+            DOCKER_ID
+        ))
+        .unwrap();
+        assert_eq!(cgroupfs.runtime, ContainerRuntime::Unknown);
+        assert_eq!(cgroupfs.id.as_deref(), Some(DOCKER_ID));
+
+        assert!(parse_container("/user.slice/user-1000.slice/session-2.scope").is_none());
+    }
+}
@@ -0,0 +1,324 @@
+//! Telemetry recording (`nvctl record`)
+//!
+//! Samples selected fields of each GPU at a fixed interval and writes one row
+//! per GPU and sample to CSV or NDJSON files.
+
+mod writer;
+
+pub use writer::{RecordFormat, RecordWriter, Rotation};
+
+use crate::domain::ClockType;
+use crate::nvml::{GpuDevice, GpuManager, GpuSnapshot};
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Instant, SystemTime};
+
+/// A recordable GPU reading
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecordField {
+    /// Core temperature (°C)
+    Temp,
+    /// Memory temperature (°C)
+    MemTemp,
+    /// Average fan speed (%)
+    Fan,
+    /// Power draw (W)
+    Power,
+    /// Power limit (W)
+    PowerLimit,
+    /// Graphics clock (MHz)
+    ClockGpu,
+    /// Memory clock (MHz)
+    ClockMem,
+    /// GPU utilization (%)
+    Util,
+    /// Memory controller utilization (%)
+    MemUtil,
+    /// Used VRAM (MiB)
+    VramUsed,
+    /// Performance state (0 = P0)
+    Pstate,
+    /// Clocks throttled by power or thermal limits (1) or not (0)
+    Throttled,
+}
+
+impl RecordField {
+    /// Every field, in column order
+    pub const ALL: [RecordField; 12] = [
+        Self::Temp,
+        Self::MemTemp,
+        Self::Fan,
+        Self::Power,
+        Self::PowerLimit,
+        Self::ClockGpu,
+        Self::ClockMem,
+        Self::Util,
+        Self::MemUtil,
+        Self::VramUsed,
+        Self::Pstate,
+        Self::Throttled,
+    ];
+
+    /// Name used on the command line and as column header
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Temp => "temp",
+            Self::MemTemp => "mem_temp",
+            Self::Fan => "fan",
+            Self::Power => "power",
+            Self::PowerLimit => "power_limit",
+            Self::ClockGpu => "clock_gpu",
+            Self::ClockMem => "clock_mem",
+            Self::Util => "util",
+            Self::MemUtil => "mem_util",
+            Self::VramUsed => "vram_used",
+            Self::Pstate => "pstate",
+            Self::Throttled => "throttled",
+        }
+    }
+
+    /// Position of the field in [`RecordField::ALL`]
+    pub fn position(&self) -> usize {
+        Self::ALL.iter().position(|f| f == self).unwrap_or_default()
+    }
+
+    /// Unit of the recorded value
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Self::Temp | Self::MemTemp => "°C",
+            Self::Fan | Self::Util | Self::MemUtil => "%",
+            Self::Power | Self::PowerLimit => "W",
+            Self::ClockGpu | Self::ClockMem => "MHz",
+            Self::VramUsed => "MiB",
+            Self::Pstate | Self::Throttled => "",
+        }
+    }
+
+    /// Read the field from a device, `None` if it is unavailable
+    pub fn read<D: GpuDevice>(&self, device: &D) -> Option<f64> {
+        match self {
+            Self::Temp => device.temperature().ok().map(|t| t.as_celsius() as f64),
+            Self::MemTemp => device
+                .memory_temperature()
+                .ok()
+                .flatten()
+                .map(|t| t.as_celsius() as f64),
+            Self::Fan => {
+                let count = device.fan_count().ok()?;
+                let speeds: Vec<f64> = (0..count)
+                    .filter_map(|i| device.fan_speed(i).ok())
+                    .map(|s| s.as_percentage() as f64)
+                    .collect();
+                (!speeds.is_empty()).then(|| speeds.iter().sum::<f64>() / speeds.len() as f64)
+            }
+            Self::Power => device
+                .power_usage()
+                .ok()
+                .map(|p| p.as_milliwatts() as f64 / 1000.0),
+            Self::PowerLimit => device
+                .power_limit()
+                .ok()
+                .map(|p| p.as_milliwatts() as f64 / 1000.0),
+            Self::ClockGpu => device
+                .clock_speed(ClockType::Graphics)
+                .ok()
+                .map(|c| c.as_mhz() as f64),
+            Self::ClockMem => device
+                .clock_speed(ClockType::Memory)
+                .ok()
+                .map(|c| c.as_mhz() as f64),
+            Self::Util => device.utilization().ok().map(|u| u.gpu_percent() as f64),
+            Self::MemUtil => device.utilization().ok().map(|u| u.memory_percent() as f64),
+            Self::VramUsed => device.memory_info().ok().map(|m| m.used_mb() as f64),
+            Self::Pstate => device.performance_state().ok().map(|p| p.as_raw() as f64),
+            Self::Throttled => device
+                .throttle_reasons()
+                .ok()
+                .map(|r| f64::from(u8::from(r.is_throttling()))),
+        }
+    }
+}
+
+impl RecordField {
+    /// Take the field from a snapshot, `None` if it was unavailable
+    pub fn read_snapshot(&self, snapshot: &GpuSnapshot) -> Option<f64> {
+        match self {
+            Self::Temp => snapshot
+                .temperature
+                .as_ref()
+                .ok()
+                .map(|t| t.as_celsius() as f64),
+            Self::MemTemp => snapshot
+                .memory_temperature
+                .as_ref()
+                .ok()
+                .and_then(|t| t.as_ref())
+                .map(|t| t.as_celsius() as f64),
+            Self::Fan => {
+                let speeds: Vec<f64> = snapshot
+                    .fan_speeds
+                    .iter()
+                    .filter_map(|s| s.as_ref().ok())
+                    .map(|s| s.as_percentage() as f64)
+                    .collect();
+                (!speeds.is_empty()).then(|| speeds.iter().sum::<f64>() / speeds.len() as f64)
+            }
+            Self::Power => snapshot
+                .power_usage
+                .as_ref()
+                .ok()
+                .map(|p| p.as_milliwatts() as f64 / 1000.0),
+            Self::PowerLimit => snapshot
+                .power_limit
+                .as_ref()
+                .ok()
+                .map(|p| p.as_milliwatts() as f64 / 1000.0),
+            Self::ClockGpu => snapshot
+                .graphics_clock
+                .as_ref()
+                .ok()
+                .map(|c| c.as_mhz() as f64),
+            Self::ClockMem => snapshot
+                .memory_clock
+                .as_ref()
+                .ok()
+                .map(|c| c.as_mhz() as f64),
+            Self::Util => snapshot
+                .utilization
+                .as_ref()
+                .ok()
+                .map(|u| u.gpu_percent() as f64),
+            Self::MemUtil => snapshot
+                .utilization
+                .as_ref()
+                .ok()
+                .map(|u| u.memory_percent() as f64),
+            Self::VramUsed => snapshot
+                .memory_info
+                .as_ref()
+                .ok()
+                .map(|m| m.used_mb() as f64),
+            Self::Pstate => snapshot
+                .performance_state
+                .as_ref()
+                .ok()
+                .map(|p| p.as_raw() as f64),
+            Self::Throttled => snapshot
+                .throttle_reasons
+                .as_ref()
+                .ok()
+                .map(|r| f64::from(u8::from(r.is_throttling()))),
+        }
+    }
+}
+
+impl fmt::Display for RecordField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for RecordField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Self::ALL.iter().map(|f| f.name()).collect();
+                format!(
+                    "unknown field '{}' (expected one of {})",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// One GPU's readings at one point in time
+#[derive(Debug, Clone)]
+pub struct RecordRow {
+    /// Wall-clock time of the sample
+    pub wall_time: SystemTime,
+    /// Seconds since recording started (monotonic clock)
+    pub monotonic_secs: f64,
+    /// Device index
+    pub gpu: u32,
+    /// GPU UUID
+    pub uuid: String,
+    /// Values in the order of the recorded fields
+    pub values: Vec<Option<f64>>,
+}
+
+/// Samples a set of GPUs
+pub struct Recorder {
+    indices: Vec<u32>,
+    fields: Vec<RecordField>,
+    started: Instant,
+}
+
+impl Recorder {
+    /// Create a recorder for GPUs and fields
+    pub fn new(indices: Vec<u32>, fields: Vec<RecordField>) -> Self {
+        Self {
+            indices,
+            fields,
+            started: Instant::now(),
+        }
+    }
+
+    /// Recorded fields
+    pub fn fields(&self) -> &[RecordField] {
+        &self.fields
+    }
+
+    /// Read one row per GPU; GPUs that can't be opened are skipped
+    pub fn sample<M: GpuManager>(&self, manager: &M) -> Vec<RecordRow> {
+        let wall_time = SystemTime::now();
+        let monotonic_secs = self.started.elapsed().as_secs_f64();
+
+        self.indices
+            .iter()
+            .filter_map(|&index| match manager.device_by_index(index) {
+                Ok(device) => Some(RecordRow {
+                    wall_time,
+                    monotonic_secs,
+                    gpu: index,
+                    uuid: device.uuid().unwrap_or_default(),
+                    values: self.fields.iter().map(|f| f.read(&device)).collect(),
+                }),
+                Err(e) => {
+                    log::warn!("Failed to open GPU {}: {}", index, e);
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockManager;
+
+    #[test]
+    fn test_parse_fields_and_sample() {
+        let fields: Vec<RecordField> = "temp,mem_temp,fan,power"
+            .split(',')
+            .map(|s| s.parse().unwrap())
+            .collect();
+        assert!("speed".parse::<RecordField>().is_err());
+
+        let manager = MockManager::new(2);
+        let recorder = Recorder::new(vec![0, 1], fields);
+        let rows = recorder.sample(&manager);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].gpu, 1);
+        assert!(rows[0].values[0].is_some());
+        // Mock GPUs have no memory sensor
+        assert_eq!(rows[0].values[1], None);
+    }
+}
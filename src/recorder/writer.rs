@@ -0,0 +1,326 @@
+//! CSV and NDJSON record files with rotation and optional gzip
+
+use super::{RecordField, RecordRow};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use jiff::Timestamp;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Record file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum RecordFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+impl RecordFormat {
+    /// Guess the format from a file name, defaulting to CSV
+    pub fn from_path(path: &Path) -> Self {
+        let name = path.to_string_lossy();
+        let name = name.strip_suffix(".gz").unwrap_or(&name);
+        if name.ends_with(".ndjson") || name.ends_with(".jsonl") || name.ends_with(".json") {
+            Self::Ndjson
+        } else {
+            Self::Csv
+        }
+    }
+}
+
+/// When to start a new file
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rotation {
+    /// Rotate after this many (uncompressed) bytes
+    pub max_bytes: Option<u64>,
+    /// Rotate after this long
+    pub max_age: Option<Duration>,
+}
+
+impl Rotation {
+    fn is_enabled(&self) -> bool {
+        self.max_bytes.is_some() || self.max_age.is_some()
+    }
+}
+
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Output {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Plain(w) => w,
+            Self::Gzip(w) => w,
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Plain(mut w) => w.flush(),
+            Self::Gzip(w) => w.finish()?.flush(),
+        }
+    }
+}
+
+/// Writes record rows, rotating files as configured
+///
+/// Without rotation rows go to the given path. With rotation, files are
+/// numbered: `run.csv` becomes `run-0001.csv`, `run-0002.csv`, ...
+pub struct RecordWriter {
+    path: PathBuf,
+    format: RecordFormat,
+    gzip: bool,
+    rotation: Rotation,
+    fields: Vec<RecordField>,
+    output: Option<Output>,
+    files: Vec<PathBuf>,
+    bytes: u64,
+    opened_at: Instant,
+}
+
+impl RecordWriter {
+    /// Create a writer; the first file is opened on the first row
+    pub fn new(path: PathBuf, format: RecordFormat, fields: Vec<RecordField>) -> Self {
+        Self {
+            path,
+            format,
+            gzip: false,
+            rotation: Rotation::default(),
+            fields,
+            output: None,
+            files: Vec::new(),
+            bytes: 0,
+            opened_at: Instant::now(),
+        }
+    }
+
+    /// Compress files with gzip
+    pub fn with_gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    /// Rotate files by size and/or age
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Files written so far
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// Write the rows of one sample and flush them to disk
+    pub fn write_rows(&mut self, rows: &[RecordRow]) -> io::Result<()> {
+        if self.should_rotate() {
+            if let Some(output) = self.output.take() {
+                output.finish()?;
+            }
+        }
+        if self.output.is_none() {
+            self.open()?;
+        }
+
+        let mut text = String::new();
+        for row in rows {
+            text.push_str(&self.format_row(row));
+            text.push('\n');
+        }
+        self.write_text(&text)?;
+        // Sync-flush so an interrupted recording stays readable
+        if let Some(output) = &mut self.output {
+            output.writer().flush()?;
+        }
+        Ok(())
+    }
+
+    /// Finish the current file (writes the gzip trailer)
+    pub fn finish(&mut self) -> io::Result<()> {
+        match self.output.take() {
+            Some(output) => output.finish(),
+            None => Ok(()),
+        }
+    }
+
+    fn should_rotate(&self) -> bool {
+        self.output.is_some()
+            && (self.rotation.max_bytes.is_some_and(|max| self.bytes >= max)
+                || self
+                    .rotation
+                    .max_age
+                    .is_some_and(|age| self.opened_at.elapsed() >= age))
+    }
+
+    fn open(&mut self) -> io::Result<()> {
+        let path = self.next_path();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = BufWriter::new(File::create(&path)?);
+        log::info!("Recording to {}", path.display());
+
+        self.output = Some(if self.gzip {
+            Output::Gzip(GzEncoder::new(file, Compression::default()))
+        } else {
+            Output::Plain(file)
+        });
+        self.files.push(path);
+        self.bytes = 0;
+        self.opened_at = Instant::now();
+
+        if self.format == RecordFormat::Csv {
+            let mut header = vec!["wall_time", "monotonic_s", "gpu", "uuid"];
+            header.extend(self.fields.iter().map(|f| f.name()));
+            self.write_text(&format!("{}\n", header.join(",")))?;
+        }
+        Ok(())
+    }
+
+    fn next_path(&self) -> PathBuf {
+        let mut path = if self.rotation.is_enabled() {
+            let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+            let (stem, ext) = match name.find('.') {
+                Some(pos) => name.split_at(pos),
+                None => (name.as_ref(), ""),
+            };
+            self.path
+                .with_file_name(format!("{}-{:04}{}", stem, self.files.len() + 1, ext))
+        } else {
+            self.path.clone()
+        };
+        if self.gzip && path.extension().is_none_or(|ext| ext != "gz") {
+            let mut name = path.into_os_string();
+            name.push(".gz");
+            path = name.into();
+        }
+        path
+    }
+
+    fn write_text(&mut self, text: &str) -> io::Result<()> {
+        if let Some(output) = &mut self.output {
+            output.writer().write_all(text.as_bytes())?;
+            self.bytes += text.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn format_row(&self, row: &RecordRow) -> String {
+        let wall_time = Timestamp::try_from(row.wall_time)
+            .map(|t| t.round(jiff::Unit::Millisecond).unwrap_or(t).to_string())
+            .unwrap_or_default();
+        let monotonic = format!("{:.3}", row.monotonic_secs);
+
+        match self.format {
+            RecordFormat::Csv => {
+                let mut cells = vec![wall_time, monotonic, row.gpu.to_string(), row.uuid.clone()];
+                cells.extend(
+                    row.values
+                        .iter()
+                        .map(|v| v.map(|v| v.to_string()).unwrap_or_default()),
+                );
+                cells.join(",")
+            }
+            RecordFormat::Ndjson => {
+                let mut object = serde_json::Map::new();
+                object.insert("wall_time".into(), wall_time.into());
+                object.insert("monotonic_s".into(), row.monotonic_secs.into());
+                object.insert("gpu".into(), row.gpu.into());
+                object.insert("uuid".into(), row.uuid.clone().into());
+                for (field, value) in self.fields.iter().zip(&row.values) {
+                    object.insert(field.name().into(), (*value).into());
+                }
+                serde_json::Value::Object(object).to_string()
+            }
+        }
+    }
+}
+
+impl Drop for RecordWriter {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            log::warn!("Failed to finish recording: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::time::SystemTime;
+
+    fn row(gpu: u32) -> RecordRow {
+        RecordRow {
+            wall_time: SystemTime::UNIX_EPOCH + Duration::from_millis(1500),
+            monotonic_secs: 0.5,
+            gpu,
+            uuid: format!("GPU-{}", gpu),
+            values: vec![Some(61.0), None],
+        }
+    }
+
+    #[test]
+    fn test_csv_rotates_by_size_with_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RecordWriter::new(
+            dir.path().join("run.csv"),
+            RecordFormat::Csv,
+            vec![RecordField::Temp, RecordField::Fan],
+        )
+        .with_rotation(Rotation {
+            max_bytes: Some(1),
+            max_age: None,
+        });
+
+        writer.write_rows(&[row(0)]).unwrap();
+        writer.write_rows(&[row(1)]).unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(
+            writer.files(),
+            [
+                dir.path().join("run-0001.csv"),
+                dir.path().join("run-0002.csv")
+            ]
+        );
+        let second = fs::read_to_string(dir.path().join("run-0002.csv")).unwrap();
+        assert_eq!(
+            second,
+            "wall_time,monotonic_s,gpu,uuid,temp,fan\n\
+             1970-01-01T00:00:01.5Z,0.500,1,GPU-1,61,\n"
+        );
+    }
+
+    #[test]
+    fn test_gzip_ndjson() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.ndjson");
+        assert_eq!(RecordFormat::from_path(&path), RecordFormat::Ndjson);
+
+        let mut writer = RecordWriter::new(
+            path,
+            RecordFormat::Ndjson,
+            vec![RecordField::Temp, RecordField::Fan],
+        )
+        .with_gzip(true);
+        writer.write_rows(&[row(0)]).unwrap();
+        writer.finish().unwrap();
+
+        let mut text = String::new();
+        GzDecoder::new(File::open(dir.path().join("run.ndjson.gz")).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(text.trim()).unwrap();
+        assert_eq!(value["temp"], 61.0);
+        assert!(value["fan"].is_null());
+        assert_eq!(value["gpu"], 0);
+    }
+}
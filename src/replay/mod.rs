@@ -0,0 +1,412 @@
+//! Replay backend driven by recorded telemetry
+//!
+//! `ReplayManager` and `ReplayDevice` implement the GPU traits on top of a
+//! trace written by `nvctl record`, so the control loop, alerts, health
+//! scoring and the GUI can be run against historic data. Playback follows the
+//! wall clock, optionally sped up and looped. Writes are rejected.
+
+mod trace;
+
+pub use trace::{GpuTrace, Trace, TraceSample};
+
+use crate::domain::{
+    AcousticLimits, ClockSpeed, ClockType, CoolerTarget, DecoderUtilization, EccErrors, EccMode,
+    EncoderUtilization, FanPolicy, FanSpeed, GpuInfo, MemoryInfo, PcieMetrics, PerformanceState,
+    PowerConstraints, PowerLimit, ProcessAccounting, ProcessList, ProcessUtilization, Temperature,
+    ThermalThresholds, ThrottleReasons, Utilization,
+};
+use crate::error::NvmlError;
+use crate::nvml::{GpuDevice, GpuManager};
+use crate::recorder::RecordField;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How to play back a trace
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Trace file
+    pub path: PathBuf,
+    /// Playback speed multiplier
+    pub speed: f64,
+    /// Start over at the end of the trace
+    pub looped: bool,
+}
+
+/// Maps wall-clock time to a position in the trace
+#[derive(Debug, Clone)]
+struct ReplayClock {
+    started: Instant,
+    speed: f64,
+    looped: bool,
+    duration: f64,
+}
+
+impl ReplayClock {
+    fn position_after(&self, elapsed: Duration) -> f64 {
+        let position = elapsed.as_secs_f64() * self.speed;
+        if self.looped && self.duration > 0.0 {
+            position % self.duration
+        } else {
+            position.min(self.duration)
+        }
+    }
+
+    fn position(&self) -> f64 {
+        self.position_after(self.started.elapsed())
+    }
+}
+
+/// GPU manager replaying a recorded trace
+#[derive(Debug, Clone)]
+pub struct ReplayManager {
+    trace: Arc<Trace>,
+    clock: ReplayClock,
+}
+
+impl ReplayManager {
+    /// Load a trace and start playback
+    pub fn open(options: &ReplayOptions) -> io::Result<Self> {
+        let trace = Trace::load(&options.path)?;
+        log::info!(
+            "Replaying {} GPU(s) over {:.1}s from {} at {}x{}",
+            trace.gpus.len(),
+            trace.duration,
+            options.path.display(),
+            options.speed,
+            if options.looped { ", looped" } else { "" }
+        );
+        Ok(Self::new(trace, options.speed, options.looped))
+    }
+
+    /// Start playback of a loaded trace
+    pub fn new(trace: Trace, speed: f64, looped: bool) -> Self {
+        let clock = ReplayClock {
+            started: Instant::now(),
+            speed: if speed > 0.0 { speed } else { 1.0 },
+            looped,
+            duration: trace.duration,
+        };
+        Self {
+            trace: Arc::new(trace),
+            clock,
+        }
+    }
+
+    /// Current position in the trace (seconds since the recording started)
+    pub fn position(&self) -> f64 {
+        self.clock.position()
+    }
+
+    /// Check if playback reached the end of a non-looping trace
+    pub fn is_finished(&self) -> bool {
+        !self.clock.looped && self.position() >= self.trace.duration
+    }
+
+    fn device(&self, pos: usize) -> ReplayDevice {
+        ReplayDevice {
+            trace: self.trace.clone(),
+            pos,
+            clock: self.clock.clone(),
+        }
+    }
+}
+
+impl GpuManager for ReplayManager {
+    type Device = ReplayDevice;
+
+    fn device_count(&self) -> Result<u32, NvmlError> {
+        Ok(self.trace.gpus.len() as u32)
+    }
+
+    fn device_by_index(&self, index: u32) -> Result<Self::Device, NvmlError> {
+        self.trace
+            .gpus
+            .iter()
+            .position(|gpu| gpu.index == index)
+            .map(|pos| self.device(pos))
+            .ok_or(NvmlError::DeviceNotFound(index))
+    }
+
+    fn device_by_uuid(&self, uuid: &str) -> Result<Self::Device, NvmlError> {
+        self.trace
+            .gpus
+            .iter()
+            .position(|gpu| gpu.uuid == uuid)
+            .map(|pos| self.device(pos))
+            .ok_or_else(|| NvmlError::DeviceNotFoundByUuid(uuid.to_string()))
+    }
+
+    fn device_by_name(&self, name: &str) -> Result<Self::Device, NvmlError> {
+        let name_lower = name.to_lowercase();
+        (0..self.trace.gpus.len())
+            .map(|pos| self.device(pos))
+            .find(|device| device.display_name().to_lowercase().contains(&name_lower))
+            .ok_or_else(|| NvmlError::Unknown(format!("No GPU found matching name: {}", name)))
+    }
+
+    fn all_devices(&self) -> Result<Vec<Self::Device>, NvmlError> {
+        // Recorded indices need not be contiguous (`record --gpu 1`)
+        Ok((0..self.trace.gpus.len())
+            .map(|pos| self.device(pos))
+            .collect())
+    }
+
+    fn driver_version(&self) -> Result<String, NvmlError> {
+        Ok("replay".to_string())
+    }
+
+    fn nvml_version(&self) -> Result<String, NvmlError> {
+        Ok("replay".to_string())
+    }
+}
+
+/// A recorded GPU, reporting the sample at the current playback position
+#[derive(Debug, Clone)]
+pub struct ReplayDevice {
+    trace: Arc<Trace>,
+    pos: usize,
+    clock: ReplayClock,
+}
+
+impl ReplayDevice {
+    fn gpu(&self) -> &GpuTrace {
+        &self.trace.gpus[self.pos]
+    }
+
+    fn display_name(&self) -> String {
+        format!("Replay GPU {}", self.gpu().index)
+    }
+
+    fn value(&self, field: RecordField) -> Result<f64, NvmlError> {
+        self.gpu()
+            .sample_at(self.clock.position())
+            .get(field)
+            .ok_or_else(|| NvmlError::NotSupported(format!("{} was not recorded", field)))
+    }
+
+    fn read_only<T>(&self, operation: &str) -> Result<T, NvmlError> {
+        Err(NvmlError::NotSupported(format!(
+            "{} on a replayed GPU",
+            operation
+        )))
+    }
+
+    fn not_recorded<T>(&self, what: &str) -> Result<T, NvmlError> {
+        Err(NvmlError::NotSupported(format!("{} is not recorded", what)))
+    }
+}
+
+impl GpuDevice for ReplayDevice {
+    fn info(&self) -> Result<GpuInfo, NvmlError> {
+        let mut info = GpuInfo::new(self.index(), self.display_name(), self.gpu().uuid.clone());
+        info.fan_count = self.fan_count()?;
+        Ok(info)
+    }
+
+    fn name(&self) -> Result<String, NvmlError> {
+        Ok(self.display_name())
+    }
+
+    fn uuid(&self) -> Result<String, NvmlError> {
+        Ok(self.gpu().uuid.clone())
+    }
+
+    fn index(&self) -> u32 {
+        self.gpu().index
+    }
+
+    fn temperature(&self) -> Result<Temperature, NvmlError> {
+        self.value(RecordField::Temp)
+            .map(|t| Temperature::new(t.round() as i32))
+    }
+
+    fn memory_temperature(&self) -> Result<Option<Temperature>, NvmlError> {
+        Ok(self
+            .value(RecordField::MemTemp)
+            .ok()
+            .map(|t| Temperature::new(t.round() as i32)))
+    }
+
+    fn thermal_thresholds(&self) -> Result<ThermalThresholds, NvmlError> {
+        // Thresholds aren't recorded; report them as unknown
+        Ok(ThermalThresholds::default())
+    }
+
+    fn acoustic_limits(&self) -> Result<AcousticLimits, NvmlError> {
+        self.not_recorded("acoustic limit")
+    }
+
+    fn set_acoustic_limit(&mut self, _temp: Temperature) -> Result<(), NvmlError> {
+        self.read_only("setting the acoustic limit")
+    }
+
+    fn fan_count(&self) -> Result<u32, NvmlError> {
+        // The recorded fan speed is an average; expose it as a single fan
+        Ok(u32::from(self.gpu().has(RecordField::Fan)))
+    }
+
+    fn fan_speed(&self, fan_idx: u32) -> Result<FanSpeed, NvmlError> {
+        if fan_idx >= self.fan_count()? {
+            return Err(NvmlError::InvalidArgument(format!("fan {}", fan_idx)));
+        }
+        let speed = self.value(RecordField::Fan)?;
+        FanSpeed::new(speed.round().clamp(0.0, 100.0) as u8)
+            .map_err(|e| NvmlError::Unknown(e.to_string()))
+    }
+
+    fn set_fan_speed(&mut self, _fan_idx: u32, _speed: FanSpeed) -> Result<(), NvmlError> {
+        self.read_only("setting fan speed")
+    }
+
+    fn fan_policy(&self, _fan_idx: u32) -> Result<FanPolicy, NvmlError> {
+        self.not_recorded("fan policy")
+    }
+
+    fn set_fan_policy(&mut self, _fan_idx: u32, _policy: FanPolicy) -> Result<(), NvmlError> {
+        self.read_only("setting fan policy")
+    }
+
+    fn cooler_target(&self, _fan_idx: u32) -> Result<CoolerTarget, NvmlError> {
+        Ok(CoolerTarget::All)
+    }
+
+    fn power_limit(&self) -> Result<PowerLimit, NvmlError> {
+        self.value(RecordField::PowerLimit)
+            .map(|w| PowerLimit::from_milliwatts((w * 1000.0).round() as u32))
+    }
+
+    fn power_constraints(&self) -> Result<PowerConstraints, NvmlError> {
+        self.not_recorded("power limit range")
+    }
+
+    fn set_power_limit(&mut self, _limit: PowerLimit) -> Result<(), NvmlError> {
+        self.read_only("setting the power limit")
+    }
+
+    fn power_usage(&self) -> Result<PowerLimit, NvmlError> {
+        self.value(RecordField::Power)
+            .map(|w| PowerLimit::from_milliwatts((w * 1000.0).round() as u32))
+    }
+
+    fn clock_speed(&self, clock_type: ClockType) -> Result<ClockSpeed, NvmlError> {
+        let field = match clock_type {
+            ClockType::Graphics | ClockType::SM => RecordField::ClockGpu,
+            ClockType::Memory => RecordField::ClockMem,
+            ClockType::Video => return self.not_recorded("video clock"),
+        };
+        self.value(field)
+            .map(|mhz| ClockSpeed::new(mhz.round() as u32))
+    }
+
+    fn utilization(&self) -> Result<Utilization, NvmlError> {
+        let gpu = self.value(RecordField::Util)?;
+        let memory = self.value(RecordField::MemUtil).unwrap_or(0.0);
+        Ok(Utilization::new(gpu.round() as u8, memory.round() as u8))
+    }
+
+    fn encoder_utilization(&self) -> Result<Option<EncoderUtilization>, NvmlError> {
+        Ok(None)
+    }
+
+    fn decoder_utilization(&self) -> Result<Option<DecoderUtilization>, NvmlError> {
+        Ok(None)
+    }
+
+    fn memory_info(&self) -> Result<MemoryInfo, NvmlError> {
+        self.not_recorded("total VRAM")
+    }
+
+    fn performance_state(&self) -> Result<PerformanceState, NvmlError> {
+        self.value(RecordField::Pstate)
+            .map(|p| PerformanceState::from_raw(p as u32))
+    }
+
+    fn throttle_reasons(&self) -> Result<ThrottleReasons, NvmlError> {
+        self.not_recorded("throttle reasons")
+    }
+
+    fn ecc_mode(&self) -> Result<Option<EccMode>, NvmlError> {
+        Ok(None)
+    }
+
+    fn ecc_errors(&self) -> Result<Option<EccErrors>, NvmlError> {
+        Ok(None)
+    }
+
+    fn pcie_metrics(&self) -> Result<PcieMetrics, NvmlError> {
+        self.not_recorded("PCIe metrics")
+    }
+
+    fn running_processes(&self) -> Result<ProcessList, NvmlError> {
+        Ok(ProcessList::new(Vec::new()))
+    }
+
+    fn process_utilization(
+        &self,
+        _window: Duration,
+    ) -> Result<HashMap<u32, ProcessUtilization>, NvmlError> {
+        Ok(HashMap::new())
+    }
+
+    fn accounting_enabled(&self) -> Result<bool, NvmlError> {
+        Ok(false)
+    }
+
+    fn set_accounting(&mut self, _enabled: bool) -> Result<(), NvmlError> {
+        self.read_only("changing accounting mode")
+    }
+
+    fn accounting_stats(&self) -> Result<Vec<ProcessAccounting>, NvmlError> {
+        self.not_recorded("process accounting")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recorder::RecordFormat;
+
+    fn trace() -> Trace {
+        let csv = "wall_time,monotonic_s,gpu,uuid,temp,fan,power\n\
+                   t,0,0,GPU-A,50,30,100\n\
+                   t,10,0,GPU-A,90,100,250\n";
+        Trace::parse(csv.as_bytes(), RecordFormat::Csv).unwrap()
+    }
+
+    #[test]
+    fn test_clock_speed_and_loop() {
+        let clock = ReplayClock {
+            started: Instant::now(),
+            speed: 4.0,
+            looped: true,
+            duration: 10.0,
+        };
+        assert_eq!(clock.position_after(Duration::from_secs(2)), 8.0);
+        assert_eq!(clock.position_after(Duration::from_secs(3)), 2.0);
+
+        let clock = ReplayClock {
+            looped: false,
+            ..clock
+        };
+        assert_eq!(clock.position_after(Duration::from_secs(3)), 10.0);
+    }
+
+    #[test]
+    fn test_device_reports_recorded_values() {
+        // Fast enough to reach the second sample immediately
+        let manager = ReplayManager::new(trace(), 1e9, false);
+        std::thread::sleep(Duration::from_millis(1));
+        let mut device = manager.device_by_uuid("GPU-A").unwrap();
+
+        assert_eq!(device.temperature().unwrap().as_celsius(), 90);
+        assert_eq!(device.fan_speed(0).unwrap().as_percentage(), 100);
+        assert_eq!(device.power_usage().unwrap().as_watts(), 250);
+        assert!(device.memory_info().is_err());
+        assert!(device.set_fan_speed(0, FanSpeed::new(50).unwrap()).is_err());
+        assert!(manager.is_finished());
+    }
+}
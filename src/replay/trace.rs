@@ -0,0 +1,234 @@
+//! Loading recorded traces
+//!
+//! Reads the CSV and NDJSON files written by `nvctl record`, optionally
+//! gzip-compressed.
+
+use crate::recorder::{RecordField, RecordFormat};
+
+use flate2::read::MultiGzDecoder;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Readings of one GPU at one point of the recording
+#[derive(Debug, Clone)]
+pub struct TraceSample {
+    /// Seconds since the recording started
+    pub offset: f64,
+    /// Values indexed like [`RecordField::ALL`]
+    values: [Option<f64>; RecordField::ALL.len()],
+}
+
+impl TraceSample {
+    /// Get a recorded value
+    pub fn get(&self, field: RecordField) -> Option<f64> {
+        self.values[field.position()]
+    }
+}
+
+/// Recorded samples of one GPU
+#[derive(Debug, Clone)]
+pub struct GpuTrace {
+    /// Device index at recording time
+    pub index: u32,
+    /// GPU UUID
+    pub uuid: String,
+    /// Samples in time order
+    pub samples: Vec<TraceSample>,
+}
+
+impl GpuTrace {
+    /// The last sample taken at or before `offset` (the first one before it starts)
+    pub fn sample_at(&self, offset: f64) -> &TraceSample {
+        let pos = self.samples.partition_point(|s| s.offset <= offset);
+        &self.samples[pos.saturating_sub(1)]
+    }
+
+    /// Check if any sample has a value for `field`
+    pub fn has(&self, field: RecordField) -> bool {
+        self.samples.iter().any(|s| s.get(field).is_some())
+    }
+}
+
+/// A complete recording
+#[derive(Debug, Clone)]
+pub struct Trace {
+    /// GPUs ordered by index
+    pub gpus: Vec<GpuTrace>,
+    /// Offset of the last sample
+    pub duration: f64,
+}
+
+impl Trace {
+    /// Load a trace file, detecting format and compression from its name
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let reader: Box<dyn Read> = if path.extension().is_some_and(|ext| ext == "gz") {
+            Box::new(MultiGzDecoder::new(file))
+        } else {
+            Box::new(file)
+        };
+        Self::parse(BufReader::new(reader), RecordFormat::from_path(path))
+    }
+
+    /// Parse a trace from a reader
+    pub fn parse(reader: impl BufRead, format: RecordFormat) -> io::Result<Self> {
+        let mut gpus: BTreeMap<u32, GpuTrace> = BTreeMap::new();
+        let mut header: Option<Vec<String>> = None;
+
+        for (number, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                // An interrupted gzip recording ends mid-stream; keep what was read
+                Err(e) if number > 0 && e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let row = match format {
+                RecordFormat::Csv => match &header {
+                    None => {
+                        header = Some(line.split(',').map(str::to_string).collect());
+                        continue;
+                    }
+                    Some(header) => parse_csv_row(header, &line),
+                },
+                RecordFormat::Ndjson => parse_json_row(&line),
+            };
+            let (index, uuid, sample) = row.map_err(|message| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {}", number + 1, message),
+                )
+            })?;
+
+            gpus.entry(index)
+                .or_insert_with(|| GpuTrace {
+                    index,
+                    uuid,
+                    samples: Vec::new(),
+                })
+                .samples
+                .push(sample);
+        }
+
+        let mut gpus: Vec<GpuTrace> = gpus.into_values().collect();
+        if gpus.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "trace contains no samples",
+            ));
+        }
+        for gpu in &mut gpus {
+            gpu.samples.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        }
+        let duration = gpus
+            .iter()
+            .filter_map(|gpu| gpu.samples.last())
+            .map(|s| s.offset)
+            .fold(0.0, f64::max);
+
+        Ok(Self { gpus, duration })
+    }
+}
+
+type Row = (u32, String, TraceSample);
+
+fn parse_csv_row(header: &[String], line: &str) -> Result<Row, String> {
+    let mut index = None;
+    let mut uuid = String::new();
+    let mut sample = TraceSample {
+        offset: 0.0,
+        values: Default::default(),
+    };
+
+    for (column, value) in header.iter().zip(line.split(',')) {
+        match column.as_str() {
+            "monotonic_s" => {
+                sample.offset = value
+                    .parse()
+                    .map_err(|_| format!("invalid monotonic_s '{}'", value))?
+            }
+            "gpu" => {
+                index = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid gpu '{}'", value))?,
+                )
+            }
+            "uuid" => uuid = value.to_string(),
+            name => {
+                if let Ok(field) = name.parse::<RecordField>() {
+                    sample.values[field.position()] = value.parse().ok();
+                }
+            }
+        }
+    }
+
+    let index = index.ok_or("missing gpu column")?;
+    Ok((index, uuid, sample))
+}
+
+fn parse_json_row(line: &str) -> Result<Row, String> {
+    let object: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(line).map_err(|e| e.to_string())?;
+
+    let index = object
+        .get("gpu")
+        .and_then(|v| v.as_u64())
+        .ok_or("missing gpu")? as u32;
+    let uuid = object
+        .get("uuid")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let mut sample = TraceSample {
+        offset: object
+            .get("monotonic_s")
+            .and_then(|v| v.as_f64())
+            .ok_or("missing monotonic_s")?,
+        values: Default::default(),
+    };
+    for field in RecordField::ALL {
+        sample.values[field.position()] = object.get(field.name()).and_then(|v| v.as_f64());
+    }
+
+    Ok((index, uuid, sample))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv_and_ndjson() {
+        let csv = "wall_time,monotonic_s,gpu,uuid,temp,fan\n\
+                   t,0.000,0,GPU-A,50,30\n\
+                   t,0.000,1,GPU-B,40,\n\
+                   t,1.000,0,GPU-A,70,60\n";
+        let trace = Trace::parse(csv.as_bytes(), RecordFormat::Csv).unwrap();
+        assert_eq!(trace.gpus.len(), 2);
+        assert_eq!(trace.duration, 1.0);
+        assert_eq!(
+            trace.gpus[0].sample_at(0.5).get(RecordField::Temp),
+            Some(50.0)
+        );
+        assert_eq!(
+            trace.gpus[0].sample_at(5.0).get(RecordField::Fan),
+            Some(60.0)
+        );
+        assert!(!trace.gpus[1].has(RecordField::Fan));
+
+        let ndjson =
+            r#"{"wall_time":"t","monotonic_s":0.5,"gpu":2,"uuid":"GPU-C","temp":66,"fan":null}"#;
+        let trace = Trace::parse(ndjson.as_bytes(), RecordFormat::Ndjson).unwrap();
+        assert_eq!(trace.gpus[0].index, 2);
+        assert_eq!(
+            trace.gpus[0].sample_at(0.0).get(RecordField::Temp),
+            Some(66.0)
+        );
+    }
+}
@@ -4,7 +4,8 @@
 
 use crate::alerts::{Alert, AlertManager, AlertManagerConfig, AlertRule, NotificationManager};
 use crate::error::AppError;
-use crate::nvml::GpuDevice;
+use crate::events::GpuEvent;
+use crate::nvml::{GpuSnapshot, SnapshotScope};
 
 /// Alert service for GPU monitoring
 pub struct AlertService {
@@ -28,6 +29,12 @@ impl AlertService {
         }
     }
 
+    /// Send notifications through these channels instead of the terminal
+    pub fn with_notifications(mut self, notifier: NotificationManager) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
     /// Create a disabled alert service (no-op)
     pub fn disabled() -> Self {
         Self {
@@ -41,22 +48,69 @@ impl AlertService {
         }
     }
 
-    /// Evaluate alerts for a device and send notifications
-    pub fn evaluate<D: GpuDevice>(&mut self, device: &D, gpu_index: u32) -> Result<(), AppError> {
+    /// Evaluate alerts against a snapshot and send notifications
+    pub fn evaluate(&mut self, snapshot: &GpuSnapshot, gpu_index: u32) -> Result<(), AppError> {
         if !self.enabled {
             return Ok(());
         }
 
-        let new_alerts = self.manager.evaluate(device, gpu_index)?;
+        let new_alerts = self.manager.evaluate(snapshot, gpu_index)?;
+
+        // Send notifications for new and resolved alerts
+        let mut alerts = new_alerts;
+        alerts.extend_from_slice(self.manager.just_resolved());
+        if let Err(e) = self.notifier.notify_batch(&alerts) {
+            log::warn!("Failed to send notifications for GPU {}: {}", gpu_index, e);
+        }
+
+        Ok(())
+    }
+
+    /// Evaluate event rules for a GPU event and send notifications
+    pub fn process_event(&mut self, event: &GpuEvent) {
+        if !self.enabled {
+            return;
+        }
 
-        // Send notifications for new alerts
-        for alert in &new_alerts {
-            if let Err(e) = self.notifier.notify_all(alert) {
+        for alert in self.manager.process_event(event) {
+            if let Err(e) = self.notifier.notify_all(&alert) {
                 log::warn!("Failed to send notification for alert {}: {}", alert.id, e);
             }
         }
+    }
 
-        Ok(())
+    /// Raise a GPU lost alert and send notifications
+    pub fn gpu_lost(&mut self, gpu_index: u32, uuid: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        let alert = self.manager.record_gpu_lost(gpu_index, uuid);
+        if let Err(e) = self.notifier.notify_all(&alert) {
+            log::warn!("Failed to send notification for alert {}: {}", alert.id, e);
+        }
+    }
+
+    /// Resolve a GPU lost alert and send notifications
+    pub fn gpu_recovered(&mut self, gpu_index: u32, uuid: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        if let Some(alert) = self.manager.record_gpu_recovered(gpu_index, uuid) {
+            if let Err(e) = self.notifier.notify_all(&alert) {
+                log::warn!("Failed to send notification for alert {}: {}", alert.id, e);
+            }
+        }
+    }
+
+    /// Get the snapshot readings the alert rules evaluate
+    pub fn snapshot_scope(&self) -> SnapshotScope {
+        if self.enabled {
+            self.manager.snapshot_scope()
+        } else {
+            SnapshotScope::CONTROL
+        }
     }
 
     /// Get active alerts
@@ -131,7 +185,7 @@ mod tests {
         device.set_temperature(Temperature::new(85));
 
         // Evaluate should succeed
-        let result = service.evaluate(&device, 0);
+        let result = service.evaluate(&GpuSnapshot::capture(&device), 0);
         assert!(result.is_ok());
 
         // Should have one active alert
@@ -145,7 +199,7 @@ mod tests {
         device.set_temperature(Temperature::new(100));
 
         // Evaluate should succeed but do nothing
-        let result = service.evaluate(&device, 0);
+        let result = service.evaluate(&GpuSnapshot::capture(&device), 0);
         assert!(result.is_ok());
 
         // Should have no active alerts
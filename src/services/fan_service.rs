@@ -3,8 +3,8 @@
 //! Applies fan curves based on GPU temperature.
 
 use crate::domain::{FanCurve, FanPolicy, FanSpeed};
-use crate::error::ServiceError;
-use crate::nvml::GpuDevice;
+use crate::error::{NvmlError, ServiceError};
+use crate::nvml::{GpuDevice, GpuSnapshot};
 
 /// Service for managing fan speed based on temperature
 pub struct FanService {
@@ -18,9 +18,13 @@ impl FanService {
         Self { curve, dry_run }
     }
 
-    /// Apply the fan curve to a device
-    pub fn apply_curve<D: GpuDevice>(&self, device: &mut D) -> Result<FanSpeed, ServiceError> {
-        let temp = device.temperature()?;
+    /// Apply the fan curve to a device using this tick's snapshot
+    pub fn apply_curve<D: GpuDevice>(
+        &self,
+        device: &mut D,
+        snapshot: &GpuSnapshot,
+    ) -> Result<FanSpeed, ServiceError> {
+        let temp = snapshot.temperature.clone()?;
         let target_speed = self.curve.speed_for_temperature(temp.as_celsius());
 
         if self.dry_run {
@@ -32,7 +36,7 @@ impl FanService {
             return Ok(target_speed);
         }
 
-        let fan_count = device.fan_count()?;
+        let fan_count = snapshot.fan_count.clone()?;
         for fan_idx in 0..fan_count {
             device.set_fan_speed(fan_idx, target_speed)?;
         }
@@ -61,6 +65,39 @@ impl FanService {
         Ok(())
     }
 
+    /// Set a fixed speed on one fan, or on every fan
+    pub fn set_speed<D: GpuDevice>(
+        &self,
+        device: &mut D,
+        speed: FanSpeed,
+        fan_index: Option<u32>,
+    ) -> Result<(), ServiceError> {
+        let fan_count = device.fan_count()?;
+        let fans = match fan_index {
+            Some(idx) if idx >= fan_count => {
+                return Err(NvmlError::InvalidArgument(format!(
+                    "fan {} does not exist, the GPU has {} fans",
+                    idx, fan_count
+                ))
+                .into())
+            }
+            Some(idx) => idx..idx + 1,
+            None => 0..fan_count,
+        };
+
+        if self.dry_run {
+            log::info!("DRY RUN: Would set fan speed to {}", speed);
+            return Ok(());
+        }
+
+        for fan_idx in fans {
+            device.set_fan_speed(fan_idx, speed)?;
+        }
+
+        log::debug!("Set fan speed to {}", speed);
+        Ok(())
+    }
+
     /// Get the configured fan curve
     pub fn curve(&self) -> &FanCurve {
         &self.curve
@@ -75,7 +112,8 @@ impl FanService {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::FanCurve;
+    use crate::domain::{FanCurve, Temperature};
+    use crate::mock::MockDevice;
 
     #[test]
     fn test_fan_service_creation() {
@@ -90,4 +128,16 @@ mod tests {
         let service = FanService::new(curve, true);
         assert!(service.is_dry_run());
     }
+
+    #[test]
+    fn test_fan_service_apply_curve_from_snapshot() {
+        let mut device = MockDevice::new(0);
+        device.set_temperature(Temperature::new(90));
+        let snapshot = GpuSnapshot::capture(&device);
+
+        let service = FanService::new(FanCurve::default(), false);
+        let speed = service.apply_curve(&mut device, &snapshot).unwrap();
+        assert_eq!(device.fan_speed(0).unwrap(), speed);
+        assert_eq!(device.fan_speed(1).unwrap(), speed);
+    }
 }
@@ -0,0 +1,507 @@
+//! Batched metric delivery to push sinks
+//!
+//! Samples are encoded per sink as they are recorded and written in batches
+//! on flush. A sink that fails is retried with exponential backoff, and its
+//! undelivered lines are spooled to disk until it accepts writes again.
+//! [`PushWorker`] does all of this on a background thread.
+
+use crate::config::PushConfig;
+use crate::error::SinkError;
+use crate::metrics::Sample;
+use crate::sinks::{self, MetricsSink};
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Delay before the first retry of a failed sink
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Commands a [`PushWorker`] holds before new samples are dropped
+const WORKER_QUEUE: usize = 64;
+
+/// Undelivered lines kept on disk for one sink
+pub struct Spool {
+    path: PathBuf,
+    max_lines: usize,
+    len: usize,
+}
+
+impl Spool {
+    /// Open a spool file, counting lines left over from a previous run
+    pub fn open(path: PathBuf, max_lines: usize) -> Self {
+        let len = File::open(&path)
+            .map(|file| BufReader::new(file).lines().count())
+            .unwrap_or(0);
+        Self {
+            path,
+            max_lines,
+            len,
+        }
+    }
+
+    /// Number of spooled lines
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if nothing is spooled
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Append lines, dropping the oldest ones beyond the size limit
+    pub fn append(&mut self, lines: &[String]) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        if self.len + lines.len() > self.max_lines {
+            let mut kept = self.read()?;
+            kept.extend_from_slice(lines);
+            let dropped = kept.len().saturating_sub(self.max_lines);
+            log::warn!(
+                "Spool {} is full, dropping {} oldest lines",
+                self.path.display(),
+                dropped
+            );
+            kept.drain(..dropped);
+            self.rewrite(&kept)
+        } else {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            for line in lines {
+                writeln!(file, "{}", line)?;
+            }
+            self.len += lines.len();
+            Ok(())
+        }
+    }
+
+    /// Remove and return every spooled line
+    pub fn take(&mut self) -> io::Result<Vec<String>> {
+        let lines = self.read()?;
+        match fs::remove_file(&self.path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        self.len = 0;
+        Ok(lines)
+    }
+
+    fn read(&self) -> io::Result<Vec<String>> {
+        match File::open(&self.path) {
+            Ok(file) => BufReader::new(file).lines().collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn rewrite(&mut self, lines: &[String]) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        for line in lines {
+            writeln!(file, "{}", line)?;
+        }
+        file.sync_all()?;
+        fs::rename(&tmp, &self.path)?;
+        self.len = lines.len();
+        Ok(())
+    }
+}
+
+/// A sink with its queue and retry state
+struct Target {
+    sink: Box<dyn MetricsSink>,
+    pending: VecDeque<String>,
+    spool: Option<Spool>,
+    failures: u32,
+    retry_at: Option<Instant>,
+}
+
+impl Target {
+    /// Queue spooled lines ahead of new ones, oldest first
+    fn restore_spool(&mut self) {
+        let Some(spool) = self.spool.as_mut().filter(|s| !s.is_empty()) else {
+            return;
+        };
+        match spool.take() {
+            Ok(lines) => {
+                log::info!(
+                    "Replaying {} spooled lines to {}",
+                    lines.len(),
+                    self.sink.describe()
+                );
+                for line in lines.into_iter().rev() {
+                    self.pending.push_front(line);
+                }
+            }
+            Err(e) => log::warn!("Failed to read spool: {}", e),
+        }
+    }
+
+    /// Move queued lines to the spool, or drop them if there is none
+    fn spill(&mut self) {
+        let lines: Vec<String> = self.pending.drain(..).collect();
+        match &mut self.spool {
+            Some(spool) => {
+                if let Err(e) = spool.append(&lines) {
+                    log::warn!("Failed to spool {} lines: {}", lines.len(), e);
+                }
+            }
+            None => log::warn!(
+                "Dropping {} lines for {}",
+                lines.len(),
+                self.sink.describe()
+            ),
+        }
+    }
+}
+
+/// Delivers samples to one or more sinks
+pub struct MetricsPusher {
+    targets: Vec<Target>,
+    batch_size: usize,
+    max_backoff: Duration,
+}
+
+impl MetricsPusher {
+    /// Create a pusher without sinks
+    pub fn new(batch_size: usize, max_backoff: Duration) -> Self {
+        Self {
+            targets: Vec::new(),
+            batch_size: batch_size.max(1),
+            max_backoff,
+        }
+    }
+
+    /// Create a pusher for the sinks of a `[push]` config section
+    pub fn from_config(config: &PushConfig) -> Result<Self, SinkError> {
+        let mut pusher = Self::new(
+            config.batch_size,
+            Duration::from_secs(config.max_backoff_seconds),
+        );
+        for sink_config in &config.sinks {
+            let sink = sinks::from_config(sink_config)?;
+            let spool = config.spool.then(|| {
+                Spool::open(
+                    spool_path(&config.spool_dir(), &*sink),
+                    config.max_spool_lines,
+                )
+            });
+            pusher = pusher.with_sink(sink, spool);
+        }
+        Ok(pusher)
+    }
+
+    /// Add a sink, with an optional spool for undelivered lines
+    pub fn with_sink(mut self, sink: Box<dyn MetricsSink>, spool: Option<Spool>) -> Self {
+        self.targets.push(Target {
+            sink,
+            pending: VecDeque::new(),
+            spool,
+            failures: 0,
+            retry_at: None,
+        });
+        self
+    }
+
+    /// Check if no sinks are configured
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Number of lines waiting in memory across all sinks
+    pub fn pending(&self) -> usize {
+        self.targets.iter().map(|t| t.pending.len()).sum()
+    }
+
+    /// Queue samples taken at `timestamp`
+    pub fn record(&mut self, timestamp: SystemTime, samples: &[Sample]) {
+        for target in &mut self.targets {
+            let lines = target.sink.encode(timestamp, samples);
+            target.pending.extend(lines);
+        }
+    }
+
+    /// Write queued lines to every sink that isn't backing off
+    pub fn flush(&mut self, now: Instant) {
+        for target in &mut self.targets {
+            if target.retry_at.is_some_and(|at| now < at) {
+                // Keep memory bounded while the sink is down
+                if target.pending.len() >= self.batch_size {
+                    target.spill();
+                }
+                continue;
+            }
+
+            target.restore_spool();
+            while !target.pending.is_empty() {
+                let count = target.pending.len().min(self.batch_size);
+                let batch: Vec<String> = target.pending.iter().take(count).cloned().collect();
+                match target.sink.write(&batch) {
+                    Ok(()) => {
+                        target.pending.drain(..count);
+                        if target.failures > 0 {
+                            log::info!("{} is accepting writes again", target.sink.describe());
+                        }
+                        target.failures = 0;
+                        target.retry_at = None;
+                    }
+                    Err(e) => {
+                        target.failures += 1;
+                        let backoff = INITIAL_BACKOFF
+                            .saturating_mul(1 << target.failures.min(16).saturating_sub(1))
+                            .min(self.max_backoff);
+                        log::warn!(
+                            "Failed to push to {} (attempt {}), retrying in {:?}: {}",
+                            target.sink.describe(),
+                            target.failures,
+                            backoff,
+                            e
+                        );
+                        target.retry_at = Some(now + backoff);
+                        target.spill();
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+enum Command {
+    Record(SystemTime, Vec<Sample>),
+    Flush,
+}
+
+/// Runs a [`MetricsPusher`] on its own thread
+///
+/// Samples reach the thread over a bounded channel, so slow or unreachable
+/// sinks never hold up the caller. Samples recorded while the channel is
+/// full are dropped. Dropping the worker flushes what is left and waits for
+/// the thread.
+pub struct PushWorker {
+    sender: Option<SyncSender<Command>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PushWorker {
+    /// Move a pusher to a new thread
+    pub fn spawn(mut pusher: MetricsPusher) -> Self {
+        let (sender, receiver) = mpsc::sync_channel(WORKER_QUEUE);
+        let handle = thread::spawn(move || {
+            for command in receiver {
+                match command {
+                    Command::Record(timestamp, samples) => pusher.record(timestamp, &samples),
+                    Command::Flush => pusher.flush(Instant::now()),
+                }
+            }
+            pusher.flush(Instant::now());
+        });
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        }
+    }
+
+    /// Queue samples taken at `timestamp`
+    pub fn record(&self, timestamp: SystemTime, samples: Vec<Sample>) {
+        if !self.send(Command::Record(timestamp, samples)) {
+            log::warn!("Metrics push queue is full, dropping samples");
+        }
+    }
+
+    /// Write queued lines to every sink that isn't backing off
+    ///
+    /// Returns without waiting for the writes. A flush that finds the queue
+    /// full is skipped; the next one delivers the lines.
+    pub fn flush(&self) {
+        self.send(Command::Flush);
+    }
+
+    fn send(&self, command: Command) -> bool {
+        let Some(sender) = &self.sender else {
+            return false;
+        };
+        match sender.try_send(command) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => false,
+            Err(TrySendError::Disconnected(_)) => {
+                log::error!("Metrics push thread stopped");
+                false
+            }
+        }
+    }
+}
+
+impl Drop for PushWorker {
+    fn drop(&mut self) {
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Spool file for a sink, named after its description
+fn spool_path(dir: &Path, sink: &dyn MetricsSink) -> PathBuf {
+    let name: String = sink
+        .describe()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    dir.join(format!("{}.spool", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{Sample, TEMPERATURE};
+    use std::sync::{Arc, Mutex};
+
+    /// Sink that records batches and fails while `down` is set
+    struct TestSink {
+        batches: Arc<Mutex<Vec<Vec<String>>>>,
+        down: Arc<Mutex<bool>>,
+    }
+
+    impl MetricsSink for TestSink {
+        fn describe(&self) -> String {
+            "test".to_string()
+        }
+
+        fn encode(&self, _timestamp: SystemTime, samples: &[Sample]) -> Vec<String> {
+            samples.iter().map(|s| s.value.to_string()).collect()
+        }
+
+        fn write(&mut self, lines: &[String]) -> Result<(), SinkError> {
+            if *self.down.lock().unwrap() {
+                return Err(SinkError::Http("connection refused".to_string()));
+            }
+            self.batches.lock().unwrap().push(lines.to_vec());
+            Ok(())
+        }
+    }
+
+    fn samples(values: &[f64]) -> Vec<Sample> {
+        values
+            .iter()
+            .map(|v| Sample::new(&TEMPERATURE, *v))
+            .collect()
+    }
+
+    #[test]
+    fn test_flush_batches_lines() {
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let sink = TestSink {
+            batches: batches.clone(),
+            down: Arc::new(Mutex::new(false)),
+        };
+        let mut pusher =
+            MetricsPusher::new(2, Duration::from_secs(60)).with_sink(Box::new(sink), None);
+
+        pusher.record(SystemTime::now(), &samples(&[1.0, 2.0, 3.0]));
+        pusher.flush(Instant::now());
+
+        assert_eq!(
+            *batches.lock().unwrap(),
+            vec![
+                vec!["1".to_string(), "2".to_string()],
+                vec!["3".to_string()]
+            ]
+        );
+        assert_eq!(pusher.pending(), 0);
+    }
+
+    #[test]
+    fn test_failed_writes_are_spooled_and_replayed() {
+        let dir = tempfile::tempdir().unwrap();
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let down = Arc::new(Mutex::new(true));
+        let sink = TestSink {
+            batches: batches.clone(),
+            down: down.clone(),
+        };
+        let spool = Spool::open(dir.path().join("test.spool"), 100);
+        let mut pusher =
+            MetricsPusher::new(10, Duration::from_secs(60)).with_sink(Box::new(sink), Some(spool));
+
+        let start = Instant::now();
+        pusher.record(SystemTime::now(), &samples(&[1.0]));
+        pusher.flush(start);
+        assert_eq!(pusher.pending(), 0);
+        assert_eq!(pusher.targets[0].spool.as_ref().unwrap().len(), 1);
+
+        // Still backing off: new lines wait in memory
+        *down.lock().unwrap() = false;
+        pusher.record(SystemTime::now(), &samples(&[2.0]));
+        pusher.flush(start + Duration::from_millis(500));
+        assert!(batches.lock().unwrap().is_empty());
+
+        // After the backoff the spool is replayed ahead of new lines
+        pusher.flush(start + INITIAL_BACKOFF);
+        assert_eq!(
+            *batches.lock().unwrap(),
+            vec![vec!["1".to_string(), "2".to_string()]]
+        );
+        assert!(!dir.path().join("test.spool").exists());
+    }
+
+    /// Sink whose writes wait until the test lets them through
+    struct GatedSink {
+        gate: Arc<Mutex<()>>,
+        batches: Arc<Mutex<Vec<Vec<String>>>>,
+    }
+
+    impl MetricsSink for GatedSink {
+        fn describe(&self) -> String {
+            "gated".to_string()
+        }
+
+        fn encode(&self, _timestamp: SystemTime, samples: &[Sample]) -> Vec<String> {
+            samples.iter().map(|s| s.value.to_string()).collect()
+        }
+
+        fn write(&mut self, lines: &[String]) -> Result<(), SinkError> {
+            let _open = self.gate.lock().unwrap();
+            self.batches.lock().unwrap().push(lines.to_vec());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_worker_does_not_wait_for_sinks() {
+        let gate = Arc::new(Mutex::new(()));
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let sink = GatedSink {
+            gate: gate.clone(),
+            batches: batches.clone(),
+        };
+        let pusher =
+            MetricsPusher::new(100, Duration::from_secs(60)).with_sink(Box::new(sink), None);
+
+        let closed = gate.lock().unwrap();
+        let worker = PushWorker::spawn(pusher);
+        let start = Instant::now();
+        for value in 0..(WORKER_QUEUE * 2) {
+            worker.record(SystemTime::now(), samples(&[value as f64]));
+            worker.flush();
+        }
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        // Once the sink is through, dropping the worker delivers the rest
+        drop(closed);
+        drop(worker);
+        let delivered: usize = batches.lock().unwrap().iter().map(Vec::len).sum();
+        assert!(delivered > 0);
+        assert_eq!(batches.lock().unwrap()[0], vec!["0".to_string()]);
+    }
+}
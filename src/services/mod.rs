@@ -5,10 +5,22 @@
 
 pub mod alert_service;
 pub mod fan_service;
+pub mod metrics_pusher;
 pub mod monitor;
+pub mod mqtt_publisher;
 pub mod power_service;
+pub mod process_history;
+pub mod process_watcher;
+pub mod scheduler;
+pub mod thermal_service;
 
 pub use alert_service::AlertService;
 pub use fan_service::FanService;
-pub use monitor::Monitor;
+pub use metrics_pusher::{MetricsPusher, PushWorker, Spool};
+pub use monitor::{Monitor, MonitorConfig};
+pub use mqtt_publisher::MqttPublisher;
 pub use power_service::PowerService;
+pub use process_history::{ProcessHistory, ProcessRecord};
+pub use process_watcher::ProcessWatcher;
+pub use scheduler::{Clock, ManualClock, ScheduleEntry, Scheduler, SystemClock};
+pub use thermal_service::ThermalService;
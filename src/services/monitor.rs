@@ -2,12 +2,22 @@
 //!
 //! Orchestrates the control loop, applying services at regular intervals.
 
-use crate::domain::{FanCurve, PowerLimit};
-use crate::error::AppError;
-use crate::nvml::{GpuDevice, GpuManager};
-use crate::services::{AlertService, FanService, PowerService};
+use crate::config::{DeviceSettings, GpuIdentity, GpuOverride, ProfileSettings};
+use crate::domain::{FanCurve, FanPolicy, GpuProcess, PowerLimit, Temperature};
+use crate::error::{AppError, NvmlError};
+use crate::events::EventListener;
+use crate::health::HealthCalculator;
+use crate::history::{HistoryGpu, HistoryWriter};
+use crate::metrics;
+use crate::nvml::{DeviceState, DeviceTracker, GpuDevice, GpuManager, GpuSnapshot, SnapshotScope};
+use crate::recorder::RecordField;
+use crate::services::{
+    AlertService, FanService, MetricsPusher, MqttPublisher, PowerService, ProcessHistory,
+    ProcessWatcher, PushWorker, Scheduler, ThermalService,
+};
 
-use std::time::Duration;
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /// Configuration for the monitor
 #[derive(Debug, Clone)]
@@ -24,8 +34,16 @@ pub struct MonitorConfig {
     pub fan_curve: FanCurve,
     /// Optional power limit
     pub power_limit: Option<PowerLimit>,
+    /// Optional acoustic temperature limit
+    pub acoustic_limit: Option<Temperature>,
+    /// Per-GPU overrides, first match wins
+    pub gpu_overrides: Vec<GpuOverride>,
+    /// Named profiles the scheduler and process rules can switch to
+    pub profiles: HashMap<String, ProfileSettings>,
     /// Dry run mode
     pub dry_run: bool,
+    /// Log every GPU's temperature and target fan speed each tick
+    pub verbose: bool,
 }
 
 impl Default for MonitorConfig {
@@ -37,30 +55,93 @@ impl Default for MonitorConfig {
             retry_interval: Duration::from_secs(10),
             fan_curve: FanCurve::default(),
             power_limit: None,
+            acoustic_limit: None,
+            gpu_overrides: Vec::new(),
+            profiles: HashMap::new(),
             dry_run: false,
+            verbose: false,
         }
     }
 }
 
+/// Services applying one set of settings to a device
+struct DeviceServices {
+    section: Option<String>,
+    fan_service: FanService,
+    power_service: PowerService,
+    thermal_service: ThermalService,
+}
+
+impl DeviceServices {
+    fn new(section: Option<String>, settings: &DeviceSettings, dry_run: bool) -> Self {
+        Self {
+            section,
+            fan_service: FanService::new(settings.fan_curve.clone(), dry_run),
+            power_service: PowerService::new(settings.power_limit, dry_run),
+            thermal_service: ThermalService::new(settings.acoustic_limit, dry_run),
+        }
+    }
+}
+
+/// Limits a GPU had before the control loop first set it up
+#[derive(Debug, Clone, Copy)]
+struct OriginalLimits {
+    power_limit: Option<PowerLimit>,
+    acoustic_limit: Option<Temperature>,
+}
+
 /// Control loop monitor
 pub struct Monitor {
     config: MonitorConfig,
-    fan_service: FanService,
-    power_service: PowerService,
+    base: ProfileSettings,
+    defaults: DeviceServices,
+    devices: HashMap<String, DeviceServices>,
     alert_service: Option<AlertService>,
+    events: Option<EventListener>,
+    scheduler: Option<Scheduler>,
+    process_watcher: Option<ProcessWatcher>,
+    process_history: Option<ProcessHistory>,
+    metrics_pusher: Option<PushWorker>,
+    history: Option<HistoryWriter>,
+    mqtt: Option<MqttPublisher>,
+    power_overrides: HashMap<String, PowerLimit>,
+    original_limits: HashMap<String, OriginalLimits>,
+    processes: Vec<GpuProcess>,
+    active_profile: Option<String>,
+    health: HealthCalculator,
 }
 
 impl Monitor {
     /// Create a new monitor with the given configuration
     pub fn new(config: MonitorConfig) -> Self {
-        let fan_service = FanService::new(config.fan_curve.clone(), config.dry_run);
-        let power_service = PowerService::new(config.power_limit, config.dry_run);
+        let base = ProfileSettings {
+            settings: DeviceSettings {
+                fan_curve: config.fan_curve.clone(),
+                power_limit: config.power_limit,
+                acoustic_limit: config.acoustic_limit,
+            },
+            gpu_overrides: config.gpu_overrides.clone(),
+        };
+        let defaults = DeviceServices::new(None, &base.settings, config.dry_run);
 
         Self {
             config,
-            fan_service,
-            power_service,
+            base,
+            defaults,
+            devices: HashMap::new(),
             alert_service: None,
+            events: None,
+            scheduler: None,
+            process_watcher: None,
+            process_history: None,
+            metrics_pusher: None,
+            history: None,
+            mqtt: None,
+            power_overrides: HashMap::new(),
+            original_limits: HashMap::new(),
+            processes: Vec::new(),
+            active_profile: None,
+            health: HealthCalculator::default(),
         }
     }
 
@@ -70,26 +151,155 @@ impl Monitor {
         self
     }
 
+    /// Feed events from an event listener into the alert service each tick
+    pub fn with_event_listener(mut self, events: EventListener) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Switch between profiles according to a schedule
+    pub fn with_scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Switch profiles while matching GPU processes are running
+    ///
+    /// Process rules take precedence over the schedule.
+    pub fn with_process_watcher(mut self, watcher: ProcessWatcher) -> Self {
+        self.process_watcher = Some(watcher);
+        self
+    }
+
+    /// Record finished GPU processes in a rolling history
+    pub fn with_process_history(mut self, history: ProcessHistory) -> Self {
+        self.process_history = Some(history);
+        self
+    }
+
+    /// Push every GPU's metrics to sinks each tick
+    ///
+    /// Sinks are written from a background thread, so they never delay control.
+    pub fn with_metrics_pusher(mut self, pusher: MetricsPusher) -> Self {
+        self.metrics_pusher = Some(PushWorker::spawn(pusher));
+        self
+    }
+
+    /// Append every GPU's readings to the on-disk history each tick
+    pub fn with_history(mut self, history: HistoryWriter) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Publish every GPU's state over MQTT and accept profile and power limit commands
+    ///
+    /// A profile selected over MQTT takes precedence over process rules and
+    /// the schedule until `auto` is selected again.
+    pub fn with_mqtt(mut self, publisher: MqttPublisher) -> Self {
+        self.mqtt = Some(publisher);
+        self
+    }
+
     /// Execute a single control tick on a device
+    ///
+    /// Uses the settings of the device's matching `[[gpus]]` section once it has
+    /// been set up by [`Monitor::run_tick`], and the global settings otherwise.
     pub fn tick<D: GpuDevice>(&mut self, device: &mut D, gpu_index: u32) -> Result<(), AppError> {
-        // Apply fan curve
-        self.fan_service.apply_curve(device)?;
+        // Read the device once; every service decides on the same values
+        let snapshot = GpuSnapshot::capture_scope(device, self.snapshot_scope());
+        if let Some(e) = snapshot.loss_error() {
+            return Err(e.clone().into());
+        }
+
+        let services = snapshot
+            .uuid
+            .as_ref()
+            .ok()
+            .and_then(|uuid| self.devices.get(uuid))
+            .unwrap_or(&self.defaults);
+
+        // A failed write only affects this GPU; losing it ends the tick for it
+        match services.fan_service.apply_curve(device, &snapshot) {
+            Ok(speed) if self.config.verbose => {
+                if let Ok(temp) = &snapshot.temperature {
+                    log::info!("GPU {}: temp={}, target_speed={}", gpu_index, temp, speed);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => Self::warn_unless_lost(e.into(), "apply fan curve", gpu_index)?,
+        }
 
         // Apply power limit if configured
-        self.power_service.apply_limit(device)?;
+        if let Err(e) = services.power_service.apply_limit(device, &snapshot) {
+            Self::warn_unless_lost(e.into(), "apply power limit", gpu_index)?;
+        }
 
         // Evaluate alerts if configured
         if let Some(alert_service) = &mut self.alert_service {
-            alert_service.evaluate(device, gpu_index)?;
+            alert_service.evaluate(&snapshot, gpu_index)?;
+        }
+
+        if let Some(pusher) = &self.metrics_pusher {
+            let throttle = snapshot.throttle_reasons.as_ref().ok();
+            let health = self.health.calculate_snapshot(&snapshot);
+            pusher.record(
+                SystemTime::now(),
+                metrics::gpu_samples(&snapshot, throttle, health.as_ref()),
+            );
+        }
+
+        if let Some(mqtt) = &mut self.mqtt {
+            mqtt.publish_state(&snapshot);
+        }
+
+        if let (Some(history), Ok(uuid)) = (&mut self.history, &snapshot.uuid) {
+            let gpu = HistoryGpu {
+                uuid: uuid.clone(),
+                index: gpu_index,
+                name: snapshot.name.clone().unwrap_or_default(),
+            };
+            let values: Vec<Option<f64>> = RecordField::ALL
+                .iter()
+                .map(|f| f.read_snapshot(&snapshot))
+                .collect();
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs() as i64);
+            if let Err(e) = history.record(&gpu, now, &values) {
+                log::warn!(
+                    "Failed to write history {}: {}",
+                    history.store().dir().display(),
+                    e
+                );
+            }
         }
 
         Ok(())
     }
 
+    /// Readings the configured consumers of a tick's snapshot use
+    fn snapshot_scope(&self) -> SnapshotScope {
+        if self.metrics_pusher.is_some() || self.mqtt.is_some() || self.history.is_some() {
+            return SnapshotScope::ALL;
+        }
+        self.alert_service
+            .as_ref()
+            .map_or(SnapshotScope::CONTROL, AlertService::snapshot_scope)
+    }
+
     /// Run the control loop
-    pub fn run<M: GpuManager>(&mut self, manager: &M, gpu_indices: &[u32]) -> Result<(), AppError> {
+    ///
+    /// GPUs are tracked by UUID so control follows them across driver reloads
+    /// and index reordering. Lost GPUs are skipped until they come back.
+    pub fn run<M: GpuManager>(
+        &mut self,
+        manager: &mut M,
+        gpu_indices: &[u32],
+    ) -> Result<(), AppError> {
+        let mut tracker = DeviceTracker::from_indices(manager, gpu_indices)?;
+
         loop {
-            match self.run_tick(manager, gpu_indices) {
+            match self.run_tick(manager, &mut tracker) {
                 Ok(()) => {}
                 Err(e) => {
                     log::error!("Control tick failed: {}", e);
@@ -113,18 +323,270 @@ impl Monitor {
         Ok(())
     }
 
-    fn run_tick<M: GpuManager>(
+    /// Execute one control tick across all tracked GPUs
+    ///
+    /// Re-initializes the manager with backoff after errors that require it,
+    /// and reapplies the fan policy to GPUs that are opened for the first
+    /// time or recovered.
+    pub fn run_tick<M: GpuManager>(
         &mut self,
-        manager: &M,
-        gpu_indices: &[u32],
+        manager: &mut M,
+        tracker: &mut DeviceTracker,
     ) -> Result<(), AppError> {
-        for &idx in gpu_indices {
-            let mut device = manager.device_by_index(idx)?;
-            self.tick(&mut device, idx)?;
+        if let (Some(events), Some(alert_service)) = (&self.events, &mut self.alert_service) {
+            for event in events.drain() {
+                log::info!("GPU event: {}", event);
+                alert_service.process_event(&event);
+            }
+        }
+
+        if let Some(mqtt) = &mut self.mqtt {
+            for (uuid, limit) in mqtt.poll() {
+                log::info!("Setting power limit of GPU {} to {} over MQTT", uuid, limit);
+                self.devices.remove(&uuid);
+                self.power_overrides.insert(uuid, limit);
+            }
+        }
+
+        self.update_profile();
+        let mut processes = Vec::new();
+
+        // SAFETY: devices are opened per tick below and none outlive a tick
+        unsafe { tracker.reinit_if_due(manager) };
+
+        for pos in 0..tracker.len() {
+            let result =
+                tracker
+                    .open(manager, pos)
+                    .map_err(AppError::from)
+                    .and_then(|mut device| {
+                        let previous = tracker.mark_online(pos);
+                        let index = tracker.gpus()[pos].index;
+                        let uuid = &tracker.gpus()[pos].uuid;
+                        if previous != DeviceState::Online || !self.devices.contains_key(uuid) {
+                            self.setup_device(&mut device, index);
+                        }
+                        if previous == DeviceState::Lost {
+                            let uuid = &tracker.gpus()[pos].uuid;
+                            log::info!("GPU {} ({}) recovered", index, uuid);
+                            if let Some(alert_service) = &mut self.alert_service {
+                                alert_service.gpu_recovered(index, uuid);
+                            }
+                        }
+                        self.tick(&mut device, index)?;
+
+                        if self.process_watcher.is_some() || self.process_history.is_some() {
+                            match device.running_processes() {
+                                Ok(mut list) => {
+                                    if let Some(history) = &mut self.process_history {
+                                        if let Ok(util) =
+                                            device.process_utilization(self.config.interval)
+                                        {
+                                            list.apply_utilization(&util);
+                                        }
+                                        let now = SystemTime::now()
+                                            .duration_since(UNIX_EPOCH)
+                                            .map_or(0, |d| d.as_secs());
+                                        if let Err(e) = history.observe(uuid, &list.processes, now)
+                                        {
+                                            log::warn!(
+                                                "Failed to write process history {}: {}",
+                                                history.path().display(),
+                                                e
+                                            );
+                                        }
+                                    }
+                                    processes.extend(list.processes);
+                                }
+                                Err(e) => log::debug!("No processes for GPU {}: {}", index, e),
+                            }
+                        }
+                        Ok(())
+                    });
+
+            match result {
+                Ok(()) => {}
+                Err(e) if e.as_nvml().is_some_and(Self::is_loss) => {
+                    if e.as_nvml().is_some_and(NvmlError::requires_reinit) {
+                        tracker.request_reinit();
+                    }
+                    if tracker.mark_lost(pos) {
+                        let gpu = &tracker.gpus()[pos];
+                        log::error!("GPU {} ({}) lost: {}", gpu.index, gpu.uuid, e);
+                        if let Some(alert_service) = &mut self.alert_service {
+                            alert_service.gpu_lost(gpu.index, &gpu.uuid);
+                        }
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        self.processes = processes;
+        if let Some(pusher) = &self.metrics_pusher {
+            pusher.flush();
+        }
+        Ok(())
+    }
+
+    /// Apply the profile selected over MQTT, by process rules or the schedule if it changed
+    ///
+    /// Uses the processes seen on the previous tick. Devices are set up again
+    /// on their next tick with the new settings.
+    fn update_profile(&mut self) {
+        if self.scheduler.is_none() && self.process_watcher.is_none() && self.mqtt.is_none() {
+            return;
+        }
+
+        let selected = self
+            .mqtt
+            .as_ref()
+            .and_then(|mqtt| mqtt.selected_profile())
+            .map(str::to_string);
+        let triggered = self.process_watcher.as_mut().and_then(|watcher| {
+            watcher.update(&self.processes, Instant::now());
+            watcher.active_profile().map(str::to_string)
+        });
+        let profile = selected.or(triggered).or_else(|| {
+            self.scheduler
+                .as_ref()
+                .and_then(|scheduler| scheduler.active().profile)
+        });
+        if profile == self.active_profile {
+            return;
+        }
+
+        match &profile {
+            Some(name) if !self.config.profiles.contains_key(name) => {
+                log::warn!("Profile '{}' is not defined", name);
+            }
+            Some(name) => log::info!("Switching to profile '{}'", name),
+            None => log::info!("Switching to default settings"),
         }
+
+        self.active_profile = profile;
+        self.defaults =
+            DeviceServices::new(None, &self.profile_settings().settings, self.config.dry_run);
+        self.devices.clear();
+    }
+
+    /// Settings of the active profile, or the configured settings
+    fn profile_settings(&self) -> &ProfileSettings {
+        self.active_profile
+            .as_ref()
+            .and_then(|name| self.config.profiles.get(name))
+            .unwrap_or(&self.base)
+    }
+
+    fn is_loss(err: &NvmlError) -> bool {
+        err.is_gpu_lost() || err.requires_reinit()
+    }
+
+    /// Log a failed write, passing on errors that mean the GPU is gone
+    fn warn_unless_lost(err: AppError, action: &str, gpu_index: u32) -> Result<(), AppError> {
+        if err.as_nvml().is_some_and(Self::is_loss) {
+            return Err(err);
+        }
+        log::warn!("Failed to {} on GPU {}: {}", action, gpu_index, err);
         Ok(())
     }
 
+    /// Resolve settings for a newly opened device and take control of it
+    fn setup_device<D: GpuDevice>(&mut self, device: &mut D, gpu_index: u32) {
+        let identity = match GpuIdentity::from_device(device) {
+            Ok(identity) => identity,
+            Err(e) => {
+                log::warn!("Failed to identify GPU {}: {}", gpu_index, e);
+                return;
+            }
+        };
+
+        let profile = self.profile_settings();
+        let mut services = match profile
+            .gpu_overrides
+            .iter()
+            .find(|o| o.selector.matches(&identity))
+        {
+            Some(o) => {
+                log::info!("GPU {} uses [[gpus]] section '{}'", gpu_index, o.name);
+                DeviceServices::new(Some(o.name.clone()), &o.settings, self.config.dry_run)
+            }
+            None => DeviceServices::new(None, &profile.settings, self.config.dry_run),
+        };
+        if let Some(limit) = self.power_overrides.get(&identity.uuid) {
+            services.power_service = PowerService::new(Some(*limit), self.config.dry_run);
+        }
+
+        if let Err(e) = services.fan_service.set_policy(device, FanPolicy::Manual) {
+            log::warn!("Failed to set fan policy on GPU {}: {}", gpu_index, e);
+        }
+        if let Err(e) = services.thermal_service.apply_limit(device) {
+            log::warn!("Failed to set acoustic limit on GPU {}: {}", gpu_index, e);
+        }
+
+        let original = *self
+            .original_limits
+            .entry(identity.uuid.clone())
+            .or_insert_with(|| OriginalLimits {
+                power_limit: device.power_limit().ok(),
+                acoustic_limit: device.acoustic_limits().ok().and_then(|l| l.current),
+            });
+        self.restore_limits(device, &services, original, gpu_index);
+
+        self.devices.insert(identity.uuid, services);
+    }
+
+    /// Put back the original limits that the new settings leave unset
+    ///
+    /// A profile's limits stay on the card after switching away from it, so
+    /// reverting to settings without limits must undo them.
+    fn restore_limits<D: GpuDevice>(
+        &self,
+        device: &mut D,
+        services: &DeviceServices,
+        original: OriginalLimits,
+        gpu_index: u32,
+    ) {
+        let power_limit = original
+            .power_limit
+            .filter(|_| services.power_service.target_limit().is_none())
+            .filter(|&limit| device.power_limit().ok() != Some(limit));
+        if let Some(limit) = power_limit {
+            if self.config.dry_run {
+                log::info!("DRY RUN: Would restore power limit to {}", limit);
+            } else if let Err(e) = device.set_power_limit(limit) {
+                log::warn!("Failed to restore power limit on GPU {}: {}", gpu_index, e);
+            } else {
+                log::info!("Restored power limit {} on GPU {}", limit, gpu_index);
+            }
+        }
+
+        let acoustic_limit = original
+            .acoustic_limit
+            .filter(|_| services.thermal_service.acoustic_limit().is_none())
+            .filter(|&limit| device.acoustic_limits().ok().and_then(|l| l.current) != Some(limit));
+        if acoustic_limit.is_some() {
+            let thermal_service = ThermalService::new(acoustic_limit, self.config.dry_run);
+            if let Err(e) = thermal_service.apply_limit(device) {
+                log::warn!(
+                    "Failed to restore acoustic limit on GPU {}: {}",
+                    gpu_index,
+                    e
+                );
+            }
+        }
+    }
+
+    /// Get the name of the `[[gpus]]` section applied to a GPU, if any
+    pub fn section_for(&self, uuid: &str) -> Option<&str> {
+        self.devices.get(uuid).and_then(|s| s.section.as_deref())
+    }
+
+    /// Get the name of the selected, scheduled or process-triggered profile in effect, if any
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_profile.as_deref()
+    }
+
     /// Get the monitor configuration
     pub fn config(&self) -> &MonitorConfig {
         &self.config
@@ -134,6 +596,9 @@ impl Monitor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::alerts::MetricType;
+    use crate::config::{Config, GpuSection, GpuSelector};
+    use crate::mock::{MockDevice, MockManager};
 
     #[test]
     fn test_monitor_config_default() {
@@ -149,4 +614,268 @@ mod tests {
         let monitor = Monitor::new(config);
         assert!(!monitor.config().dry_run);
     }
+
+    fn test_monitor() -> Monitor {
+        let config = MonitorConfig {
+            single_use: true,
+            retry: false,
+            ..MonitorConfig::default()
+        };
+        Monitor::new(config).with_alert_service(AlertService::new(
+            crate::alerts::AlertManagerConfig::default(),
+            Vec::new(),
+        ))
+    }
+
+    #[test]
+    fn test_run_tick_recovers_lost_gpu() {
+        let mut manager = MockManager::new(2);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0, 1]).unwrap();
+        let mut monitor = test_monitor();
+
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert!(!tracker.any_lost());
+
+        // GPU 1 falls off the bus; GPU 0 keeps being controlled
+        manager.set_gpu_lost(1, true);
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(tracker.gpus()[1].state, DeviceState::Lost);
+        assert_eq!(tracker.gpus()[0].state, DeviceState::Online);
+        let alerts = monitor.alert_service.as_ref().unwrap().active_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].metric, MetricType::GpuLost);
+
+        manager.set_gpu_lost(1, false);
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert!(!tracker.any_lost());
+        assert!(manager.reinit_count() >= 1);
+        let service = monitor.alert_service.as_ref().unwrap();
+        assert!(service.active_alerts().is_empty());
+        assert_eq!(service.history().len(), 1);
+    }
+
+    #[test]
+    fn test_run_tick_continues_after_failed_write() {
+        use crate::domain::PowerConstraints;
+
+        // GPU 0 cannot take the configured 350W limit; GPU 1 can
+        let mut manager = MockManager::with_devices(vec![
+            MockDevice::new(0).with_power_constraints(PowerConstraints::new(
+                PowerLimit::from_watts(100),
+                PowerLimit::from_watts(250),
+                PowerLimit::from_watts(200),
+            )),
+            MockDevice::new(1),
+        ]);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0, 1]).unwrap();
+        let mut monitor = Monitor::new(MonitorConfig {
+            power_limit: Some(PowerLimit::from_watts(350)),
+            ..MonitorConfig::default()
+        });
+
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert!(!tracker.any_lost());
+        assert!(monitor.devices.contains_key("GPU-MOCK-0001"));
+    }
+
+    #[test]
+    fn test_run_tick_applies_matching_section() {
+        let mut manager = MockManager::with_devices(vec![
+            MockDevice::new(0),
+            MockDevice::new(1).with_name("NVIDIA GeForce RTX 4090"),
+        ]);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0, 1]).unwrap();
+
+        let config = Config {
+            gpus: vec![GpuSection {
+                label: Some("big".to_string()),
+                selector: GpuSelector {
+                    name: Some("*4090".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }],
+            ..Config::default()
+        };
+        let mut monitor = Monitor::new(MonitorConfig {
+            gpu_overrides: config.gpu_overrides().unwrap(),
+            ..MonitorConfig::default()
+        });
+
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(monitor.section_for("GPU-MOCK-0000"), None);
+        assert_eq!(monitor.section_for("GPU-MOCK-0001"), Some("big"));
+    }
+
+    #[test]
+    fn test_run_tick_switches_scheduled_profile() {
+        use crate::services::ManualClock;
+
+        let config: Config = toml::from_str(
+            r#"
+            [power]
+            limit_watts = 300
+
+            [profiles.quiet.power]
+            limit_watts = 200
+
+            [schedule]
+            timezone = "UTC"
+
+            [[schedule.rules]]
+            profile = "quiet"
+            start = "22:00"
+            end = "07:00"
+            "#,
+        )
+        .unwrap();
+
+        let clock = ManualClock::new("2024-06-12T12:00:00Z".parse().unwrap());
+        let scheduler = Scheduler::new(&config.schedule)
+            .unwrap()
+            .with_clock(clock.clone());
+        let profiles = HashMap::from([(
+            "quiet".to_string(),
+            config.profile_settings("quiet").unwrap().unwrap(),
+        )]);
+        let mut monitor = Monitor::new(MonitorConfig {
+            power_limit: config.base_settings().unwrap().power_limit,
+            profiles,
+            ..MonitorConfig::default()
+        })
+        .with_scheduler(scheduler);
+
+        let mut manager = MockManager::new(1);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0]).unwrap();
+        // Mock devices don't keep writes, so check the limit the monitor targets
+        let power_limit = |monitor: &Monitor| {
+            monitor.devices["GPU-MOCK-0000"]
+                .power_service
+                .target_limit()
+                .unwrap()
+                .as_watts()
+        };
+
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(monitor.active_profile(), None);
+        assert_eq!(power_limit(&monitor), 300);
+
+        clock.set("2024-06-12T23:00:00Z".parse().unwrap());
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(monitor.active_profile(), Some("quiet"));
+        assert_eq!(power_limit(&monitor), 200);
+
+        clock.set("2024-06-13T08:00:00Z".parse().unwrap());
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(monitor.active_profile(), None);
+        assert_eq!(power_limit(&monitor), 300);
+    }
+
+    #[test]
+    fn test_run_tick_switches_profile_for_process() {
+        use crate::domain::ProcessType;
+
+        let config: Config = toml::from_str(
+            r#"
+            [profiles.render.power]
+            limit_watts = 380
+
+            [process_triggers]
+            debounce_secs = 0
+
+            [[process_triggers.rules]]
+            profile = "render"
+            name = "blender"
+            "#,
+        )
+        .unwrap();
+        let profiles = HashMap::from([(
+            "render".to_string(),
+            config.profile_settings("render").unwrap().unwrap(),
+        )]);
+        let mut monitor = Monitor::new(MonitorConfig {
+            profiles,
+            ..MonitorConfig::default()
+        })
+        .with_process_watcher(ProcessWatcher::new(&config.process_triggers).unwrap());
+
+        let mut manager = MockManager::new(2);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0, 1]).unwrap();
+
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(monitor.active_profile(), None);
+
+        // Blender starts on GPU 1; the profile applies from the next tick
+        manager.set_processes(
+            1,
+            vec![GpuProcess::with_name(
+                7,
+                "blender".to_string(),
+                1 << 30,
+                ProcessType::Graphics,
+            )],
+        );
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(monitor.active_profile(), Some("render"));
+        let limit = monitor.devices["GPU-MOCK-0000"]
+            .power_service
+            .target_limit();
+        assert_eq!(limit.unwrap().as_watts(), 380);
+        let device = manager.device_by_index(0).unwrap();
+        assert_eq!(device.power_limit().unwrap().as_watts(), 380);
+
+        // And reverts once it exits
+        manager.set_processes(1, Vec::new());
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(monitor.active_profile(), None);
+
+        // The profile's power limit does not outlive it
+        let device = manager.device_by_index(0).unwrap();
+        assert_eq!(device.power_limit().unwrap().as_watts(), 300);
+    }
+
+    #[test]
+    fn test_run_tick_records_process_history() {
+        use crate::domain::ProcessType;
+        use crate::services::ProcessHistory;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("processes.jsonl");
+        let mut monitor = test_monitor().with_process_history(ProcessHistory::new(&path, 10));
+        let mut manager = MockManager::new(1);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0]).unwrap();
+
+        manager.set_processes(0, vec![GpuProcess::new(9, 1 << 20, ProcessType::Compute)]);
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert!(ProcessHistory::load(&path).unwrap().is_empty());
+
+        manager.set_processes(0, Vec::new());
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        let records = ProcessHistory::load(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].gpu_uuid, "GPU-MOCK-0000");
+        assert_eq!(records[0].max_memory, 1 << 20);
+        // Idle processes count as 0% utilization
+        assert_eq!(records[0].sm_utilization, Some(0));
+    }
+
+    #[test]
+    fn test_run_tick_reinitializes_after_driver_reload() {
+        let mut manager = MockManager::new(2);
+        let mut tracker = DeviceTracker::from_indices(&manager, &[0, 1]).unwrap();
+        let mut monitor = test_monitor();
+
+        manager.invalidate();
+        manager.swap_devices(0, 1);
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert!(tracker.any_lost());
+
+        monitor.run_tick(&mut manager, &mut tracker).unwrap();
+        assert_eq!(manager.reinit_count(), 1);
+        assert!(!tracker.any_lost());
+        assert_eq!(tracker.gpus()[0].index, 1);
+        assert_eq!(tracker.gpus()[1].index, 0);
+    }
 }
@@ -0,0 +1,323 @@
+//! MQTT publishing for the control loop
+//!
+//! Publishes each GPU's state every tick, announces GPUs to Home Assistant
+//! the first time they are seen after connecting, and turns messages on the
+//! command topics into power limit requests and a profile selection.
+
+use crate::config::MqttConfig;
+use crate::domain::{PowerConstraints, PowerLimit};
+use crate::error::MqttError;
+use crate::health::HealthCalculator;
+use crate::mqtt::{self, Command, DiscoveryGpu, MqttClient, Topics, AUTO_PROFILE, ONLINE};
+use crate::nvml::GpuSnapshot;
+
+use std::collections::{HashMap, HashSet};
+
+/// GPU whose state has been published
+struct PublishedGpu {
+    uuid: String,
+    constraints: Option<PowerConstraints>,
+}
+
+/// Publishes state to an MQTT broker and collects commands
+pub struct MqttPublisher {
+    client: MqttClient,
+    topics: Topics,
+    client_id: String,
+    discovery: bool,
+    profiles: Vec<String>,
+    selected: Option<String>,
+    connections: u64,
+    announced: HashSet<String>,
+    gpus: HashMap<String, PublishedGpu>,
+}
+
+impl MqttPublisher {
+    /// Connect to the configured broker
+    ///
+    /// `profiles` are the names offered by the Home Assistant profile selector.
+    pub fn connect(config: &MqttConfig, mut profiles: Vec<String>) -> Result<Self, MqttError> {
+        let client_id = config.client_id();
+        let topics = Topics::new(&config.base_topic, &config.discovery_prefix);
+        let client = MqttClient::connect(config, &client_id, &topics.status())?;
+        profiles.sort();
+
+        Ok(Self {
+            client,
+            topics,
+            client_id,
+            discovery: config.discovery,
+            profiles,
+            selected: None,
+            connections: 0,
+            announced: HashSet::new(),
+            gpus: HashMap::new(),
+        })
+    }
+
+    /// Profile selected over MQTT, if any
+    pub fn selected_profile(&self) -> Option<&str> {
+        self.selected.as_deref()
+    }
+
+    /// Handle received commands, returning requested power limits by GPU UUID
+    ///
+    /// Limits outside the GPU's allowed range are rejected here so a bad
+    /// command cannot stop the control loop.
+    pub fn poll(&mut self) -> Vec<(String, PowerLimit)> {
+        self.sync_connection();
+
+        let mut limits = Vec::new();
+        for (topic, payload) in self.client.take_messages() {
+            match self.topics.parse_command(&topic, &payload) {
+                Some(Command::SetPowerLimit { node, watts }) => {
+                    let Some(gpu) = self.gpus.get(&node) else {
+                        log::warn!("MQTT power limit for unknown GPU '{}'", node);
+                        continue;
+                    };
+                    let limit = PowerLimit::from_watts(watts);
+                    if let Some(Err(e)) = gpu.constraints.as_ref().map(|c| limit.validate(c)) {
+                        log::warn!("Ignoring MQTT power limit for GPU {}: {}", gpu.uuid, e);
+                        continue;
+                    }
+                    limits.push((gpu.uuid.clone(), limit));
+                }
+                Some(Command::SetProfile(Some(name))) if !self.profiles.contains(&name) => {
+                    log::warn!("Ignoring MQTT selection of unknown profile '{}'", name);
+                }
+                Some(Command::SetProfile(profile)) => {
+                    self.selected = profile;
+                    self.publish_profile();
+                }
+                None => log::warn!("Ignoring invalid MQTT command on {}", topic),
+            }
+        }
+        limits
+    }
+
+    /// Publish one GPU's state, announcing it first if needed
+    pub fn publish_state(&mut self, snapshot: &GpuSnapshot) {
+        self.sync_connection();
+        let Ok(uuid) = &snapshot.uuid else {
+            return;
+        };
+        let node = mqtt::node_id(uuid);
+        let constraints = snapshot.power_constraints.clone().ok();
+
+        if self.discovery && !self.announced.contains(uuid) {
+            let gpu = DiscoveryGpu {
+                uuid,
+                name: snapshot.name.as_deref().unwrap_or("NVIDIA GPU"),
+                constraints: constraints.as_ref(),
+            };
+            for (topic, payload) in mqtt::gpu_discovery(&self.topics, &gpu, snapshot) {
+                self.send(&topic, &payload, true);
+            }
+        }
+        self.announced.insert(uuid.clone());
+        self.gpus.insert(
+            node.clone(),
+            PublishedGpu {
+                uuid: uuid.clone(),
+                constraints,
+            },
+        );
+
+        let health = HealthCalculator::default().calculate_snapshot(snapshot);
+        let payload = mqtt::state_payload(snapshot, health.as_ref());
+        self.send(&self.topics.state(&node), &payload, false);
+    }
+
+    /// Restore subscriptions and retained topics after each (re)connect
+    fn sync_connection(&mut self) {
+        let connections = self.client.connections();
+        if connections == self.connections {
+            return;
+        }
+        self.connections = connections;
+        self.announced.clear();
+
+        for filter in self.topics.command_filters() {
+            if let Err(e) = self.client.subscribe(&filter) {
+                log::warn!("Failed to subscribe to {}: {}", filter, e);
+            }
+        }
+        self.send(&self.topics.status(), ONLINE, true);
+        if self.discovery {
+            let (topic, payload) =
+                mqtt::profile_discovery(&self.topics, &self.client_id, &self.profiles);
+            self.send(&topic, &payload, true);
+        }
+        self.publish_profile();
+    }
+
+    fn publish_profile(&self) {
+        let profile = self.selected.as_deref().unwrap_or(AUTO_PROFILE);
+        self.send(&self.topics.profile(), profile, true);
+    }
+
+    fn send(&self, topic: &str, payload: &str, retain: bool) {
+        if let Err(e) = self.client.publish(topic, payload, retain) {
+            log::debug!("Dropped MQTT message on {}: {}", topic, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+
+    use bytes::BytesMut;
+    use rumqttc::mqttbytes::v4::{self, ConnAck, ConnectReturnCode, Packet, PingResp, PubAck};
+    use rumqttc::mqttbytes::v4::{Publish, SubAck, SubscribeReasonCode};
+    use rumqttc::mqttbytes::{Error as PacketError, QoS};
+    use std::io::{ErrorKind, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    /// Topic and payload of a message
+    type Message = (String, String);
+
+    /// Local broker stand-in for a single client
+    ///
+    /// Acknowledges the connection and subscriptions, reports every packet the
+    /// client publishes and sends the client whatever is injected.
+    fn stand_in_broker() -> (u16, Receiver<Message>, Sender<Message>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (seen_tx, seen) = mpsc::channel();
+        let (inject, inject_rx) = mpsc::channel::<Message>();
+
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_millis(20)))
+                .unwrap();
+            let mut input = BytesMut::new();
+            loop {
+                let mut output = BytesMut::new();
+                for (topic, payload) in inject_rx.try_iter() {
+                    Publish::new(topic, QoS::AtMostOnce, payload)
+                        .write(&mut output)
+                        .unwrap();
+                }
+
+                let mut chunk = [0; 4096];
+                match stream.read(&mut chunk) {
+                    Ok(0) => return,
+                    Ok(n) => input.extend_from_slice(&chunk[..n]),
+                    Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                    Err(_) => return,
+                }
+
+                loop {
+                    let reply = match v4::read(&mut input, 1 << 20) {
+                        Ok(Packet::Connect(_)) => {
+                            ConnAck::new(ConnectReturnCode::Success, false).write(&mut output)
+                        }
+                        Ok(Packet::Subscribe(subscribe)) => {
+                            for filter in &subscribe.filters {
+                                let _ =
+                                    seen_tx.send(("SUBSCRIBE".to_string(), filter.path.clone()));
+                            }
+                            let codes = subscribe
+                                .filters
+                                .iter()
+                                .map(|_| SubscribeReasonCode::Success(QoS::AtLeastOnce))
+                                .collect();
+                            SubAck::new(subscribe.pkid, codes).write(&mut output)
+                        }
+                        Ok(Packet::Publish(publish)) => {
+                            let payload = String::from_utf8_lossy(&publish.payload).to_string();
+                            let _ = seen_tx.send((publish.topic.clone(), payload));
+                            if publish.qos == QoS::AtLeastOnce {
+                                PubAck::new(publish.pkid).write(&mut output)
+                            } else {
+                                Ok(0)
+                            }
+                        }
+                        Ok(Packet::PingReq) => PingResp.write(&mut output),
+                        Ok(Packet::Disconnect) => return,
+                        Ok(_) => Ok(0),
+                        Err(PacketError::InsufficientBytes(_)) => break,
+                        Err(_) => return,
+                    };
+                    reply.unwrap();
+                }
+                if !output.is_empty() && stream.write_all(&output).is_err() {
+                    return;
+                }
+            }
+        });
+
+        (port, seen, inject)
+    }
+
+    /// Wait for a message on a topic
+    fn expect(seen: &Receiver<Message>, topic: &str) -> String {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while let Ok((t, payload)) = seen.recv_timeout(deadline - Instant::now()) {
+            if t == topic {
+                return payload;
+            }
+        }
+        panic!("no message on {}", topic);
+    }
+
+    #[test]
+    fn test_publisher_against_stand_in_broker() {
+        let (port, seen, inject) = stand_in_broker();
+        let config = MqttConfig {
+            enabled: true,
+            host: "127.0.0.1".to_string(),
+            port,
+            client_id: Some("test-host".to_string()),
+            ..MqttConfig::default()
+        };
+        let mut publisher = MqttPublisher::connect(&config, vec!["quiet".to_string()]).unwrap();
+
+        let device = MockDevice::new(0);
+        let snapshot = GpuSnapshot::capture(&device);
+        let node = mqtt::node_id(snapshot.uuid.as_ref().unwrap());
+
+        // Nothing is announced until the broker acknowledges the connection
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while publisher.client.connections() == 0 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        publisher.publish_state(&snapshot);
+
+        assert_eq!(expect(&seen, "SUBSCRIBE"), "nvctl/+/power_limit/set");
+        assert_eq!(expect(&seen, "nvctl/status"), "online");
+        let discovery = expect(&seen, &format!("homeassistant/sensor/{}/temp/config", node));
+        assert!(discovery.contains(&format!("nvctl/{}/state", node)));
+        let state = expect(&seen, &format!("nvctl/{}/state", node));
+        assert!(state.contains("\"temp\":"));
+
+        inject
+            .send((format!("nvctl/{}/power_limit/set", node), "250".to_string()))
+            .unwrap();
+        inject
+            .send(("nvctl/profile/set".to_string(), "quiet".to_string()))
+            .unwrap();
+        let mut limits = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while publisher.selected_profile().is_none() && Instant::now() < deadline {
+            limits.extend(publisher.poll());
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(
+            limits,
+            vec![(snapshot.uuid.clone().unwrap(), PowerLimit::from_watts(250))]
+        );
+        assert_eq!(publisher.selected_profile(), Some("quiet"));
+        assert_eq!(expect(&seen, "nvctl/profile"), "quiet");
+
+        // Dropping the publisher marks it offline before disconnecting
+        drop(publisher);
+        assert_eq!(expect(&seen, "nvctl/status"), "offline");
+    }
+}
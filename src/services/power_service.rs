@@ -4,7 +4,7 @@
 
 use crate::domain::PowerLimit;
 use crate::error::ServiceError;
-use crate::nvml::GpuDevice;
+use crate::nvml::{GpuDevice, GpuSnapshot};
 
 /// Service for managing power limits
 pub struct PowerService {
@@ -21,19 +21,25 @@ impl PowerService {
         }
     }
 
-    /// Apply the configured power limit to a device
+    /// Apply the configured power limit to a device using this tick's snapshot
     pub fn apply_limit<D: GpuDevice>(
         &self,
         device: &mut D,
+        snapshot: &GpuSnapshot,
     ) -> Result<Option<PowerLimit>, ServiceError> {
         let Some(limit) = self.target_limit else {
             return Ok(None);
         };
 
         // Validate against device constraints
-        let constraints = device.power_constraints()?;
+        let constraints = snapshot.power_constraints.clone()?;
         limit.validate(&constraints)?;
 
+        // Power limits persist, so only write when the device drifted
+        if snapshot.power_limit.as_ref().ok() == Some(&limit) {
+            return Ok(Some(limit));
+        }
+
         if self.dry_run {
             log::info!("DRY RUN: Would set power limit to {}", limit);
             return Ok(Some(limit));
@@ -59,6 +65,7 @@ impl PowerService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock::MockDevice;
 
     #[test]
     fn test_power_service_creation() {
@@ -72,4 +79,17 @@ mod tests {
         let service = PowerService::new(None, false);
         assert!(service.target_limit().is_none());
     }
+
+    #[test]
+    fn test_power_service_apply_with_snapshot() {
+        let mut device = MockDevice::new(0);
+        let service = PowerService::new(Some(PowerLimit::from_watts(250)), false);
+
+        let snapshot = GpuSnapshot::capture(&device);
+        service.apply_limit(&mut device, &snapshot).unwrap();
+        assert_eq!(device.power_limit().unwrap().as_watts(), 250);
+
+        let out_of_range = PowerService::new(Some(PowerLimit::from_watts(500)), false);
+        assert!(out_of_range.apply_limit(&mut device, &snapshot).is_err());
+    }
 }
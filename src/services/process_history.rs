@@ -0,0 +1,265 @@
+//! Rolling history of finished GPU processes
+//!
+//! Fallback for GPUs without NVML accounting mode: processes seen while
+//! polling are tracked until they exit and then appended to a JSON lines file
+//! that keeps the most recent entries.
+
+use crate::config::ProcessHistoryConfig;
+use crate::domain::GpuProcess;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A finished GPU process
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessRecord {
+    /// UUID of the GPU the process ran on
+    pub gpu_uuid: String,
+    /// Process ID
+    pub pid: u32,
+    /// Process name (if known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Owning user (if known)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Start time (or first time seen) in seconds since the Unix epoch
+    pub start_time: u64,
+    /// Last time the process was seen, in seconds since the Unix epoch
+    pub end_time: u64,
+    /// Peak GPU memory seen (bytes)
+    pub max_memory: u64,
+    /// Average SM utilization over the samples taken (if sampled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sm_utilization: Option<u32>,
+    /// Average memory utilization over the samples taken (if sampled)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_utilization: Option<u32>,
+}
+
+impl ProcessRecord {
+    /// Run time in seconds
+    pub fn run_time_secs(&self) -> u64 {
+        self.end_time.saturating_sub(self.start_time)
+    }
+}
+
+/// A running process and its utilization totals
+struct Tracked {
+    record: ProcessRecord,
+    sm_total: u64,
+    memory_total: u64,
+    samples: u64,
+}
+
+impl Tracked {
+    fn observe(&mut self, process: &GpuProcess, now: u64) {
+        self.record.end_time = now;
+        self.record.max_memory = self.record.max_memory.max(process.used_memory);
+        if let Some(util) = process.utilization {
+            self.sm_total += util.sm as u64;
+            self.memory_total += util.memory as u64;
+            self.samples += 1;
+        }
+    }
+
+    fn finish(mut self) -> ProcessRecord {
+        let average = |total: u64| total.checked_div(self.samples).map(|avg| avg as u32);
+        self.record.sm_utilization = average(self.sm_total);
+        self.record.memory_utilization = average(self.memory_total);
+        self.record
+    }
+}
+
+/// Tracks running GPU processes and records them when they exit
+pub struct ProcessHistory {
+    path: PathBuf,
+    max_entries: usize,
+    running: HashMap<(String, u32), Tracked>,
+}
+
+impl ProcessHistory {
+    /// Create a history writing to `path`, keeping at most `max_entries`
+    pub fn new(path: impl Into<PathBuf>, max_entries: usize) -> Self {
+        Self {
+            path: path.into(),
+            max_entries,
+            running: HashMap::new(),
+        }
+    }
+
+    /// Create a history from the `[process_history]` configuration
+    pub fn from_config(config: &ProcessHistoryConfig) -> Self {
+        Self::new(config.path(), config.max_entries)
+    }
+
+    /// Get the history file path
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record the processes currently running on a GPU
+    ///
+    /// Processes of this GPU that are no longer running (or whose PID was
+    /// reused) are written to the history file.
+    pub fn observe(
+        &mut self,
+        gpu_uuid: &str,
+        processes: &[GpuProcess],
+        now: u64,
+    ) -> io::Result<()> {
+        let mut finished = Vec::new();
+
+        for process in processes {
+            let key = (gpu_uuid.to_string(), process.pid);
+            let reused = self.running.get(&key).is_some_and(|tracked| {
+                process
+                    .start_time
+                    .is_some_and(|start| start != tracked.record.start_time)
+            });
+            if reused {
+                finished.extend(self.running.remove(&key));
+            }
+
+            self.running
+                .entry(key)
+                .or_insert_with(|| Tracked {
+                    record: ProcessRecord {
+                        gpu_uuid: gpu_uuid.to_string(),
+                        pid: process.pid,
+                        name: process.name.clone(),
+                        user: process.user.clone(),
+                        start_time: process.start_time.unwrap_or(now),
+                        end_time: now,
+                        max_memory: 0,
+                        sm_utilization: None,
+                        memory_utilization: None,
+                    },
+                    sm_total: 0,
+                    memory_total: 0,
+                    samples: 0,
+                })
+                .observe(process, now);
+        }
+
+        let gone: Vec<(String, u32)> = self
+            .running
+            .keys()
+            .filter(|(uuid, pid)| uuid == gpu_uuid && !processes.iter().any(|p| p.pid == *pid))
+            .cloned()
+            .collect();
+        finished.extend(gone.iter().filter_map(|key| self.running.remove(key)));
+
+        if finished.is_empty() {
+            return Ok(());
+        }
+        let mut records: Vec<ProcessRecord> = finished.into_iter().map(Tracked::finish).collect();
+        records.sort_by_key(|r| r.end_time);
+        self.append(records)
+    }
+
+    /// Append records, dropping the oldest beyond `max_entries`
+    fn append(&self, records: Vec<ProcessRecord>) -> io::Result<()> {
+        let mut all = Self::load(&self.path)?;
+        all.extend(records);
+        let skip = all.len().saturating_sub(self.max_entries);
+
+        let mut contents = String::new();
+        for record in &all[skip..] {
+            contents.push_str(&serde_json::to_string(record).map_err(io::Error::other)?);
+            contents.push('\n');
+        }
+
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        // Write a new file and swap it in so readers never see a partial history
+        let tmp = self.path.with_extension("jsonl.tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    /// Load the records in a history file, oldest first
+    ///
+    /// A missing file is an empty history; unreadable lines are skipped.
+    pub fn load(path: &Path) -> io::Result<Vec<ProcessRecord>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(contents
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ProcessType, ProcessUtilization};
+
+    fn process(pid: u32, memory: u64, sm: u32) -> GpuProcess {
+        let mut process =
+            GpuProcess::with_name(pid, "train".to_string(), memory, ProcessType::Compute);
+        process.utilization = Some(ProcessUtilization {
+            sm,
+            ..Default::default()
+        });
+        process
+    }
+
+    #[test]
+    fn test_records_finished_processes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history/processes.jsonl");
+        let mut history = ProcessHistory::new(&path, 10);
+
+        history
+            .observe("GPU-A", &[process(1, 100, 80), process(2, 50, 0)], 1000)
+            .unwrap();
+        history
+            .observe("GPU-A", &[process(1, 300, 40)], 1010)
+            .unwrap();
+        // Other GPUs don't end processes on GPU-A
+        history.observe("GPU-B", &[], 1015).unwrap();
+        history.observe("GPU-A", &[], 1020).unwrap();
+
+        let records = ProcessHistory::load(&path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].pid, 2);
+        let first = &records[1];
+        assert_eq!(first.max_memory, 300);
+        assert_eq!(first.sm_utilization, Some(60));
+        assert_eq!(first.run_time_secs(), 10);
+    }
+
+    #[test]
+    fn test_keeps_most_recent_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("processes.jsonl");
+        let mut history = ProcessHistory::new(&path, 2);
+
+        for pid in 1..=3 {
+            let now = pid as u64 * 10;
+            history
+                .observe("GPU-A", &[process(pid, 1, 0)], now)
+                .unwrap();
+            history.observe("GPU-A", &[], now + 1).unwrap();
+        }
+
+        let pids: Vec<u32> = ProcessHistory::load(&path)
+            .unwrap()
+            .iter()
+            .map(|r| r.pid)
+            .collect();
+        assert_eq!(pids, vec![2, 3]);
+        assert!(ProcessHistory::load(&dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+}
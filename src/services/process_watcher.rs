@@ -0,0 +1,207 @@
+//! Process-triggered profile switching
+//!
+//! Watches GPU processes and selects the profile of the highest priority
+//! matching rule, with a debounce so short-lived processes don't flap profiles.
+
+use crate::config::{ProcessRule, ProcessTriggerConfig};
+use crate::domain::GpuProcess;
+use crate::error::ConfigError;
+
+use regex::Regex;
+use std::time::{Duration, Instant};
+
+struct CompiledRule {
+    rule: ProcessRule,
+    cmdline: Option<Regex>,
+}
+
+impl CompiledRule {
+    fn matches(&self, process: &GpuProcess) -> bool {
+        let cmdline_ok = self.cmdline.as_ref().is_none_or(|re| {
+            process
+                .cmdline
+                .as_ref()
+                .or(process.name.as_ref())
+                .is_some_and(|cmdline| re.is_match(cmdline))
+        });
+        cmdline_ok && self.rule.matches_fields(process)
+    }
+}
+
+/// Selects profiles based on running GPU processes
+pub struct ProcessWatcher {
+    rules: Vec<CompiledRule>,
+    debounce: Duration,
+    active: Option<usize>,
+    pending: Option<(Option<usize>, Instant)>,
+}
+
+impl ProcessWatcher {
+    /// Create a watcher from the `[process_triggers]` configuration
+    pub fn new(config: &ProcessTriggerConfig) -> Result<Self, ConfigError> {
+        let rules = config
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(pos, rule)| {
+                let cmdline = rule
+                    .cmdline
+                    .as_deref()
+                    .map(Regex::new)
+                    .transpose()
+                    .map_err(|e| ConfigError::InvalidValue {
+                        key: rule.display_name(pos),
+                        message: e.to_string(),
+                    })?;
+                Ok(CompiledRule {
+                    rule: rule.clone(),
+                    cmdline,
+                })
+            })
+            .collect::<Result<_, ConfigError>>()?;
+
+        Ok(Self {
+            rules,
+            debounce: Duration::from_secs(config.debounce_secs),
+            active: None,
+            pending: None,
+        })
+    }
+
+    /// Find the highest priority rule matching any process
+    ///
+    /// Ties go to the rule listed first.
+    pub fn matching_rule(&self, processes: &[GpuProcess]) -> Option<usize> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| processes.iter().any(|p| rule.matches(p)))
+            .min_by_key(|(pos, rule)| (std::cmp::Reverse(rule.rule.priority), *pos))
+            .map(|(pos, _)| pos)
+    }
+
+    /// Update the active rule from the current processes
+    ///
+    /// A new match (or losing the match) only takes effect after it has been
+    /// seen for the debounce period. Returns true if the active rule changed.
+    pub fn update(&mut self, processes: &[GpuProcess], now: Instant) -> bool {
+        let candidate = self.matching_rule(processes);
+        if candidate == self.active {
+            self.pending = None;
+            return false;
+        }
+
+        let since = match self.pending {
+            Some((pending, since)) if pending == candidate => since,
+            _ => {
+                self.pending = Some((candidate, now));
+                now
+            }
+        };
+        if now.duration_since(since) < self.debounce {
+            return false;
+        }
+
+        match candidate {
+            Some(pos) => log::info!(
+                "Process rule '{}' matched, using profile '{}'",
+                self.rules[pos].rule.display_name(pos),
+                self.rules[pos].rule.profile
+            ),
+            None => log::info!("No process rule matches any more"),
+        }
+        self.active = candidate;
+        self.pending = None;
+        true
+    }
+
+    /// Get the rule currently in effect
+    pub fn active_rule(&self) -> Option<&ProcessRule> {
+        self.active.map(|pos| &self.rules[pos].rule)
+    }
+
+    /// Get the profile of the rule currently in effect
+    pub fn active_profile(&self) -> Option<&str> {
+        self.active_rule().map(|rule| rule.profile.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ProcessType;
+
+    fn watcher(debounce_secs: u64) -> ProcessWatcher {
+        let config: ProcessTriggerConfig = toml::from_str(&format!(
+            r#"
+            debounce_secs = {}
+
+            [[rules]]
+            profile = "training"
+            cmdline = "python.*train\\.py"
+            process_type = "compute"
+
+            [[rules]]
+            profile = "render"
+            name = "blender"
+            priority = 10
+            "#,
+            debounce_secs
+        ))
+        .unwrap();
+        ProcessWatcher::new(&config).unwrap()
+    }
+
+    fn blender() -> GpuProcess {
+        GpuProcess::with_name(1, "blender".to_string(), 0, ProcessType::Graphics)
+    }
+
+    fn training() -> GpuProcess {
+        let mut process = GpuProcess::with_name(2, "python3".to_string(), 0, ProcessType::Compute);
+        process.cmdline = Some("/usr/bin/python3 train.py --epochs 10".to_string());
+        process
+    }
+
+    #[test]
+    fn test_priority_ordering() {
+        let watcher = watcher(0);
+        assert_eq!(watcher.matching_rule(&[]), None);
+        assert_eq!(watcher.matching_rule(&[training()]), Some(0));
+        // Blender has the higher priority
+        assert_eq!(watcher.matching_rule(&[training(), blender()]), Some(1));
+    }
+
+    #[test]
+    fn test_debounce() {
+        let mut watcher = watcher(5);
+        let start = Instant::now();
+
+        assert!(!watcher.update(&[blender()], start));
+        assert!(!watcher.update(&[blender()], start + Duration::from_secs(3)));
+        assert_eq!(watcher.active_profile(), None);
+        assert!(watcher.update(&[blender()], start + Duration::from_secs(5)));
+        assert_eq!(watcher.active_profile(), Some("render"));
+
+        // A brief exit doesn't revert
+        assert!(!watcher.update(&[], start + Duration::from_secs(6)));
+        assert!(!watcher.update(&[blender()], start + Duration::from_secs(7)));
+        assert!(!watcher.update(&[], start + Duration::from_secs(8)));
+        assert_eq!(watcher.active_profile(), Some("render"));
+
+        assert!(watcher.update(&[], start + Duration::from_secs(13)));
+        assert_eq!(watcher.active_profile(), None);
+    }
+
+    #[test]
+    fn test_invalid_regex() {
+        let config: ProcessTriggerConfig = toml::from_str(
+            r#"
+            [[rules]]
+            profile = "x"
+            cmdline = "("
+            "#,
+        )
+        .unwrap();
+        assert!(ProcessWatcher::new(&config).is_err());
+    }
+}
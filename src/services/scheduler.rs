@@ -0,0 +1,257 @@
+//! Profile scheduler
+//!
+//! Picks the active profile from `[schedule]` rules. The current time comes
+//! from a [`Clock`] so schedules can be tested at fixed instants.
+
+use crate::config::{ScheduleConfig, ScheduleRule};
+use crate::error::ConfigError;
+
+use jiff::tz::TimeZone;
+use jiff::{Timestamp, Zoned};
+use std::sync::{Arc, Mutex};
+
+/// Source of the current time
+pub trait Clock: Send {
+    /// Get the current time
+    fn now(&self) -> Timestamp;
+}
+
+/// Clock reading the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Timestamp {
+        Timestamp::now()
+    }
+}
+
+/// Clock that only moves when told to
+///
+/// Clones share the same time, so a test can keep one and hand another to
+/// the scheduler.
+#[derive(Debug, Clone)]
+pub struct ManualClock(Arc<Mutex<Timestamp>>);
+
+impl ManualClock {
+    /// Create a clock set to `now`
+    pub fn new(now: Timestamp) -> Self {
+        Self(Arc::new(Mutex::new(now)))
+    }
+
+    /// Set the current time
+    pub fn set(&self, now: Timestamp) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = now;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Timestamp {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The schedule entry in effect at some instant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleEntry {
+    /// Position of the matching rule, `None` for the default
+    pub rule: Option<usize>,
+    /// Profile to apply, `None` for the global settings
+    pub profile: Option<String>,
+}
+
+/// Selects profiles based on time-of-day rules
+pub struct Scheduler {
+    rules: Vec<ScheduleRule>,
+    default: Option<String>,
+    timezone: TimeZone,
+    clock: Box<dyn Clock>,
+}
+
+impl Scheduler {
+    /// Create a scheduler using the system clock
+    pub fn new(config: &ScheduleConfig) -> Result<Self, ConfigError> {
+        let timezone = match &config.timezone {
+            Some(name) => TimeZone::get(name).map_err(|e| ConfigError::InvalidValue {
+                key: "schedule.timezone".to_string(),
+                message: e.to_string(),
+            })?,
+            None => TimeZone::system(),
+        };
+
+        Ok(Self {
+            rules: config.rules.clone(),
+            default: config.default.clone(),
+            timezone,
+            clock: Box::new(SystemClock),
+        })
+    }
+
+    /// Use a different clock
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Box::new(clock);
+        self
+    }
+
+    /// Get the configured rules
+    pub fn rules(&self) -> &[ScheduleRule] {
+        &self.rules
+    }
+
+    /// Get the timezone rules are evaluated in
+    pub fn timezone(&self) -> &TimeZone {
+        &self.timezone
+    }
+
+    /// Get the current time in the schedule's timezone
+    pub fn now(&self) -> Zoned {
+        self.clock.now().to_zoned(self.timezone.clone())
+    }
+
+    /// Get the entry in effect now
+    pub fn active(&self) -> ScheduleEntry {
+        self.entry_at(self.clock.now())
+    }
+
+    /// Get the entry in effect at `at`
+    pub fn entry_at(&self, at: Timestamp) -> ScheduleEntry {
+        let local = at.to_zoned(self.timezone.clone());
+        let weekday = local.weekday().to_monday_zero_offset() as u8;
+        let minute = local.hour() as u16 * 60 + local.minute() as u16;
+
+        match self
+            .rules
+            .iter()
+            .position(|rule| rule.matches(weekday, minute))
+        {
+            Some(pos) => ScheduleEntry {
+                rule: Some(pos),
+                profile: Some(self.rules[pos].profile.clone()),
+            },
+            None => ScheduleEntry {
+                rule: None,
+                profile: self.default.clone(),
+            },
+        }
+    }
+
+    /// Get the next profile change after `at` and the entry it switches to
+    ///
+    /// Looks up to a week ahead; `None` means the profile never changes.
+    pub fn next_change(&self, at: Timestamp) -> Option<(Zoned, ScheduleEntry)> {
+        let current = self.entry_at(at);
+        let today = at.to_zoned(self.timezone.clone()).date();
+
+        // Every window edge in the coming week is a possible change
+        let mut edges: Vec<Zoned> = (0..=8)
+            .filter_map(|days| today.checked_add(jiff::Span::new().days(days)).ok())
+            .flat_map(|date| {
+                self.rules
+                    .iter()
+                    .flat_map(|rule| [rule.start.minutes(), rule.end.minutes()])
+                    .filter_map(move |minutes| {
+                        let date = if minutes >= 24 * 60 {
+                            date.tomorrow().ok()?
+                        } else {
+                            date
+                        };
+                        let minutes = minutes % (24 * 60);
+                        date.at((minutes / 60) as i8, (minutes % 60) as i8, 0, 0)
+                            .to_zoned(self.timezone.clone())
+                            .ok()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .filter(|edge| edge.timestamp() > at)
+            .collect();
+        edges.sort();
+        edges.dedup();
+
+        edges.into_iter().find_map(|edge| {
+            let entry = self.entry_at(edge.timestamp());
+            (entry.profile != current.profile).then_some((edge, entry))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler(toml: &str) -> Scheduler {
+        let config: ScheduleConfig = toml::from_str(toml).unwrap();
+        Scheduler::new(&config).unwrap()
+    }
+
+    fn at(s: &str) -> Timestamp {
+        s.parse().unwrap()
+    }
+
+    const NIGHTS: &str = r#"
+        timezone = "Europe/Berlin"
+        default = "performance"
+
+        [[rules]]
+        profile = "quiet"
+        days = "mon-fri"
+        start = "22:00"
+        end = "07:00"
+
+        [[rules]]
+        profile = "quiet"
+        days = "weekends"
+        start = "00:00"
+        end = "24:00"
+    "#;
+
+    #[test]
+    fn test_active_entry_follows_clock() {
+        // Wednesday 2024-06-12, Berlin is UTC+2
+        let clock = ManualClock::new(at("2024-06-12T10:00:00Z"));
+        let scheduler = scheduler(NIGHTS).with_clock(clock.clone());
+
+        let entry = scheduler.active();
+        assert_eq!(entry.rule, None);
+        assert_eq!(entry.profile.as_deref(), Some("performance"));
+
+        // 22:30 local time
+        clock.set(at("2024-06-12T20:30:00Z"));
+        let entry = scheduler.active();
+        assert_eq!(entry.rule, Some(0));
+        assert_eq!(entry.profile.as_deref(), Some("quiet"));
+
+        // Saturday afternoon
+        clock.set(at("2024-06-15T13:00:00Z"));
+        assert_eq!(scheduler.active().rule, Some(1));
+    }
+
+    #[test]
+    fn test_next_change() {
+        let scheduler = scheduler(NIGHTS);
+
+        let (when, entry) = scheduler.next_change(at("2024-06-12T10:00:00Z")).unwrap();
+        assert_eq!(when.timestamp(), at("2024-06-12T20:00:00Z"));
+        assert_eq!(entry.profile.as_deref(), Some("quiet"));
+
+        // Friday night runs into the weekend rule without a change until Monday
+        let (when, entry) = scheduler.next_change(at("2024-06-14T21:00:00Z")).unwrap();
+        assert_eq!(when.timestamp(), at("2024-06-16T22:00:00Z"));
+        assert_eq!(entry.profile.as_deref(), Some("performance"));
+    }
+
+    #[test]
+    fn test_no_rules_never_changes() {
+        let scheduler = scheduler("timezone = \"UTC\"");
+        assert_eq!(scheduler.active().profile, None);
+        assert!(scheduler.next_change(Timestamp::now()).is_none());
+    }
+
+    #[test]
+    fn test_invalid_timezone() {
+        let config = ScheduleConfig {
+            timezone: Some("Mars/Olympus_Mons".to_string()),
+            ..Default::default()
+        };
+        assert!(Scheduler::new(&config).is_err());
+    }
+}
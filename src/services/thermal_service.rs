@@ -0,0 +1,87 @@
+//! Thermal management service
+//!
+//! Manages GPU acoustic temperature limits.
+
+use crate::domain::Temperature;
+use crate::error::{DomainError, ServiceError};
+use crate::nvml::GpuDevice;
+
+/// Service for managing acoustic limits
+pub struct ThermalService {
+    acoustic_limit: Option<Temperature>,
+    dry_run: bool,
+}
+
+impl ThermalService {
+    /// Create a new thermal service
+    pub fn new(acoustic_limit: Option<Temperature>, dry_run: bool) -> Self {
+        Self {
+            acoustic_limit,
+            dry_run,
+        }
+    }
+
+    /// Apply the configured acoustic limit to a device
+    pub fn apply_limit<D: GpuDevice>(
+        &self,
+        device: &mut D,
+    ) -> Result<Option<Temperature>, ServiceError> {
+        let Some(limit) = self.acoustic_limit else {
+            return Ok(None);
+        };
+
+        // Validate against device range
+        let limits = device.acoustic_limits()?;
+        if !limits.is_valid(limit) {
+            return Err(DomainError::InvalidTemperature(limit.as_celsius()).into());
+        }
+
+        if self.dry_run {
+            log::info!("DRY RUN: Would set acoustic limit to {}", limit);
+            return Ok(Some(limit));
+        }
+
+        device.set_acoustic_limit(limit)?;
+        log::debug!("Applied acoustic limit {}", limit);
+
+        Ok(Some(limit))
+    }
+
+    /// Get the configured acoustic limit
+    pub fn acoustic_limit(&self) -> Option<Temperature> {
+        self.acoustic_limit
+    }
+
+    /// Check if in dry-run mode
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+
+    #[test]
+    fn test_thermal_service_apply() {
+        let mut device = MockDevice::new(0);
+        let service = ThermalService::new(Some(Temperature::new(75)), false);
+
+        assert_eq!(
+            service.apply_limit(&mut device).unwrap(),
+            Some(Temperature::new(75))
+        );
+        assert_eq!(
+            device.acoustic_limits().unwrap().current,
+            Some(Temperature::new(75))
+        );
+    }
+
+    #[test]
+    fn test_thermal_service_out_of_range() {
+        let mut device = MockDevice::new(0);
+        let service = ThermalService::new(Some(Temperature::new(99)), false);
+        assert!(service.apply_limit(&mut device).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! InfluxDB line protocol sinks
+
+use super::MetricsSink;
+use crate::error::SinkError;
+use crate::metrics::Sample;
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Encode samples as InfluxDB line protocol
+///
+/// Each metric becomes a measurement with a single `value` field; labels
+/// become tags.
+fn encode_lines(timestamp: SystemTime, samples: &[Sample]) -> Vec<String> {
+    let nanos = timestamp
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+
+    samples
+        .iter()
+        .map(|sample| {
+            let mut line = escape(sample.metric.name, &[',', ' ']);
+            for (name, value) in &sample.labels {
+                // Influx rejects empty tag values
+                if !value.is_empty() {
+                    line.push(',');
+                    line.push_str(&escape(name, &[',', '=', ' ']));
+                    line.push('=');
+                    line.push_str(&escape(value, &[',', '=', ' ']));
+                }
+            }
+            format!("{} value={} {}", line, sample.value, nanos)
+        })
+        .collect()
+}
+
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Writes line protocol to an InfluxDB HTTP write endpoint
+///
+/// The URL is used as-is, e.g. `http://localhost:8086/api/v2/write?org=o&bucket=b`
+/// for InfluxDB 2.x or `http://localhost:8086/write?db=gpus` for 1.x.
+pub struct InfluxHttpSink {
+    url: String,
+    token: Option<String>,
+    agent: ureq::Agent,
+}
+
+impl InfluxHttpSink {
+    /// Create a sink for a write URL, with an optional API token
+    pub fn new(url: String, token: Option<String>) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(10))
+            .build();
+        Self { url, token, agent }
+    }
+}
+
+impl MetricsSink for InfluxHttpSink {
+    fn describe(&self) -> String {
+        format!("influx {}", self.url)
+    }
+
+    fn encode(&self, timestamp: SystemTime, samples: &[Sample]) -> Vec<String> {
+        encode_lines(timestamp, samples)
+    }
+
+    fn write(&mut self, lines: &[String]) -> Result<(), SinkError> {
+        let mut request = self
+            .agent
+            .post(&self.url)
+            .set("Content-Type", "text/plain; charset=utf-8");
+        if let Some(token) = &self.token {
+            request = request.set("Authorization", &format!("Token {}", token));
+        }
+
+        match request.send_string(&lines.join("\n")) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(status, response)) => Err(SinkError::Rejected {
+                status,
+                body: response.into_string().unwrap_or_default(),
+            }),
+            Err(e) => Err(SinkError::Http(e.to_string())),
+        }
+    }
+}
+
+/// Appends line protocol to a file, e.g. for Telegraf's `tail` input
+pub struct InfluxFileSink {
+    path: PathBuf,
+}
+
+impl InfluxFileSink {
+    /// Create a sink appending to `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl MetricsSink for InfluxFileSink {
+    fn describe(&self) -> String {
+        format!("influx file {}", self.path.display())
+    }
+
+    fn encode(&self, timestamp: SystemTime, samples: &[Sample]) -> Vec<String> {
+        encode_lines(timestamp, samples)
+    }
+
+    fn write(&mut self, lines: &[String]) -> Result<(), SinkError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let mut text = lines.join("\n");
+        text.push('\n');
+        file.write_all(text.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{Sample, FAN_SPEED};
+    use std::thread;
+
+    #[test]
+    fn test_encode_lines_escapes_tags() {
+        let sample = Sample::new(&FAN_SPEED, 40.0)
+            .with_label("gpu", "0")
+            .with_label("name", "RTX 4090, rev=a")
+            .with_label("uuid", "");
+        let lines = encode_lines(UNIX_EPOCH + Duration::from_secs(2), &[sample]);
+
+        assert_eq!(
+            lines,
+            vec!["nvctl_gpu_fan_speed_percent,gpu=0,name=RTX\\ 4090\\,\\ rev\\=a value=40 2000000000"]
+        );
+    }
+
+    #[test]
+    fn test_http_sink_posts_batch() {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/v2/write", server.server_addr());
+        let handle = thread::spawn(move || {
+            let mut request = server.recv().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let auth = request
+                .headers()
+                .iter()
+                .find(|h| h.field.equiv("Authorization"))
+                .map(|h| h.value.to_string());
+            request.respond(tiny_http::Response::empty(204)).unwrap();
+            (body, auth)
+        });
+
+        let mut sink = InfluxHttpSink::new(url, Some("secret".to_string()));
+        sink.write(&["a value=1 1".to_string(), "b value=2 1".to_string()])
+            .unwrap();
+
+        let (body, auth) = handle.join().unwrap();
+        assert_eq!(body, "a value=1 1\nb value=2 1");
+        assert_eq!(auth.as_deref(), Some("Token secret"));
+    }
+}
@@ -0,0 +1,43 @@
+//! Push targets for metrics
+//!
+//! A sink encodes metric samples into lines of its wire format and writes
+//! batches of those lines. Encoding happens when samples are recorded so that
+//! batches that can't be delivered can be spooled to disk as-is.
+
+mod influx;
+mod statsd;
+
+pub use influx::{InfluxFileSink, InfluxHttpSink};
+pub use statsd::StatsdSink;
+
+use crate::config::SinkConfig;
+use crate::error::SinkError;
+use crate::metrics::Sample;
+
+use std::time::SystemTime;
+
+/// A destination for pushed metrics
+pub trait MetricsSink: Send {
+    /// Human-readable description of the target, used in logs and spool file names
+    fn describe(&self) -> String;
+
+    /// Encode samples taken at `timestamp` into lines of the wire format
+    fn encode(&self, timestamp: SystemTime, samples: &[Sample]) -> Vec<String>;
+
+    /// Deliver a batch of encoded lines
+    fn write(&mut self, lines: &[String]) -> Result<(), SinkError>;
+}
+
+/// Create a sink from its configuration
+pub fn from_config(config: &SinkConfig) -> Result<Box<dyn MetricsSink>, SinkError> {
+    Ok(match config {
+        SinkConfig::Influx { url, token } => {
+            Box::new(InfluxHttpSink::new(url.clone(), token.clone()))
+        }
+        SinkConfig::InfluxFile { path } => Box::new(InfluxFileSink::new(path.clone())),
+        SinkConfig::Statsd { address, prefix } => Box::new(StatsdSink::connect(
+            address,
+            prefix.clone().unwrap_or_default(),
+        )?),
+    })
+}
@@ -0,0 +1,114 @@
+//! StatsD sink over UDP
+
+use super::MetricsSink;
+use crate::error::SinkError;
+use crate::metrics::Sample;
+
+use std::net::UdpSocket;
+use std::time::SystemTime;
+
+/// Largest datagram that fits a typical Ethernet MTU without fragmentation
+const MAX_PACKET: usize = 1432;
+
+/// Sends gauges to a StatsD server
+///
+/// Labels are sent as DogStatsD-style tags (`|#gpu:0,uuid:...`), which
+/// Telegraf, Datadog and statsd_exporter understand. Every metric is sent as
+/// a gauge since NVML counters are already absolute totals.
+pub struct StatsdSink {
+    socket: UdpSocket,
+    address: String,
+    prefix: String,
+}
+
+impl StatsdSink {
+    /// Connect to a StatsD server at `address` (e.g. `127.0.0.1:8125`)
+    pub fn connect(address: &str, prefix: String) -> Result<Self, SinkError> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.connect(address)?;
+        Ok(Self {
+            socket,
+            address: address.to_string(),
+            prefix,
+        })
+    }
+}
+
+impl MetricsSink for StatsdSink {
+    fn describe(&self) -> String {
+        format!("statsd {}", self.address)
+    }
+
+    fn encode(&self, _timestamp: SystemTime, samples: &[Sample]) -> Vec<String> {
+        samples
+            .iter()
+            .map(|sample| {
+                let tags: Vec<String> = sample
+                    .labels
+                    .iter()
+                    .filter(|(_, value)| !value.is_empty())
+                    .map(|(name, value)| format!("{}:{}", name, value.replace([',', '|'], "_")))
+                    .collect();
+                let mut line = format!("{}{}:{}|g", self.prefix, sample.metric.name, sample.value);
+                if !tags.is_empty() {
+                    line.push_str("|#");
+                    line.push_str(&tags.join(","));
+                }
+                line
+            })
+            .collect()
+    }
+
+    fn write(&mut self, lines: &[String]) -> Result<(), SinkError> {
+        // Pack as many lines as fit into each datagram
+        let mut packet = String::with_capacity(MAX_PACKET);
+        for line in lines {
+            if !packet.is_empty() && packet.len() + 1 + line.len() > MAX_PACKET {
+                self.socket.send(packet.as_bytes())?;
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(line);
+        }
+        if !packet.is_empty() {
+            self.socket.send(packet.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::{Sample, TEMPERATURE};
+    use std::time::Duration;
+
+    #[test]
+    fn test_statsd_sends_tagged_gauges() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let address = server.local_addr().unwrap().to_string();
+
+        let mut sink = StatsdSink::connect(&address, "site1.".to_string()).unwrap();
+        let samples = [
+            Sample::new(&TEMPERATURE, 61.0)
+                .with_label("gpu", "0")
+                .with_label("name", "A|B"),
+            Sample::new(&TEMPERATURE, 55.0).with_label("gpu", "1"),
+        ];
+        let lines = sink.encode(SystemTime::now(), &samples);
+        sink.write(&lines).unwrap();
+
+        let mut buf = [0u8; MAX_PACKET];
+        let len = server.recv(&mut buf).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buf[..len]).unwrap(),
+            "site1.nvctl_gpu_temperature_celsius:61|g|#gpu:0,name:A_B\n\
+             site1.nvctl_gpu_temperature_celsius:55|g|#gpu:1"
+        );
+    }
+}
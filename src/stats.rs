@@ -0,0 +1,288 @@
+//! Session statistics (`nvctl stats`)
+//!
+//! Summarizes a GPU time series, sampled live or read from a recording, into
+//! per-metric percentiles, time spent in temperature bands and throttle time.
+
+use crate::recorder::RecordField;
+
+use serde::Serialize;
+
+/// Min/max/mean and percentiles of one metric
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Summary {
+    pub min: f64,
+    pub max: f64,
+    pub avg: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub samples: usize,
+}
+
+impl Summary {
+    /// Summarize values, `None` if there are none
+    pub fn from_values(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+
+        Some(Self {
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            avg: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            samples: sorted.len(),
+        })
+    }
+}
+
+/// Summary of one recorded field
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricStats {
+    pub metric: &'static str,
+    pub unit: &'static str,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+/// Time spent in one temperature band
+#[derive(Debug, Clone, Serialize)]
+pub struct BandTime {
+    pub band: String,
+    pub seconds: f64,
+    pub share: f64,
+}
+
+/// Statistics for one GPU
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuStats {
+    pub gpu: u32,
+    pub uuid: String,
+    pub duration_secs: f64,
+    pub samples: usize,
+    pub metrics: Vec<MetricStats>,
+    pub temperature_bands: Vec<BandTime>,
+    /// Slowdown threshold used for `time_above_slowdown_secs`
+    pub slowdown_temperature: Option<f64>,
+    pub time_above_slowdown_secs: Option<f64>,
+    /// Fraction of time with power or thermal throttling active
+    pub throttle_share: Option<f64>,
+}
+
+/// Statistics for all GPUs of a session
+#[derive(Debug, Clone, Serialize)]
+pub struct StatsReport {
+    pub source: String,
+    pub gpus: Vec<GpuStats>,
+}
+
+/// Accumulates samples of one GPU
+#[derive(Debug, Clone)]
+pub struct StatsCollector {
+    gpu: u32,
+    uuid: String,
+    band_edges: Vec<f64>,
+    slowdown: Option<f64>,
+    values: Vec<Vec<f64>>,
+    band_secs: Vec<f64>,
+    above_slowdown_secs: f64,
+    throttled_secs: f64,
+    throttle_known_secs: f64,
+    first_offset: Option<f64>,
+    last: Option<LastSample>,
+    samples: usize,
+}
+
+/// State of the previous sample, which the following interval is attributed to
+#[derive(Debug, Clone, Copy)]
+struct LastSample {
+    offset: f64,
+    temp: Option<f64>,
+    throttled: Option<bool>,
+}
+
+impl StatsCollector {
+    /// Create a collector with ascending temperature band edges (°C)
+    pub fn new(gpu: u32, uuid: impl Into<String>, band_edges: &[f64]) -> Self {
+        let mut band_edges = band_edges.to_vec();
+        band_edges.sort_by(f64::total_cmp);
+        band_edges.dedup();
+        Self {
+            gpu,
+            uuid: uuid.into(),
+            band_secs: vec![0.0; band_edges.len() + 1],
+            band_edges,
+            slowdown: None,
+            values: vec![Vec::new(); RecordField::ALL.len()],
+            above_slowdown_secs: 0.0,
+            throttled_secs: 0.0,
+            throttle_known_secs: 0.0,
+            first_offset: None,
+            last: None,
+            samples: 0,
+        }
+    }
+
+    /// Count time above this temperature as time above slowdown
+    pub fn with_slowdown(mut self, slowdown: Option<f64>) -> Self {
+        self.slowdown = slowdown;
+        self
+    }
+
+    /// Add a sample taken `offset` seconds into the session
+    ///
+    /// `values` follows [`RecordField::ALL`]; `throttled` is `None` when
+    /// throttle reasons are unknown (e.g. in recordings).
+    pub fn add(&mut self, offset: f64, values: &[Option<f64>], throttled: Option<bool>) {
+        for (series, value) in self.values.iter_mut().zip(values) {
+            if let Some(value) = value {
+                series.push(*value);
+            }
+        }
+
+        if let Some(last) = self.last {
+            let dt = (offset - last.offset).max(0.0);
+            if let Some(temp) = last.temp {
+                let band = self.band_edges.partition_point(|&edge| edge <= temp);
+                self.band_secs[band] += dt;
+                if self.slowdown.is_some_and(|slowdown| temp >= slowdown) {
+                    self.above_slowdown_secs += dt;
+                }
+            }
+            if let Some(throttled) = last.throttled {
+                self.throttle_known_secs += dt;
+                if throttled {
+                    self.throttled_secs += dt;
+                }
+            }
+        }
+
+        self.first_offset.get_or_insert(offset);
+        self.last = Some(LastSample {
+            offset,
+            temp: values.get(RecordField::Temp.position()).copied().flatten(),
+            throttled,
+        });
+        self.samples += 1;
+    }
+
+    /// Compute the statistics
+    pub fn finish(&self) -> GpuStats {
+        let duration = match (self.first_offset, self.last) {
+            (Some(first), Some(last)) => last.offset - first,
+            _ => 0.0,
+        };
+        let band_total: f64 = self.band_secs.iter().sum();
+
+        GpuStats {
+            gpu: self.gpu,
+            uuid: self.uuid.clone(),
+            duration_secs: duration,
+            samples: self.samples,
+            metrics: RecordField::ALL
+                .iter()
+                .zip(&self.values)
+                .filter_map(|(field, values)| {
+                    Some(MetricStats {
+                        metric: field.name(),
+                        unit: field.unit(),
+                        summary: Summary::from_values(values)?,
+                    })
+                })
+                .collect(),
+            temperature_bands: self
+                .band_secs
+                .iter()
+                .enumerate()
+                .map(|(i, &seconds)| BandTime {
+                    band: self.band_label(i),
+                    seconds,
+                    share: if band_total > 0.0 {
+                        seconds / band_total
+                    } else {
+                        0.0
+                    },
+                })
+                .collect(),
+            slowdown_temperature: self.slowdown,
+            time_above_slowdown_secs: self.slowdown.map(|_| self.above_slowdown_secs),
+            throttle_share: (self.throttle_known_secs > 0.0)
+                .then(|| self.throttled_secs / self.throttle_known_secs),
+        }
+    }
+
+    fn band_label(&self, band: usize) -> String {
+        let edges = &self.band_edges;
+        if edges.is_empty() {
+            "all".to_string()
+        } else if band == 0 {
+            format!("<{}°C", edges[0])
+        } else if band == edges.len() {
+            format!(">={}°C", edges[band - 1])
+        } else {
+            format!("{}-{}°C", edges[band - 1], edges[band])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(temp: f64, power: f64) -> Vec<Option<f64>> {
+        RecordField::ALL
+            .iter()
+            .map(|f| match f {
+                RecordField::Temp => Some(temp),
+                RecordField::Power => Some(power),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_summary_percentiles() {
+        let values: Vec<f64> = (1..=100).map(f64::from).collect();
+        let summary = Summary::from_values(&values).unwrap();
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 100.0);
+        assert_eq!(summary.avg, 50.5);
+        assert_eq!(summary.p50, 50.0);
+        assert_eq!(summary.p95, 95.0);
+        assert_eq!(summary.p99, 99.0);
+        assert!(Summary::from_values(&[]).is_none());
+    }
+
+    #[test]
+    fn test_collector_time_in_band_and_throttle() {
+        let mut collector =
+            StatsCollector::new(0, "GPU-A", &[60.0, 80.0]).with_slowdown(Some(85.0));
+        collector.add(0.0, &values(50.0, 100.0), Some(false));
+        collector.add(10.0, &values(70.0, 200.0), Some(true));
+        collector.add(20.0, &values(90.0, 300.0), Some(true));
+        collector.add(25.0, &values(90.0, 300.0), None);
+
+        let stats = collector.finish();
+        assert_eq!(stats.duration_secs, 25.0);
+        assert_eq!(stats.samples, 4);
+        assert_eq!(stats.metrics.len(), 2);
+        assert_eq!(stats.metrics[1].metric, "power");
+        assert_eq!(stats.metrics[1].summary.max, 300.0);
+
+        let secs: Vec<f64> = stats.temperature_bands.iter().map(|b| b.seconds).collect();
+        assert_eq!(secs, vec![10.0, 10.0, 5.0]);
+        assert_eq!(stats.temperature_bands[1].band, "60-80°C");
+        assert_eq!(stats.time_above_slowdown_secs, Some(5.0));
+        assert_eq!(stats.throttle_share, Some(15.0 / 25.0));
+    }
+}
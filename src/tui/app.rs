@@ -0,0 +1,518 @@
+//! Dashboard state and key handling
+//!
+//! Kept free of terminal I/O so it can be driven by tests and the mock backend.
+
+use crate::alerts::{Alert, AlertManager, AlertManagerConfig, AlertRule};
+use crate::domain::{FanPolicy, FanSpeed, GpuProcess, PowerLimit};
+use crate::error::AppError;
+use crate::nvml::{GpuDevice, GpuManager, GpuSnapshot};
+
+use ratatui::crossterm::event::KeyCode;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of samples kept for sparklines
+pub const HISTORY_LEN: usize = 120;
+
+/// Fan speed step for `+`/`-` in percent
+const FAN_STEP: u8 = 5;
+
+/// Power limit step for `]`/`[` in watts
+const POWER_STEP: u32 = 10;
+
+/// Rolling samples of one metric
+#[derive(Debug, Clone, Default)]
+pub struct History(VecDeque<u64>);
+
+impl History {
+    /// Add a sample, dropping the oldest beyond [`HISTORY_LEN`]
+    pub fn push(&mut self, value: u64) {
+        if self.0.len() == HISTORY_LEN {
+            self.0.pop_front();
+        }
+        self.0.push_back(value);
+    }
+
+    /// Get the samples, oldest first
+    pub fn values(&self) -> Vec<u64> {
+        self.0.iter().copied().collect()
+    }
+
+    /// Get the latest sample
+    pub fn last(&self) -> Option<u64> {
+        self.0.back().copied()
+    }
+}
+
+/// State of one GPU
+pub struct GpuView {
+    /// Device index
+    pub index: u32,
+    /// Latest snapshot
+    pub snapshot: Option<GpuSnapshot>,
+    /// Processes at the latest refresh
+    pub processes: Vec<GpuProcess>,
+    /// Core temperature in Celsius
+    pub temperature: History,
+    /// Power draw in watts
+    pub power: History,
+    /// GPU utilization in percent
+    pub gpu_util: History,
+    /// Memory controller utilization in percent
+    pub mem_util: History,
+    /// VRAM usage in percent
+    pub vram: History,
+}
+
+impl GpuView {
+    fn new(index: u32) -> Self {
+        Self {
+            index,
+            snapshot: None,
+            processes: Vec::new(),
+            temperature: History::default(),
+            power: History::default(),
+            gpu_util: History::default(),
+            mem_util: History::default(),
+            vram: History::default(),
+        }
+    }
+
+    /// Product name, or a placeholder until the first refresh
+    pub fn name(&self) -> String {
+        self.snapshot
+            .as_ref()
+            .and_then(|s| s.name.clone().ok())
+            .unwrap_or_else(|| format!("GPU {}", self.index))
+    }
+
+    fn record(&mut self, snapshot: GpuSnapshot) {
+        if let Ok(temp) = &snapshot.temperature {
+            self.temperature.push(temp.as_celsius().max(0) as u64);
+        }
+        if let Ok(power) = &snapshot.power_usage {
+            self.power.push(power.as_watts() as u64);
+        }
+        if let Ok(util) = &snapshot.utilization {
+            self.gpu_util.push(util.gpu_percent() as u64);
+            self.mem_util.push(util.memory_percent() as u64);
+        }
+        if let Ok(memory) = &snapshot.memory_info {
+            self.vram.push(memory.usage_percent() as u64);
+        }
+        self.snapshot = Some(snapshot);
+    }
+
+    fn fan_speed(&self) -> Option<u8> {
+        self.snapshot
+            .as_ref()?
+            .fan_speeds
+            .iter()
+            .find_map(|speed| speed.as_ref().ok())
+            .map(|speed| speed.as_percentage())
+    }
+
+    fn power_limit(&self) -> Option<u32> {
+        self.snapshot
+            .as_ref()?
+            .power_limit
+            .as_ref()
+            .ok()
+            .map(|limit| limit.as_watts())
+    }
+
+    fn power_range(&self) -> Option<(u32, u32)> {
+        self.snapshot
+            .as_ref()?
+            .power_constraints
+            .as_ref()
+            .ok()
+            .map(|c| (c.min.as_watts(), c.max.as_watts()))
+    }
+}
+
+/// Process table sort order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSortKey {
+    /// GPU memory (descending)
+    Memory,
+    /// SM utilization (descending)
+    Utilization,
+    /// Process ID
+    Pid,
+    /// Process name
+    Name,
+}
+
+impl ProcessSortKey {
+    fn next(self) -> Self {
+        match self {
+            Self::Memory => Self::Utilization,
+            Self::Utilization => Self::Pid,
+            Self::Pid => Self::Name,
+            Self::Name => Self::Memory,
+        }
+    }
+
+    /// Label shown in the process table title
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Memory => "memory",
+            Self::Utilization => "utilization",
+            Self::Pid => "pid",
+            Self::Name => "name",
+        }
+    }
+}
+
+/// A change waiting for confirmation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingAction {
+    /// Set every fan to a fixed speed
+    FanSpeed { gpu: u32, percent: u8 },
+    /// Return fans to automatic control
+    FanAuto { gpu: u32 },
+    /// Set the power limit
+    PowerLimit { gpu: u32, watts: u32 },
+}
+
+impl PendingAction {
+    /// Question shown in the confirmation prompt
+    pub fn prompt(&self) -> String {
+        match self {
+            Self::FanSpeed { gpu, percent } => {
+                format!("Set fans on GPU {} to {}%?", gpu, percent)
+            }
+            Self::FanAuto { gpu } => format!("Return fans on GPU {} to automatic control?", gpu),
+            Self::PowerLimit { gpu, watts } => {
+                format!("Set power limit on GPU {} to {}W?", gpu, watts)
+            }
+        }
+    }
+}
+
+/// Dashboard state
+pub struct TopApp {
+    gpus: Vec<GpuView>,
+    selected: usize,
+    sort: ProcessSortKey,
+    alerts: AlertManager,
+    pending: Option<PendingAction>,
+    status: Option<String>,
+    dry_run: bool,
+    quit: bool,
+}
+
+impl TopApp {
+    /// Create a dashboard for the given GPUs
+    pub fn new(indices: &[u32], rules: Vec<AlertRule>, dry_run: bool) -> Self {
+        let mut alerts = AlertManager::new(AlertManagerConfig::default());
+        alerts.add_rules(rules);
+
+        Self {
+            gpus: indices.iter().map(|&index| GpuView::new(index)).collect(),
+            selected: 0,
+            sort: ProcessSortKey::Memory,
+            alerts,
+            pending: None,
+            status: None,
+            dry_run,
+            quit: false,
+        }
+    }
+
+    /// Get all GPUs
+    pub fn gpus(&self) -> &[GpuView] {
+        &self.gpus
+    }
+
+    /// Get the position of the selected GPU
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Get the selected GPU
+    pub fn selected_gpu(&self) -> Option<&GpuView> {
+        self.gpus.get(self.selected)
+    }
+
+    /// Get the process sort order
+    pub fn sort(&self) -> ProcessSortKey {
+        self.sort
+    }
+
+    /// Get the change waiting for confirmation
+    pub fn pending(&self) -> Option<&PendingAction> {
+        self.pending.as_ref()
+    }
+
+    /// Get the last status message
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    /// Check if the user asked to quit
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Get the currently firing alerts
+    pub fn active_alerts(&self) -> Vec<&Alert> {
+        self.alerts.active_alerts()
+    }
+
+    /// Get the selected GPU's processes in the current sort order
+    pub fn sorted_processes(&self) -> Vec<&GpuProcess> {
+        let Some(gpu) = self.selected_gpu() else {
+            return Vec::new();
+        };
+        let mut processes: Vec<&GpuProcess> = gpu.processes.iter().collect();
+        match self.sort {
+            ProcessSortKey::Memory => {
+                processes.sort_by_key(|p| std::cmp::Reverse(p.used_memory));
+            }
+            ProcessSortKey::Utilization => {
+                processes.sort_by_key(|p| std::cmp::Reverse(p.utilization.map_or(0, |u| u.sm)))
+            }
+            ProcessSortKey::Pid => processes.sort_by_key(|p| p.pid),
+            ProcessSortKey::Name => processes.sort_by_key(|p| p.display_name().to_lowercase()),
+        }
+        processes
+    }
+
+    /// Read fresh metrics, processes and alerts from every GPU
+    pub fn refresh<M: GpuManager>(&mut self, manager: &M, sample_window: Duration) {
+        for gpu in &mut self.gpus {
+            let device = match manager.device_by_index(gpu.index) {
+                Ok(device) => device,
+                Err(e) => {
+                    log::debug!("GPU {} unavailable: {}", gpu.index, e);
+                    continue;
+                }
+            };
+
+            let snapshot = GpuSnapshot::capture(&device);
+            if let Err(e) = self.alerts.evaluate(&snapshot, gpu.index) {
+                log::debug!("Alert evaluation failed on GPU {}: {}", gpu.index, e);
+            }
+            gpu.record(snapshot);
+
+            gpu.processes = match device.running_processes() {
+                Ok(mut list) => {
+                    if let Ok(util) = device.process_utilization(sample_window) {
+                        list.apply_utilization(&util);
+                    }
+                    list.processes
+                }
+                Err(_) => Vec::new(),
+            };
+        }
+    }
+
+    /// Handle a key press
+    ///
+    /// Returns an action once the user has confirmed it.
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<PendingAction> {
+        if let Some(pending) = self.pending {
+            match key {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    self.pending = None;
+                    return Some(pending);
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.pending = None;
+                    self.status = Some("Cancelled".to_string());
+                    return None;
+                }
+                // Further adjustments change the pending value
+                KeyCode::Char('+' | '-' | '[' | ']') => {}
+                _ => return None,
+            }
+        }
+
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => self.select_offset(1),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.select_offset(self.gpus.len().saturating_sub(1))
+            }
+            KeyCode::Char(c @ '0'..='9') => {
+                let index = c.to_digit(10).unwrap_or(0);
+                if let Some(pos) = self.gpus.iter().position(|g| g.index == index) {
+                    self.selected = pos;
+                }
+            }
+            KeyCode::Char('s') => self.sort = self.sort.next(),
+            KeyCode::Char('+') => self.adjust_fan(FAN_STEP as i16),
+            KeyCode::Char('-') => self.adjust_fan(-(FAN_STEP as i16)),
+            KeyCode::Char('a') => {
+                if let Some(gpu) = self.selected_gpu() {
+                    self.pending = Some(PendingAction::FanAuto { gpu: gpu.index });
+                }
+            }
+            KeyCode::Char(']') => self.adjust_power(POWER_STEP as i64),
+            KeyCode::Char('[') => self.adjust_power(-(POWER_STEP as i64)),
+            _ => {}
+        }
+        None
+    }
+
+    fn select_offset(&mut self, offset: usize) {
+        if !self.gpus.is_empty() {
+            self.selected = (self.selected + offset) % self.gpus.len();
+        }
+    }
+
+    fn adjust_fan(&mut self, delta: i16) {
+        let Some(gpu) = self.selected_gpu() else {
+            return;
+        };
+        let current = match self.pending {
+            Some(PendingAction::FanSpeed { percent, .. }) => Some(percent),
+            _ => gpu.fan_speed(),
+        };
+        let Some(current) = current else {
+            self.status = Some(format!("GPU {} has no controllable fans", gpu.index));
+            return;
+        };
+
+        let percent = (current as i16 + delta).clamp(0, FanSpeed::MAX as i16) as u8;
+        self.pending = Some(PendingAction::FanSpeed {
+            gpu: gpu.index,
+            percent,
+        });
+    }
+
+    fn adjust_power(&mut self, delta: i64) {
+        let Some(gpu) = self.selected_gpu() else {
+            return;
+        };
+        let current = match self.pending {
+            Some(PendingAction::PowerLimit { watts, .. }) => Some(watts),
+            _ => gpu.power_limit(),
+        };
+        let Some(current) = current else {
+            self.status = Some(format!("GPU {} has no power limit", gpu.index));
+            return;
+        };
+
+        let (min, max) = gpu.power_range().unwrap_or((0, u32::MAX));
+        let watts = (current as i64 + delta).clamp(min as i64, max as i64) as u32;
+        self.pending = Some(PendingAction::PowerLimit {
+            gpu: gpu.index,
+            watts,
+        });
+    }
+
+    /// Apply a confirmed action and report the outcome in the status line
+    pub fn execute<M: GpuManager>(&mut self, manager: &M, action: PendingAction) {
+        let prefix = if self.dry_run { "[DRY RUN] " } else { "" };
+        let result = if self.dry_run {
+            Ok(())
+        } else {
+            Self::apply(manager, action)
+        };
+
+        self.status = Some(match (result, action) {
+            (Ok(()), PendingAction::FanSpeed { gpu, percent }) => {
+                format!("{}Set fans on GPU {} to {}%", prefix, gpu, percent)
+            }
+            (Ok(()), PendingAction::FanAuto { gpu }) => {
+                format!(
+                    "{}Fans on GPU {} returned to automatic control",
+                    prefix, gpu
+                )
+            }
+            (Ok(()), PendingAction::PowerLimit { gpu, watts }) => {
+                format!("{}Set power limit on GPU {} to {}W", prefix, gpu, watts)
+            }
+            (Err(e), _) => format!("Failed: {}", e),
+        });
+    }
+
+    fn apply<M: GpuManager>(manager: &M, action: PendingAction) -> Result<(), AppError> {
+        match action {
+            PendingAction::FanSpeed { gpu, percent } => {
+                let mut device = manager.device_by_index(gpu)?;
+                let speed = FanSpeed::new(percent)?;
+                for fan in 0..device.fan_count()? {
+                    device.set_fan_policy(fan, FanPolicy::Manual)?;
+                    device.set_fan_speed(fan, speed)?;
+                }
+            }
+            PendingAction::FanAuto { gpu } => {
+                let mut device = manager.device_by_index(gpu)?;
+                for fan in 0..device.fan_count()? {
+                    device.set_fan_policy(fan, FanPolicy::Auto)?;
+                }
+            }
+            PendingAction::PowerLimit { gpu, watts } => {
+                let mut device = manager.device_by_index(gpu)?;
+                device.set_power_limit(PowerLimit::from_watts(watts))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockManager;
+
+    fn app(manager: &MockManager) -> TopApp {
+        let mut app = TopApp::new(&[0, 1], Vec::new(), false);
+        app.refresh(manager, Duration::from_secs(1));
+        app
+    }
+
+    #[test]
+    fn test_refresh_records_history() {
+        let manager = MockManager::new(2);
+        let mut app = app(&manager);
+        app.refresh(&manager, Duration::from_secs(1));
+
+        let gpu = app.selected_gpu().unwrap();
+        assert_eq!(gpu.name(), "Mock GPU 0");
+        assert_eq!(gpu.temperature.values().len(), 2);
+        assert!(gpu.power.last().is_some());
+    }
+
+    #[test]
+    fn test_keys_select_gpu_and_confirm_changes() {
+        let manager = MockManager::new(2);
+        let mut app = app(&manager);
+
+        app.handle_key(KeyCode::Tab);
+        assert_eq!(app.selected(), 1);
+        app.handle_key(KeyCode::Char('0'));
+        assert_eq!(app.selected(), 0);
+
+        // Two steps up from the mock's 50%, then confirm
+        assert!(app.handle_key(KeyCode::Char('+')).is_none());
+        assert!(app.handle_key(KeyCode::Char('+')).is_none());
+        let action = app.handle_key(KeyCode::Char('y')).unwrap();
+        assert_eq!(
+            action,
+            PendingAction::FanSpeed {
+                gpu: 0,
+                percent: 60
+            }
+        );
+        app.execute(&manager, action);
+        assert_eq!(app.status(), Some("Set fans on GPU 0 to 60%"));
+
+        // Cancelling leaves nothing pending; 'q' only quits outside a prompt
+        app.handle_key(KeyCode::Char(']'));
+        assert!(matches!(
+            app.pending(),
+            Some(PendingAction::PowerLimit { .. })
+        ));
+        app.handle_key(KeyCode::Char('q'));
+        assert!(!app.should_quit());
+        app.handle_key(KeyCode::Char('n'));
+        assert!(app.pending().is_none());
+        app.handle_key(KeyCode::Char('q'));
+        assert!(app.should_quit());
+    }
+}
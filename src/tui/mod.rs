@@ -0,0 +1,51 @@
+//! Full-screen terminal dashboard (`nvctl top`)
+//!
+//! Works with any [`GpuManager`], so it also runs against the mock backend.
+
+pub mod app;
+pub mod ui;
+
+pub use app::{GpuView, History, PendingAction, ProcessSortKey, TopApp};
+
+use crate::error::AppError;
+use crate::nvml::GpuManager;
+
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::time::{Duration, Instant};
+
+/// Run the dashboard until the user quits
+///
+/// Metrics are refreshed every `interval`; key presses are handled as they arrive.
+pub fn run<M: GpuManager>(
+    manager: &M,
+    mut app: TopApp,
+    interval: Duration,
+) -> Result<(), AppError> {
+    let mut terminal = ratatui::init();
+    let result = (|| -> Result<(), AppError> {
+        let mut next_refresh = Instant::now();
+        while !app.should_quit() {
+            if Instant::now() >= next_refresh {
+                app.refresh(manager, interval);
+                next_refresh = Instant::now() + interval;
+            }
+            terminal.draw(|frame| ui::draw(frame, &app))?;
+
+            let timeout = next_refresh.saturating_duration_since(Instant::now());
+            if event::poll(timeout)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        if let Some(action) = app.handle_key(key.code) {
+                            app.execute(manager, action);
+                            // Show the new values right away
+                            next_refresh = Instant::now();
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    })();
+    ratatui::restore();
+    result
+}
@@ -0,0 +1,325 @@
+//! Dashboard rendering
+
+use crate::alerts::AlertSeverity;
+use crate::nvml::GpuSnapshot;
+use crate::tui::app::{History, TopApp};
+
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Sparkline, Table};
+use ratatui::Frame;
+
+const HELP: &str = "q quit  ←/→ GPU  s sort  +/- fan  a fan auto  [/] power";
+
+/// Draw the whole dashboard
+pub fn draw(frame: &mut Frame, app: &TopApp) {
+    let alerts = app.active_alerts().len().clamp(1, 5) as u16;
+    let [gpus, details, processes, alert_area, footer] = Layout::vertical([
+        Constraint::Length(app.gpus().len() as u16 + 3),
+        Constraint::Length(10),
+        Constraint::Min(5),
+        Constraint::Length(alerts + 2),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    draw_gpu_table(frame, app, gpus);
+    draw_details(frame, app, details);
+    draw_processes(frame, app, processes);
+    draw_alerts(frame, app, alert_area);
+    draw_footer(frame, app, footer);
+}
+
+fn value<T>(result: &Result<T, crate::error::NvmlError>, f: impl Fn(&T) -> String) -> String {
+    result.as_ref().map_or("-".to_string(), f)
+}
+
+fn draw_gpu_table(frame: &mut Frame, app: &TopApp, area: Rect) {
+    let header = Row::new(["GPU", "Name", "Temp", "Fan", "Power", "Util", "VRAM"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+
+    let rows = app.gpus().iter().enumerate().map(|(pos, gpu)| {
+        let cells = match &gpu.snapshot {
+            Some(s) => [
+                value(&s.temperature, |t| format!("{}°C", t.as_celsius())),
+                s.fan_speeds
+                    .iter()
+                    .find_map(|f| f.as_ref().ok())
+                    .map_or("-".to_string(), |f| format!("{}%", f.as_percentage())),
+                value(&s.power_usage, |p| format!("{}W", p.as_watts())),
+                value(&s.utilization, |u| format!("{}%", u.gpu_percent())),
+                value(&s.memory_info, |m| format!("{}%", m.usage_percent())),
+            ],
+            None => Default::default(),
+        };
+        let style = if pos == app.selected() {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        Row::new(
+            [gpu.index.to_string(), gpu.name()]
+                .into_iter()
+                .chain(cells)
+                .map(Cell::from),
+        )
+        .style(style)
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Min(20),
+            Constraint::Length(6),
+            Constraint::Length(5),
+            Constraint::Length(6),
+            Constraint::Length(5),
+            Constraint::Length(5),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title(" GPUs "));
+    frame.render_widget(table, area);
+}
+
+fn draw_details(frame: &mut Frame, app: &TopApp, area: Rect) {
+    let Some(gpu) = app.selected_gpu() else {
+        return;
+    };
+    let [metrics, sparklines] =
+        Layout::horizontal([Constraint::Length(42), Constraint::Min(20)]).areas(area);
+
+    let lines = gpu.snapshot.as_ref().map(metric_lines).unwrap_or_default();
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(format!(
+            " [{}] {} ",
+            gpu.index,
+            gpu.name()
+        ))),
+        metrics,
+    );
+
+    let charts: [(&str, &History, Option<u64>, Color); 4] = [
+        ("Temp °C", &gpu.temperature, None, Color::Red),
+        ("Power W", &gpu.power, None, Color::Yellow),
+        ("GPU %", &gpu.gpu_util, Some(100), Color::Green),
+        ("VRAM %", &gpu.vram, Some(100), Color::Cyan),
+    ];
+    let areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Ratio(1, 4); 4])
+        .split(sparklines);
+    for ((title, history, max, color), area) in charts.into_iter().zip(areas.iter()) {
+        // Show the most recent samples that fit
+        let values = history.values();
+        let width = area.width.saturating_sub(2) as usize;
+        let data = &values[values.len().saturating_sub(width)..];
+        let title = format!(
+            " {} {} ",
+            title,
+            history.last().map_or("-".to_string(), |v| v.to_string())
+        );
+        let mut sparkline = Sparkline::default()
+            .block(
+                Block::default()
+                    .borders(Borders::LEFT | Borders::RIGHT)
+                    .title(title),
+            )
+            .data(data)
+            .style(Style::default().fg(color));
+        if let Some(max) = max {
+            sparkline = sparkline.max(max);
+        }
+        frame.render_widget(sparkline, *area);
+    }
+}
+
+fn metric_lines(s: &GpuSnapshot) -> Vec<Line<'static>> {
+    let fans: Vec<String> = s
+        .fan_speeds
+        .iter()
+        .zip(&s.fan_policies)
+        .map(|(speed, policy)| {
+            format!(
+                "{} ({})",
+                value(speed, |f| format!("{}%", f.as_percentage())),
+                value(policy, |p| p.to_string().to_lowercase())
+            )
+        })
+        .collect();
+
+    let row = |label: &str, text: String| {
+        Line::from(vec![
+            Span::styled(
+                format!("{:<12}", label),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::raw(text),
+        ])
+    };
+
+    vec![
+        row(
+            "Temperature",
+            value(&s.temperature, |t| format!("{}°C", t.as_celsius())),
+        ),
+        row(
+            "Fans",
+            if fans.is_empty() {
+                "-".to_string()
+            } else {
+                fans.join(", ")
+            },
+        ),
+        row(
+            "Power",
+            format!(
+                "{} / {}",
+                value(&s.power_usage, |p| format!("{}W", p.as_watts())),
+                value(&s.power_limit, |p| format!("{}W", p.as_watts()))
+            ),
+        ),
+        row(
+            "Clocks",
+            format!(
+                "{} / {} MHz",
+                value(&s.graphics_clock, |c| c.as_mhz().to_string()),
+                value(&s.memory_clock, |c| c.as_mhz().to_string())
+            ),
+        ),
+        row(
+            "VRAM",
+            value(&s.memory_info, |m| {
+                format!("{} / {} MB", m.used_mb(), m.total_mb())
+            }),
+        ),
+        row(
+            "Utilization",
+            value(&s.utilization, |u| {
+                format!("GPU {}%  Mem {}%", u.gpu_percent(), u.memory_percent())
+            }),
+        ),
+        row("P-State", value(&s.performance_state, |p| p.to_string())),
+    ]
+}
+
+fn draw_processes(frame: &mut Frame, app: &TopApp, area: Rect) {
+    let header = Row::new(["PID", "User", "Memory", "SM", "Mem", "Type", "Name"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let percent = |value: Option<u32>| value.map_or("-".to_string(), |v| format!("{}%", v));
+
+    let rows = app.sorted_processes().into_iter().map(|p| {
+        Row::new([
+            p.pid.to_string(),
+            p.user.clone().unwrap_or_else(|| "-".to_string()),
+            format!("{:.0} MB", p.memory_mb()),
+            percent(p.utilization.map(|u| u.sm)),
+            percent(p.utilization.map(|u| u.memory)),
+            p.process_type.to_string(),
+            p.cmdline.clone().unwrap_or_else(|| p.display_name()),
+        ])
+    });
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(5),
+            Constraint::Length(5),
+            Constraint::Length(16),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Processes (sort: {}) ", app.sort().label())),
+    );
+    frame.render_widget(table, area);
+}
+
+fn draw_alerts(frame: &mut Frame, app: &TopApp, area: Rect) {
+    let alerts = app.active_alerts();
+    let lines: Vec<Line> = if alerts.is_empty() {
+        vec![Line::styled(
+            "No active alerts",
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        alerts
+            .iter()
+            .map(|alert| {
+                let color = match alert.severity {
+                    AlertSeverity::Critical | AlertSeverity::Emergency => Color::Red,
+                    AlertSeverity::Warning => Color::Yellow,
+                    AlertSeverity::Info => Color::Blue,
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<9}", alert.severity.to_string()),
+                        Style::default().fg(color),
+                    ),
+                    Span::raw(format!("GPU {}  {}", alert.gpu_index, alert.message)),
+                ])
+            })
+            .collect()
+    };
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" Alerts ")),
+        area,
+    );
+}
+
+fn draw_footer(frame: &mut Frame, app: &TopApp, area: Rect) {
+    let line = match (app.pending(), app.status()) {
+        (Some(pending), _) => Line::styled(
+            format!("{}  (y to confirm, n to cancel)", pending.prompt()),
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        (None, Some(status)) => Line::from(vec![
+            Span::styled(status.to_string(), Style::default().fg(Color::Green)),
+            Span::raw("  "),
+            Span::styled(HELP, Style::default().fg(Color::DarkGray)),
+        ]),
+        (None, None) => Line::styled(HELP, Style::default().fg(Color::DarkGray)),
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockManager;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::time::Duration;
+
+    #[test]
+    fn test_draw_dashboard() {
+        let manager = MockManager::new(2);
+        let mut app = TopApp::new(&[0, 1], Vec::new(), false);
+        app.refresh(&manager, Duration::from_secs(1));
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 40)).unwrap();
+        terminal.draw(|frame| draw(frame, &app)).unwrap();
+
+        let text: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(text.contains("Mock GPU 1"));
+        assert!(text.contains("Processes (sort: memory)"));
+        assert!(text.contains("No active alerts"));
+    }
+}